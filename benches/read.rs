@@ -0,0 +1,49 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use hmm_tblout::{Reader, Writer};
+
+/// The nhmmer test file with its records repeated to make a larger
+/// file.
+fn file() -> Vec<u8> {
+    let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+    let records = reader
+        .records()
+        .collect::<hmm_tblout::Result<Vec<_>>>()
+        .unwrap();
+    let records = records
+        .iter()
+        .cycle()
+        .take(100_000)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut writer = Writer::new(Vec::new());
+    writer
+        .write_all(reader.header(), &records, reader.meta())
+        .unwrap();
+    writer.into_inner().unwrap()
+}
+
+fn read(c: &mut Criterion) {
+    let file = file();
+
+    let mut group = c.benchmark_group("read 100k records");
+    group.sample_size(20);
+    group.bench_function("collect", |b| {
+        b.iter(|| {
+            let reader = Reader::from_reader(file.as_slice()).unwrap();
+            reader
+                .into_records()
+                .collect::<hmm_tblout::Result<Vec<_>>>()
+                .unwrap()
+        })
+    });
+    group.bench_function("read_all_strict", |b| {
+        b.iter(|| {
+            let mut reader = Reader::from_reader(file.as_slice()).unwrap();
+            reader.read_all_strict().unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read);
+criterion_main!(benches);
@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use hmm_tblout::{Reader, Record};
+use std::io::Write;
+
+/// The records of the nhmmer test file, repeated to make a larger file.
+fn records() -> (Vec<Record>, Vec<usize>) {
+    let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+    let widths = reader.header().calculate_dashes();
+    let records = reader
+        .records()
+        .collect::<hmm_tblout::Result<Vec<_>>>()
+        .unwrap();
+    let records = records.iter().cycle().take(100_000).cloned().collect();
+    (records, widths)
+}
+
+fn write(c: &mut Criterion) {
+    let (records, widths) = records();
+    let mut out = Vec::with_capacity(20_000_000);
+
+    let mut group = c.benchmark_group("write 100k records");
+    group.sample_size(20);
+    group.bench_function("display", |b| {
+        b.iter(|| {
+            out.clear();
+            for record in &records {
+                writeln!(out, "{}", record).unwrap();
+            }
+        })
+    });
+    group.bench_function("write_to", |b| {
+        b.iter(|| {
+            out.clear();
+            for record in &records {
+                record.write_to(&mut out, &widths).unwrap();
+                out.push(b'\n');
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, write);
+criterion_main!(benches);
@@ -0,0 +1,28 @@
+use hmm_tblout::prelude::*;
+
+fn main() -> Result<(), Error> {
+    // get the command line args, the tblout file and
+    // the largest E-value to keep
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() < 3 {
+        println!("Usage: filter_hits <tblout_file> <max_evalue>");
+        std::process::exit(1);
+    }
+    let max_evalue: f64 = args[2].parse()?;
+
+    let mut reader = Reader::from_path(&args[1])?;
+    let mut writer = Writer::to_stdout();
+    writer.write_header(reader.header())?;
+
+    for record in reader.records() {
+        let r = record?;
+        let evalue = r.evalue_opt().unwrap_or(f64::MAX);
+        if evalue <= max_evalue {
+            writer.write_record(&r)?;
+        }
+    }
+
+    writer.finish(reader.meta())?;
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-extern crate hmm_tblout;
+use hmm_tblout::prelude::*;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // get the command line args, only parse the
@@ -9,14 +9,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::process::exit(1);
     }
 
-    let reader = hmm_tblout::Reader::from_path(args[1].clone())?;
+    let reader = Reader::from_path(args[1].clone())?;
 
-    for record in reader.into_records() {
+    // coordinates are only written by nhmmer and nhmmscan
+    for record in reader.into_dna_records()? {
         let r = record?;
         let tname = r.target_name();
-        let strand = r.strand().unwrap();
-        let alifrom = r.ali_from().unwrap();
-        let alito = r.ali_to().unwrap();
+        let strand = r.strand();
+        let alifrom = r.ali_from();
+        let alito = r.ali_to();
 
         println!("{}\t{}\t{}\t{}", tname, strand, alifrom, alito);
     }
@@ -0,0 +1,32 @@
+//! Parse arbitrary bytes as the output of each program, and with the
+//! program taken from the metadata in them. Errors are expected, but
+//! nothing should panic.
+
+#![no_main]
+
+use hmm_tblout::{Meta, Program, Reader};
+use libfuzzer_sys::fuzz_target;
+
+const PROGRAMS: [Program; 7] = [
+    Program::None,
+    Program::Nhmmer,
+    Program::Nhmmscan,
+    Program::Jackhmmer,
+    Program::Hmmscan,
+    Program::Hmmsearch,
+    Program::Phmmer,
+];
+
+fuzz_target!(|data: &[u8]| {
+    for program in PROGRAMS {
+        let mut reader = Reader::new(data, Meta::for_program(program));
+        // records which parse must display without panicking
+        for record in reader.records().flatten() {
+            let _ = record.to_string();
+        }
+    }
+
+    if let Ok(mut reader) = Reader::from_reader(data) {
+        reader.records().for_each(drop);
+    }
+});
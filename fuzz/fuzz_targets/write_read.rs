@@ -0,0 +1,44 @@
+//! Write arbitrary records and read them back, checking the fields
+//! which are written exactly come back unchanged.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use hmm_tblout::{DNARecord, Header, Meta, ProteinRecord, Reader, Record, Writer};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    meta: Meta,
+    dna: Vec<DNARecord>,
+    protein: Vec<ProteinRecord>,
+}
+
+fuzz_target!(|input: Input| {
+    let meta = input.meta;
+    // only records of the program's kind can be written
+    let records: Vec<Record> = if meta.program().is_dna_search() {
+        input.dna.into_iter().map(Record::Dna).collect()
+    } else {
+        input.protein.into_iter().map(Record::Protein).collect()
+    };
+    let header = Header::for_program(meta.program()).unwrap();
+
+    let mut writer = Writer::new(Vec::new());
+    writer.write_all(&header, &records, &meta).unwrap();
+    let out = writer.into_inner().unwrap();
+
+    let mut reader = Reader::from_reader(out.as_slice()).unwrap();
+    assert_eq!(reader.meta(), &meta);
+    let read = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(read.len(), records.len());
+    for (read, written) in read.iter().zip(&records) {
+        assert_eq!(read.target_name(), written.target_name());
+        assert_eq!(read.target_accession(), written.target_accession());
+        assert_eq!(read.query_name(), written.query_name());
+        assert_eq!(read.query_accession(), written.query_accession());
+        assert_eq!(read.ali_from(), written.ali_from());
+        assert_eq!(read.strand(), written.strand());
+        assert_eq!(read.description(), written.description());
+    }
+});
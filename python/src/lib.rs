@@ -0,0 +1,210 @@
+//! Python bindings for `hmm_tblout`.
+//!
+//! ```python
+//! import hmm_tblout
+//!
+//! reader = hmm_tblout.read("hits.tbl")
+//! print(reader.meta()["program"])
+//! for record in reader:
+//!     print(record["target_name"], record["e_value"])
+//! ```
+//!
+//! Records are read one at a time as the reader is iterated, so large
+//! files are never held in memory.
+
+use pyo3::{
+    exceptions::{PyKeyError, PyOSError, PyValueError},
+    prelude::*,
+    types::PyDict,
+    IntoPyObjectExt,
+};
+use std::{fs::File, path::PathBuf};
+use tblout::{field_names, Meta, Reader, RecordKind, RecordParts, RecordsIntoIter};
+
+/// Convert an error from the parser into a Python exception, giving
+/// the line it was found on when that is known.
+fn py_error(err: tblout::Error, line: Option<u64>) -> PyErr {
+    let msg = match line {
+        Some(line) => format!("line {}: {}", line, err),
+        None => err.to_string(),
+    };
+    match err.kind() {
+        tblout::ErrorKind::Io(_) => PyOSError::new_err(msg),
+        _ => PyValueError::new_err(msg),
+    }
+}
+
+/// A record, which can be indexed by column name like a dict.
+#[pyclass(module = "hmm_tblout", name = "Record", frozen)]
+struct PyRecord {
+    parts: RecordParts,
+}
+
+impl PyRecord {
+    fn kind_of(&self) -> RecordKind {
+        match self.parts {
+            RecordParts::Protein(_) => RecordKind::Protein,
+            RecordParts::Dna(_) => RecordKind::Dna,
+        }
+    }
+
+    /// The value of a column, or `None` if the record has no such
+    /// column.
+    fn get_value(&self, py: Python<'_>, key: &str) -> PyResult<Option<PyObject>> {
+        let value = match &self.parts {
+            RecordParts::Protein(p) => match key {
+                "target_name" => p.target_name.as_str().into_py_any(py),
+                "target_accession" => p.target_accession.as_str().into_py_any(py),
+                "query_name" => p.query_name.as_str().into_py_any(py),
+                "query_accession" => p.query_accession.as_str().into_py_any(py),
+                "e_value_full" => p.e_value_full.into_py_any(py),
+                "score_full" => p.score_full.into_py_any(py),
+                "bias_full" => p.bias_full.into_py_any(py),
+                "e_value_best" => p.e_value_best.into_py_any(py),
+                "score_best" => p.score_best.into_py_any(py),
+                "bias_best" => p.bias_best.into_py_any(py),
+                "exp" => p.exp.into_py_any(py),
+                "reg" => p.reg.into_py_any(py),
+                "clu" => p.clu.into_py_any(py),
+                "ov" => p.ov.into_py_any(py),
+                "env" => p.env.into_py_any(py),
+                "dom" => p.dom.into_py_any(py),
+                "rep" => p.rep.into_py_any(py),
+                "inc" => p.inc.into_py_any(py),
+                "description" => p.description.as_str().into_py_any(py),
+                _ => return Ok(None),
+            },
+            RecordParts::Dna(p) => match key {
+                "target_name" => p.target_name.as_str().into_py_any(py),
+                "target_accession" => p.target_accession.as_str().into_py_any(py),
+                "query_name" => p.query_name.as_str().into_py_any(py),
+                "query_accession" => p.query_accession.as_str().into_py_any(py),
+                "hmm_from" => p.hmm_from.into_py_any(py),
+                "hmm_to" => p.hmm_to.into_py_any(py),
+                "ali_from" => p.ali_from.into_py_any(py),
+                "ali_to" => p.ali_to.into_py_any(py),
+                "env_from" => p.env_from.into_py_any(py),
+                "env_to" => p.env_to.into_py_any(py),
+                "sq_len" => p.sq_len.into_py_any(py),
+                "strand" => p.strand.to_string().into_py_any(py),
+                "e_value" => p.e_value.into_py_any(py),
+                "score" => p.score.into_py_any(py),
+                "bias" => p.bias.into_py_any(py),
+                "description" => p.description.as_str().into_py_any(py),
+                _ => return Ok(None),
+            },
+        };
+        value.map(Some)
+    }
+}
+
+#[pymethods]
+impl PyRecord {
+    /// The kind of record, `"dna"` or `"protein"`.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.kind_of() {
+            RecordKind::Protein => "protein",
+            RecordKind::Dna => "dna",
+        }
+    }
+
+    /// The column names, in the order of the tblout columns.
+    fn keys(&self) -> Vec<&'static str> {
+        field_names(self.kind_of()).to_vec()
+    }
+
+    /// The value of a column, or `default` if there is no such column.
+    #[pyo3(signature = (key, default = None))]
+    fn get(&self, py: Python<'_>, key: &str, default: Option<PyObject>) -> PyResult<PyObject> {
+        match self.get_value(py, key)? {
+            Some(value) => Ok(value),
+            None => Ok(default.unwrap_or_else(|| py.None())),
+        }
+    }
+
+    /// The columns as a dict.
+    fn to_dict<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let dict = PyDict::new(py);
+        for key in field_names(self.kind_of()) {
+            dict.set_item(key, self.get_value(py, key)?)?;
+        }
+        Ok(dict)
+    }
+
+    fn __getitem__(&self, py: Python<'_>, key: &str) -> PyResult<PyObject> {
+        self.get_value(py, key)?
+            .ok_or_else(|| PyKeyError::new_err(key.to_string()))
+    }
+
+    fn __contains__(&self, key: &str) -> bool {
+        field_names(self.kind_of()).contains(&key)
+    }
+
+    fn __len__(&self) -> usize {
+        field_names(self.kind_of()).len()
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("Record({})", self.to_dict(py)?.repr()?))
+    }
+}
+
+/// A reader over the records of a tblout file, yielding a `Record`
+/// for each line as it is iterated.
+#[pyclass(module = "hmm_tblout", name = "Reader")]
+struct PyReader {
+    records: RecordsIntoIter<File>,
+    meta: Meta,
+}
+
+#[pymethods]
+impl PyReader {
+    /// The metadata at the end of the file, as a dict.
+    fn meta<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let m = &self.meta;
+        let dict = PyDict::new(py);
+        dict.set_item("program", m.program().to_string())?;
+        dict.set_item("version", m.version())?;
+        dict.set_item("pipeline_mode", m.pipeline_mode())?;
+        dict.set_item("query_file", m.query_file())?;
+        dict.set_item("target_file", m.target_file())?;
+        dict.set_item("options", m.options())?;
+        dict.set_item("current_dir", m.current_dir())?;
+        dict.set_item("date", m.date())?;
+        Ok(dict)
+    }
+
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> PyResult<Option<PyRecord>> {
+        match self.records.next() {
+            Some(Ok(record)) => Ok(Some(PyRecord {
+                parts: record.into_parts(),
+            })),
+            Some(Err(err)) => Err(py_error(err, Some(self.records.reader().line()))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Open a tblout file for reading.
+#[pyfunction]
+fn read(path: PathBuf) -> PyResult<PyReader> {
+    let reader = Reader::from_path(&path).map_err(|e| py_error(e, None))?;
+    let meta = reader.meta().clone();
+    Ok(PyReader {
+        records: reader.into_records(),
+        meta,
+    })
+}
+
+#[pymodule]
+fn hmm_tblout(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(read, m)?)?;
+    m.add_class::<PyReader>()?;
+    m.add_class::<PyRecord>()?;
+    Ok(())
+}
@@ -0,0 +1,59 @@
+"""Smoke tests for the Python bindings.
+
+Build the module into the current environment with `maturin develop`
+from the `python` directory, then run `pytest tests`.
+"""
+
+import pathlib
+
+import pytest
+
+import hmm_tblout
+
+DATA = pathlib.Path(__file__).resolve().parents[2] / "data"
+
+
+def test_read_records():
+    reader = hmm_tblout.read(DATA / "test.tbl")
+    assert reader.meta()["program"] == "nhmmer"
+
+    records = list(reader)
+    assert len(records) == 31
+    first = records[0]
+    assert first.kind == "dna"
+    assert first["target_name"] == "2"
+    assert first["strand"] == "+"
+    assert isinstance(first["e_value"], float)
+    assert isinstance(first["ali_from"], int)
+    assert records[30]["ali_from"] == 94773395
+    assert list(first.keys()) == list(first.to_dict().keys())
+    assert "score" in first
+    assert first.get("e_value_full") is None
+    with pytest.raises(KeyError):
+        first["e_value_full"]
+
+
+def test_protein_records():
+    reader = hmm_tblout.read(DATA / "corpus" / "hmmsearch.tbl")
+    first = next(iter(reader))
+    assert first.kind == "protein"
+    assert first["query_name"] == "SOR"
+    assert first["inc"] == 1
+
+
+def test_errors_give_line_numbers(tmp_path):
+    lines = (DATA / "test.tbl").read_text().splitlines(keepends=True)
+    # break the second record, on the fourth line
+    lines[3] = "not a record\n"
+    path = tmp_path / "broken.tbl"
+    path.write_text("".join(lines))
+
+    reader = hmm_tblout.read(path)
+    next(reader)
+    with pytest.raises(ValueError, match="line 4"):
+        next(reader)
+
+
+def test_missing_file(tmp_path):
+    with pytest.raises(OSError):
+        hmm_tblout.read(tmp_path / "missing.tbl")
@@ -0,0 +1,191 @@
+//! R bindings for `hmm_tblout`.
+//!
+//! ```r
+//! hits <- hmmtblout::read_tblout("hits.tbl")
+//! attr(hits, "program")
+//! hits[hits$e_value < 1e-5, c("target_name", "ali_from", "ali_to")]
+//! ```
+//!
+//! Records are streamed from the file into one vector per column, which
+//! are copied into R once at the end, so memory use grows with the
+//! columns alone, and a file is never held as a list of records.
+
+use extendr_api::prelude::*;
+use tblout::{field_names, Meta, Reader, RecordKind, RecordParts};
+
+/// A column of the data frame, filled in as records are read.
+enum Column {
+    Character(Vec<String>),
+    Integer(Vec<i32>),
+    Double(Vec<f64>),
+}
+
+impl Column {
+    fn into_robj(self) -> Robj {
+        match self {
+            Column::Character(v) => v.into(),
+            Column::Integer(v) => v.into(),
+            Column::Double(v) => v.into(),
+        }
+    }
+}
+
+/// The columns of the data frame, in the order of `field_names`.
+struct Columns {
+    kind: RecordKind,
+    columns: Vec<Column>,
+    rows: usize,
+}
+
+impl Columns {
+    fn new(kind: RecordKind) -> Columns {
+        // one letter for each column: character, integer or double.
+        // R's integers are 32-bit, so sequence positions are doubles
+        let types = match kind {
+            RecordKind::Dna => "CCCCIIDDDDDCDDDC",
+            RecordKind::Protein => "CCCCDDDDDDDIIIIIIIC",
+        };
+        let columns = types
+            .chars()
+            .map(|t| match t {
+                'I' => Column::Integer(Vec::new()),
+                'D' => Column::Double(Vec::new()),
+                _ => Column::Character(Vec::new()),
+            })
+            .collect();
+        Columns {
+            kind,
+            columns,
+            rows: 0,
+        }
+    }
+
+    /// Add a record as a row, in column order.
+    fn push(&mut self, parts: RecordParts) {
+        let mut c = self.columns.iter_mut();
+        let mut next = || c.next().expect("a column for every field");
+        match parts {
+            RecordParts::Dna(p) => {
+                push_str(next(), p.target_name);
+                push_str(next(), p.target_accession);
+                push_str(next(), p.query_name);
+                push_str(next(), p.query_accession);
+                push_int(next(), p.hmm_from);
+                push_int(next(), p.hmm_to);
+                for value in [p.ali_from, p.ali_to, p.env_from, p.env_to, p.sq_len] {
+                    push_double(next(), value as f64);
+                }
+                push_str(next(), p.strand.to_string());
+                push_double(next(), p.e_value);
+                push_double(next(), p.score.into());
+                push_double(next(), p.bias.into());
+                push_str(next(), p.description);
+            }
+            RecordParts::Protein(p) => {
+                push_str(next(), p.target_name);
+                push_str(next(), p.target_accession);
+                push_str(next(), p.query_name);
+                push_str(next(), p.query_accession);
+                for value in [
+                    p.e_value_full,
+                    p.score_full.into(),
+                    p.bias_full.into(),
+                    p.e_value_best,
+                    p.score_best.into(),
+                    p.bias_best.into(),
+                    p.exp.into(),
+                ] {
+                    push_double(next(), value);
+                }
+                for value in [p.reg, p.clu, p.ov, p.env, p.dom, p.rep, p.inc] {
+                    push_int(next(), value);
+                }
+                push_str(next(), p.description);
+            }
+        }
+        self.rows += 1;
+    }
+
+    /// Build the data frame, with the metadata as its attributes.
+    fn into_data_frame(self, meta: &Meta) -> Result<Robj> {
+        let names = field_names(self.kind).iter().copied();
+        let values = self.columns.into_iter().map(Column::into_robj);
+        let mut df: Robj = List::from_names_and_values(names, values)?.into();
+        // the compact form of the row names 1..n, as R writes them
+        df.set_attrib("row.names", vec![i32::MIN, -(self.rows as i32)])?;
+        df.set_class(["data.frame"])?;
+
+        df.set_attrib("program", meta.program().to_string())?;
+        df.set_attrib("version", meta.version())?;
+        df.set_attrib("pipeline_mode", meta.pipeline_mode())?;
+        df.set_attrib("query_file", meta.query_file().display().to_string())?;
+        df.set_attrib("target_file", meta.target_file().display().to_string())?;
+        df.set_attrib("options", meta.options())?;
+        df.set_attrib("current_dir", meta.current_dir().display().to_string())?;
+        df.set_attrib("date", meta.date())?;
+        Ok(df)
+    }
+}
+
+fn push_str(column: &mut Column, value: String) {
+    match column {
+        Column::Character(v) => v.push(value),
+        _ => unreachable!("not a character column"),
+    }
+}
+
+fn push_int(column: &mut Column, value: i32) {
+    match column {
+        Column::Integer(v) => v.push(value),
+        _ => unreachable!("not an integer column"),
+    }
+}
+
+fn push_double(column: &mut Column, value: f64) {
+    match column {
+        Column::Double(v) => v.push(value),
+        _ => unreachable!("not a double column"),
+    }
+}
+
+/// Read a HMMER tblout file into a data frame.
+///
+/// Each column is named as in the file, with model positions and counts
+/// as integers, sequence positions and lengths, E-values and scores as
+/// doubles and the rest as character.
+/// The metadata at the end of the file (`program`, `version`,
+/// `pipeline_mode`, `query_file`, `target_file`, `options`,
+/// `current_dir` and `date`) is kept as attributes of the data frame.
+///
+/// Records are read one at a time into the columns, so memory use grows
+/// with the columns alone.
+///
+/// @param path The path of the file.
+/// @return A data frame with a row for each hit.
+/// @export
+#[extendr]
+fn read_tblout(path: &str) -> Result<Robj> {
+    let reader = Reader::from_path(path).map_err(|e| Error::Other(format!("{}: {}", path, e)))?;
+    let meta = reader.meta().clone();
+    let kind = meta.program().record_kind().ok_or_else(|| {
+        Error::Other(format!(
+            "{}: the program which produced the file is unknown",
+            path
+        ))
+    })?;
+
+    let mut columns = Columns::new(kind);
+    let mut records = reader.into_records();
+    while let Some(record) = records.next() {
+        let record = record.map_err(|e| {
+            Error::Other(format!("{} line {}: {}", path, records.reader().line(), e))
+        })?;
+        columns.push(record.into_parts());
+    }
+    columns.into_data_frame(&meta)
+}
+
+extendr_module! {
+    mod hmmtblout;
+    fn read_tblout;
+}
@@ -0,0 +1,9 @@
+#!/bin/sh
+# Check that the library builds for the browser, where there is no
+# filesystem, with the `std-fs` feature off. Needs the target, from
+# `rustup target add wasm32-unknown-unknown`.
+set -eu
+cd "$(dirname "$0")/.."
+
+cargo check --lib --target wasm32-unknown-unknown --no-default-features
+cargo check --lib --target wasm32-unknown-unknown --no-default-features --features bgzf,json
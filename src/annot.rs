@@ -0,0 +1,87 @@
+use crate::{
+    record::{DNARecord, Record, Strand},
+    Error, ErrorKind, Result,
+};
+use bio::data_structures::annot_map::AnnotMap;
+use bio_types::{annot::contig::Contig, strand::ReqStrand};
+
+/// The location of a DNA hit on its target, as a `rust-bio` contig.
+/// HMMER's coordinates are 1-based and inclusive, with `from` greater
+/// than `to` on the minus strand, while a contig starts at its 0-based
+/// left-most position whatever its strand.
+impl From<&DNARecord> for Contig<String, ReqStrand> {
+    fn from(r: &DNARecord) -> Contig<String, ReqStrand> {
+        let (start, end) = r.interval();
+        let strand = match r.strand() {
+            Strand::Positive => ReqStrand::Forward,
+            Strand::Negative => ReqStrand::Reverse,
+        };
+        Contig::new(
+            r.target_name(),
+            start as isize - 1,
+            (end - start) as usize + 1,
+            strand,
+        )
+    }
+}
+
+/// Collect records into an `AnnotMap` keyed by target name, at the
+/// locations of their alignments, so they can be found by overlap.
+///
+/// Protein records have no coordinates on the target, so they give an
+/// `ErrorKind::Unsupported` error.
+pub fn annot_map_from_records(
+    records: impl IntoIterator<Item = Result<Record>>,
+) -> Result<AnnotMap<String, Record>> {
+    let mut map = AnnotMap::new();
+    for record in records {
+        let record = record?;
+        let contig = match &record {
+            Record::Dna(r) => Contig::from(r),
+            Record::Protein(r) => {
+                return Err(Error::new(ErrorKind::Unsupported(format!(
+                    "protein record for target {} has no coordinates to map",
+                    r.target_name()
+                ))))
+            }
+        };
+        map.insert_at(record, &contig);
+    }
+    Ok(map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+    use bio_types::annot::loc::Loc;
+
+    #[test]
+    fn test_annot_map() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let map = annot_map_from_records(reader.into_records()).unwrap();
+
+        // 0-based, covering 339201..=339500 on target 2
+        let query = Contig::new("2".to_string(), 339200, 300, ReqStrand::Forward);
+        let mut hits = map
+            .find(&query)
+            .map(|e| e.data().ali_from().unwrap())
+            .collect::<Vec<_>>();
+        hits.sort();
+        assert_eq!(hits, [338944, 339462]);
+
+        let query = Contig::new("7".to_string(), 339200, 300, ReqStrand::Forward);
+        assert_eq!(map.find(&query).count(), 0);
+    }
+
+    #[test]
+    fn test_contig() {
+        let line = "6                    -          TR                   -                5     360  61501865  61501510  61501866  61501509 170805979    -     3.4e-34  125.9   2.2  -";
+        let record = crate::reader::parse_dna_line(line).unwrap();
+        let contig = Contig::from(&record);
+        assert_eq!(contig.refid(), "6");
+        assert_eq!(contig.start(), 61501509);
+        assert_eq!(contig.length(), 356);
+        assert_eq!(contig.strand(), ReqStrand::Reverse);
+    }
+}
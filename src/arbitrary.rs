@@ -0,0 +1,169 @@
+use crate::{
+    header::Header,
+    record::{DNAParts, DNARecord, Meta, Program, ProteinParts, ProteinRecord},
+};
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+use std::path::PathBuf;
+
+/// The programs with a header, i.e. all but `Program::None`.
+const PROGRAMS: [Program; 6] = [
+    Program::Nhmmer,
+    Program::Nhmmscan,
+    Program::Jackhmmer,
+    Program::Hmmscan,
+    Program::Hmmsearch,
+    Program::Phmmer,
+];
+
+/// A name or accession, which HMMER writes as a single column: no
+/// whitespace, and not starting with `#` as it may be the first column
+/// on the line.
+fn column(u: &mut Unstructured) -> Result<String> {
+    let s = String::arbitrary(u)?
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    let s = s.trim_start_matches('#');
+    Ok(if s.is_empty() { "-" } else { s }.to_string())
+}
+
+/// Text filling the rest of a line, such as a description or a
+/// metadata value.
+fn rest_of_line(u: &mut Unstructured) -> Result<String> {
+    Ok(String::arbitrary(u)?
+        .chars()
+        .filter(|c| !matches!(c, '\n' | '\r'))
+        .collect())
+}
+
+/// A description, which reads back as `-` if it is blank.
+fn description(u: &mut Unstructured) -> Result<String> {
+    let s = rest_of_line(u)?;
+    Ok(if s.trim().is_empty() {
+        "-".to_string()
+    } else {
+        s
+    })
+}
+
+/// E-values are never negative.
+fn e_value(u: &mut Unstructured) -> Result<f64> {
+    Ok(f64::arbitrary(u)?.abs())
+}
+
+/// Records are generated as HMMER could write them, with names which
+/// are single columns and descriptions on one line, so that they can
+/// be written and read back.
+impl<'a> Arbitrary<'a> for DNARecord {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<DNARecord> {
+        Ok(DNARecord::from_parts(DNAParts {
+            target_name: column(u)?,
+            target_accession: column(u)?,
+            query_name: column(u)?,
+            query_accession: column(u)?,
+            hmm_from: u.arbitrary()?,
+            hmm_to: u.arbitrary()?,
+            ali_from: u.arbitrary()?,
+            ali_to: u.arbitrary()?,
+            env_from: u.arbitrary()?,
+            env_to: u.arbitrary()?,
+            sq_len: u.arbitrary()?,
+            strand: u.arbitrary()?,
+            e_value: e_value(u)?,
+            score: u.arbitrary()?,
+            bias: u.arbitrary()?,
+            description: description(u)?,
+        }))
+    }
+}
+
+impl<'a> Arbitrary<'a> for ProteinRecord {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<ProteinRecord> {
+        Ok(ProteinRecord::from_parts(ProteinParts {
+            target_name: column(u)?,
+            target_accession: column(u)?,
+            query_name: column(u)?,
+            query_accession: column(u)?,
+            e_value_full: e_value(u)?,
+            score_full: u.arbitrary()?,
+            bias_full: u.arbitrary()?,
+            e_value_best: e_value(u)?,
+            score_best: u.arbitrary()?,
+            bias_best: u.arbitrary()?,
+            exp: u.arbitrary()?,
+            reg: u.arbitrary()?,
+            clu: u.arbitrary()?,
+            ov: u.arbitrary()?,
+            env: u.arbitrary()?,
+            dom: u.arbitrary()?,
+            rep: u.arbitrary()?,
+            inc: u.arbitrary()?,
+            description: description(u)?,
+        }))
+    }
+}
+
+/// The header HMMER writes for one of the programs.
+impl<'a> Arbitrary<'a> for Header {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Header> {
+        let program = *u.choose(&PROGRAMS)?;
+        Ok(Header::for_program(program).expect("every program but None has a header"))
+    }
+}
+
+/// Metadata for one of the programs, with values which fit on their
+/// lines. Surrounding whitespace is trimmed when the metadata is read,
+/// so there is none.
+impl<'a> Arbitrary<'a> for Meta {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Meta> {
+        let mut meta = Meta::for_program(*u.choose(&PROGRAMS)?);
+        let mut value = || rest_of_line(u).map(|s| s.trim().to_string());
+        meta.set_version(value()?);
+        meta.set_pipeline_mode(value()?);
+        meta.set_query_file(PathBuf::from(value()?));
+        meta.set_target_file(PathBuf::from(value()?));
+        meta.set_options(value()?);
+        meta.set_current_dir(PathBuf::from(value()?));
+        meta.set_date(value()?);
+        Ok(meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Record, Writer};
+
+    #[test]
+    fn test_arbitrary_round_trip() {
+        // a fixed, varied input standing in for the fuzzer's
+        let data = (0..4096u32)
+            .map(|i| (i.wrapping_mul(2654435761) >> 13) as u8)
+            .collect::<Vec<_>>();
+        let mut u = Unstructured::new(&data);
+
+        let meta = Meta::arbitrary(&mut u).unwrap();
+        let records = (0..8)
+            .map(|_| match meta.program().is_dna_search() {
+                true => DNARecord::arbitrary(&mut u).map(Record::Dna),
+                false => ProteinRecord::arbitrary(&mut u).map(Record::Protein),
+            })
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let header = Header::for_program(meta.program()).unwrap();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_all(&header, &records, &meta).unwrap();
+        let out = writer.into_inner().unwrap();
+
+        let mut reader = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(reader.meta().program(), meta.program());
+        let read = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        assert_eq!(read.len(), records.len());
+        for (read, written) in read.iter().zip(&records) {
+            assert_eq!(read.target_name(), written.target_name());
+            assert_eq!(read.query_accession(), written.query_accession());
+            assert_eq!(read.description(), written.description());
+        }
+    }
+}
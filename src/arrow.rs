@@ -0,0 +1,336 @@
+use crate::{
+    record::{Record, RecordKind, RecordParts},
+    tsv::field_names,
+    Error, ErrorKind, Result,
+};
+use arrow_array::{
+    builder::{
+        ArrayBuilder, Float32Builder, Float64Builder, Int32Builder, Int64Builder, StringBuilder,
+        StringDictionaryBuilder,
+    },
+    types::Int32Type,
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::sync::Arc;
+
+/// The columns of the Arrow batches made by `ArrowBatchIter`.
+///
+/// The columns are named as in `field_names`, with sequence coordinates
+/// and lengths as `Int64`, other integers as `Int32`, E-values as
+/// `Float64`, scores as `Float32` and the rest as strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArrowOptions {
+    /// The kind of the records.
+    kind: RecordKind,
+    /// Whether query names are dictionary encoded.
+    dictionary_query_names: bool,
+}
+
+impl ArrowOptions {
+    /// Options for records of `kind`, with dictionary encoded query
+    /// names.
+    pub fn new(kind: RecordKind) -> ArrowOptions {
+        ArrowOptions {
+            kind,
+            dictionary_query_names: true,
+        }
+    }
+
+    /// Set whether the query names are dictionary encoded, which is
+    /// the default. HMMER writes many hits for each query, so the names
+    /// repeat heavily.
+    pub fn dictionary_query_names(mut self, yes: bool) -> ArrowOptions {
+        self.dictionary_query_names = yes;
+        self
+    }
+
+    /// The schema of the batches.
+    pub fn schema(&self) -> SchemaRef {
+        let fields = field_names(self.kind)
+            .iter()
+            .zip(self.data_types())
+            .map(|(name, data_type)| Field::new(*name, data_type, false))
+            .collect::<Vec<_>>();
+        Arc::new(Schema::new(fields))
+    }
+
+    /// The type of each column, in order.
+    fn data_types(&self) -> Vec<DataType> {
+        use DataType::{Float32 as F32, Float64 as F64, Int32 as I32, Int64 as I64, Utf8};
+        let query = if self.dictionary_query_names {
+            DataType::Dictionary(Box::new(I32), Box::new(Utf8))
+        } else {
+            Utf8
+        };
+        let names = [Utf8, Utf8, query, Utf8];
+        let rest = match self.kind {
+            RecordKind::Dna => vec![I32, I32, I64, I64, I64, I64, I64, Utf8, F64, F32, F32],
+            RecordKind::Protein => vec![
+                F64, F32, F32, F64, F32, F32, F32, I32, I32, I32, I32, I32, I32, I32,
+            ],
+        };
+        names.into_iter().chain(rest).chain([Utf8]).collect()
+    }
+}
+
+/// An iterator which builds Arrow record batches of `batch_size`
+/// records, for use by Arrow based tools in the same process, such as
+/// DataFusion.
+///
+/// Records are added to column builders as they are read, so only one
+/// batch is held in memory. All the records must be of the kind given
+/// in the options, or an `ErrorKind::InvalidInput` error is returned.
+pub struct ArrowBatchIter<I> {
+    /// The records to convert.
+    records: I,
+    /// The schema of every batch.
+    schema: SchemaRef,
+    /// The kind of the records.
+    kind: RecordKind,
+    /// The number of records in each batch but the last.
+    batch_size: usize,
+    /// Whether the records have run out or an error has been returned.
+    done: bool,
+}
+
+impl<I: Iterator<Item = Result<Record>>> ArrowBatchIter<I> {
+    /// Construct a new iterator over batches of the records. A batch
+    /// size of zero is taken as one.
+    pub fn new(
+        records: impl IntoIterator<IntoIter = I>,
+        options: ArrowOptions,
+        batch_size: usize,
+    ) -> ArrowBatchIter<I> {
+        ArrowBatchIter {
+            records: records.into_iter(),
+            schema: options.schema(),
+            kind: options.kind,
+            batch_size: batch_size.max(1),
+            done: false,
+        }
+    }
+
+    /// The schema of the batches.
+    pub fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+
+    /// Build the next batch, or `None` if there are no more records.
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let mut columns = self
+            .schema
+            .fields()
+            .iter()
+            .map(|f| Column::new(f.data_type(), self.batch_size))
+            .collect::<Vec<_>>();
+        let mut n = 0;
+        while n < self.batch_size {
+            let record = match self.records.next() {
+                Some(record) => record?,
+                None => break,
+            };
+            if record.kind() != self.kind {
+                return Err(Error::new(ErrorKind::InvalidInput(format!(
+                    "cannot add a {:?} record for target {} to a batch of {:?} records",
+                    record.kind(),
+                    record.target_name(),
+                    self.kind
+                ))));
+            }
+            append(&mut columns, record);
+            n += 1;
+        }
+        if n == 0 {
+            return Ok(None);
+        }
+        let arrays = columns.iter_mut().map(Column::finish).collect();
+        RecordBatch::try_new(self.schema.clone(), arrays)
+            .map(Some)
+            .map_err(|e| Error::new(ErrorKind::Arrow(e)))
+    }
+}
+
+impl<I: Iterator<Item = Result<Record>>> Iterator for ArrowBatchIter<I> {
+    type Item = Result<RecordBatch>;
+
+    fn next(&mut self) -> Option<Result<RecordBatch>> {
+        if self.done {
+            return None;
+        }
+        let batch = self.next_batch().transpose();
+        if !matches!(batch, Some(Ok(_))) {
+            self.done = true;
+        }
+        batch
+    }
+}
+
+/// A builder for one column of a batch.
+enum Column {
+    Str(StringBuilder),
+    Dict(StringDictionaryBuilder<Int32Type>),
+    I32(Int32Builder),
+    I64(Int64Builder),
+    F64(Float64Builder),
+    F32(Float32Builder),
+}
+
+impl Column {
+    fn new(data_type: &DataType, capacity: usize) -> Column {
+        match data_type {
+            DataType::Dictionary(..) => Column::Dict(StringDictionaryBuilder::new()),
+            DataType::Int32 => Column::I32(Int32Builder::with_capacity(capacity)),
+            DataType::Int64 => Column::I64(Int64Builder::with_capacity(capacity)),
+            DataType::Float64 => Column::F64(Float64Builder::with_capacity(capacity)),
+            DataType::Float32 => Column::F32(Float32Builder::with_capacity(capacity)),
+            _ => Column::Str(StringBuilder::new()),
+        }
+    }
+
+    fn str(&mut self, value: &str) {
+        match self {
+            Column::Str(b) => b.append_value(value),
+            Column::Dict(b) => b.append_value(value),
+            _ => unreachable!("not a string column"),
+        }
+    }
+
+    fn i32(&mut self, value: i32) {
+        match self {
+            Column::I32(b) => b.append_value(value),
+            _ => unreachable!("not an Int32 column"),
+        }
+    }
+
+    fn i64(&mut self, value: i64) {
+        match self {
+            Column::I64(b) => b.append_value(value),
+            _ => unreachable!("not an Int64 column"),
+        }
+    }
+
+    fn f64(&mut self, value: f64) {
+        match self {
+            Column::F64(b) => b.append_value(value),
+            _ => unreachable!("not a Float64 column"),
+        }
+    }
+
+    fn f32(&mut self, value: f32) {
+        match self {
+            Column::F32(b) => b.append_value(value),
+            _ => unreachable!("not a Float32 column"),
+        }
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            Column::Str(b) => Arc::new(b.finish()),
+            Column::Dict(b) => Arc::new(b.finish()),
+            Column::I32(b) => ArrayBuilder::finish(b),
+            Column::I64(b) => ArrayBuilder::finish(b),
+            Column::F64(b) => ArrayBuilder::finish(b),
+            Column::F32(b) => ArrayBuilder::finish(b),
+        }
+    }
+}
+
+/// Append the fields of a record to the columns, in column order.
+fn append(columns: &mut [Column], record: Record) {
+    let mut c = columns.iter_mut();
+    let mut next = || c.next().expect("a column for every field");
+    match record.into_parts() {
+        RecordParts::Dna(p) => {
+            next().str(&p.target_name);
+            next().str(&p.target_accession);
+            next().str(&p.query_name);
+            next().str(&p.query_accession);
+            next().i32(p.hmm_from);
+            next().i32(p.hmm_to);
+            for value in [p.ali_from, p.ali_to, p.env_from, p.env_to, p.sq_len] {
+                next().i64(value);
+            }
+            next().str(&p.strand.to_string());
+            next().f64(p.e_value);
+            next().f32(p.score);
+            next().f32(p.bias);
+            next().str(&p.description);
+        }
+        RecordParts::Protein(p) => {
+            next().str(&p.target_name);
+            next().str(&p.target_accession);
+            next().str(&p.query_name);
+            next().str(&p.query_accession);
+            next().f64(p.e_value_full);
+            next().f32(p.score_full);
+            next().f32(p.bias_full);
+            next().f64(p.e_value_best);
+            next().f32(p.score_best);
+            next().f32(p.bias_best);
+            next().f32(p.exp);
+            for value in [p.reg, p.clu, p.ov, p.env, p.dom, p.rep, p.inc] {
+                next().i32(value);
+            }
+            next().str(&p.description);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+    use arrow_array::{cast::AsArray, types::Int64Type, Array};
+
+    #[test]
+    fn test_dna_batches() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let options = ArrowOptions::new(RecordKind::Dna);
+        let batches = ArrowBatchIter::new(reader.into_records(), options, 10)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        let rows = batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>();
+        assert_eq!(rows, [10, 10, 10, 1]);
+        assert_eq!(batches[0].schema(), options.schema());
+        assert_eq!(batches[0].num_columns(), 16);
+
+        let query = batches[0].column_by_name("query_name").unwrap();
+        let query = query.as_dictionary::<Int32Type>();
+        // every hit is for the one query
+        assert_eq!(query.values().len(), 1);
+        assert_eq!(query.values().as_string::<i32>().value(0), "TR");
+
+        let ali_from = batches[0].column_by_name("ali_from").unwrap();
+        let ali_from = ali_from.as_primitive::<Int64Type>();
+        assert_eq!(ali_from.value(1), 61501865);
+        let strand = batches[0].column_by_name("strand").unwrap();
+        assert_eq!(strand.as_string::<i32>().value(1), "-");
+    }
+
+    #[test]
+    fn test_protein_batches() {
+        let reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let options = ArrowOptions::new(RecordKind::Protein).dictionary_query_names(false);
+        let mut batches = ArrowBatchIter::new(reader.into_records(), options, 100);
+        let batch = batches.next().unwrap().unwrap();
+        assert!(batches.next().is_none());
+
+        assert_eq!(batch.num_rows(), 1);
+        assert_eq!(batch.num_columns(), 19);
+        let query = batch.column_by_name("query_name").unwrap();
+        assert_eq!(query.data_type(), &DataType::Utf8);
+        assert_eq!(query.as_string::<i32>().value(0), "SOR");
+    }
+
+    #[test]
+    fn test_kind_mismatch() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let options = ArrowOptions::new(RecordKind::Protein);
+        let mut batches = ArrowBatchIter::new(reader.into_records(), options, 10);
+        let err = batches.next().unwrap().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+        assert!(batches.next().is_none());
+    }
+}
@@ -0,0 +1,260 @@
+use crate::{hit::Hit, Error, ErrorKind, Result, Strand};
+use std::io::{self, Write};
+
+/// The value the BED score column is scaled from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreSource {
+    /// The bit score of the hit.
+    BitScore,
+    /// The E-value of the hit as `-log10(E-value)`, so that better hits
+    /// score higher. An E-value of zero scores the most.
+    NegLog10EValue,
+}
+
+/// Options for writing hits as a BED track for a genome browser.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BedTrackOptions {
+    /// The name of the track.
+    name: String,
+    /// The description of the track, if it has one.
+    description: Option<String>,
+    /// The value the score column is scaled from.
+    score_from: ScoreSource,
+    /// The values scaled to scores of 0 and 1000.
+    score_range: (f32, f32),
+    /// Whether to color the hits by strand, which needs BED9.
+    item_rgb_by_strand: bool,
+}
+
+impl BedTrackOptions {
+    /// Options for a track named `name`, with scores scaled from bit
+    /// scores of 0 to 100 and no colors.
+    pub fn new(name: &str) -> BedTrackOptions {
+        BedTrackOptions {
+            name: name.into(),
+            description: None,
+            score_from: ScoreSource::BitScore,
+            score_range: (0.0, 100.0),
+            item_rgb_by_strand: false,
+        }
+    }
+
+    /// Set the description of the track.
+    pub fn description(mut self, description: &str) -> BedTrackOptions {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Set the value the score column is scaled from,
+    /// `ScoreSource::BitScore` by default.
+    pub fn score_from(mut self, score_from: ScoreSource) -> BedTrackOptions {
+        self.score_from = score_from;
+        self
+    }
+
+    /// Set the values scaled to scores of 0 and 1000, `(0.0, 100.0)` by
+    /// default. Values in between are scaled linearly, and those beyond
+    /// are clamped.
+    pub fn score_range(mut self, min: f32, max: f32) -> BedTrackOptions {
+        self.score_range = (min, max);
+        self
+    }
+
+    /// Color the hits by strand, blue on the plus strand and red on the
+    /// minus, writing BED9 rather than BED6.
+    pub fn item_rgb_by_strand(mut self, yes: bool) -> BedTrackOptions {
+        self.item_rgb_by_strand = yes;
+        self
+    }
+
+    /// The `track` line heading the file.
+    fn track_line(&self) -> String {
+        let mut line = format!("track name=\"{}\"", quoted(&self.name));
+        if let Some(description) = &self.description {
+            line.push_str(&format!(" description=\"{}\"", quoted(description)));
+        }
+        line.push_str(" useScore=1");
+        if self.item_rgb_by_strand {
+            line.push_str(" itemRgb=\"On\"");
+        }
+        line
+    }
+
+    /// Scale a value into the BED score range of 0 to 1000.
+    fn scale(&self, value: f64) -> u32 {
+        let (min, max) = (self.score_range.0 as f64, self.score_range.1 as f64);
+        if value >= max {
+            1000
+        } else if value <= min {
+            0
+        } else {
+            ((value - min) / (max - min) * 1000.0).round() as u32
+        }
+    }
+}
+
+/// Track lines have no escapes, so double quotes in a value become
+/// single quotes.
+fn quoted(s: &str) -> String {
+    s.replace('"', "'")
+}
+
+/// Write DNA records as a BED track for the UCSC and IGV browsers: a
+/// `track` line, then a feature for each record. Returns the number of
+/// features written. The records may be `Record`s, the concrete
+/// records, or `Hit`s.
+///
+/// Each feature spans the alignment on the target, in BED's 0-based,
+/// half-open coordinates, and is named after the query. Its score is
+/// scaled into 0 to 1000 (see `BedTrackOptions::score_range`), which
+/// the browsers use to shade it. With `item_rgb_by_strand` the
+/// features are BED9, with the thick part the whole feature. Hits
+/// without coordinates on the target, such as those of protein
+/// records, are rejected with an `ErrorKind::Unsupported` error.
+pub fn to_bed_track<W: Write, R: Into<Hit>>(
+    records: impl IntoIterator<Item = Result<R>>,
+    w: W,
+    options: &BedTrackOptions,
+) -> Result<usize> {
+    let mut w = io::BufWriter::new(w);
+    writeln!(w, "{}", options.track_line())?;
+
+    let mut n = 0;
+    for record in records {
+        let hit: Hit = record?.into();
+        let ((start, end), strand) = coordinates(&hit, "BED")?;
+        let value = match options.score_from {
+            ScoreSource::BitScore => hit.score as f64,
+            ScoreSource::NegLog10EValue => -hit.e_value.log10(),
+        };
+        write!(
+            w,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            hit.target_name,
+            start - 1,
+            end,
+            hit.query_name,
+            options.scale(value),
+            strand
+        )?;
+        if options.item_rgb_by_strand {
+            let rgb = match strand {
+                Strand::Positive => "0,0,255",
+                Strand::Negative => "255,0,0",
+            };
+            write!(w, "\t{}\t{}\t{}", start - 1, end, rgb)?;
+        }
+        writeln!(w)?;
+        n += 1;
+    }
+    w.flush()?;
+    Ok(n)
+}
+
+/// The interval and strand of a hit, or an `ErrorKind::Unsupported`
+/// error naming the `format` if it has none.
+pub(crate) fn coordinates(hit: &Hit, format: &str) -> Result<((i64, i64), Strand)> {
+    match (hit.interval, hit.strand) {
+        (Some(interval), Some(strand)) => Ok((interval, strand)),
+        _ => Err(Error::new(ErrorKind::Unsupported(format!(
+            "hit on target {} has no coordinates for {}",
+            hit.target_name, format
+        )))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    #[test]
+    fn test_scale() {
+        let options = BedTrackOptions::new("hits").score_range(20.0, 120.0);
+        assert_eq!(options.scale(20.0), 0);
+        assert_eq!(options.scale(70.0), 500);
+        assert_eq!(options.scale(120.0), 1000);
+        assert_eq!(options.scale(45.04), 250);
+        // beyond the anchors
+        assert_eq!(options.scale(-3.0), 0);
+        assert_eq!(options.scale(500.0), 1000);
+
+        let options = BedTrackOptions::new("hits")
+            .score_from(ScoreSource::NegLog10EValue)
+            .score_range(0.0, 40.0);
+        assert_eq!(options.scale(-(1e-20f64).log10()), 500);
+        assert_eq!(options.scale(-(0.0f64).log10()), 1000);
+        assert_eq!(options.scale(-(9.9f64).log10()), 0);
+    }
+
+    #[test]
+    fn test_track_line() {
+        let options = BedTrackOptions::new("TR hits");
+        assert_eq!(options.track_line(), "track name=\"TR hits\" useScore=1");
+
+        let options = BedTrackOptions::new("TR")
+            .description("nhmmer \"TR\" hits")
+            .item_rgb_by_strand(true);
+        assert_eq!(
+            options.track_line(),
+            "track name=\"TR\" description=\"nhmmer 'TR' hits\" useScore=1 itemRgb=\"On\""
+        );
+    }
+
+    #[test]
+    fn test_bed_track() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let options = BedTrackOptions::new("TR").score_range(0.0, 400.0);
+        let mut out = Vec::new();
+        assert_eq!(
+            to_bed_track(reader.into_records(), &mut out, &options).unwrap(),
+            31
+        );
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "track name=\"TR\" useScore=1");
+        // bit scores of 136.7 and 122.3
+        assert_eq!(lines[1], "2\t338943\t339292\tTR\t342\t+");
+        assert_eq!(lines[2], "6\t61501509\t61501865\tTR\t306\t-");
+
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let options = options.item_rgb_by_strand(true);
+        let mut out = Vec::new();
+        to_bed_track(reader.into_records(), &mut out, &options).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[1],
+            "2\t338943\t339292\tTR\t342\t+\t338943\t339292\t0,0,255"
+        );
+        assert_eq!(
+            lines[2],
+            "6\t61501509\t61501865\tTR\t306\t-\t61501509\t61501865\t255,0,0"
+        );
+        assert!(lines[1..].iter().all(|l| l.split('\t').count() == 9));
+    }
+
+    #[test]
+    fn test_bed_track_rejects_protein() {
+        let reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let err = to_bed_track(
+            reader.into_records(),
+            io::sink(),
+            &BedTrackOptions::new("x"),
+        )
+        .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+    }
+
+    #[test]
+    fn test_bed_track_from_hits() {
+        let options = BedTrackOptions::new("hits");
+        let records = || Reader::from_path("./data/test.tbl").unwrap().into_records();
+        let mut from_records = Vec::new();
+        to_bed_track(records(), &mut from_records, &options).unwrap();
+        let hits = records().map(|r| r.map(Hit::from));
+        let mut from_hits = Vec::new();
+        assert_eq!(to_bed_track(hits, &mut from_hits, &options).unwrap(), 31);
+        assert_eq!(from_records, from_hits);
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    record::{Record, Strand},
+    Error, ErrorKind, Result,
+};
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+/// Options for converting records to a bedGraph coverage track.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BedGraphOptions {
+    /// Only count the hits on this strand, or all hits if `None`.
+    strand: Option<Strand>,
+}
+
+impl BedGraphOptions {
+    /// Only count the hits on `strand`, by the strand column. All hits
+    /// are counted by default.
+    pub fn strand(mut self, strand: Strand) -> BedGraphOptions {
+        self.strand = Some(strand);
+        self
+    }
+}
+
+/// Write the depth of hits along each target as bedGraph, with a
+/// `chrom start end depth` line for each run of positions covered by
+/// the same number of hits. Returns the number of lines written.
+///
+/// The coordinates are 0-based and half-open, as BED's are, and
+/// positions covered by no hits are left out. Targets are written in
+/// the order they are first seen, each sorted by position. The depth
+/// is found by sweeping over the starts and ends of the hits, so
+/// memory grows with the number of hits rather than the length of the
+/// targets. Protein records have no coordinates on the target, so are
+/// rejected with an `ErrorKind::Unsupported` error.
+pub fn to_bedgraph<W: Write>(
+    records: impl IntoIterator<Item = Result<Record>>,
+    w: W,
+    options: &BedGraphOptions,
+) -> Result<usize> {
+    let mut targets: Vec<String> = Vec::new();
+    let mut intervals: HashMap<String, Vec<(i64, i64)>> = HashMap::new();
+    for record in records {
+        let record = match record? {
+            Record::Dna(r) => r,
+            Record::Protein(r) => {
+                return Err(Error::new(ErrorKind::Unsupported(format!(
+                    "protein record for target {} has no coordinates for bedGraph",
+                    r.target_name()
+                ))))
+            }
+        };
+        if options.strand.is_some_and(|s| s != record.strand()) {
+            continue;
+        }
+        let (start, end) = record.interval();
+        let target = record.target_name();
+        match intervals.get_mut(&target) {
+            Some(ivs) => ivs.push((start - 1, end)),
+            None => {
+                targets.push(target.clone());
+                intervals.insert(target, vec![(start - 1, end)]);
+            }
+        }
+    }
+
+    let mut w = io::BufWriter::new(w);
+    let mut n = 0;
+    for target in targets {
+        for (start, end, depth) in depth_profile(&intervals[&target]) {
+            writeln!(w, "{}\t{}\t{}\t{}", target, start, end, depth)?;
+            n += 1;
+        }
+    }
+    w.flush()?;
+    Ok(n)
+}
+
+/// The depth of a set of half-open intervals as `(start, end, depth)`
+/// runs in order, with adjacent runs of the same depth merged and
+/// runs of no depth left out.
+fn depth_profile(intervals: &[(i64, i64)]) -> Vec<(i64, i64, u64)> {
+    // each interval adds one at its start and takes one at its end
+    let mut events = intervals
+        .iter()
+        .flat_map(|&(start, end)| [(start, 1), (end, -1)])
+        .collect::<Vec<(i64, i64)>>();
+    events.sort_unstable();
+
+    let mut runs: Vec<(i64, i64, u64)> = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < events.len() {
+        // apply all the events at a position before looking at the depth
+        let position = events[i].0;
+        let before = depth;
+        while i < events.len() && events[i].0 == position {
+            depth += events[i].1;
+            i += 1;
+        }
+        if depth == before {
+            continue;
+        }
+        if before > 0 {
+            if let Some(run) = runs.last_mut() {
+                run.1 = position;
+            }
+        }
+        if depth > 0 {
+            runs.push((position, position, depth as u64));
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader};
+
+    /// A record for a hit on target `chr1` from `from` to `to`.
+    fn hit(from: i64, to: i64) -> Result<Record> {
+        let strand = if from <= to { "+" } else { "-" };
+        let line = format!(
+            "chr1 -  TR  -  2  317  {}  {}  {}  {}  133811386  {}  1.9e-37  136.7  1.4  -",
+            from, to, from, to, strand
+        );
+        Ok(Record::Dna(parse_dna_line(&line).unwrap()))
+    }
+
+    fn bedgraph(records: Vec<Result<Record>>, options: &BedGraphOptions) -> String {
+        let mut out = Vec::new();
+        to_bedgraph(records, &mut out, options).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_depth_profile() {
+        assert_eq!(depth_profile(&[]), vec![]);
+        assert_eq!(
+            depth_profile(&[(0, 10), (5, 15), (20, 30)]),
+            vec![(0, 5, 1), (5, 10, 2), (10, 15, 1), (20, 30, 1)]
+        );
+        // one ending where another starts leaves the depth as it was
+        assert_eq!(depth_profile(&[(10, 20), (0, 10)]), vec![(0, 20, 1)]);
+        assert_eq!(depth_profile(&[(0, 10), (0, 10)]), vec![(0, 10, 2)]);
+    }
+
+    #[test]
+    fn test_bedgraph_steps() {
+        // 1-based, inclusive hits, one of them on the minus strand
+        let records = vec![hit(1, 100), hit(51, 150), hit(120, 76), hit(201, 250)];
+        assert_eq!(
+            bedgraph(records, &BedGraphOptions::default()),
+            concat!(
+                "chr1\t0\t50\t1\n",
+                "chr1\t50\t75\t2\n",
+                "chr1\t75\t100\t3\n",
+                "chr1\t100\t120\t2\n",
+                "chr1\t120\t150\t1\n",
+                "chr1\t200\t250\t1\n",
+            )
+        );
+
+        let records = vec![hit(1, 100), hit(51, 150), hit(120, 76), hit(201, 250)];
+        let options = BedGraphOptions::default().strand(Strand::Negative);
+        assert_eq!(bedgraph(records, &options), "chr1\t75\t120\t1\n");
+    }
+
+    #[test]
+    fn test_bedgraph_targets() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let mut out = Vec::new();
+        let n = to_bedgraph(reader.into_records(), &mut out, &BedGraphOptions::default()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), n);
+        // the first hit, on target 2 from 338944 to 339292
+        assert_eq!(out.lines().next(), Some("2\t338943\t339292\t1"));
+        for line in out.lines() {
+            let cols = line.split('\t').collect::<Vec<_>>();
+            assert_eq!(cols.len(), 4);
+            assert!(cols[1].parse::<i64>().unwrap() < cols[2].parse::<i64>().unwrap());
+            assert!(cols[3].parse::<u64>().unwrap() > 0);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{Error, ErrorKind, Result};
+use flate2::{read::DeflateDecoder, Crc};
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+/// The gzip magic bytes.
+const GZIP_MAGIC: [u8; 2] = [31, 139];
+
+/// A reader over a BGZF (blocked gzip) compressed file, as written
+/// by `bgzip`.
+///
+/// Positions in the file are BGZF virtual offsets: the compressed
+/// offset of a block shifted left by 16 bits, plus the offset within
+/// the uncompressed block. `Seek` only accepts `SeekFrom::Start` with
+/// such a virtual offset.
+pub struct BgzfReader<R> {
+    /// The underlying compressed reader.
+    inner: R,
+    /// The uncompressed data of the current block.
+    block: Vec<u8>,
+    /// The read position within the current block.
+    pos: usize,
+    /// The compressed offset of the current block.
+    block_offset: u64,
+    /// The compressed offset of the next block.
+    next_offset: u64,
+}
+
+impl<R: Read> BgzfReader<R> {
+    /// Construct a new BGZF reader. The first block is read straight
+    /// away so that plain gzip files can be rejected with a clear error.
+    pub fn new(inner: R) -> Result<BgzfReader<R>> {
+        let mut reader = BgzfReader {
+            inner,
+            block: Vec::new(),
+            pos: 0,
+            block_offset: 0,
+            next_offset: 0,
+        };
+        reader.read_block()?;
+        Ok(reader)
+    }
+
+    /// The virtual offset of the next byte to be read.
+    pub fn virtual_offset(&self) -> u64 {
+        if self.pos == self.block.len() {
+            self.next_offset << 16
+        } else {
+            (self.block_offset << 16) | self.pos as u64
+        }
+    }
+
+    /// Read the next block, returning false at the end of the file.
+    fn read_block(&mut self) -> Result<bool> {
+        let mut header = [0; 12];
+        match self.inner.read_exact(&mut header[..1]) {
+            Ok(()) => self.inner.read_exact(&mut header[1..])?,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                self.block.clear();
+                self.pos = 0;
+                self.block_offset = self.next_offset;
+                return Ok(false);
+            }
+            Err(e) => return Err(Error::new(ErrorKind::Io(e))),
+        }
+
+        if header[..2] != GZIP_MAGIC {
+            return Err(Error::new(ErrorKind::Parser(format!(
+                "the data at offset {} is not gzip compressed",
+                self.next_offset
+            ))));
+        }
+        // FEXTRA must be set for the block size subfield to exist
+        if header[3] & 4 == 0 {
+            return Err(not_bgzf());
+        }
+
+        let xlen = u16::from_le_bytes([header[10], header[11]]) as usize;
+        let mut extra = vec![0; xlen];
+        self.inner.read_exact(&mut extra)?;
+
+        // look for the `BC` subfield holding the total block size - 1
+        let mut bsize = None;
+        let mut i = 0;
+        while i + 4 <= xlen {
+            let slen = u16::from_le_bytes([extra[i + 2], extra[i + 3]]) as usize;
+            if extra[i] == b'B' && extra[i + 1] == b'C' && slen == 2 && i + 6 <= xlen {
+                bsize = Some(u16::from_le_bytes([extra[i + 4], extra[i + 5]]) as usize);
+            }
+            i += 4 + slen;
+        }
+        let bsize = bsize.ok_or_else(not_bgzf)?;
+
+        // the remaining compressed data, then the CRC32 and ISIZE
+        let cdata_len = bsize
+            .checked_sub(xlen + 19)
+            .ok_or_else(|| Error::new(ErrorKind::Parser("malformed BGZF block size".into())))?;
+        let mut cdata = vec![0; cdata_len];
+        self.inner.read_exact(&mut cdata)?;
+        let mut trailer = [0; 8];
+        self.inner.read_exact(&mut trailer)?;
+        let crc32 = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let isize = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]) as usize;
+
+        self.block.clear();
+        self.block.reserve(isize);
+        DeflateDecoder::new(&cdata[..]).read_to_end(&mut self.block)?;
+        // check the block against its trailer, so corruption is an
+        // error rather than garbage records
+        let mut crc = Crc::new();
+        crc.update(&self.block);
+        if self.block.len() != isize || crc.sum() != crc32 {
+            return Err(Error::new(ErrorKind::Parser(format!(
+                "the BGZF block at offset {} is corrupt: its size or CRC32 does not match",
+                self.next_offset
+            ))));
+        }
+        self.pos = 0;
+        self.block_offset = self.next_offset;
+        self.next_offset += bsize as u64 + 1;
+
+        Ok(true)
+    }
+}
+
+/// The error for gzip files which are not BGZF.
+fn not_bgzf() -> Error {
+    Error::new(ErrorKind::Parser(
+        "the file is gzip but not BGZF compressed, so it cannot be indexed; \
+         recompress it with `bgzip`"
+            .into(),
+    ))
+}
+
+impl<R: Read> Read for BgzfReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for BgzfReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // skip over empty blocks, such as the EOF marker
+        while self.pos == self.block.len() {
+            if !self.read_block().map_err(into_io)? {
+                break;
+            }
+        }
+        Ok(&self.block[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.block.len());
+    }
+}
+
+impl<R: Read + Seek> Seek for BgzfReader<R> {
+    /// Seek to a virtual offset. Only `SeekFrom::Start` is supported.
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let voffset = match pos {
+            SeekFrom::Start(v) => v,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "BGZF files can only be seeked to a virtual offset",
+                ))
+            }
+        };
+
+        let (coffset, uoffset) = (voffset >> 16, (voffset & 0xffff) as usize);
+        self.inner.seek(SeekFrom::Start(coffset))?;
+        self.next_offset = coffset;
+        self.read_block().map_err(into_io)?;
+        if uoffset > self.block.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("virtual offset {} is past the end of its block", voffset),
+            ));
+        }
+        self.pos = uoffset;
+        Ok(voffset)
+    }
+}
+
+/// Convert a crate error back into an I/O error for the `io` traits.
+fn into_io(err: Error) -> io::Error {
+    match err.into_kind() {
+        ErrorKind::Io(e) => e,
+        kind => io::Error::new(io::ErrorKind::InvalidData, Error::new(kind)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, TbloutIndex};
+    use flate2::{write::GzEncoder, Compression};
+    use std::{fs::File, io::Write};
+
+    const BGZF_FILE: &str = "./data/test.tbl.gz";
+
+    #[test]
+    fn test_bgzf_streaming() {
+        let plain = Reader::from_path("./data/test.tbl").unwrap();
+        let bgzf = Reader::from_bgzf_path(BGZF_FILE).unwrap();
+        assert_eq!(bgzf.meta().version(), "3.4 (Aug 2023)".to_string());
+
+        let expected = plain.into_records().map(|r| r.unwrap().ali_from());
+        let got = bgzf.into_records().map(|r| r.unwrap().ali_from());
+        assert!(expected.eq(got));
+    }
+
+    #[test]
+    fn test_bgzf_indexed() {
+        let index = TbloutIndex::build_bgzf(
+            BgzfReader::new(File::open(BGZF_FILE).unwrap()).unwrap(),
+            None,
+        )
+        .unwrap();
+        assert!(index.is_bgzf());
+        assert_eq!(index.len(), 31);
+
+        let mut reader = Reader::from_bgzf_path(BGZF_FILE).unwrap();
+        // target "6" has hits spread across several blocks
+        let records = reader.records_for_target(&index, "6").unwrap();
+        assert_eq!(records.len(), 14);
+        assert!(records.iter().all(|r| r.target_name() == "6"));
+        assert_eq!(records[0].ali_from(), Some(61501865));
+
+        assert!(reader.seek_to_query(&index, "TR").unwrap());
+        let first = reader.records().next().unwrap().unwrap();
+        assert_eq!(first.ali_from(), Some(338944));
+        assert!(!reader.seek_to_query(&index, "missing").unwrap());
+    }
+
+    #[test]
+    fn test_corrupt_block() {
+        let mut bgzf = std::fs::read(BGZF_FILE).unwrap();
+        let bsize = u16::from_le_bytes([bgzf[16], bgzf[17]]) as usize;
+        // flip a bit of the first block's CRC32
+        bgzf[bsize + 1 - 8] ^= 1;
+        let err = BgzfReader::new(bgzf.as_slice()).err().unwrap();
+        assert!(err.to_string().contains("corrupt"));
+    }
+
+    #[test]
+    fn test_index_kind_mismatch() {
+        let plain = TbloutIndex::build(File::open("./data/test.tbl").unwrap(), None).unwrap();
+        let bgzf = TbloutIndex::build_bgzf(
+            BgzfReader::new(File::open(BGZF_FILE).unwrap()).unwrap(),
+            None,
+        )
+        .unwrap();
+
+        let mut reader = Reader::from_bgzf_path(BGZF_FILE).unwrap();
+        let err = reader.records_for_target(&plain, "6").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+        assert!(reader.seek_to_query(&plain, "TR").is_err());
+
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let err = reader.records_for_target(&bgzf, "6").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+        assert!(reader.seek_to_query(&bgzf, "TR").is_err());
+    }
+
+    #[test]
+    fn test_plain_gzip_rejected() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(std::fs::read("./data/test.tbl").unwrap().as_slice())
+            .unwrap();
+        let gz = encoder.finish().unwrap();
+
+        let err = BgzfReader::new(gz.as_slice()).err().unwrap();
+        assert!(err.to_string().contains("not BGZF"));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::record::{Record, TbloutRecord};
+
+/// How much of a hit's bit score its composition bias correction
+/// accounts for, as `Record::bias_flag` judges it.
+///
+/// HMMER lowers the score of a hit for biased composition, such as
+/// short tandem repeats or low complexity regions, but the correction
+/// is not always strong enough. A bias as large as the score is a red
+/// flag for a false positive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BiasFlag {
+    /// The bias is small next to the score.
+    Ok,
+    /// The bias is a large part of the score, so the hit is worth a
+    /// look.
+    Warning,
+    /// The bias is as large as the score, or nearly so, so the hit is
+    /// likely a false positive.
+    Suspect,
+}
+
+/// The ratios of bias to bit score at which `Record::bias_flag_with`
+/// flags a hit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BiasThresholds {
+    /// The ratio at or above which a hit is a `BiasFlag::Warning`.
+    warning: f32,
+    /// The ratio at or above which a hit is a `BiasFlag::Suspect`.
+    suspect: f32,
+}
+
+/// A warning at a bias of half the score, and suspect at a bias as
+/// large as the score, as HMMER's documentation warns of.
+impl Default for BiasThresholds {
+    fn default() -> BiasThresholds {
+        BiasThresholds {
+            warning: 0.5,
+            suspect: 1.0,
+        }
+    }
+}
+
+impl BiasThresholds {
+    /// Set the ratio of bias to score at or above which a hit is a
+    /// `BiasFlag::Warning`, 0.5 by default.
+    pub fn warning(mut self, ratio: f32) -> BiasThresholds {
+        self.warning = ratio;
+        self
+    }
+
+    /// Set the ratio of bias to score at or above which a hit is a
+    /// `BiasFlag::Suspect`, 1.0 by default.
+    pub fn suspect(mut self, ratio: f32) -> BiasThresholds {
+        self.suspect = ratio;
+        self
+    }
+
+    /// Flag a hit by its bias and score. Compared as `bias >= ratio *
+    /// score`, so a hit whose score is not positive is suspect.
+    fn flag(&self, bias: f32, score: f32) -> BiasFlag {
+        if bias >= self.suspect * score {
+            BiasFlag::Suspect
+        } else if bias >= self.warning * score {
+            BiasFlag::Warning
+        } else {
+            BiasFlag::Ok
+        }
+    }
+}
+
+impl Record {
+    /// Flag the hit by how much of its bit score is composition bias,
+    /// with the default thresholds (see `BiasThresholds`): a
+    /// `BiasFlag::Warning` when the bias is at least half the score,
+    /// and `BiasFlag::Suspect` when it is at least the whole score.
+    ///
+    /// Protein records are judged by the full sequence bias and score,
+    /// which HMMER reports hits by, and DNA records by their only
+    /// ones. Returns `None` if either is not a number.
+    pub fn bias_flag(&self) -> Option<BiasFlag> {
+        self.bias_flag_with(&BiasThresholds::default())
+    }
+
+    /// Flag the hit as `bias_flag` does, with the given thresholds.
+    pub fn bias_flag_with(&self, thresholds: &BiasThresholds) -> Option<BiasFlag> {
+        let (bias, score) = (self.bias_opt()?, self.score_opt()?);
+        if bias.is_nan() || score.is_nan() {
+            return None;
+        }
+        Some(thresholds.flag(bias, score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::{parse_dna_line, parse_protein_line};
+
+    /// A DNA record with the given score and bias.
+    fn dna(score: f32, bias: f32) -> Record {
+        let line = format!(
+            "chr1 - TR - 2 317 1 100 1 100 133811386 + 1.9e-37 {} {} -",
+            score, bias
+        );
+        Record::Dna(parse_dna_line(&line).unwrap())
+    }
+
+    /// A protein record with the given full sequence score and bias,
+    /// and a best domain which would flag differently.
+    fn protein(score: f32, bias: f32) -> Record {
+        let line = format!(
+            "sp|P1 - SOR PF07682.13 1e-20 {} {} 1e-20 80.0 0.0 1.0 1 0 0 1 1 1 1 -",
+            score, bias
+        );
+        Record::Protein(parse_protein_line(&line).unwrap())
+    }
+
+    #[test]
+    fn test_bias_flag_defaults() {
+        for record in [dna, protein] {
+            assert_eq!(record(40.0, 0.0).bias_flag(), Some(BiasFlag::Ok));
+            assert_eq!(record(40.0, 19.9).bias_flag(), Some(BiasFlag::Ok));
+            assert_eq!(record(40.0, 20.0).bias_flag(), Some(BiasFlag::Warning));
+            assert_eq!(record(40.0, 39.9).bias_flag(), Some(BiasFlag::Warning));
+            assert_eq!(record(40.0, 40.0).bias_flag(), Some(BiasFlag::Suspect));
+            assert_eq!(record(40.0, 55.0).bias_flag(), Some(BiasFlag::Suspect));
+            // no score left over the bias
+            assert_eq!(record(-1.5, 0.2).bias_flag(), Some(BiasFlag::Suspect));
+        }
+        // the best domain's bias is not the one judged
+        assert_eq!(protein(10.0, 0.0).bias_flag(), Some(BiasFlag::Ok));
+    }
+
+    #[test]
+    fn test_bias_flag_thresholds() {
+        let thresholds = BiasThresholds::default().warning(0.25).suspect(0.75);
+        let flag = |bias| dna(40.0, bias).bias_flag_with(&thresholds);
+        assert_eq!(flag(9.9), Some(BiasFlag::Ok));
+        assert_eq!(flag(10.0), Some(BiasFlag::Warning));
+        assert_eq!(flag(29.9), Some(BiasFlag::Warning));
+        assert_eq!(flag(30.0), Some(BiasFlag::Suspect));
+        assert_eq!(dna(40.0, f32::NAN).bias_flag(), None);
+        assert!(BiasFlag::Ok < BiasFlag::Warning && BiasFlag::Warning < BiasFlag::Suspect);
+    }
+}
@@ -0,0 +1,294 @@
+//! Command line tools for tblout files, built with the `cli` feature.
+//!
+//! ```text
+//! tblout-tools stats <file>
+//! tblout-tools filter [--max-evalue E] [--min-score S] [--strand +|-]
+//!                      [--exclude-bias-suspect] <file> [-o <out>]
+//! tblout-tools to-bed <file> [-o <out>]
+//! tblout-tools to-tsv <file> [-o <out>]
+//! tblout-tools merge <file>... [-o <out>]
+//! ```
+//!
+//! A file of `-` is standard input, and output goes to standard output
+//! unless `-o` is given. Files are streamed, except standard input,
+//! which is read into memory first as the metadata is at its end.
+
+use hmm_tblout::prelude::*;
+use hmm_tblout::{merge, BiasFlag, TsvWriter};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, Read, Write},
+    process,
+};
+
+const USAGE: &str = "\
+usage: tblout-tools <command> [options] <file>...
+
+commands:
+  stats    summarise the hits in a file
+  filter   keep the hits passing --max-evalue, --min-score and --strand,
+           and with --exclude-bias-suspect those not mostly bias
+  to-bed   write the hits of a DNA search as BED
+  to-tsv   write the hits as a tab separated table
+  merge    merge several files, ordered by E-value
+
+A file of - is standard input. Output goes to standard output unless
+-o <file> is given.";
+
+/// An error with the file and line it was found on, if known.
+struct CliError(String);
+
+impl<E: std::error::Error> From<E> for CliError {
+    fn from(err: E) -> CliError {
+        CliError(err.to_string())
+    }
+}
+
+type CliResult<T> = std::result::Result<T, CliError>;
+
+/// The arguments after the command name.
+#[derive(Default)]
+struct Args {
+    inputs: Vec<String>,
+    output: Option<String>,
+    max_evalue: Option<f64>,
+    min_score: Option<f32>,
+    strand: Option<Strand>,
+    exclude_bias_suspect: bool,
+}
+
+impl Args {
+    fn parse(args: impl IntoIterator<Item = String>) -> CliResult<Args> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .ok_or_else(|| CliError(format!("{} needs a value", name)))
+            };
+            match arg.as_str() {
+                "-o" | "--output" => parsed.output = Some(value(&arg)?),
+                "--max-evalue" => parsed.max_evalue = Some(value(&arg)?.parse()?),
+                "--min-score" => parsed.min_score = Some(value(&arg)?.parse()?),
+                "--strand" => parsed.strand = Some(value(&arg)?.parse()?),
+                "--exclude-bias-suspect" => parsed.exclude_bias_suspect = true,
+                s if s.starts_with('-') && s != "-" => {
+                    return Err(CliError(format!("unknown option {}", s)))
+                }
+                _ => parsed.inputs.push(arg),
+            }
+        }
+        if parsed.inputs.is_empty() {
+            return Err(CliError("no input file given".to_string()));
+        }
+        Ok(parsed)
+    }
+
+    /// The single input, for the commands which take one.
+    fn input(&self) -> CliResult<&str> {
+        match self.inputs.as_slice() {
+            [input] => Ok(input),
+            _ => Err(CliError("expected a single input file".to_string())),
+        }
+    }
+
+    /// The output file, or standard output.
+    fn output(&self) -> CliResult<Box<dyn Write>> {
+        Ok(match self.output.as_deref() {
+            None | Some("-") => Box::new(io::stdout().lock()),
+            Some(path) => Box::new(File::create(path)?),
+        })
+    }
+}
+
+/// An open input, remembering its name for error messages.
+struct Input {
+    name: String,
+    reader: Reader<Box<dyn Read>>,
+}
+
+impl Input {
+    fn open(path: &str) -> CliResult<Input> {
+        let reader = if path == "-" {
+            let mut buf = Vec::new();
+            io::stdin().read_to_end(&mut buf)?;
+            let first = Reader::from_reader(buf.as_slice()).map_err(|e| in_file(path, e))?;
+            let (meta, header) = (first.meta().clone(), first.header().clone());
+            Reader::new(Box::new(io::Cursor::new(buf)) as Box<dyn Read>, meta).with_header(header)
+        } else {
+            let first = Reader::from_path(path).map_err(|e| in_file(path, e))?;
+            let (meta, header) = (first.meta().clone(), first.header().clone());
+            Reader::new(Box::new(File::open(path)?) as Box<dyn Read>, meta).with_header(header)
+        };
+        Ok(Input {
+            name: if path == "-" { "<stdin>" } else { path }.to_string(),
+            reader,
+        })
+    }
+
+    /// Read every record, failing with the line of the first bad one.
+    fn for_each(&mut self, mut f: impl FnMut(Record) -> CliResult<()>) -> CliResult<()> {
+        let mut records = self.reader.records();
+        while let Some(record) = records.next() {
+            match record {
+                Ok(record) => f(record)?,
+                Err(e) => {
+                    return Err(CliError(format!(
+                        "{} line {}: {}",
+                        self.name,
+                        records.reader().line(),
+                        e
+                    )))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An error opening a file, which happens before any line is read.
+fn in_file(path: &str, err: hmm_tblout::Error) -> CliError {
+    CliError(format!("{}: {}", path, err))
+}
+
+fn stats(args: &Args) -> CliResult<()> {
+    let mut input = Input::open(args.input()?)?;
+    let (mut hits, mut queries, mut targets) = (0u64, HashSet::new(), HashSet::new());
+    let mut best: Option<f64> = None;
+    input.for_each(|r| {
+        hits += 1;
+        queries.insert(r.query_name());
+        targets.insert(r.target_name());
+        if let Some(e) = r.evalue_opt() {
+            best = Some(best.map_or(e, |b| b.min(e)));
+        }
+        Ok(())
+    })?;
+
+    let mut out = args.output()?;
+    let meta = input.reader.meta();
+    writeln!(out, "program\t{}", meta.program())?;
+    writeln!(out, "version\t{}", meta.version())?;
+    writeln!(out, "hits\t{}", hits)?;
+    writeln!(out, "queries\t{}", queries.len())?;
+    writeln!(out, "targets\t{}", targets.len())?;
+    match best {
+        Some(e) => writeln!(out, "best_evalue\t{:e}", e)?,
+        None => writeln!(out, "best_evalue\t-")?,
+    }
+    Ok(())
+}
+
+fn filter(args: &Args) -> CliResult<()> {
+    let mut input = Input::open(args.input()?)?;
+    let mut writer = Writer::new(args.output()?);
+    writer.write_header(input.reader.header())?;
+    input.for_each(|r| {
+        let keep = passes(args.max_evalue, |max| {
+            r.evalue_opt().is_some_and(|e| e <= max)
+        }) && passes(args.min_score, |min| {
+            r.score_opt().is_some_and(|s| s >= min)
+        }) && passes(args.strand, |strand| r.strand() == Some(strand))
+            && !(args.exclude_bias_suspect && r.bias_flag() == Some(BiasFlag::Suspect));
+        if keep {
+            writer.write_record(&r)?;
+        }
+        Ok(())
+    })?;
+    writer.finish(input.reader.meta())?;
+    Ok(())
+}
+
+/// Whether a record passes a filter, which it does if there is none.
+fn passes<T>(filter: Option<T>, f: impl FnOnce(T) -> bool) -> bool {
+    match filter {
+        Some(value) => f(value),
+        None => true,
+    }
+}
+
+fn to_bed(args: &Args) -> CliResult<()> {
+    let mut input = Input::open(args.input()?)?;
+    let mut out = io::BufWriter::new(args.output()?);
+    input.for_each(|r| {
+        let r = match r {
+            Record::Dna(r) => r,
+            Record::Protein(r) => {
+                return Err(CliError(format!(
+                    "protein record for target {} has no coordinates for BED",
+                    r.target_name()
+                )))
+            }
+        };
+        // BED is 0-based and half open, HMMER 1-based and inclusive
+        let (from, to) = (r.ali_from(), r.ali_to());
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            r.target_name(),
+            from.min(to) - 1,
+            from.max(to),
+            r.query_name(),
+            r.score(),
+            r.strand()
+        )?;
+        Ok(())
+    })?;
+    out.flush()?;
+    Ok(())
+}
+
+fn to_tsv(args: &Args) -> CliResult<()> {
+    let mut input = Input::open(args.input()?)?;
+    let mut writer = TsvWriter::new(args.output()?);
+    input.for_each(|r| Ok(writer.write_record(&r)?))?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn merge_files(args: &Args) -> CliResult<()> {
+    let mut inputs = Vec::new();
+    let mut records = Vec::new();
+    for path in &args.inputs {
+        let mut input = Input::open(path)?;
+        let mut these = Vec::new();
+        input.for_each(|r| {
+            these.push(Ok(r));
+            Ok(())
+        })?;
+        records.push(these);
+        inputs.push(input);
+    }
+    let merged = merge(records, None)?;
+
+    // the first file's header and metadata stand for them all
+    let first = &inputs[0].reader;
+    let mut writer = Writer::new(args.output()?);
+    writer.write_all(first.header(), &merged, first.meta())?;
+    Ok(())
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> CliResult<()> {
+    let command = args.next().unwrap_or_default();
+    if matches!(command.as_str(), "" | "-h" | "--help" | "help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let parsed = Args::parse(args)?;
+    match command.as_str() {
+        "stats" => stats(&parsed),
+        "filter" => filter(&parsed),
+        "to-bed" => to_bed(&parsed),
+        "to-tsv" => to_tsv(&parsed),
+        "merge" => merge_files(&parsed),
+        other => Err(CliError(format!("unknown command {}\n\n{}", other, USAGE))),
+    }
+}
+
+fn main() {
+    if let Err(CliError(msg)) = run(std::env::args().skip(1)) {
+        eprintln!("tblout-tools: {}", msg);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    record::{Meta, Program},
+    writer::{WritableRecord, Writer},
+    Result,
+};
+use std::io;
+use std::path::PathBuf;
+#[cfg(feature = "std-fs")]
+use std::{fs::File, path::Path};
+
+/// A builder of complete tblout files, for tests or for converting
+/// other formats into tables HMMER's tools and this crate can read.
+///
+/// The file is written as HMMER writes it: the header for the program,
+/// with the columns widened to fit the records, the records, then the
+/// metadata naming the program and any other fields set. Records may
+/// be `Record`s or the concrete records, and must all be of the kind
+/// the program writes.
+///
+/// ```
+/// use hmm_tblout::{DNARecord, Program, Reader, TbloutBuilder};
+///
+/// # let records: Vec<DNARecord> = Reader::from_path("./data/test.tbl")?.into_dna_records()?.collect::<hmm_tblout::Result<_>>()?;
+/// let file = TbloutBuilder::new(Program::Nhmmer)
+///     .version("3.4 (Aug 2023)")
+///     .to_string(records.iter().cloned())?;
+/// let mut reader = Reader::from_reader(file.as_bytes())?;
+/// assert_eq!(reader.meta().program(), Program::Nhmmer);
+/// assert_eq!(reader.records().count(), records.len());
+/// # Ok::<(), hmm_tblout::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TbloutBuilder {
+    /// The metadata written after the records, naming the program.
+    meta: Meta,
+}
+
+impl TbloutBuilder {
+    /// A builder for the output of `program`, with the other fields of
+    /// the metadata empty.
+    pub fn new(program: Program) -> TbloutBuilder {
+        TbloutBuilder {
+            meta: Meta::for_program(program),
+        }
+    }
+
+    /// A builder writing `meta` after the records, e.g. the metadata of
+    /// a file the records were read from.
+    pub fn with_meta(meta: Meta) -> TbloutBuilder {
+        TbloutBuilder { meta }
+    }
+
+    /// Set the version of the program.
+    pub fn version(mut self, version: &str) -> TbloutBuilder {
+        self.meta.set_version(version.into());
+        self
+    }
+
+    /// Set the pipeline mode, e.g. `SEARCH` or `SCAN`.
+    pub fn pipeline_mode(mut self, pipeline_mode: &str) -> TbloutBuilder {
+        self.meta.set_pipeline_mode(pipeline_mode.into());
+        self
+    }
+
+    /// Set the path to the query file.
+    pub fn query_file<P: Into<PathBuf>>(mut self, path: P) -> TbloutBuilder {
+        self.meta.set_query_file(path.into());
+        self
+    }
+
+    /// Set the path to the target file.
+    pub fn target_file<P: Into<PathBuf>>(mut self, path: P) -> TbloutBuilder {
+        self.meta.set_target_file(path.into());
+        self
+    }
+
+    /// Set the options the program was run with.
+    pub fn options(mut self, options: &str) -> TbloutBuilder {
+        self.meta.set_options(options.into());
+        self
+    }
+
+    /// Set the directory the program was run in.
+    pub fn current_dir<P: Into<PathBuf>>(mut self, path: P) -> TbloutBuilder {
+        self.meta.set_current_dir(path.into());
+        self
+    }
+
+    /// Set the date the program was run.
+    pub fn date(mut self, date: &str) -> TbloutBuilder {
+        self.meta.set_date(date.into());
+        self
+    }
+
+    /// The metadata which will be written.
+    pub fn meta(&self) -> &Meta {
+        &self.meta
+    }
+
+    /// Write the file to `wtr`, returning the number of records
+    /// written. The records are held until all have been seen, to fit
+    /// the columns to them. An `ErrorKind::InvalidInput` error is
+    /// returned, before anything is written, if the program is unknown
+    /// or a record is of the wrong kind for it.
+    pub fn write<W: io::Write, T: WritableRecord>(
+        &self,
+        wtr: W,
+        records: impl IntoIterator<Item = T>,
+    ) -> Result<usize> {
+        let records = records.into_iter().collect::<Vec<_>>();
+        let mut writer = Writer::new(wtr);
+        let n = writer.write_aligned(self.meta.program(), &records)?;
+        writer.write_meta(&self.meta)?;
+        writer.flush()?;
+        Ok(n)
+    }
+
+    /// Write the file to a string. See `write`.
+    pub fn to_string<T: WritableRecord>(
+        &self,
+        records: impl IntoIterator<Item = T>,
+    ) -> Result<String> {
+        let mut out = Vec::new();
+        self.write(&mut out, records)?;
+        Ok(String::from_utf8(out).expect("records are written as UTF-8"))
+    }
+
+    /// Write the file to `path`, replacing any file there. See `write`.
+    #[cfg(feature = "std-fs")]
+    pub fn to_path<P: AsRef<Path>, T: WritableRecord>(
+        &self,
+        path: P,
+        records: impl IntoIterator<Item = T>,
+    ) -> Result<usize> {
+        self.write(File::create(path)?, records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, Reader, Record};
+
+    fn records(path: &str) -> Vec<Record> {
+        let reader = Reader::from_path(path).unwrap();
+        reader.into_records().collect::<Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn test_build_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("built.tbl");
+        let dna = records("./data/test.tbl");
+        let n = TbloutBuilder::new(Program::Nhmmer)
+            .version("3.4 (Aug 2023)")
+            .pipeline_mode("SEARCH")
+            .query_file("TR.hmm")
+            .to_path(&path, dna.iter().cloned())
+            .unwrap();
+        assert_eq!(n, 31);
+
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(reader.meta().program(), Program::Nhmmer);
+        assert_eq!(reader.meta().version(), "3.4 (Aug 2023)");
+        assert_eq!(reader.meta().query_file(), PathBuf::from("TR.hmm"));
+        let read = reader.records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(read, dna);
+    }
+
+    #[test]
+    fn test_build_concrete_records() {
+        let reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let meta = reader.meta().clone();
+        let protein = reader
+            .into_protein_records()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let file = TbloutBuilder::with_meta(meta.clone())
+            .to_string(protein.clone())
+            .unwrap();
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(reader.meta(), &meta);
+        let read = reader
+            .protein_records()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(read, protein);
+
+        // without records, as HMMER writes a search without hits
+        let file = TbloutBuilder::new(Program::Phmmer)
+            .to_string(Vec::<Record>::new())
+            .unwrap();
+        let reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(reader.meta().program(), Program::Phmmer);
+        assert!(!reader.header().is_empty());
+    }
+
+    #[test]
+    fn test_build_rejects_wrong_kind() {
+        let dna = records("./data/test.tbl");
+        let mut out = Vec::new();
+        let err = TbloutBuilder::new(Program::Hmmsearch)
+            .write(&mut out, dna)
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+        assert!(out.is_empty());
+
+        let err = TbloutBuilder::new(Program::None)
+            .to_string(Vec::<Record>::new())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+    }
+}
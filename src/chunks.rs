@@ -0,0 +1,144 @@
+use crate::{Error, Result};
+
+/// Adapters for iterators of records, such as `RecordsIter` and
+/// `RecordsIntoIter`, or any iterator of `Result`s of records.
+pub trait RecordsIterExt<T>: Iterator<Item = Result<T>> + Sized {
+    /// Group the records into chunks of `n`, for inserting in batches.
+    /// Every chunk is `n` records long but the last, which holds those
+    /// left over.
+    ///
+    /// An error ends the chunk it is found in: the records before it
+    /// are yielded as a shorter chunk, if there are any, then the
+    /// error. Records after it go in a new chunk, so to stop at the
+    /// first error, stop at the first `Err`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    ///
+    /// ```no_run
+    /// use hmm_tblout::{Reader, RecordsIterExt};
+    ///
+    /// let reader = Reader::from_path("hits.tbl")?;
+    /// for chunk in reader.into_records().chunks_of(1000) {
+    ///     let chunk = chunk?;
+    ///     println!("inserting {} records", chunk.len());
+    /// }
+    /// # Ok::<(), hmm_tblout::Error>(())
+    /// ```
+    fn chunks_of(self, n: usize) -> Chunks<Self> {
+        assert!(n > 0, "chunk size must be positive");
+        Chunks {
+            iter: self,
+            n,
+            error: None,
+        }
+    }
+}
+
+impl<T, I: Iterator<Item = Result<T>>> RecordsIterExt<T> for I {}
+
+/// An iterator over chunks of records, made by
+/// `RecordsIterExt::chunks_of`.
+pub struct Chunks<I> {
+    /// The iterator of records.
+    iter: I,
+    /// The number of records in a whole chunk.
+    n: usize,
+    /// An error which ended the last chunk, yielded after it.
+    error: Option<Error>,
+}
+
+impl<T, I: Iterator<Item = Result<T>>> Iterator for Chunks<I> {
+    type Item = Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Result<Vec<T>>> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        let mut chunk = Vec::with_capacity(self.n);
+        while chunk.len() < self.n {
+            match self.iter.next() {
+                Some(Ok(record)) => chunk.push(record),
+                Some(Err(e)) if chunk.is_empty() => return Some(Err(e)),
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    break;
+                }
+                None => break,
+            }
+        }
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(Ok(chunk))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ErrorKind, Reader, Record};
+
+    /// The lengths of the chunks, or `None` for errors.
+    fn lengths<T>(chunks: impl Iterator<Item = Result<Vec<T>>>) -> Vec<Option<usize>> {
+        chunks.map(|c| c.ok().map(|c| c.len())).collect()
+    }
+
+    #[test]
+    fn test_chunk_sizes() {
+        // 31 records
+        let records = || Reader::from_path("./data/test.tbl").unwrap().into_records();
+        assert_eq!(
+            lengths(records().chunks_of(10)),
+            [Some(10), Some(10), Some(10), Some(1)]
+        );
+        assert_eq!(lengths(records().chunks_of(31)), [Some(31)]);
+        assert_eq!(lengths(records().chunks_of(100)), [Some(31)]);
+
+        // the records in order
+        let chunked = records()
+            .chunks_of(7)
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+            .concat();
+        let all = records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(chunked, all);
+
+        assert_eq!(
+            lengths(Vec::<Result<Record>>::new().into_iter().chunks_of(3)),
+            []
+        );
+    }
+
+    #[test]
+    fn test_chunk_errors() {
+        let err = || Err(Error::new(ErrorKind::Parser("bad line".into())));
+        let records: Vec<Result<u32>> =
+            vec![Ok(1), Ok(2), Ok(3), err(), Ok(5), err(), err(), Ok(8)];
+        // the prefix of the chunk, then the error, then on
+        assert_eq!(
+            lengths(records.into_iter().chunks_of(2)),
+            [Some(2), Some(1), None, Some(1), None, None, Some(1)]
+        );
+
+        let file = std::fs::read_to_string("./data/test.tbl")
+            .unwrap()
+            .replacen(" 66.5 ", " x ", 1);
+        let reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let mut chunks = reader.into_dna_records().unwrap().chunks_of(10);
+        assert_eq!(chunks.next().unwrap().unwrap().len(), 2);
+        assert!(matches!(
+            chunks.next().unwrap().unwrap_err().kind(),
+            ErrorKind::Float(_)
+        ));
+        assert_eq!(lengths(chunks), [Some(10), Some(10), Some(8)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "chunk size must be positive")]
+    fn test_zero_chunk_size() {
+        Vec::<Result<Record>>::new().into_iter().chunks_of(0);
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fs::File,
+    io::{self, Write},
+};
+
+/// A compression format for output files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Gzip, readable by `gzip -d` and `zcat`.
+    #[cfg(feature = "gzip")]
+    Gzip,
+    /// Zstandard, readable by `zstd -d`.
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+/// A file written through a compression encoder.
+///
+/// The compressed stream is only complete once it has been finished,
+/// which `Writer::finish` and `Writer::into_inner` do. Writing to it
+/// afterwards is an error. Dropping the file also finishes the stream,
+/// but any error in doing so is ignored, so finish it explicitly to
+/// find out whether the output was written.
+pub struct CompressedFile {
+    encoder: Encoder,
+}
+
+enum Encoder {
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<File>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::Encoder<'static, File>),
+}
+
+impl CompressedFile {
+    /// Wrap a file in an encoder for `format` at compression `level`.
+    /// Gzip levels go from 0 to 9, and zstd levels from 1 to 22.
+    pub(crate) fn new(file: File, format: Format, level: u32) -> io::Result<CompressedFile> {
+        let encoder = match format {
+            #[cfg(feature = "gzip")]
+            Format::Gzip => Encoder::Gzip(flate2::write::GzEncoder::new(
+                file,
+                flate2::Compression::new(level),
+            )),
+            #[cfg(feature = "zstd")]
+            Format::Zstd => {
+                let level = i32::try_from(level).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "zstd level out of range")
+                })?;
+                Encoder::Zstd(zstd::Encoder::new(file, level)?)
+            }
+        };
+        Ok(CompressedFile { encoder })
+    }
+
+    /// Write the end of the compressed stream, such as the gzip footer.
+    pub(crate) fn try_finish(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(e) => e.try_finish(),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(e) => e.do_finish(),
+        }
+    }
+
+    /// The format the file is compressed with.
+    pub fn format(&self) -> Format {
+        match &self.encoder {
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(_) => Format::Gzip,
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(_) => Format::Zstd,
+        }
+    }
+}
+
+impl Drop for CompressedFile {
+    fn drop(&mut self) {
+        let _ = self.try_finish();
+    }
+}
+
+impl Write for CompressedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.encoder {
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(e) => e.write(buf),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(e) => e.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.encoder {
+            #[cfg(feature = "gzip")]
+            Encoder::Gzip(e) => e.flush(),
+            #[cfg(feature = "zstd")]
+            Encoder::Zstd(e) => e.flush(),
+        }
+    }
+}
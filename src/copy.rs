@@ -0,0 +1,118 @@
+use crate::{reader::Reader, record::Record, writer::Writer, Result};
+use std::io;
+
+/// The number of records `copy_filtered` kept and dropped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CopyStats {
+    /// The number of records written.
+    pub kept: u64,
+    /// The number of records the predicate rejected.
+    pub dropped: u64,
+}
+
+/// Copy a tblout file from `reader` to `writer`, keeping the records
+/// for which `pred` returns true: the header is written, then the
+/// records which are kept, then the metadata of the original file.
+/// The writer is flushed afterwards. Stops at the first error.
+///
+/// Records are streamed, so the file is never held in memory. For the
+/// kept records to be written as the lines they were read from, read
+/// with `Reader::keep_raw_lines` and write with
+/// `WriterBuilder::round_trip`; otherwise they are written aligned to
+/// the header.
+///
+/// ```no_run
+/// use hmm_tblout::{copy_filtered, Reader, TbloutRecord, WriterBuilder};
+///
+/// let reader = Reader::from_path("hits.tbl")?.keep_raw_lines(true);
+/// let mut writer = WriterBuilder::new()
+///     .round_trip(true)
+///     .build(std::fs::File::create("significant.tbl")?);
+/// let stats = copy_filtered(reader, &mut writer, |r| {
+///     r.evalue_opt().is_some_and(|e| e <= 1e-5)
+/// })?;
+/// println!("kept {} of {}", stats.kept, stats.kept + stats.dropped);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn copy_filtered<R: io::Read, W: io::Write>(
+    mut reader: Reader<R>,
+    writer: &mut Writer<W>,
+    mut pred: impl FnMut(&Record) -> bool,
+) -> Result<CopyStats> {
+    let mut stats = CopyStats::default();
+    writer.write_header(reader.header())?;
+    for record in reader.records() {
+        let record = record?;
+        if pred(&record) {
+            writer.write_record(&record)?;
+            stats.kept += 1;
+        } else {
+            stats.dropped += 1;
+        }
+    }
+    writer.write_meta(reader.meta())?;
+    writer.flush()?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TbloutRecord, WriterBuilder};
+
+    #[test]
+    fn test_copy_filtered() {
+        let reader = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .keep_raw_lines(true);
+        let mut writer = WriterBuilder::new().round_trip(true).build(Vec::new());
+        let stats = copy_filtered(reader, &mut writer, |r| {
+            r.evalue_opt().is_some_and(|e| e <= 1e-5)
+        })
+        .unwrap();
+        assert_eq!(
+            stats,
+            CopyStats {
+                kept: 6,
+                dropped: 25
+            }
+        );
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        // a complete file, with the kept lines as they were
+        let original = std::fs::read_to_string("./data/test.tbl").unwrap();
+        let expected: String = original
+            .split_inclusive('\n')
+            .filter(|line| {
+                line.starts_with('#') || {
+                    let e_value = line.split_whitespace().nth(12).unwrap();
+                    e_value.parse::<f64>().unwrap() <= 1e-5
+                }
+            })
+            .collect();
+        assert_eq!(out, expected);
+
+        let mut read = Reader::from_reader(out.as_bytes()).unwrap();
+        let original = Reader::from_path("./data/test.tbl").unwrap();
+        assert_eq!(read.meta(), original.meta());
+        assert_eq!(read.header(), original.header());
+        let records = read.records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 6);
+        assert!(records.iter().all(|r| r.evalue_opt().unwrap() <= 1e-5));
+    }
+
+    #[test]
+    fn test_copy_filtered_aligned() {
+        // without the raw lines, records are aligned to the header
+        let reader = Reader::from_path("./data/corpus/hmmscan.tbl").unwrap();
+        let mut writer = Writer::new(Vec::new());
+        let stats = copy_filtered(reader, &mut writer, |_| false).unwrap();
+        assert_eq!(stats.kept, 0);
+        assert!(stats.dropped > 0);
+
+        let out = writer.into_inner().unwrap();
+        let mut read = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(read.meta().program().to_string(), "hmmscan");
+        assert_eq!(read.records().count(), 0);
+    }
+}
@@ -0,0 +1,316 @@
+use crate::{
+    progress::{report, Progress, ProgressFn},
+    record::TbloutRecord,
+    Result,
+};
+use std::collections::HashMap;
+
+/// Calculate the number of bases in each target sequence covered
+/// by at least one hit. Overlapping hits are only counted once.
+/// Records without coordinates, i.e. protein records, are skipped. The
+/// optional `progress` callback is called periodically and may
+/// cancel the calculation.
+pub fn coverage_per_target<I, R>(
+    records: I,
+    progress: Option<ProgressFn>,
+) -> Result<HashMap<String, u64>>
+where
+    I: IntoIterator<Item = Result<R>>,
+    R: TbloutRecord,
+{
+    let mut intervals: HashMap<String, Vec<(i64, i64)>> = HashMap::new();
+    for (n, record) in records.into_iter().enumerate() {
+        let record = record?;
+        if let Some((from, to)) = record.interval_opt() {
+            intervals
+                .entry(record.target_name())
+                .or_default()
+                .push((from.min(to), from.max(to)));
+        }
+        report(
+            progress,
+            Progress {
+                records: n as u64 + 1,
+                bytes: None,
+            },
+        )?;
+    }
+
+    Ok(intervals
+        .into_iter()
+        .map(|(target, ivs)| (target, union_length(ivs)))
+        .collect())
+}
+
+/// The bases of a target covered by the hits of each query (model),
+/// and by the hits of any query.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StackedCoverage {
+    /// The number of bases covered by the hits of each query.
+    pub per_query: HashMap<String, u64>,
+    /// The number of bases covered by the hits of any query.
+    pub union: u64,
+}
+
+impl StackedCoverage {
+    /// The number of bases covered again by further queries: the sum of
+    /// the coverage of each query less the coverage of all together.
+    /// Zero if no two queries hit the same bases.
+    pub fn redundant(&self) -> u64 {
+        self.per_query.values().sum::<u64>() - self.union
+    }
+}
+
+/// Calculate the number of bases in each target sequence covered by
+/// the hits of each query, e.g. each model of a repeat library, and by
+/// the hits of any query, to see how much the models overlap.
+/// Overlapping hits of a query are only counted once, as are
+/// overlapping hits of all queries in the union. Records without
+/// coordinates, i.e. protein records, are skipped. The optional
+/// `progress` callback is called periodically and may cancel the
+/// calculation.
+///
+/// The intervals of the hits are held until the end, so memory grows
+/// with the number of hits.
+pub fn stacked_coverage<I, R>(
+    records: I,
+    progress: Option<ProgressFn>,
+) -> Result<HashMap<String, StackedCoverage>>
+where
+    I: IntoIterator<Item = Result<R>>,
+    R: TbloutRecord,
+{
+    // the intervals of each query on each target
+    let mut intervals: HashMap<String, HashMap<String, Vec<(i64, i64)>>> = HashMap::new();
+    for (n, record) in records.into_iter().enumerate() {
+        let record = record?;
+        if let Some((from, to)) = record.interval_opt() {
+            intervals
+                .entry(record.target_name())
+                .or_default()
+                .entry(record.query_name())
+                .or_default()
+                .push((from.min(to), from.max(to)));
+        }
+        report(
+            progress,
+            Progress {
+                records: n as u64 + 1,
+                bytes: None,
+            },
+        )?;
+    }
+
+    Ok(intervals
+        .into_iter()
+        .map(|(target, queries)| {
+            let all = queries.values().flatten().copied().collect();
+            let coverage = StackedCoverage {
+                union: union_length(all),
+                per_query: queries
+                    .into_iter()
+                    .map(|(query, ivs)| (query, union_length(ivs)))
+                    .collect(),
+            };
+            (target, coverage)
+        })
+        .collect())
+}
+
+/// The hits on each strand of a target, and the bases they cover.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StrandBias {
+    /// The number of hits on the plus strand.
+    pub plus_hits: u64,
+    /// The number of hits on the minus strand.
+    pub minus_hits: u64,
+    /// The number of bases covered by hits on the plus strand.
+    pub plus_bases: u64,
+    /// The number of bases covered by hits on the minus strand.
+    pub minus_bases: u64,
+}
+
+impl StrandBias {
+    /// The fraction of the hits on the plus strand: 0.5 for no skew,
+    /// 1.0 if all are on the plus strand and 0.0 if all are on the
+    /// minus.
+    pub fn hit_ratio(&self) -> f64 {
+        plus_fraction(self.plus_hits, self.minus_hits)
+    }
+
+    /// The fraction of the covered bases covered on the plus strand, as
+    /// `hit_ratio`. Bases covered on both strands count for both.
+    pub fn coverage_ratio(&self) -> f64 {
+        plus_fraction(self.plus_bases, self.minus_bases)
+    }
+}
+
+/// The fraction of a count on the plus strand, 0.5 if there is none.
+fn plus_fraction(plus: u64, minus: u64) -> f64 {
+    if plus + minus == 0 {
+        0.5
+    } else {
+        plus as f64 / (plus + minus) as f64
+    }
+}
+
+/// Count the hits on each strand of each target sequence, and the
+/// bases they cover, to find targets whose hits are skewed to one
+/// strand. Overlapping hits on a strand are only counted once. The
+/// strand is that of the coordinates, on the minus strand if `from` is
+/// greater than `to`. Records without coordinates, i.e. protein
+/// records, are skipped. The optional `progress` callback is called
+/// periodically and may cancel the calculation.
+pub fn strand_bias_per_target<I, R>(
+    records: I,
+    progress: Option<ProgressFn>,
+) -> Result<HashMap<String, StrandBias>>
+where
+    I: IntoIterator<Item = Result<R>>,
+    R: TbloutRecord,
+{
+    // the plus and minus strand intervals of each target
+    let mut intervals: HashMap<String, [Vec<(i64, i64)>; 2]> = HashMap::new();
+    for (n, record) in records.into_iter().enumerate() {
+        let record = record?;
+        if let Some((from, to)) = record.interval_opt() {
+            let strand = usize::from(from > to);
+            intervals.entry(record.target_name()).or_default()[strand]
+                .push((from.min(to), from.max(to)));
+        }
+        report(
+            progress,
+            Progress {
+                records: n as u64 + 1,
+                bytes: None,
+            },
+        )?;
+    }
+
+    Ok(intervals
+        .into_iter()
+        .map(|(target, [plus, minus])| {
+            let bias = StrandBias {
+                plus_hits: plus.len() as u64,
+                minus_hits: minus.len() as u64,
+                plus_bases: union_length(plus),
+                minus_bases: union_length(minus),
+            };
+            (target, bias)
+        })
+        .collect())
+}
+
+/// The number of positions covered by a set of closed intervals.
+pub(crate) fn union_length(mut intervals: Vec<(i64, i64)>) -> u64 {
+    intervals.sort_unstable();
+
+    let mut total = 0;
+    let mut current: Option<(i64, i64)> = None;
+    for (start, end) in intervals {
+        current = match current {
+            Some((s, e)) if start <= e + 1 => Some((s, e.max(end))),
+            Some((s, e)) => {
+                total += (e - s + 1) as u64;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    if let Some((s, e)) = current {
+        total += (e - s + 1) as u64;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader, Record};
+
+    #[test]
+    fn test_union_length() {
+        assert_eq!(union_length(vec![]), 0);
+        assert_eq!(union_length(vec![(1, 10), (5, 15), (20, 20)]), 16);
+        // adjacent intervals are merged
+        assert_eq!(union_length(vec![(11, 20), (1, 10)]), 20);
+    }
+
+    #[test]
+    fn test_coverage_per_target() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let coverage = coverage_per_target(reader.into_records(), None).unwrap();
+        // two disjoint hits on target "1"
+        assert_eq!(
+            coverage["1"],
+            (70216942 - 70216788 + 1) + (110824829 - 110824763 + 1)
+        );
+        assert_eq!(coverage.len(), 5);
+    }
+
+    /// A record for a hit of `query` on `target` from `from` to `to`.
+    fn hit(target: &str, query: &str, from: i64, to: i64) -> Result<Record> {
+        let strand = if from <= to { "+" } else { "-" };
+        let line = format!(
+            "{} - {} - 2 317 {} {} {} {} 133811386 {} 1.9e-37 136.7 1.4 -",
+            target, query, from, to, from, to, strand
+        );
+        Ok(Record::Dna(parse_dna_line(&line).unwrap()))
+    }
+
+    #[test]
+    fn test_stacked_coverage() {
+        let records = vec![
+            // overlapping hits of one model, counted once
+            hit("chr1", "L1", 1, 100),
+            hit("chr1", "L1", 51, 150),
+            // a second model over the end of the first, on either strand
+            hit("chr1", "Alu", 200, 101),
+            hit("chr1", "Alu", 301, 350),
+            hit("chr2", "Alu", 1, 10),
+        ];
+        let coverage = stacked_coverage(records, None).unwrap();
+        assert_eq!(coverage.len(), 2);
+
+        let chr1 = &coverage["chr1"];
+        assert_eq!(chr1.per_query["L1"], 150);
+        assert_eq!(chr1.per_query["Alu"], 150);
+        // 1 to 200, and 301 to 350
+        assert_eq!(chr1.union, 250);
+        assert_eq!(chr1.redundant(), 50);
+
+        let chr2 = &coverage["chr2"];
+        assert_eq!(chr2.per_query.len(), 1);
+        assert_eq!(chr2.union, 10);
+        assert_eq!(chr2.redundant(), 0);
+    }
+
+    #[test]
+    fn test_strand_bias_per_target() {
+        let reader = Reader::from_path("./data/strand_skew.tbl").unwrap();
+        let bias = strand_bias_per_target(reader.into_records(), None).unwrap();
+        assert_eq!(bias.len(), 2);
+        // three overlapping hits on the plus strand, one on the minus
+        assert_eq!(
+            bias["chr1"],
+            StrandBias {
+                plus_hits: 3,
+                minus_hits: 1,
+                plus_bases: 250,
+                minus_bases: 100,
+            }
+        );
+        assert_eq!(bias["chr1"].hit_ratio(), 0.75);
+        assert!((bias["chr1"].coverage_ratio() - 250.0 / 350.0).abs() < 1e-12);
+        // all on the minus strand
+        assert_eq!(bias["chr2"].minus_bases, 150);
+        assert_eq!(bias["chr2"].hit_ratio(), 0.0);
+        assert_eq!(bias["chr2"].coverage_ratio(), 0.0);
+        assert_eq!(StrandBias::default().hit_ratio(), 0.5);
+
+        // protein records are skipped
+        let reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let bias = strand_bias_per_target(reader.into_records(), None).unwrap();
+        assert!(bias.is_empty());
+    }
+}
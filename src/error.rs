@@ -1,3 +1,4 @@
+use crate::validate::{CoordinateError, ValidationIssue};
 use std::{
     error::Error as StdError,
     fmt, io,
@@ -42,8 +43,30 @@ pub enum ErrorKind {
     Parser(String),
     /// Error whilst reading a record.
     ReadRecord(String),
+    /// I/O error whilst writing the record for a target.
+    WriteRecord { target: String, error: io::Error },
     /// Metadata error
     Meta(String),
+    /// The operation was cancelled by a progress callback.
+    Cancelled,
+    /// The operation does not support this kind of record or input.
+    Unsupported(String),
+    /// An argument given to an operation is not valid.
+    InvalidInput(String),
+    /// A record read by a strict reader is inconsistent with itself.
+    Validation {
+        line: u64,
+        issues: Vec<ValidationIssue>,
+    },
+    /// The coordinates of a DNA record read by a strict reader are
+    /// inconsistent.
+    Coordinates { line: u64, error: CoordinateError },
+    /// SQLite error.
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+    /// Arrow error.
+    #[cfg(feature = "arrow")]
+    Arrow(arrow_schema::ArrowError),
 }
 
 impl From<io::Error> for Error {
@@ -71,7 +94,25 @@ impl fmt::Display for Error {
             ErrorKind::Float(ref err) => write!(f, "parsing float error - {}", err),
             ErrorKind::Parser(ref err) => write!(f, "parser error - {}", err),
             ErrorKind::ReadRecord(ref err) => write!(f, "reading record - {}", err),
+            ErrorKind::WriteRecord {
+                ref target,
+                ref error,
+            } => write!(f, "writing record for target {} - {}", target, error),
             ErrorKind::Meta(ref err) => write!(f, "metadata error - {}", err),
+            ErrorKind::Cancelled => write!(f, "operation cancelled"),
+            ErrorKind::Unsupported(ref err) => write!(f, "unsupported - {}", err),
+            ErrorKind::InvalidInput(ref err) => write!(f, "invalid input - {}", err),
+            ErrorKind::Validation { line, ref issues } => {
+                let issues = issues.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+                write!(f, "invalid record on line {} - {}", line, issues.join("; "))
+            }
+            ErrorKind::Coordinates { line, ref error } => {
+                write!(f, "invalid coordinates on line {} - {}", line, error)
+            }
+            #[cfg(feature = "sqlite")]
+            ErrorKind::Sqlite(ref err) => write!(f, "SQLite error - {}", err),
+            #[cfg(feature = "arrow")]
+            ErrorKind::Arrow(ref err) => write!(f, "Arrow error - {}", err),
         }
     }
 }
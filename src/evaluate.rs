@@ -0,0 +1,425 @@
+use crate::{merge::e_value, record::TbloutRecord, Error, ErrorKind, Result};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Write},
+};
+
+/// The column names of the curve, as `write_curve` writes them.
+const CURVE_COLUMNS: [&str; 6] = [
+    "e_value",
+    "true_positives",
+    "false_positives",
+    "false_negatives",
+    "precision",
+    "recall",
+];
+
+/// A truth set of regions of target sequences, such as curated
+/// annotations, to evaluate hits against with `evaluate`.
+///
+/// Regions are in HMMER's 1-based, inclusive coordinates, and have no
+/// strand.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Regions {
+    /// The regions of each target, as `(start, end)`.
+    by_target: HashMap<String, Vec<(i64, i64)>>,
+    /// The number of regions.
+    len: usize,
+}
+
+impl Regions {
+    /// An empty truth set.
+    pub fn new() -> Regions {
+        Regions::default()
+    }
+
+    /// Read regions from BED, converting its 0-based, half-open
+    /// coordinates. Only the first three columns are read, and
+    /// `track`, `browser` and `#` lines are skipped.
+    pub fn from_bed<R: BufRead>(rdr: R) -> Result<Regions> {
+        let mut regions = Regions::new();
+        for (i, line) in rdr.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty()
+                || line.starts_with('#')
+                || line.starts_with("track")
+                || line.starts_with("browser")
+            {
+                continue;
+            }
+            let mut fields = line.split('\t');
+            let (target, start, end) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(target), Some(start), Some(end)) => (target, start, end),
+                _ => {
+                    return Err(Error::new(ErrorKind::Parser(format!(
+                        "BED line {} has fewer than three columns",
+                        i + 1
+                    ))))
+                }
+            };
+            let start = start.trim().parse::<i64>()?;
+            let end = end.trim().parse::<i64>()?;
+            regions.add(target, start + 1, end);
+        }
+        Ok(regions)
+    }
+
+    /// Add the region of `target` from `start` to `end`, 1-based and
+    /// inclusive, in either order.
+    pub fn add(&mut self, target: &str, start: i64, end: i64) {
+        self.by_target
+            .entry(target.to_string())
+            .or_default()
+            .push((start.min(end), start.max(end)));
+        self.len += 1;
+    }
+
+    /// The number of regions.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no regions.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// The counts of hits at an E-value threshold, a point of an
+/// `EvaluationCurve`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EvaluationPoint {
+    /// The E-value threshold: hits at or below it are called.
+    pub e_value: f64,
+    /// The number of hits called which found a truth region.
+    pub true_positives: u64,
+    /// The number of hits called which found no truth region, or one
+    /// already found.
+    pub false_positives: u64,
+    /// The number of truth regions no hit called found.
+    pub false_negatives: u64,
+}
+
+impl EvaluationPoint {
+    /// The fraction of the hits called which are true, or `None` if no
+    /// hits are called.
+    pub fn precision(&self) -> Option<f64> {
+        let called = self.true_positives + self.false_positives;
+        match called {
+            0 => None,
+            _ => Some(self.true_positives as f64 / called as f64),
+        }
+    }
+
+    /// The fraction of the truth regions found, or `None` if there are
+    /// none.
+    pub fn recall(&self) -> Option<f64> {
+        let truth = self.true_positives + self.false_negatives;
+        match truth {
+            0 => None,
+            _ => Some(self.true_positives as f64 / truth as f64),
+        }
+    }
+}
+
+/// The counts of true and false positives at each E-value threshold of
+/// a set of hits, from which ROC and precision-recall curves are drawn.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationCurve {
+    /// A point at the E-value of each hit, lowest first, with hits of
+    /// the same E-value together.
+    pub points: Vec<EvaluationPoint>,
+    /// The number of truth regions.
+    pub truth: u64,
+}
+
+impl EvaluationCurve {
+    /// The counts at an E-value threshold, e.g. HMMER's inclusion
+    /// threshold of 0.01, calling the hits with an E-value at or below
+    /// it.
+    pub fn at(&self, e_value: f64) -> EvaluationPoint {
+        let called = self.points.partition_point(|p| p.e_value <= e_value);
+        match called {
+            0 => EvaluationPoint {
+                e_value,
+                true_positives: 0,
+                false_positives: 0,
+                false_negatives: self.truth,
+            },
+            _ => EvaluationPoint {
+                e_value,
+                ..self.points[called - 1]
+            },
+        }
+    }
+}
+
+/// Evaluate hits against a truth set, to tune an E-value threshold.
+/// Returns the counts of true and false positives as the threshold is
+/// raised past the E-value of each hit.
+///
+/// A hit is true when it covers at least `overlap_frac` of a truth
+/// region of its target not found by a hit of a lower E-value; of
+/// several, it finds the one it covers most. Each region is found
+/// once, so a further hit on a region already found is false. Strands
+/// are not compared, as truth regions have none. Records without
+/// coordinates, i.e. protein records, are skipped.
+///
+/// ```no_run
+/// use hmm_tblout::{evaluate, Reader, Regions};
+/// use std::{fs::File, io::BufReader};
+///
+/// let truth = Regions::from_bed(BufReader::new(File::open("curated.bed")?))?;
+/// let reader = Reader::from_path("hits.tbl")?;
+/// let curve = evaluate(reader.into_records(), &truth, 0.5)?;
+/// let point = curve.at(0.01);
+/// println!("precision {:?} recall {:?}", point.precision(), point.recall());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn evaluate<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    truth: &Regions,
+    overlap_frac: f64,
+) -> Result<EvaluationCurve> {
+    let mut hits = Vec::new();
+    for record in records {
+        let record = record?;
+        if let Some((from, to)) = record.interval_opt() {
+            hits.push((
+                e_value(&record),
+                record.target_name(),
+                from.min(to),
+                from.max(to),
+            ));
+        }
+    }
+    hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut regions = truth.by_target.clone();
+    for ivs in regions.values_mut() {
+        ivs.sort_unstable();
+    }
+    let mut found: HashMap<&str, Vec<bool>> = regions
+        .iter()
+        .map(|(target, ivs)| (target.as_str(), vec![false; ivs.len()]))
+        .collect();
+
+    let mut points: Vec<EvaluationPoint> = Vec::new();
+    let (mut tp, mut fp) = (0, 0);
+    for (e_value, target, start, end) in hits {
+        let true_hit = match (regions.get(&target), found.get_mut(target.as_str())) {
+            (Some(ivs), Some(found)) => match best_region(ivs, found, (start, end), overlap_frac) {
+                Some(i) => {
+                    found[i] = true;
+                    true
+                }
+                None => false,
+            },
+            _ => false,
+        };
+        match true_hit {
+            true => tp += 1,
+            false => fp += 1,
+        }
+        let point = EvaluationPoint {
+            e_value,
+            true_positives: tp,
+            false_positives: fp,
+            false_negatives: truth.len as u64 - tp,
+        };
+        match points.last_mut() {
+            Some(last) if last.e_value == e_value => *last = point,
+            _ => points.push(point),
+        }
+    }
+    Ok(EvaluationCurve {
+        points,
+        truth: truth.len as u64,
+    })
+}
+
+/// The region not yet found which a hit covers the most of, if it
+/// covers at least `overlap_frac` of it. The regions are sorted.
+fn best_region(
+    regions: &[(i64, i64)],
+    found: &[bool],
+    (start, end): (i64, i64),
+    overlap_frac: f64,
+) -> Option<usize> {
+    // only regions starting before the hit ends can overlap it
+    let candidates = regions.partition_point(|&(s, _)| s <= end);
+    let mut best: Option<(usize, f64)> = None;
+    for (i, &(s, e)) in regions[..candidates].iter().enumerate() {
+        let overlap = e.min(end) - s.max(start) + 1;
+        if found[i] || overlap <= 0 {
+            continue;
+        }
+        let covered = overlap as f64 / (e - s + 1) as f64;
+        let better = match best {
+            Some((_, most)) => covered > most,
+            None => true,
+        };
+        if covered >= overlap_frac && better {
+            best = Some((i, covered));
+        }
+    }
+    best.map(|(i, _)| i)
+}
+
+/// Write the points of a curve as tab separated values, after a row of
+/// column names, with `-` for a precision or recall which is not
+/// defined.
+pub fn write_curve<W: Write>(curve: &EvaluationCurve, w: W) -> Result<()> {
+    let mut w = io::BufWriter::new(w);
+    writeln!(w, "{}", CURVE_COLUMNS.join("\t"))?;
+    let ratio = |r: Option<f64>| match r {
+        Some(r) => format!("{:.4}", r),
+        None => "-".to_string(),
+    };
+    for point in &curve.points {
+        writeln!(
+            w,
+            "{:e}\t{}\t{}\t{}\t{}\t{}",
+            point.e_value,
+            point.true_positives,
+            point.false_positives,
+            point.false_negatives,
+            ratio(point.precision()),
+            ratio(point.recall())
+        )?;
+    }
+    w.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader, Record};
+
+    /// A record for a hit on `target` from `from` to `to`.
+    fn hit(target: &str, from: i64, to: i64, e_value: f64) -> Result<Record> {
+        let strand = if from <= to { "+" } else { "-" };
+        let line = format!(
+            "{} - TR - 2 317 {} {} {} {} 133811386 {} {:e} 136.7 1.4 -",
+            target, from, to, from, to, strand, e_value
+        );
+        Ok(Record::Dna(parse_dna_line(&line).unwrap()))
+    }
+
+    fn truth() -> Regions {
+        let mut truth = Regions::new();
+        truth.add("chr1", 101, 200);
+        truth.add("chr1", 401, 500);
+        truth.add("chr1", 801, 900);
+        truth.add("chr2", 1, 100);
+        truth
+    }
+
+    fn hits() -> Vec<Result<Record>> {
+        vec![
+            // out of E-value order
+            hit("chr2", 1, 100, 1e-10),
+            hit("chr1", 101, 200, 1e-30),
+            // half of a region already found
+            hit("chr1", 150, 250, 1e-20),
+            // no region, and one on the minus strand, at the same E-value
+            hit("chr1", 600, 700, 1e-5),
+            hit("chr1", 500, 401, 1e-5),
+            // 40% of a region
+            hit("chr1", 801, 840, 1.0),
+            // a target with no regions
+            hit("chr3", 1, 100, 2.0),
+        ]
+    }
+
+    fn point(e_value: f64, tp: u64, fp: u64, fn_: u64) -> EvaluationPoint {
+        EvaluationPoint {
+            e_value,
+            true_positives: tp,
+            false_positives: fp,
+            false_negatives: fn_,
+        }
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let curve = evaluate(hits(), &truth(), 0.5).unwrap();
+        assert_eq!(curve.truth, 4);
+        assert_eq!(
+            curve.points,
+            vec![
+                point(1e-30, 1, 0, 3),
+                point(1e-20, 1, 1, 3),
+                point(1e-10, 2, 1, 2),
+                point(1e-5, 3, 2, 1),
+                point(1.0, 3, 3, 1),
+                point(2.0, 3, 4, 1),
+            ]
+        );
+
+        // a lower overlap finds the last region
+        let curve = evaluate(hits(), &truth(), 0.4).unwrap();
+        assert_eq!(curve.points[4], point(1.0, 4, 2, 0));
+    }
+
+    #[test]
+    fn test_precision_recall() {
+        let curve = evaluate(hits(), &truth(), 0.5).unwrap();
+        let at = curve.at(0.01);
+        assert_eq!(at, point(0.01, 3, 2, 1));
+        assert_eq!(at.precision(), Some(0.6));
+        assert_eq!(at.recall(), Some(0.75));
+
+        let at = curve.at(1e-15);
+        assert_eq!((at.precision(), at.recall()), (Some(0.5), Some(0.25)));
+
+        // nothing called
+        let at = curve.at(1e-40);
+        assert_eq!((at.precision(), at.recall()), (None, Some(0.0)));
+
+        let curve = evaluate(hits(), &Regions::new(), 0.5).unwrap();
+        assert_eq!(curve.at(1.0).recall(), None);
+        assert_eq!(curve.at(1.0).precision(), Some(0.0));
+    }
+
+    #[test]
+    fn test_write_curve() {
+        let curve = evaluate(hits(), &truth(), 0.5).unwrap();
+        let mut out = Vec::new();
+        write_curve(&curve, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "e_value\ttrue_positives\tfalse_positives\tfalse_negatives\tprecision\trecall"
+        );
+        assert_eq!(lines[1], "1e-30\t1\t0\t3\t1.0000\t0.2500");
+        assert_eq!(lines[4], "1e-5\t3\t2\t1\t0.6000\t0.7500");
+        assert_eq!(lines.len(), 7);
+    }
+
+    #[test]
+    fn test_regions_from_bed() {
+        let bed = "track name=truth\n# curated\nchr1\t100\t200\tLINE\nchr2\t0\t100\n";
+        let regions = Regions::from_bed(bed.as_bytes()).unwrap();
+        assert_eq!(regions, truth_of(&[("chr1", 101, 200), ("chr2", 1, 100)]));
+
+        let err = Regions::from_bed("chr1\t100\n".as_bytes()).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Parser(_)));
+        assert!(Regions::from_bed("chr1\tx\t100\n".as_bytes()).is_err());
+
+        // protein records are skipped
+        let reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let curve = evaluate(reader.into_records(), &regions, 0.5).unwrap();
+        assert!(curve.points.is_empty());
+    }
+
+    fn truth_of(regions: &[(&str, i64, i64)]) -> Regions {
+        let mut truth = Regions::new();
+        for &(target, start, end) in regions {
+            truth.add(target, start, end);
+        }
+        truth
+    }
+}
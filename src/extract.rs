@@ -0,0 +1,232 @@
+use crate::{
+    record::{Record, Strand},
+    Error, ErrorKind, Result,
+};
+use std::fmt::{self, Display, Formatter};
+
+/// The line length sequences are wrapped to when written as FASTA.
+const FASTA_LINE_LENGTH: usize = 60;
+
+/// A sequence extracted for a hit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastaRecord {
+    /// The sequence name, `target/start-end(strand)`.
+    pub id: String,
+    /// The name of the query which hit the sequence.
+    pub description: String,
+    /// The sequence, reverse complemented for hits on the minus strand.
+    pub seq: Vec<u8>,
+}
+
+/// Writes the record in FASTA format, wrapping the sequence at 60
+/// characters.
+impl Display for FastaRecord {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, ">{} {}", self.id, self.description)?;
+        for line in self.seq.chunks(FASTA_LINE_LENGTH) {
+            writeln!(f, "{}", String::from_utf8_lossy(line))?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for extracting hit sequences.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ExtractOptions {
+    /// The number of bases to add either side of each hit.
+    flank: usize,
+    /// Whether to skip protein records rather than error.
+    skip_protein: bool,
+}
+
+impl ExtractOptions {
+    /// The default options: no flanking bases, and protein records
+    /// are an error.
+    pub fn new() -> ExtractOptions {
+        ExtractOptions::default()
+    }
+
+    /// Add `flank` bases either side of each hit, stopping at the
+    /// ends of the sequence.
+    pub fn flank(mut self, flank: usize) -> ExtractOptions {
+        self.flank = flank;
+        self
+    }
+
+    /// Skip protein records, which have no coordinates on the target,
+    /// rather than returning an error for them.
+    pub fn skip_protein(mut self, yes: bool) -> ExtractOptions {
+        self.skip_protein = yes;
+        self
+    }
+}
+
+/// Extract the sequence of each DNA hit from its target sequence,
+/// which `lookup` returns given the target name.
+///
+/// The alignment coordinates are used, with any flanking bases, and
+/// hits on the minus strand are reverse complemented. An error is
+/// returned for a target `lookup` can't find, or a hit which runs
+/// past the end of its target.
+pub fn extract_sequences<'s, I, F>(
+    records: I,
+    lookup: F,
+    opts: ExtractOptions,
+) -> impl Iterator<Item = Result<FastaRecord>> + 's
+where
+    I: IntoIterator<Item = Result<Record>>,
+    I::IntoIter: 's,
+    F: Fn(&str) -> Option<&'s [u8]> + 's,
+{
+    records.into_iter().filter_map(move |record| {
+        let record = match record {
+            Ok(Record::Dna(r)) => r,
+            Ok(Record::Protein(_)) if opts.skip_protein => return None,
+            Ok(Record::Protein(r)) => {
+                return Some(Err(Error::new(ErrorKind::Unsupported(format!(
+                    "protein record for target {} has no coordinates to extract",
+                    r.target_name()
+                )))))
+            }
+            Err(e) => return Some(Err(e)),
+        };
+
+        let target = record.target_name();
+        let seq = match lookup(&target) {
+            Some(seq) => seq,
+            None => {
+                return Some(Err(Error::new(ErrorKind::ReadRecord(format!(
+                    "target {} is not in the sequences",
+                    target
+                )))))
+            }
+        };
+
+        // 1-based inclusive coordinates, start before end
+        let (start, end) = record.interval();
+        let (start, end) = (start.max(1) as usize, end.max(1) as usize);
+        if end > seq.len() {
+            return Some(Err(Error::new(ErrorKind::ReadRecord(format!(
+                "hit {}-{} runs past the end of target {} ({} bases)",
+                start,
+                end,
+                target,
+                seq.len()
+            )))));
+        }
+        let start = start.saturating_sub(opts.flank).max(1);
+        let end = (end + opts.flank).min(seq.len());
+
+        let mut sub = seq[start - 1..end].to_vec();
+        if record.strand() == Strand::Negative {
+            sub.reverse();
+            sub.iter_mut().for_each(|b| *b = complement(*b));
+        }
+        Some(Ok(FastaRecord {
+            id: format!("{}/{}-{}({})", target, start, end, record.strand()),
+            description: record.query_name(),
+            seq: sub,
+        }))
+    })
+}
+
+/// The complement of a nucleotide, including the IUPAC ambiguity
+/// codes, keeping its case.
+fn complement(b: u8) -> u8 {
+    let c = match b.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'T' | b'U' => b'A',
+        b'G' => b'C',
+        b'C' => b'G',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        other => other,
+    };
+    if b.is_ascii_lowercase() {
+        c.to_ascii_lowercase()
+    } else {
+        c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reader::parse_dna_line;
+    use std::collections::HashMap;
+
+    fn record(target: &str, from: i32, to: i32, strand: &str) -> Result<Record> {
+        let line = format!(
+            "{} - TR - 1 10 {} {} {} {} 40 {} 1e-10 40.0 0.1 -",
+            target, from, to, from, to, strand
+        );
+        parse_dna_line(&line).map(Record::Dna)
+    }
+
+    #[test]
+    fn test_extract() {
+        let mut fasta = HashMap::new();
+        fasta.insert(
+            "chr1".to_string(),
+            b"AAAACCCCGGGGTTTTacgtNNNNAAAACCCCGGGGTTTT".to_vec(),
+        );
+        let lookup = |name: &str| fasta.get(name).map(|s| s.as_slice());
+
+        let records = vec![
+            record("chr1", 5, 8, "+"),
+            // minus strand, coordinates from high to low
+            record("chr1", 20, 13, "-"),
+            // flanks clamped at the contig end
+            record("chr1", 37, 40, "+"),
+            record("chr2", 1, 4, "+"),
+        ];
+        let out =
+            extract_sequences(records, lookup, ExtractOptions::new().flank(2)).collect::<Vec<_>>();
+
+        let first = out[0].as_ref().unwrap();
+        assert_eq!(first.id, "chr1/3-10(+)");
+        assert_eq!(first.description, "TR");
+        assert_eq!(first.seq, b"AACCCCGG");
+
+        let minus = out[1].as_ref().unwrap();
+        assert_eq!(minus.id, "chr1/11-22(-)");
+        // GGTTTTacgtNN reverse complemented
+        assert_eq!(minus.seq, b"NNacgtAAAACC");
+
+        let clamped = out[2].as_ref().unwrap();
+        assert_eq!(clamped.id, "chr1/35-40(+)");
+        assert_eq!(clamped.seq, b"GGTTTT");
+        assert_eq!(clamped.to_string(), ">chr1/35-40(+) TR\nGGTTTT\n");
+
+        assert!(out[3].is_err());
+    }
+
+    #[test]
+    fn test_protein_records() {
+        let protein = crate::reader::parse_protein_line(
+            "HBB_HUMAN - MYG_ESCGI - 2.3e-11 30.3 0.1 2.5e-11 30.2 0.1 1.0 1 0 0 1 1 1 1 -",
+        )
+        .map(Record::Protein);
+        let lookup = |_: &str| None;
+
+        let skipped = extract_sequences(
+            vec![protein],
+            lookup,
+            ExtractOptions::new().skip_protein(true),
+        );
+        assert_eq!(skipped.count(), 0);
+
+        let protein = crate::reader::parse_protein_line(
+            "HBB_HUMAN - MYG_ESCGI - 2.3e-11 30.3 0.1 2.5e-11 30.2 0.1 1.0 1 0 0 1 1 1 1 -",
+        )
+        .map(Record::Protein);
+        let mut errors = extract_sequences(vec![protein], lookup, ExtractOptions::new());
+        assert!(errors.next().unwrap().is_err());
+    }
+}
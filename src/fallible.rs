@@ -0,0 +1,102 @@
+use crate::{
+    reader::{
+        DNARecordsIntoIter, DNARecordsIter, ProteinRecordsIntoIter, ProteinRecordsIter,
+        RecordsIntoIter, RecordsIter,
+    },
+    record::{DNARecord, ProteinRecord, Record},
+    Error, Result,
+};
+use fallible_iterator::FallibleIterator;
+use std::io;
+
+// The record iterators also implement `Iterator`, whose adapters have
+// the same names, so the first adapter of a chain is called through the
+// trait, e.g. `FallibleIterator::filter(records, ..)`. The adapters it
+// returns are only fallible iterators.
+
+/// Implement `FallibleIterator` for a record iterator by its
+/// `Iterator` impl, so errors end the iteration.
+macro_rules! fallible_records {
+    ($iter:ident<$($lt:lifetime,)? R>, $item:ty) => {
+        /// Records are yielded until the first error, which is returned
+        /// from the adapter chain, such as from `fold`.
+        impl<$($lt,)? R: io::Read> FallibleIterator for $iter<$($lt,)? R> {
+            type Item = $item;
+            type Error = Error;
+
+            fn next(&mut self) -> Result<Option<$item>> {
+                Iterator::next(self).transpose()
+            }
+        }
+    };
+}
+
+fallible_records!(RecordsIter<'r, R>, Record);
+fallible_records!(RecordsIntoIter<R>, Record);
+fallible_records!(DNARecordsIter<'r, R>, DNARecord);
+fallible_records!(DNARecordsIntoIter<R>, DNARecord);
+fallible_records!(ProteinRecordsIter<'r, R>, ProteinRecord);
+fallible_records!(ProteinRecordsIntoIter<R>, ProteinRecord);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{record::TbloutRecord, ErrorKind, Reader};
+
+    #[test]
+    fn test_filter_map_fold() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let total = FallibleIterator::filter(reader.into_records(), |r| {
+            Ok(r.score_opt().unwrap_or(0.0) > 30.0)
+        })
+        .map(|r| Ok(r.query_name()))
+        .fold(0, |n, query| Ok(n + (query == "TR") as usize))
+        .unwrap();
+        // the std adapters are still there
+        let records = Reader::from_path("./data/test.tbl").unwrap().into_records();
+        let expected =
+            Iterator::filter(records, |r| r.as_ref().unwrap().score_opt().unwrap() > 30.0).count();
+        assert_eq!(total, expected);
+        assert!(total > 0);
+    }
+
+    #[test]
+    fn test_parse_error_surfaces_at_fold() {
+        let file = std::fs::read_to_string("./data/test.tbl")
+            .unwrap()
+            .replacen(" 66.5 ", " x ", 1);
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let mut seen = 0;
+        let err = FallibleIterator::map(reader.dna_records().unwrap(), |r| {
+            seen += 1;
+            Ok(r.score())
+        })
+        .filter(|score| Ok(*score > 30.0))
+        .fold(0.0, |sum, score| Ok(sum + score))
+        .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Float(_)));
+        // the two records before the bad one were mapped, then the
+        // chain stopped at the third
+        assert_eq!(seen, 2);
+        assert_eq!(reader.line(), 5);
+    }
+
+    #[test]
+    fn test_protein_records() {
+        let mut reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let first = FallibleIterator::next(&mut reader.protein_records().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.query_name(), "SOR");
+        let count = FallibleIterator::count(reader.into_protein_records().unwrap()).unwrap();
+        assert_eq!(
+            count + 1,
+            FallibleIterator::count(
+                Reader::from_path("./data/corpus/hmmsearch.tbl")
+                    .unwrap()
+                    .into_records()
+            )
+            .unwrap()
+        );
+    }
+}
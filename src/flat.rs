@@ -0,0 +1,168 @@
+use crate::record::{DNARecord, ProteinRecord, Strand};
+use serde::{Deserialize, Serialize};
+
+/// A protein record with public fields in the order of the tblout
+/// columns, for use with `serde` based writers such as the `csv`
+/// crate's `Writer::serialize`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlatProteinRecord {
+    pub target_name: String,
+    pub target_accession: String,
+    pub query_name: String,
+    pub query_accession: String,
+    pub e_value_full: f64,
+    pub score_full: f32,
+    pub bias_full: f32,
+    pub e_value_best: f64,
+    pub score_best: f32,
+    pub bias_best: f32,
+    pub exp: f32,
+    pub reg: i32,
+    pub clu: i32,
+    pub ov: i32,
+    pub env: i32,
+    pub dom: i32,
+    pub rep: i32,
+    pub inc: i32,
+    /// The description of the target, or `-` if there is none.
+    pub description: String,
+}
+
+impl From<&ProteinRecord> for FlatProteinRecord {
+    fn from(r: &ProteinRecord) -> FlatProteinRecord {
+        FlatProteinRecord {
+            target_name: r.target_name(),
+            target_accession: r.target_accession(),
+            query_name: r.query_name(),
+            query_accession: r.query_accession(),
+            e_value_full: r.e_value_full(),
+            score_full: r.score_full(),
+            bias_full: r.bias_full(),
+            e_value_best: r.e_value_best(),
+            score_best: r.score_best(),
+            bias_best: r.bias_best(),
+            exp: r.exp(),
+            reg: r.reg(),
+            clu: r.clu(),
+            ov: r.ov(),
+            env: r.env(),
+            dom: r.dom(),
+            rep: r.rep(),
+            inc: r.inc(),
+            description: r.description(),
+        }
+    }
+}
+
+/// A DNA record with public fields in the order of the tblout
+/// columns, for use with `serde` based writers such as the `csv`
+/// crate's `Writer::serialize`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlatDnaRecord {
+    pub target_name: String,
+    pub target_accession: String,
+    pub query_name: String,
+    pub query_accession: String,
+    pub hmm_from: i32,
+    pub hmm_to: i32,
+    pub ali_from: i64,
+    pub ali_to: i64,
+    pub env_from: i64,
+    pub env_to: i64,
+    pub sq_len: i64,
+    pub strand: Strand,
+    pub e_value: f64,
+    pub score: f32,
+    pub bias: f32,
+    /// The description of the target, or `-` if there is none.
+    pub description: String,
+}
+
+impl From<&DNARecord> for FlatDnaRecord {
+    fn from(r: &DNARecord) -> FlatDnaRecord {
+        FlatDnaRecord {
+            target_name: r.target_name(),
+            target_accession: r.target_accession(),
+            query_name: r.query_name(),
+            query_accession: r.query_accession(),
+            hmm_from: r.hmm_from(),
+            hmm_to: r.hmm_to(),
+            ali_from: r.ali_from(),
+            ali_to: r.ali_to(),
+            env_from: r.env_from(),
+            env_to: r.env_to(),
+            sq_len: r.sq_len(),
+            strand: r.strand(),
+            e_value: r.e_value(),
+            score: r.score(),
+            bias: r.bias(),
+            description: r.description(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{tsv::DNA_COLUMNS, Reader, Record};
+
+    #[test]
+    fn test_csv_round_trip() {
+        let flat = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .into_records()
+            .map(|r| match r.unwrap() {
+                Record::Dna(r) => FlatDnaRecord::from(&r),
+                Record::Protein(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut wtr = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_writer(Vec::new());
+        for record in &flat {
+            wtr.serialize(record).unwrap();
+        }
+        let out = wtr.into_inner().unwrap();
+
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_reader(out.as_slice());
+        assert_eq!(rdr.headers().unwrap(), DNA_COLUMNS.as_slice());
+        let back = rdr
+            .deserialize()
+            .collect::<std::result::Result<Vec<FlatDnaRecord>, _>>()
+            .unwrap();
+        assert_eq!(back, flat);
+        assert_eq!(back[1].strand, Strand::Negative);
+        assert_eq!(back[1].ali_from, 61501865);
+    }
+
+    #[test]
+    fn test_protein_from() {
+        let record = ProteinRecord::new(
+            "HBB_HUMAN".into(),
+            "-".into(),
+            "MYG_ESCGI".into(),
+            "-".into(),
+            2.3e-11,
+            30.3,
+            0.1,
+            2.5e-11,
+            30.2,
+            0.1,
+            1.0,
+            1,
+            0,
+            0,
+            1,
+            1,
+            1,
+            1,
+        );
+        let flat = FlatProteinRecord::from(&record);
+        assert_eq!(flat.target_name, "HBB_HUMAN");
+        assert_eq!(flat.e_value_best, 2.5e-11);
+        assert_eq!(flat.inc, 1);
+    }
+}
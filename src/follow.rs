@@ -0,0 +1,141 @@
+use crate::{
+    header::Header,
+    reader::{parse_dna_line, parse_protein_line},
+    record::RecordKind,
+    Error, ErrorKind, Record, Result,
+};
+
+use std::{
+    fs::File,
+    io::{self, BufRead},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+/// An iterator which tails a tblout file that is still being
+/// written, yielding records as complete lines are appended.
+///
+/// When the end of the file is reached, the iterator sleeps for
+/// the poll interval and tries again. Iteration stops once the
+/// trailing metadata block (`# Program:`) or the `# [ok]` footer
+/// is seen.
+///
+/// The program is only named in the metadata at the end, so the kind
+/// of records is told from the header at the top, before the first
+/// record, and every record is parsed as that kind. If the header does
+/// not say, as for a file of records alone, a single `ErrorKind::Meta`
+/// error is yielded.
+pub struct FollowIter {
+    /// The underlying reader.
+    rdr: io::BufReader<File>,
+    /// How long to sleep when no new data is available.
+    poll_interval: Duration,
+    /// Bytes of the current line read so far.
+    buf: Vec<u8>,
+    /// The current line number.
+    line: u64,
+    /// The header read from the comment lines before the first record.
+    header: Header,
+    /// The kind of records, from the header, once the first record
+    /// line is reached.
+    kind: Option<RecordKind>,
+    /// Whether the footer has been reached.
+    done: bool,
+}
+
+impl FollowIter {
+    /// Open a file to follow.
+    pub(crate) fn new<P: AsRef<Path>>(path: P, poll_interval: Duration) -> Result<FollowIter> {
+        Ok(FollowIter {
+            rdr: io::BufReader::new(File::open(path)?),
+            poll_interval,
+            buf: Vec::new(),
+            line: 0,
+            header: Header::default(),
+            kind: None,
+            done: false,
+        })
+    }
+
+    /// The number of complete lines read so far.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// The header of the file, complete once the first record has been
+    /// read.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Read the next complete line, waiting for more data
+    /// at the end of the file.
+    fn next_line(&mut self) -> Result<String> {
+        loop {
+            // a partial line is kept in the buffer until the
+            // newline arrives.
+            match self.rdr.read_until(b'\n', &mut self.buf) {
+                Ok(0) => thread::sleep(self.poll_interval),
+                Ok(_) => {
+                    if self.buf.ends_with(b"\n") {
+                        self.line += 1;
+                        let bytes = std::mem::take(&mut self.buf);
+                        return String::from_utf8(bytes).map_err(|e| {
+                            Error::new(ErrorKind::ReadRecord(format!(
+                                "line {} is not valid UTF-8 - {}",
+                                self.line, e
+                            )))
+                        });
+                    }
+                }
+                Err(e) => return Err(Error::new(ErrorKind::Io(e))),
+            }
+        }
+    }
+}
+
+impl Iterator for FollowIter {
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Result<Record>> {
+        while !self.done {
+            let line = match self.next_line() {
+                Ok(l) => l,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if line.starts_with("# Program:") || line.starts_with("# [ok]") {
+                self.done = true;
+                break;
+            }
+            if line.starts_with('#') {
+                if self.kind.is_none() {
+                    self.header.push_line(&line);
+                }
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let kind = match self.kind.or_else(|| self.header.record_kind()) {
+                Some(kind) => kind,
+                None => {
+                    self.done = true;
+                    return Some(Err(Error::new(ErrorKind::Meta(
+                        "the header does not name HMMER's columns, so the kind of records is unknown"
+                            .into(),
+                    ))));
+                }
+            };
+            self.kind = Some(kind);
+            let record = match kind {
+                RecordKind::Dna => parse_dna_line(&line).map(Record::Dna),
+                RecordKind::Protein => parse_protein_line(&line).map(Record::Protein),
+            };
+            return Some(record);
+        }
+        None
+    }
+}
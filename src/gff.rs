@@ -0,0 +1,212 @@
+use crate::{bed::coordinates, hit::Hit, record::Meta, Result};
+use std::io::{self, Write};
+
+/// Options for converting records to GFF3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gff3Options {
+    /// The source column, usually the program which found the hits.
+    source: String,
+    /// The type column, a Sequence Ontology term.
+    feature_type: String,
+}
+
+impl Default for Gff3Options {
+    fn default() -> Gff3Options {
+        Gff3Options {
+            source: ".".into(),
+            feature_type: "nucleotide_match".into(),
+        }
+    }
+}
+
+impl Gff3Options {
+    /// Options for the output of a search, using the program name
+    /// from its metadata as the source.
+    pub fn new(meta: &Meta) -> Gff3Options {
+        Gff3Options {
+            source: meta.program().to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the source column.
+    pub fn source(mut self, source: &str) -> Gff3Options {
+        self.source = source.into();
+        self
+    }
+
+    /// Set the type column, `nucleotide_match` by default.
+    pub fn feature_type(mut self, feature_type: &str) -> Gff3Options {
+        self.feature_type = feature_type.into();
+        self
+    }
+}
+
+/// Write DNA records as GFF3 features, one per record, after the
+/// `##gff-version 3` line. Returns the number of features written.
+/// The records may be `Record`s, the concrete records, or `Hit`s.
+///
+/// The feature spans the alignment on the target, with the start
+/// before the end whatever the strand. The score is the bit score,
+/// and the query name and accession, E-value and description (unless
+/// it is `-`) are written as attributes. Hits without coordinates on
+/// the target, such as those of protein records, are rejected with an
+/// `ErrorKind::Unsupported` error.
+pub fn to_gff3<W: Write, R: Into<Hit>>(
+    records: impl IntoIterator<Item = Result<R>>,
+    w: W,
+    options: &Gff3Options,
+) -> Result<usize> {
+    let mut w = io::BufWriter::new(w);
+    writeln!(w, "##gff-version 3")?;
+
+    let mut n = 0;
+    for record in records {
+        let hit: Hit = record?.into();
+        let ((start, end), strand) = coordinates(&hit, "GFF3")?;
+        let description = match hit.description.as_str() {
+            "-" => String::new(),
+            d => format!(";description={}", escape(d, true)),
+        };
+        writeln!(
+            w,
+            "{}\t{}\t{}\t{}\t{}\t{:.1}\t{}\t.\tquery_name={};query_accession={};evalue={:e}{}",
+            escape_seqid(&hit.target_name),
+            escape(&options.source, false),
+            escape(&options.feature_type, false),
+            start,
+            end,
+            hit.score,
+            strand,
+            escape(&hit.query_name, true),
+            escape(&hit.query_accession, true),
+            hit.e_value,
+            description,
+        )?;
+        n += 1;
+    }
+    w.flush()?;
+    Ok(n)
+}
+
+/// Percent encode the characters GFF3 reserves in a column, or in
+/// an attribute value if `attribute` is set.
+fn escape(s: &str, attribute: bool) -> String {
+    percent_encode(s, |c| match c {
+        '\t' | '\n' | '\r' | '%' => true,
+        ';' | '=' | '&' | ',' => attribute,
+        c => c.is_control(),
+    })
+}
+
+/// Percent encode a seqid, the first column, where GFF3 allows only
+/// letters, digits and `.:^*$@!+_?-|` unescaped.
+fn escape_seqid(s: &str) -> String {
+    percent_encode(s, |c| {
+        !(c.is_ascii_alphanumeric() || ".:^*$@!+_?-|".contains(c))
+    })
+}
+
+/// Percent encode the UTF-8 bytes of each character `reserved` matches.
+fn percent_encode(s: &str, reserved: impl Fn(char) -> bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if reserved(c) {
+            let mut buf = [0; 4];
+            for b in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("%{:02X}", b));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, ErrorKind, Program, Reader, Record};
+
+    #[test]
+    fn test_gff3_structure() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let options = Gff3Options::new(reader.meta());
+        let mut out = Vec::new();
+        assert_eq!(
+            to_gff3(reader.into_records(), &mut out, &options).unwrap(),
+            31
+        );
+
+        let out = String::from_utf8(out).unwrap();
+        let mut lines = out.lines();
+        assert_eq!(lines.next(), Some("##gff-version 3"));
+        let mut minus = 0;
+        for line in lines {
+            let cols = line.split('\t').collect::<Vec<_>>();
+            assert_eq!(cols.len(), 9);
+            assert_eq!(cols[1], "nhmmer");
+            assert_eq!(cols[2], "nucleotide_match");
+            let (start, end) = (
+                cols[3].parse::<u64>().unwrap(),
+                cols[4].parse::<u64>().unwrap(),
+            );
+            assert!(1 <= start && start <= end);
+            assert!(cols[5].parse::<f32>().is_ok());
+            assert!(cols[6] == "+" || cols[6] == "-");
+            assert!(cols[8].split(';').all(|kv| kv
+                .split_once('=')
+                .is_some_and(|(k, v)| !k.is_empty() && !v.is_empty())));
+            if cols[6] == "-" {
+                minus += 1;
+            }
+        }
+        assert!(minus > 0);
+
+        // the second hit is on the minus strand, from 61501865 to 61501510
+        let second = out.lines().nth(2).unwrap();
+        assert!(
+            second.starts_with("6\tnhmmer\tnucleotide_match\t61501510\t61501865\t122.3\t-\t.\t")
+        );
+    }
+
+    #[test]
+    fn test_gff3_rejects_protein() {
+        let records = Reader::from_reader(&b"# target name\n#----\nHBB_HUMAN            -          MYG_ESCGI            -              2.3e-11   30.3   0.1   2.5e-11   30.2   0.1   1.0   1   0   0   1   1   1   1 Hemoglobin\n#\n# Program:         phmmer\n# [ok]\n"[..])
+            .unwrap()
+            .into_records();
+        let options = Gff3Options::new(&Meta::for_program(Program::Phmmer));
+        let err = to_gff3(records, io::sink(), &options).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a;b=c,d&e%f\tg", true), "a%3Bb%3Dc%2Cd%26e%25f%09g");
+        assert_eq!(escape("chr1;x", false), "chr1;x");
+        assert_eq!(escape_seqid("sp|P1|A_B-1.2"), "sp|P1|A_B-1.2");
+        assert_eq!(escape_seqid("chr 1;x=y>z"), "chr%201%3Bx%3Dy%3Ez");
+        assert_eq!(escape_seqid("chrÅ"), "chr%C3%85");
+    }
+
+    #[test]
+    fn test_gff3_description() {
+        let line = "chr1 -  TR  -  2  317  338944  339292  338943  339292  133811386  +  1.9e-37  136.7  1.4  50%; id=7 (alt)";
+        let record = Record::Dna(parse_dna_line(line).unwrap());
+        let options = Gff3Options::new(&Meta::for_program(Program::Nhmmer));
+        let mut out = Vec::new();
+        to_gff3(vec![Ok(record.clone())], &mut out, &options).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let attributes = out.lines().nth(1).unwrap().split('\t').nth(8).unwrap();
+        assert!(attributes.ends_with(";description=50%25%3B id%3D7 (alt)"));
+
+        // no attribute for a record without a description
+        let mut record = record;
+        if let Record::Dna(r) = &mut record {
+            r.set_description("-".into());
+        }
+        let mut out = Vec::new();
+        to_gff3(vec![Ok(record)], &mut out, &options).unwrap();
+        assert!(!String::from_utf8(out).unwrap().contains("description="));
+    }
+}
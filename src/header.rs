@@ -0,0 +1,268 @@
+use crate::{
+    record::{RecordKind, DNA_WIDTHS, PROTEIN_WIDTHS},
+    Program,
+};
+use std::fmt::{self, Display, Formatter};
+
+/// The comment lines at the top of a HMMER tblout file, which
+/// name the columns and show their widths.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    /// The line grouping the protein columns, e.g. `--- full sequence ----`.
+    /// Only protein searches have this line, and tools which rewrite
+    /// the file sometimes drop it.
+    group: Option<String>,
+    /// The line with the column names.
+    columns: String,
+    /// The line of dashes underlining each column.
+    dashes: String,
+}
+
+impl Header {
+    /// Construct a header from its lines, without trailing newlines.
+    pub fn new(group: Option<String>, columns: String, dashes: String) -> Header {
+        Header {
+            group,
+            columns,
+            dashes,
+        }
+    }
+
+    /// The header HMMER writes for a program, with its default
+    /// column widths. Returns `None` for `Program::None`.
+    pub fn for_program(program: Program) -> Option<Header> {
+        if program.is_dna_search() {
+            Header::canonical(program, &DNA_WIDTHS)
+        } else {
+            Header::canonical(program, &PROTEIN_WIDTHS)
+        }
+    }
+
+    /// Generate the header HMMER writes for a program, given the
+    /// column widths of its records.
+    pub(crate) fn canonical(program: Program, widths: &[usize]) -> Option<Header> {
+        let (tnamew, taccw, qnamew, qaccw) = (widths[0] - 1, widths[1], widths[2], widths[3]);
+        match program {
+            Program::None => None,
+            Program::Nhmmer | Program::Nhmmscan => {
+                let posw = widths[6];
+                // nhmmscan reports the model length rather than the sequence length
+                let len = if program == Program::Nhmmer {
+                    "sq len"
+                } else {
+                    "modlen"
+                };
+                let columns = format!(
+                    "#{:<tnamew$} {:<taccw$} {:<qnamew$} {:<qaccw$} hmmfrom hmm to {:>posw$} {:>posw$} {:>posw$} {:>posw$} {:>posw$} strand   E-value  score  bias  description of target",
+                    " target name", "accession", "query name", "accession", "alifrom", "ali to", "envfrom", "env to", len,
+                );
+                let dashes = format!(
+                    "#{:>tnamew$} {:>taccw$} {:>qnamew$} {:>qaccw$} ------- ------- {:>posw$} {:>posw$} {:>posw$} {:>posw$} {:>posw$} ------ --------- ------ ----- ---------------------",
+                    "-------------------", "----------", "--------------------", "----------", "-------", "-------", "-------", "-------", "-------",
+                );
+                Some(Header::new(None, columns, dashes))
+            }
+            _ => {
+                let group = group_line(tnamew + taccw + qnamew + qaccw + 3);
+                let columns = format!(
+                    "#{:<tnamew$} {:<taccw$} {:<qnamew$} {:<qaccw$}   E-value  score  bias   E-value  score  bias   exp reg clu  ov env dom rep inc description of target",
+                    " target name", "accession", "query name", "accession",
+                );
+                let dashes = format!(
+                    "#{:>tnamew$} {:>taccw$} {:>qnamew$} {:>qaccw$} --------- ------ ----- --------- ------ -----   --- --- --- --- --- --- --- --- ---------------------",
+                    "-------------------", "----------", "--------------------", "----------",
+                );
+                Some(Header::new(Some(group), columns, dashes))
+            }
+        }
+    }
+
+    /// Add a comment line from the top of a file to the header.
+    pub(crate) fn push_line(&mut self, line: &str) {
+        let line = line.trim_end_matches(['\n', '\r']).to_string();
+        if line.contains("--- full sequence ---") {
+            self.group = Some(line);
+        } else if line.trim_start_matches('#').trim_start().starts_with('-') {
+            self.dashes = line;
+        } else if self.columns.is_empty() {
+            self.columns = line;
+        }
+    }
+
+    /// Whether the header has no lines, as for a file of records
+    /// alone. An empty header is written as nothing at all.
+    pub fn is_empty(&self) -> bool {
+        self.group.is_none() && self.columns.is_empty() && self.dashes.is_empty()
+    }
+
+    /// The line grouping the protein columns, if there is one. See
+    /// `Display` for how a protein header without one is written.
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    /// The kind of records the header is for, from its column names,
+    /// or `None` if they are not HMMER's. Protein headers are known by
+    /// their two E-value columns, so the group line above them is not
+    /// needed.
+    pub fn record_kind(&self) -> Option<RecordKind> {
+        let columns = self.columns.trim_start_matches('#').split_whitespace();
+        let (mut e_values, mut hmmfrom) = (0, false);
+        for column in columns {
+            match column {
+                "E-value" => e_values += 1,
+                "hmmfrom" => hmmfrom = true,
+                _ => (),
+            }
+        }
+        if self.group.is_some() || (e_values == 2 && !hmmfrom) {
+            Some(RecordKind::Protein)
+        } else if hmmfrom {
+            Some(RecordKind::Dna)
+        } else {
+            None
+        }
+    }
+
+    /// The group line HMMER would write above the columns, aligned to
+    /// the widths of the names and accessions from the dashes.
+    fn canonical_group(&self) -> String {
+        let widths = self.calculate_dashes();
+        let pad = match widths.get(..4) {
+            Some(&[tname, tacc, qname, qacc]) => tname - 1 + tacc + qname + qacc + 3,
+            _ => PROTEIN_WIDTHS[..4].iter().sum::<usize>() - 1 + 3,
+        };
+        group_line(pad)
+    }
+
+    /// The line with the column names.
+    pub fn columns(&self) -> &str {
+        &self.columns
+    }
+
+    /// The line of dashes underlining each column.
+    pub fn dashes(&self) -> &str {
+        &self.dashes
+    }
+
+    /// Calculate the width of each column from the line of dashes.
+    ///
+    /// HMMER right justifies the dashes in each column, so a column
+    /// starts after the single space separating it from the previous
+    /// one and ends at its last dash. The first column includes the
+    /// leading `#`. Widths are in characters.
+    pub fn calculate_dashes(&self) -> Vec<usize> {
+        let mut widths = Vec::new();
+        let mut start = 0;
+        let mut in_run = false;
+        let mut len = 0;
+        for (i, c) in self.dashes.chars().enumerate() {
+            len = i + 1;
+            match (c == '-', in_run) {
+                (true, false) => in_run = true,
+                (false, true) => {
+                    widths.push(i - start);
+                    start = i + 1;
+                    in_run = false;
+                }
+                _ => (),
+            }
+        }
+        if in_run {
+            widths.push(len - start);
+        }
+        widths
+    }
+}
+
+/// The line grouping the protein columns, after `pad` spaces covering
+/// the names and accessions.
+fn group_line(pad: usize) -> String {
+    format!(
+        "#{:pad$} --- full sequence ---- --- best 1 domain ---- --- domain number estimation ----",
+        "",
+    )
+}
+
+/// The header lines, as HMMER writes them. A protein header without
+/// its group line gets one generated from the widths of its columns,
+/// as HMMER always writes it.
+impl Display for Header {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+        match &self.group {
+            Some(group) => writeln!(f, "{}", group)?,
+            None if self.record_kind() == Some(RecordKind::Protein) => {
+                writeln!(f, "{}", self.canonical_group())?
+            }
+            None => (),
+        }
+        writeln!(f, "{}", self.columns)?;
+        writeln!(f, "{}", self.dashes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_program() {
+        // as written by nhmmscan 3.2
+        let header = Header::for_program(Program::Nhmmscan).unwrap();
+        assert_eq!(header.columns(), "# target name        accession  query name           accession  hmmfrom hmm to alifrom  ali to envfrom  env to  modlen strand   E-value  score  bias  description of target");
+        assert_eq!(header.dashes(), "#------------------- ---------- -------------------- ---------- ------- ------- ------- ------- ------- ------- ------- ------ --------- ------ ----- ---------------------");
+        assert_eq!(header.calculate_dashes()[..15], DNA_WIDTHS);
+
+        // as written by hmmsearch 3.2.1
+        let header = Header::for_program(Program::Hmmsearch).unwrap();
+        assert_eq!(header.group(), Some("#                                                               --- full sequence ---- --- best 1 domain ---- --- domain number estimation ----"));
+        assert_eq!(header.columns(), "# target name        accession  query name           accession    E-value  score  bias   E-value  score  bias   exp reg clu  ov env dom rep inc description of target");
+        assert_eq!(header.dashes(), "#------------------- ---------- -------------------- ---------- --------- ------ ----- --------- ------ -----   --- --- --- --- --- --- --- --- ---------------------");
+        assert_eq!(header.calculate_dashes()[..18], PROTEIN_WIDTHS);
+
+        assert!(Header::for_program(Program::None).is_none());
+    }
+
+    #[test]
+    fn test_record_kind() {
+        for program in [Program::Hmmsearch, Program::Nhmmer, Program::Nhmmscan] {
+            let header = Header::for_program(program).unwrap();
+            assert_eq!(header.record_kind(), program.record_kind());
+        }
+
+        // without the group line, as some tools leave protein headers
+        let full = Header::for_program(Program::Hmmsearch).unwrap();
+        let header = Header::new(None, full.columns().into(), full.dashes().into());
+        assert_eq!(header.record_kind(), Some(RecordKind::Protein));
+        assert_eq!(header.to_string(), full.to_string());
+
+        assert_eq!(Header::default().record_kind(), None);
+        assert_eq!(Header::default().to_string(), "");
+        assert!(Header::default().calculate_dashes().is_empty());
+    }
+
+    #[test]
+    fn test_canonical_group_widths() {
+        // widened names, as HMMER writes them for long target names
+        let header = Header::canonical(Program::Phmmer, &[30, 10, 20, 10]).unwrap();
+        let stripped = Header::new(None, header.columns().into(), header.dashes().into());
+        assert_eq!(stripped.canonical_group(), header.group().unwrap());
+    }
+
+    #[test]
+    fn test_calculate_dashes() {
+        let mut header = Header::default();
+        header.push_line(
+            "#------------------- ---------- ------- -------   -------  ------ ---------\n",
+        );
+        assert_eq!(header.calculate_dashes(), vec![20, 10, 7, 7, 9, 7, 9]);
+
+        // right justified dashes in a widened first column
+        header.push_line("#  ------------------- ----------\n");
+        assert_eq!(header.calculate_dashes(), vec![22, 10]);
+    }
+}
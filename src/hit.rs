@@ -0,0 +1,186 @@
+use crate::record::{DNARecord, ProteinRecord, Record, Strand, TbloutRecord};
+
+/// The parts of a hit most tools need, the same for every kind of
+/// record, so code can take hits without matching on `Record`.
+///
+/// Values only some kinds of record have are `None` for the others.
+/// For protein records, the E-value, score and bias are those of the
+/// full sequence, which HMMER ranks hits by. The detailed record it
+/// came from is left as it was.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hit {
+    /// The name of the target.
+    pub target_name: String,
+    /// The accession of the target, or `-` if none.
+    pub target_accession: String,
+    /// The name of the query.
+    pub query_name: String,
+    /// The accession of the query, or `-` if none.
+    pub query_accession: String,
+    /// The start and end of the alignment on the target, the lower
+    /// first whatever the strand. `None` for protein records.
+    pub interval: Option<(i64, i64)>,
+    /// The strand of the target the hit is on. `None` for protein
+    /// records.
+    pub strand: Option<Strand>,
+    /// The E-value of the hit.
+    pub e_value: f64,
+    /// The bit score of the hit.
+    pub score: f32,
+    /// The biased-composition correction of the bit score.
+    pub bias: f32,
+    /// The description of the target, or `-` if there is none.
+    pub description: String,
+}
+
+impl From<&DNARecord> for Hit {
+    fn from(record: &DNARecord) -> Hit {
+        Hit {
+            target_name: record.target_name(),
+            target_accession: record.target_accession(),
+            query_name: record.query_name(),
+            query_accession: record.query_accession(),
+            interval: Some(record.interval()),
+            strand: Some(record.strand()),
+            e_value: record.e_value(),
+            score: record.score(),
+            bias: record.bias(),
+            description: record.description(),
+        }
+    }
+}
+
+impl From<&ProteinRecord> for Hit {
+    fn from(record: &ProteinRecord) -> Hit {
+        Hit {
+            target_name: record.target_name(),
+            target_accession: record.target_accession(),
+            query_name: record.query_name(),
+            query_accession: record.query_accession(),
+            interval: None,
+            strand: None,
+            e_value: record.e_value_full(),
+            score: record.score_full(),
+            bias: record.bias_full(),
+            description: record.description(),
+        }
+    }
+}
+
+impl From<&Record> for Hit {
+    fn from(record: &Record) -> Hit {
+        match record {
+            Record::Dna(record) => Hit::from(record),
+            Record::Protein(record) => Hit::from(record),
+        }
+    }
+}
+
+macro_rules! hit_from_owned {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Hit {
+                fn from(record: $ty) -> Hit {
+                    Hit::from(&record)
+                }
+            }
+        )*
+    };
+}
+
+hit_from_owned!(DNARecord, ProteinRecord, Record);
+
+impl TbloutRecord for Hit {
+    fn target_name(&self) -> String {
+        self.target_name.clone()
+    }
+    fn target_accession(&self) -> String {
+        self.target_accession.clone()
+    }
+    fn query_name(&self) -> String {
+        self.query_name.clone()
+    }
+    fn query_accession(&self) -> String {
+        self.query_accession.clone()
+    }
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+    fn evalue_opt(&self) -> Option<f64> {
+        Some(self.e_value)
+    }
+    fn score_opt(&self) -> Option<f32> {
+        Some(self.score)
+    }
+    fn bias_opt(&self) -> Option<f32> {
+        Some(self.bias)
+    }
+    /// The coordinates in the order of the strand, so `from` is greater
+    /// than `to` on the minus strand, as the records give them.
+    fn interval_opt(&self) -> Option<(i64, i64)> {
+        let (start, end) = self.interval?;
+        match self.strand {
+            Some(Strand::Negative) => Some((end, start)),
+            _ => Some((start, end)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    fn first(path: &str) -> Record {
+        let mut reader = Reader::from_path(path).unwrap();
+        reader.records().next().unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_hit_from_dna() {
+        let record = first("./data/corpus/nhmmer.tbl");
+        let hit = Hit::from(&record);
+        let dna = match &record {
+            Record::Dna(dna) => dna,
+            Record::Protein(_) => panic!("not a DNA record"),
+        };
+        assert_eq!(hit.target_name, dna.target_name());
+        assert_eq!(hit.query_name, dna.query_name());
+        assert_eq!(hit.interval, Some(dna.interval()));
+        assert_eq!(hit.strand, Some(dna.strand()));
+        assert_eq!(hit.e_value, dna.e_value());
+        assert_eq!(hit.score, dna.score());
+        assert_eq!(hit.bias, dna.bias());
+        assert_eq!(hit, Hit::from(dna.clone()));
+        // the coordinates in their own order again
+        assert_eq!(hit.interval_opt(), record.interval_opt());
+    }
+
+    #[test]
+    fn test_hit_from_protein() {
+        let record = first("./data/corpus/hmmsearch.tbl");
+        let hit = Hit::from(record.clone());
+        assert_eq!(hit.query_name, "SOR");
+        assert_eq!(hit.target_name, "sp|P29082|SOR_ACIAM");
+        assert_eq!((hit.interval, hit.strand), (None, None));
+        assert_eq!(hit.interval_opt(), None);
+        // the full sequence values
+        assert_eq!(hit.e_value, 1.5e-152);
+        assert_eq!(hit.score, 492.8);
+        assert_eq!(hit.bias, 0.8);
+        assert_eq!(hit.evalue_opt(), record.evalue_opt());
+    }
+
+    #[test]
+    fn test_minus_strand_interval() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        for record in reader.into_records() {
+            let record = record.unwrap();
+            let hit = Hit::from(&record);
+            let (start, end) = hit.interval.unwrap();
+            assert!(start <= end);
+            assert_eq!(hit.interval_opt(), record.interval_opt());
+        }
+    }
+}
@@ -0,0 +1,155 @@
+#[cfg(feature = "bgzf")]
+use crate::bgzf::BgzfReader;
+use crate::{
+    progress::{report, Progress, ProgressFn},
+    Error, ErrorKind, Result,
+};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead},
+};
+
+/// A byte offset index over the records of a tblout file, keyed
+/// by query and target name. For BGZF compressed files the offsets
+/// are virtual offsets.
+#[derive(Debug, Default, Clone)]
+pub struct TbloutIndex {
+    /// Offsets of the record lines for each query.
+    queries: HashMap<String, Vec<u64>>,
+    /// Offsets of the record lines for each target.
+    targets: HashMap<String, Vec<u64>>,
+    /// The total number of records indexed.
+    records: u64,
+    /// Whether the offsets are BGZF virtual offsets.
+    bgzf: bool,
+}
+
+impl TbloutIndex {
+    /// Build an index by scanning a tblout file once. The optional
+    /// `progress` callback is called periodically and may cancel
+    /// the build.
+    pub fn build<R: io::Read>(rdr: R, progress: Option<ProgressFn>) -> Result<TbloutIndex> {
+        TbloutIndex::build_with(io::BufReader::new(rdr), |_, bytes| bytes, progress)
+    }
+
+    /// Build an index over a BGZF compressed tblout file. The offsets
+    /// stored are BGZF virtual offsets, which a `Reader` over a
+    /// `BgzfReader` can seek to.
+    #[cfg(feature = "bgzf")]
+    pub fn build_bgzf<R: io::Read>(
+        rdr: BgzfReader<R>,
+        progress: Option<ProgressFn>,
+    ) -> Result<TbloutIndex> {
+        let mut index =
+            TbloutIndex::build_with(rdr, |r: &BgzfReader<R>, _| r.virtual_offset(), progress)?;
+        index.bgzf = true;
+        Ok(index)
+    }
+
+    /// Scan the lines of `rdr`, using `position` to find the offset of
+    /// each line from the reader and the number of bytes read so far.
+    fn build_with<B: BufRead>(
+        mut rdr: B,
+        position: impl Fn(&B, u64) -> u64,
+        progress: Option<ProgressFn>,
+    ) -> Result<TbloutIndex> {
+        let mut index = TbloutIndex::default();
+
+        let mut line = String::new();
+        let mut bytes = 0;
+        loop {
+            line.clear();
+            let offset = position(&rdr, bytes);
+            match rdr.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(n) => bytes += n as u64,
+                Err(e) => return Err(Error::new(ErrorKind::Io(e))),
+            };
+
+            if !line.starts_with('#') && !line.trim().is_empty() {
+                let mut fields = line.split_whitespace();
+                let target = fields.next();
+                let query = fields.nth(1);
+                match (target, query) {
+                    (Some(t), Some(q)) => {
+                        index.targets.entry(t.to_string()).or_default().push(offset);
+                        index.queries.entry(q.to_string()).or_default().push(offset);
+                    }
+                    _ => {
+                        return Err(Error::new(ErrorKind::ReadRecord(format!(
+                            "could not index the record at offset {}",
+                            offset
+                        ))))
+                    }
+                }
+                index.records += 1;
+                report(
+                    progress,
+                    Progress {
+                        records: index.records,
+                        bytes: Some(bytes),
+                    },
+                )?;
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// The byte offsets of the records for a query.
+    pub fn query_offsets(&self, query: &str) -> &[u64] {
+        self.queries.get(query).map(|v| v.as_slice()).unwrap_or(&[])
+    }
+
+    /// The byte offsets of the records for a target.
+    pub fn target_offsets(&self, target: &str) -> &[u64] {
+        self.targets
+            .get(target)
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// The names of all indexed queries.
+    pub fn queries(&self) -> impl Iterator<Item = &str> {
+        self.queries.keys().map(|k| k.as_str())
+    }
+
+    /// The names of all indexed targets.
+    pub fn targets(&self) -> impl Iterator<Item = &str> {
+        self.targets.keys().map(|k| k.as_str())
+    }
+
+    /// The total number of records indexed.
+    pub fn len(&self) -> u64 {
+        self.records
+    }
+
+    /// Whether the offsets are BGZF virtual offsets rather than
+    /// plain byte offsets.
+    pub fn is_bgzf(&self) -> bool {
+        self.bgzf
+    }
+
+    /// Whether the index contains no records.
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+    use std::fs::File;
+
+    #[test]
+    fn test_plain_file_indexed() {
+        let index = TbloutIndex::build(File::open("./data/test.tbl").unwrap(), None).unwrap();
+        assert!(!index.is_bgzf());
+
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let records = reader.records_for_target(&index, "1").unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].ali_from(), Some(110824763));
+    }
+}
@@ -0,0 +1,269 @@
+use crate::{
+    record::{DNARecord, Record},
+    Error, ErrorKind, Result,
+};
+use std::collections::HashMap;
+
+/// How closely two sets of hits agree, as found by `hit_set_jaccard`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JaccardReport {
+    /// The Jaccard index of the two sets: the hits matched over all the
+    /// distinct hits, from 0.0 for no matches to 1.0 for sets which
+    /// match completely.
+    pub score: f64,
+    /// The number of hits of each set matched to one of the other.
+    /// Matches are one to one, so the same for both sets.
+    pub matched: u64,
+    /// The number of hits of the first set not matched.
+    pub unmatched_a: u64,
+    /// The number of hits of the second set not matched.
+    pub unmatched_b: u64,
+}
+
+/// Measure how much two sets of DNA hits agree, e.g. the hits of one
+/// search run with two sets of parameters, as the Jaccard index of the
+/// sets: the size of their intersection over the size of their union.
+///
+/// Hits match when they have the same target, query and strand, and
+/// each overlaps the other by at least `min_reciprocal_overlap` of its
+/// length. Each hit matches at most one other: the hits of `a` are
+/// taken in order, each matched to the hit of `b` it overlaps most of
+/// those left, with ties going to the first in `b`. The union then counts a
+/// matched pair once. Two empty sets agree completely, scoring 1.0.
+///
+/// The hits of each target are sorted by their starts and swept along
+/// it, so finding the hits which overlap takes time growing with the
+/// number of hits and the overlaps among them. Protein records have no
+/// coordinates on the target, so they give an `ErrorKind::Unsupported`
+/// error.
+pub fn hit_set_jaccard(
+    a: impl IntoIterator<Item = Result<Record>>,
+    b: impl IntoIterator<Item = Result<Record>>,
+    min_reciprocal_overlap: f64,
+) -> Result<JaccardReport> {
+    let a = dna_records(a)?;
+    let b = dna_records(b)?;
+    let overlaps = overlapping(&a, &b);
+
+    let mut used = vec![false; b.len()];
+    let mut matched = 0;
+    for (hit, candidates) in a.iter().zip(overlaps) {
+        let mut best: Option<(usize, f64)> = None;
+        for i in candidates {
+            let other = &b[i];
+            if used[i] || other.query_name() != hit.query_name() || other.strand() != hit.strand() {
+                continue;
+            }
+            let overlap = reciprocal_overlap(hit.interval(), other.interval());
+            let better = match best {
+                // the overlaps are found in no set order
+                Some((j, most)) => overlap > most || (overlap == most && i < j),
+                None => true,
+            };
+            if overlap >= min_reciprocal_overlap && better {
+                best = Some((i, overlap));
+            }
+        }
+        if let Some((i, _)) = best {
+            used[i] = true;
+            matched += 1;
+        }
+    }
+
+    let union = (a.len() + b.len()) as u64 - matched;
+    Ok(JaccardReport {
+        score: match union {
+            0 => 1.0,
+            _ => matched as f64 / union as f64,
+        },
+        matched,
+        unmatched_a: a.len() as u64 - matched,
+        unmatched_b: b.len() as u64 - matched,
+    })
+}
+
+/// Collect DNA records, rejecting protein records.
+fn dna_records(records: impl IntoIterator<Item = Result<Record>>) -> Result<Vec<DNARecord>> {
+    records
+        .into_iter()
+        .map(|record| match record? {
+            Record::Dna(r) => Ok(r),
+            Record::Protein(r) => Err(Error::new(ErrorKind::Unsupported(format!(
+                "protein record for target {} has no coordinates to match",
+                r.target_name()
+            )))),
+        })
+        .collect()
+}
+
+/// The places in `b` of the hits overlapping each hit of `a` on the
+/// same target, whatever their queries and strands.
+fn overlapping(a: &[DNARecord], b: &[DNARecord]) -> Vec<Vec<usize>> {
+    let mut overlaps = vec![Vec::new(); a.len()];
+    let b_targets = by_target(b);
+    for (target, hits) in by_target(a) {
+        let others = match b_targets.get(&target) {
+            Some(others) => others,
+            None => continue,
+        };
+        // the hits of b started by the end of the last hit of a, which
+        // have not ended before its start
+        let mut active: Vec<usize> = Vec::new();
+        let mut next = 0;
+        for i in hits {
+            let (start, end) = a[i].interval();
+            while next < others.len() && b[others[next]].interval().0 <= end {
+                active.push(others[next]);
+                next += 1;
+            }
+            // the hits of a start no earlier, so those ended are done
+            active.retain(|&j| b[j].interval().1 >= start);
+            overlaps[i].extend(active.iter().filter(|&&j| b[j].interval().0 <= end));
+        }
+    }
+    overlaps
+}
+
+/// The places of the hits on each target, sorted by their starts.
+fn by_target(hits: &[DNARecord]) -> HashMap<String, Vec<usize>> {
+    let mut targets: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, hit) in hits.iter().enumerate() {
+        targets.entry(hit.target_name()).or_default().push(i);
+    }
+    for places in targets.values_mut() {
+        places.sort_by_key(|&i| hits[i].interval());
+    }
+    targets
+}
+
+/// The overlap of two closed intervals as a fraction of the longer, so
+/// the smaller of the fractions of each which the other covers.
+fn reciprocal_overlap(a: (i64, i64), b: (i64, i64)) -> f64 {
+    let overlap = (a.1.min(b.1) - a.0.max(b.0) + 1).max(0);
+    let longer = (a.1 - a.0 + 1).max(b.1 - b.0 + 1);
+    overlap as f64 / longer as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader};
+
+    /// A record for a hit of `query` on `chr1` from `from` to `to`.
+    fn hit(query: &str, from: i64, to: i64) -> Result<Record> {
+        let strand = if from <= to { "+" } else { "-" };
+        let line = format!(
+            "chr1 - {} - 2 317 {} {} {} {} 133811386 {} 1.9e-37 136.7 1.4 -",
+            query, from, to, from, to, strand
+        );
+        Ok(Record::Dna(parse_dna_line(&line).unwrap()))
+    }
+
+    #[test]
+    fn test_reciprocal_overlap() {
+        assert_eq!(reciprocal_overlap((1, 100), (1, 100)), 1.0);
+        assert_eq!(reciprocal_overlap((1, 100), (51, 150)), 0.5);
+        // half of the shorter, a quarter of the longer
+        assert_eq!(reciprocal_overlap((1, 100), (51, 250)), 0.25);
+        assert_eq!(reciprocal_overlap((1, 100), (101, 200)), 0.0);
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let dna = |hits: Vec<Result<Record>>| dna_records(hits).unwrap();
+        let a = dna(vec![
+            hit("TR", 1, 100),
+            hit("TR", 150, 160),
+            hit("TR", 600, 500),
+        ]);
+        let mut b = dna(vec![
+            // spanning all of a, so still open at its last hit
+            hit("TR", 1, 1000),
+            hit("TR", 90, 155),
+            hit("TR", 300, 400),
+            hit("TR", 601, 700),
+        ]);
+        assert_eq!(overlapping(&a, &b), [vec![0, 1], vec![0, 1], vec![0]]);
+
+        // only hits on the same target
+        let line = b[0].to_string().replacen("chr1", "chr2", 1);
+        b[0] = parse_dna_line(&line).unwrap();
+        assert_eq!(overlapping(&a, &b), [vec![1], vec![1], vec![]]);
+    }
+
+    #[test]
+    fn test_identical_sets() {
+        let records = || Reader::from_path("./data/test.tbl").unwrap().into_records();
+        let report = hit_set_jaccard(records(), records(), 0.9).unwrap();
+        assert_eq!(
+            report,
+            JaccardReport {
+                score: 1.0,
+                matched: 31,
+                unmatched_a: 0,
+                unmatched_b: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_disjoint_sets() {
+        let a = vec![hit("TR", 1, 100), hit("TR", 301, 400)];
+        // apart, on the other strand, or of another query
+        let b = vec![hit("TR", 101, 200), hit("TR", 400, 301), hit("TR2", 1, 100)];
+        let report = hit_set_jaccard(a, b, 0.0).unwrap();
+        assert_eq!(report.score, 0.0);
+        assert_eq!(
+            (report.matched, report.unmatched_a, report.unmatched_b),
+            (0, 2, 3)
+        );
+    }
+
+    #[test]
+    fn test_half_overlapping_sets() {
+        let a = || {
+            vec![
+                hit("TR", 1, 100),
+                hit("TR", 201, 300),
+                hit("TR", 401, 500),
+                hit("TR", 1000, 901),
+            ]
+        };
+        let b = || {
+            vec![
+                // overlapping by 90%
+                hit("TR", 11, 110),
+                // by half
+                hit("TR", 251, 350),
+                hit("TR", 1000, 901),
+                hit("TR", 2001, 2100),
+            ]
+        };
+        // 3 matched of 5 distinct hits
+        let report = hit_set_jaccard(a(), b(), 0.5).unwrap();
+        assert_eq!(report.matched, 3);
+        assert_eq!(report.score, 3.0 / 5.0);
+        assert_eq!((report.unmatched_a, report.unmatched_b), (1, 1));
+
+        // 2 matched of 6 distinct hits
+        let report = hit_set_jaccard(a(), b(), 0.8).unwrap();
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.score, 2.0 / 6.0);
+    }
+
+    #[test]
+    fn test_one_to_one() {
+        // one hit of b can't match both of a
+        let a = vec![hit("TR", 1, 100), hit("TR", 1, 100)];
+        let b = vec![hit("TR", 1, 100)];
+        let report = hit_set_jaccard(a, b, 0.5).unwrap();
+        assert_eq!((report.matched, report.unmatched_a), (1, 1));
+        assert_eq!(report.score, 0.5);
+
+        assert_eq!(hit_set_jaccard(vec![], vec![], 0.5).unwrap().score, 1.0);
+
+        let protein = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let err = hit_set_jaccard(protein.into_records(), vec![], 0.5).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+    }
+}
@@ -0,0 +1,137 @@
+#[cfg(feature = "std-fs")]
+use crate::header::Header;
+use crate::{
+    record::{Meta, Record},
+    Error, ErrorKind, Result,
+};
+use serde::Serialize;
+use std::io::{self, Write};
+#[cfg(feature = "std-fs")]
+use std::{fs::File, path::Path};
+
+/// Write records as newline delimited JSON, one object per line with
+/// a `kind` field of `dna` or `protein`. If `meta` is given it is
+/// written first, as `{"meta": {...}}` on a line of its own.
+///
+/// Records are written as they are read, so the whole file is never
+/// held in memory. Returns the number of records written.
+pub fn to_ndjson<W: Write>(
+    records: impl IntoIterator<Item = Result<Record>>,
+    meta: Option<&Meta>,
+    w: W,
+) -> Result<usize> {
+    let mut w = io::BufWriter::new(w);
+    if let Some(meta) = meta {
+        write_line(&mut w, &MetaLine { meta })?;
+    }
+    let mut n = 0;
+    for record in records {
+        write_line(&mut w, &record?)?;
+        n += 1;
+    }
+    w.flush()?;
+    Ok(n)
+}
+
+/// Write a whole file as a single JSON document, of the form
+/// `{"header": {...}, "meta": {...}, "records": [...]}`. Returns the
+/// number of records written.
+#[cfg(feature = "std-fs")]
+pub fn to_json_file<P: AsRef<Path>>(
+    header: &Header,
+    meta: &Meta,
+    records: &[Record],
+    path: P,
+) -> Result<usize> {
+    let mut w = io::BufWriter::new(File::create(path)?);
+    let document = Document {
+        header,
+        meta,
+        records,
+    };
+    serde_json::to_writer(&mut w, &document).map_err(json_error)?;
+    w.flush()?;
+    Ok(records.len())
+}
+
+/// The first line of NDJSON output.
+#[derive(Serialize)]
+struct MetaLine<'a> {
+    meta: &'a Meta,
+}
+
+/// A whole file as a JSON document.
+#[cfg(feature = "std-fs")]
+#[derive(Serialize)]
+struct Document<'a> {
+    header: &'a Header,
+    meta: &'a Meta,
+    records: &'a [Record],
+}
+
+/// Write a value as JSON followed by a newline.
+fn write_line<W: Write, T: Serialize>(w: &mut W, value: &T) -> Result<()> {
+    serde_json::to_writer(&mut *w, value).map_err(json_error)?;
+    w.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Convert a JSON error, keeping I/O errors as they are.
+fn json_error(err: serde_json::Error) -> Error {
+    if err.is_io() {
+        Error::new(ErrorKind::Io(err.into()))
+    } else {
+        Error::new(ErrorKind::Parser(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+    use serde_json::Value;
+
+    #[test]
+    fn test_ndjson() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let meta = reader.meta().clone();
+        let mut out = Vec::new();
+        let n = to_ndjson(reader.into_records(), Some(&meta), &mut out).unwrap();
+        assert_eq!(n, 31);
+
+        let lines = std::str::from_utf8(&out)
+            .unwrap()
+            .lines()
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), n + 1);
+
+        // like `jq .meta.program` and `jq .target_name`
+        let first: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(first["meta"]["program"], "nhmmer");
+        let record: Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(record["kind"], "dna");
+        assert_eq!(record["target_name"], "2");
+        assert_eq!(record["strand"], "+");
+        assert_eq!(record["ali_from"], 338944);
+        assert_eq!(record["e_value"].as_f64().unwrap(), 1.9e-37);
+
+        let back: Record = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(back.ali_from(), Some(338944));
+    }
+
+    #[test]
+    fn test_json_file() {
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let records = reader.records().collect::<Result<Vec<_>>>().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.json");
+        to_json_file(reader.header(), reader.meta(), &records, &path).unwrap();
+        let doc: Value = serde_json::from_reader(File::open(&path).unwrap()).unwrap();
+
+        assert_eq!(doc["meta"]["version"], "3.4 (Aug 2023)");
+        assert_eq!(doc["records"].as_array().unwrap().len(), 31);
+        assert_eq!(doc["records"][30]["target_name"], "3");
+        assert!(doc["records"][0]["score"].is_f64());
+    }
+}
@@ -0,0 +1,176 @@
+use crate::record::{Strand, TbloutRecord};
+
+/// The identity of a hit, for finding the same hit in other files,
+/// e.g. to remove duplicates or compare two runs with a `HashSet`.
+///
+/// `KeySpec` sets which parts of a hit make up its key. By default it
+/// is all of them, with the coordinates exact.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RecordKey {
+    /// The name of the target.
+    pub target: String,
+    /// The name of the query.
+    pub query: String,
+    /// The strand of the hit, from the order of its coordinates, or
+    /// `None` for hits without coordinates or with the strand left out.
+    pub strand: Option<Strand>,
+    /// The start and end of the alignment on the target, the lower
+    /// first whatever the strand, divided by the bin size. `None` for
+    /// hits without coordinates or with the coordinates left out.
+    pub interval: Option<(i64, i64)>,
+}
+
+/// The key of a hit with every part, and its coordinates exact.
+impl<R: TbloutRecord> From<&R> for RecordKey {
+    fn from(record: &R) -> RecordKey {
+        KeySpec::default().key(record)
+    }
+}
+
+/// Which parts of a hit make up its `RecordKey`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    /// Whether the strand is part of the key.
+    strand: bool,
+    /// Whether the coordinates are part of the key.
+    interval: bool,
+    /// The size of the bins coordinates are rounded down to.
+    bin_size: u64,
+}
+
+/// Every part of a hit, with exact coordinates.
+impl Default for KeySpec {
+    fn default() -> KeySpec {
+        KeySpec {
+            strand: true,
+            interval: true,
+            bin_size: 1,
+        }
+    }
+}
+
+impl KeySpec {
+    /// Set whether the strand is part of the key, so hits on opposite
+    /// strands differ. On by default.
+    pub fn strand(mut self, yes: bool) -> KeySpec {
+        self.strand = yes;
+        self
+    }
+
+    /// Set whether the coordinates are part of the key. On by default.
+    /// Without them a key is the target and query, and strand if that
+    /// is kept, so all the hits of a query on a target share one.
+    pub fn interval(mut self, yes: bool) -> KeySpec {
+        self.interval = yes;
+        self
+    }
+
+    /// Round coordinates down to bins of `size` bases, so hits whose
+    /// ends are a little apart share a key: with a size of 10, hits
+    /// from 101 to 200 and 104 to 203 share one. Hits either side of a
+    /// bin's edge don't, however close. 1 by default, for exact
+    /// coordinates.
+    ///
+    /// # Panics
+    ///
+    /// If `size` is zero.
+    pub fn bin_size(mut self, size: u64) -> KeySpec {
+        assert!(size > 0, "bin size must be positive");
+        self.bin_size = size;
+        self
+    }
+
+    /// The key of a hit.
+    pub fn key<R: TbloutRecord>(&self, record: &R) -> RecordKey {
+        let interval = record.interval_opt();
+        let bin = self.bin_size as i64;
+        RecordKey {
+            target: record.target_name(),
+            query: record.query_name(),
+            strand: interval.filter(|_| self.strand).map(|(from, to)| {
+                if from <= to {
+                    Strand::Positive
+                } else {
+                    Strand::Negative
+                }
+            }),
+            interval: interval
+                .filter(|_| self.interval)
+                .map(|(from, to)| (from.min(to).div_euclid(bin), from.max(to).div_euclid(bin))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader, Record};
+    use std::collections::HashSet;
+
+    /// A record for a hit of `TR` on `chr1` from `from` to `to`.
+    fn hit(from: i64, to: i64) -> Record {
+        let strand = if from <= to { "+" } else { "-" };
+        let line = format!(
+            "chr1 - TR - 2 317 {} {} {} {} 133811386 {} 1.9e-37 136.7 1.4 -",
+            from, to, from, to, strand
+        );
+        Record::Dna(parse_dna_line(&line).unwrap())
+    }
+
+    #[test]
+    fn test_key_from_record() {
+        let key = RecordKey::from(&hit(200, 101));
+        assert_eq!(
+            key,
+            RecordKey {
+                target: "chr1".into(),
+                query: "TR".into(),
+                strand: Some(Strand::Negative),
+                interval: Some((101, 200)),
+            }
+        );
+
+        // protein records have neither
+        let mut reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let record = reader.records().next().unwrap().unwrap();
+        let key = RecordKey::from(&record);
+        assert_eq!((key.strand, key.interval), (None, None));
+        assert_eq!(key.query, "SOR");
+    }
+
+    #[test]
+    fn test_bin_size() {
+        // 3 bp apart
+        let (a, b) = (hit(101, 200), hit(104, 203));
+        let exact = KeySpec::default();
+        assert_ne!(exact.key(&a), exact.key(&b));
+        let binned = KeySpec::default().bin_size(10);
+        assert_eq!(binned.key(&a), binned.key(&b));
+        assert_eq!(binned.key(&a).interval, Some((10, 20)));
+        // either side of a bin's edge
+        assert_ne!(binned.key(&hit(108, 200)), binned.key(&hit(111, 203)));
+    }
+
+    #[test]
+    fn test_key_parts() {
+        let (plus, minus) = (hit(101, 200), hit(200, 101));
+        assert_ne!(RecordKey::from(&plus), RecordKey::from(&minus));
+        let spec = KeySpec::default().strand(false);
+        assert_eq!(spec.key(&plus), spec.key(&minus));
+
+        let spec = KeySpec::default().interval(false);
+        assert_eq!(spec.key(&plus), spec.key(&hit(5000, 6000)));
+        assert_ne!(spec.key(&plus), spec.key(&minus));
+
+        // as a set, the duplicates of a file
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let mut records = reader
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        let n = records.len();
+        records.extend(records[..5].to_vec());
+        let keys = records.iter().map(RecordKey::from).collect::<HashSet<_>>();
+        assert_eq!(keys.len(), n);
+    }
+}
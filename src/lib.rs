@@ -6,7 +6,7 @@ the `--tblout` flag to the HMMER3 program.
 # Example
 
 ```no_run
-use hmm_tblout;
+use hmm_tblout::prelude::*;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // get the command line args, only parse the
@@ -17,14 +17,15 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         std::process::exit(1);
     }
 
-    let reader = hmm_tblout::Reader::from_path(args[1].clone())?;
+    let reader = Reader::from_path(args[1].clone())?;
 
-    for record in reader.into_records() {
+    // coordinates are only written by nhmmer and nhmmscan
+    for record in reader.into_dna_records()? {
         let r = record?;
         let tname = r.target_name();
-        let strand = r.strand().unwrap();
-        let alifrom = r.ali_from().unwrap();
-        let alito = r.ali_to().unwrap();
+        let strand = r.strand();
+        let alifrom = r.ali_from();
+        let alito = r.ali_to();
 
         println!("{}\t{}\t{}\t{}", tname, strand, alifrom, alito);
     }
@@ -32,19 +33,164 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 ```
+
+# Features
+
+Everything touching the filesystem, such as `Reader::from_path`,
+`Writer::to_path`, `Reader::follow` and `SplitWriter`, needs the
+`std-fs` feature, which is on by default. Without it the crate builds
+for `wasm32-unknown-unknown`, and files can be read from memory with
+`Reader::from_reader(bytes)` and written with `Writer::new(Vec::new())`.
+`scripts/check-wasm.sh` checks this.
+
+With the `tracing` feature, readers and writers report through
+[`tracing`](https://docs.rs/tracing). Building a reader is a span, so
+the time the first pass takes is recorded, and the counts of records
+and comment lines are given when the records run out. Events while
+reading are at most one every `PROGRESS_INTERVAL` records. Oddities
+that are worked around, such as CRLF line endings or a program
+inferred from the columns, are warnings.
+
+With the `fallible-iterator` feature, the record iterators implement
+[`FallibleIterator`](https://docs.rs/fallible-iterator), so adapters
+take the records rather than `Result`s, and the first error ends the
+chain. As the iterators are still `Iterator`s too, the first adapter is
+called through the trait:
+
+```ignore
+use fallible_iterator::FallibleIterator;
+
+let reader = Reader::from_path("hits.tbl")?;
+let total = FallibleIterator::filter(reader.into_records(), |r| {
+    Ok(r.score_opt().unwrap_or(0.0) > 30.0)
+})
+.map(|r| Ok(r.score_opt().unwrap_or(0.0)))
+.fold(0.0, |sum, score| Ok(sum + score))?;
+```
 */
 
+#[cfg(feature = "rust-bio")]
+mod annot;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "arrow")]
+mod arrow;
+mod bed;
+mod bedgraph;
+#[cfg(feature = "bgzf")]
+mod bgzf;
+mod bias;
+mod builder;
+mod chunks;
+#[cfg(all(feature = "std-fs", any(feature = "gzip", feature = "zstd")))]
+mod compress;
+mod copy;
+mod coverage;
 mod error;
+mod evaluate;
+mod extract;
+#[cfg(feature = "fallible-iterator")]
+mod fallible;
+#[cfg(feature = "serde")]
+mod flat;
+#[cfg(feature = "std-fs")]
+mod follow;
+mod gff;
+mod header;
+mod hit;
+mod index;
+mod jaccard;
+#[cfg(feature = "json")]
+mod json;
+mod key;
+mod merge;
+mod nucleotide;
+pub mod ord;
+pub mod prelude;
+mod progress;
+mod qc;
+mod rbh;
 mod reader;
 mod record;
+pub mod report;
+mod sort;
+#[cfg(feature = "std-fs")]
+mod split;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod table;
+#[cfg(any(test, feature = "test-util"))]
+mod test_util;
+mod tsv;
+mod validate;
+mod window;
+mod writer;
 
-// don't want these in the public API.
-use record::{DNARecord, ProteinRecord};
-
+#[cfg(feature = "rust-bio")]
+pub use crate::annot::annot_map_from_records;
+#[cfg(feature = "arrow")]
+pub use crate::arrow::{ArrowBatchIter, ArrowOptions};
+#[cfg(feature = "bgzf")]
+pub use crate::bgzf::BgzfReader;
+#[cfg(all(feature = "std-fs", any(feature = "gzip", feature = "zstd")))]
+pub use crate::compress::{CompressedFile, Format};
+#[cfg(feature = "serde")]
+pub use crate::flat::{FlatDnaRecord, FlatProteinRecord};
+#[cfg(feature = "std-fs")]
+pub use crate::follow::FollowIter;
+pub use crate::jaccard::{hit_set_jaccard, JaccardReport};
+#[cfg(all(feature = "std-fs", feature = "json"))]
+pub use crate::json::to_json_file;
+#[cfg(feature = "json")]
+pub use crate::json::to_ndjson;
+#[cfg(feature = "std-fs")]
+pub use crate::split::SplitWriter;
+#[cfg(feature = "sqlite")]
+pub use crate::sqlite::export_sqlite;
+#[cfg(any(test, feature = "test-util"))]
+pub use crate::test_util::{
+    assert_tblout_semantically_eq, assert_tblout_with, tblout_differences, Difference,
+    TbloutComparison,
+};
 pub use crate::{
+    bed::{to_bed_track, BedTrackOptions, ScoreSource},
+    bedgraph::{to_bedgraph, BedGraphOptions},
+    bias::{BiasFlag, BiasThresholds},
+    builder::TbloutBuilder,
+    chunks::{Chunks, RecordsIterExt},
+    copy::{copy_filtered, CopyStats},
+    coverage::{
+        coverage_per_target, stacked_coverage, strand_bias_per_target, StackedCoverage, StrandBias,
+    },
     error::{Error, ErrorKind, Result},
-    reader::{Reader, RecordsIntoIter, RecordsIter},
-    record::{Meta, Program, Record, Strand},
+    evaluate::{evaluate, write_curve, EvaluationCurve, EvaluationPoint, Regions},
+    extract::{extract_sequences, ExtractOptions, FastaRecord},
+    gff::{to_gff3, Gff3Options},
+    header::Header,
+    hit::Hit,
+    index::TbloutIndex,
+    key::{KeySpec, RecordKey},
+    merge::merge,
+    nucleotide::NucleotideHit,
+    progress::{Progress, ProgressFn, PROGRESS_INTERVAL},
+    qc::{qc_points, write_qc_points, QcOptions, QcPoint},
+    rbh::{reciprocal_best_hits, RbhEvidence, RbhOptions},
+    reader::{
+        DNARecordsIntoIter, DNARecordsIter, ProteinRecordsIntoIter, ProteinRecordsIter, Reader,
+        RecordsIntoIter, RecordsIter, RecordsWithLines,
+    },
+    record::{
+        DNAParts, DNARecord, Meta, Program, ProteinParts, ProteinRecord, Record, RecordKind,
+        RecordParts, ScanOrientation, Strand, TbloutRecord, Version,
+    },
+    sort::{SortKey, SortedWriter},
+    table::TableFormat,
+    tsv::{field_names, TsvWriter},
+    validate::{CoordinateError, ValidationIssue, Warning},
+    window::TargetHits,
+    writer::{
+        Delimiter, DescriptionMode, FloatStyle, FooterStyle, WritableRecord, Writer, WriterBuilder,
+    },
 };
 
 #[cfg(test)]
@@ -55,147 +201,12 @@ mod tests {
         s.as_bytes()
     }
 
-    const NHMMER_FILE: &str = "# target name        accession  query name           accession  hmmfrom hmm to  alifrom   ali to  envfrom   env to   sq len strand   E-value  score  bias  description of target
-#------------------- ---------- -------------------- ---------- ------- -------  -------  -------  -------  -------  ------- ------ --------- ------ ----- ---------------------
-SUPER_1              -          TR                   -                1     315 10988331 10987997 10988331 10987995 52766903    -     6.5e-34  124.1   1.2  -
-SUPER_3              -          TR                   -              139     204 17951382 17951472 17951359 17951492 49366223    +        0.74   15.6   3.0  -
-SUPER_4              -          TR                   -               29     130 20542008 20542119 20541989 20542141 47284787    +         1.1   15.1   5.7  -
-SUPER_4              -          TR                   -               29     126 32993383 32993283 32993398 32993263 47284787    -         2.2   14.1   1.0  -
-SUPER_4              -          TR                   -               23     126 14861076 14861174 14861063 14861195 47284787    +         2.4   13.9   3.4  -
-SUPER_5              -          TR                   -               21     126 27641355 27641256 27641369 27641229 46997258    -         2.5   13.8   6.5  -
-SUPER_8              -          TR                   -              158     191 33162494 33162454 33162514 33162446 33878543    -         3.1   13.6   2.0  -
-SUPER_5              -          TR                   -              107     162 46228956 46228885 46228975 46228876 46997258    -         3.1   13.6   0.0  -
-SUPER_1              -          TR                   -               68     173 15697438 15697327 15697464 15697325 52766903    -         4.4   13.1   0.7  -
-SUPER_3              -          TR                   -               24     128 36147201 36147098 36147221 36147078 49366223    -         4.5   13.0   1.9  -
-SUPER_1              -          TR                   -                1      84 36988055 36987966 36988055 36987944 52766903    -         4.7   13.0   2.1  -
-SUPER_6              -          TR                   -               38     122 20863320 20863225 20863338 20863202 43664244    -           5   12.9   2.2  -
-SUPER_8              -          TR                   -                1      79 32349659 32349576 32349659 32349556 33878543    -         5.9   12.6   2.5  -
-SUPER_2              -          TR                   -               22     128 38320620 38320502 38320631 38320480 52745556    -         6.5   12.5   0.0  -
-SUPER_1              -          TR                   -                2      55  1602497  1602446  1602498  1602420 52766903    -         8.6   12.1   7.2  -
-SUPER_2              -          TR                   -                2     100 26735237 26735139 26735238 26735122 52745556    -           9   12.0   1.4  -
-#
-# Program:         nhmmer
-# Version:         3.4 (Aug 2023)
-# Pipeline mode:   SEARCH
-# Query file:      TR.hmm
-# Target file:     /lustre/scratch124/tol/projects/darwin/data/dicots/Misopates_orontium/assembly/curated/daMisOron1.1/daMisOron1.1.primary.fa.gz
-# Option settings: /software/team301/hmmer-3.4/src/nhmmer --tblout Misopates_orontium.tbl --dna --cpu 10 TR.hmm /lustre/scratch124/tol/projects/darwin/data/dicots/Misopates_orontium/assembly/curated/daMisOron1.1/daMisOron1.1.primary.fa.gz
-# Current dir:     /lustre/scratch123/tol/teams/blaxter/users/mb39/ARU/tr_detection
-# Date:            Fri May  3 10:07:36 2024
-# [ok]";
-
-    const JACKHMMER_FILE: &str = "#                                                                 --- full sequence ---- --- best 1 domain ---- --- domain number estimation ----
-# target name          accession  query name           accession    E-value  score  bias   E-value  score  bias   exp reg clu  ov env dom rep inc description of target
-#  ------------------- ---------- -------------------- ---------- --------- ------ ----- --------- ------ -----   --- --- --- --- --- --- --- --- ---------------------
-ENSTSYP00000010994     -          CMIL_MAD||KEN1/4-29  -            9.6e-21   66.2   0.7   9.3e-20   63.1   0.3   2.9   3   0   0   3   3   1   1 -
-ENSMICP00000006968     -          CMIL_MAD||KEN1/4-29  -            1.2e-20   65.9   0.7   9.2e-20   63.1   0.3   2.7   3   0   0   3   3   1   1 -
-ENSOCUP00000005644     -          CMIL_MAD||KEN1/4-29  -            1.3e-20   65.8   1.1   9.6e-20   63.0   0.3   2.9   3   0   0   3   3   2   1 -
-ENSCJAP00000038871     -          CMIL_MAD||KEN1/4-29  -            1.5e-20   65.6   1.0   9.4e-20   63.1   0.3   2.7   3   0   0   3   3   2   1 -
-ENSSBOP00000009355     -          CMIL_MAD||KEN1/4-29  -            2.2e-20   65.1   0.5   9.3e-20   63.1   0.3   2.3   2   0   0   2   2   1   1 -
-ENSHGLP00100017467     -          CMIL_MAD||KEN1/4-29  -            2.2e-20   65.1   0.6   9.4e-20   63.1   0.3   2.3   2   0   0   2   2   1   1 -
-ENSRBIP00000038600     -          CMIL_MAD||KEN1/4-29  -            2.2e-20   65.1   0.5   9.3e-20   63.1   0.3   2.3   2   0   0   2   2   1   1 -
-ENSCCAP00000017737     -          CMIL_MAD||KEN1/4-29  -            2.3e-20   65.0   0.5   9.2e-20   63.1   0.3   2.2   2   0   0   2   2   1   1 -
-ENSAMXP00000012570     -          CMIL_MAD||CDII/858-891 -            6.4e-10   32.2   0.0   2.3e-09   30.4   0.0   2.0   1   0   0   1   1   1   1 -
-ENSLOCP00000015810     -          CMIL_MAD||CDII/858-891 -            1.5e-09   31.0   0.0   4.7e-09   29.4   0.0   2.0   1   0   0   1   1   1   1 -
-ENSXETP00000000565     -          CMIL_MAD||CDII/858-891 -            6.6e-09   28.9   2.7   1.3e-08   28.0   0.4   2.6   2   0   0   2   2   2   1 -
-ENSDARP00000101544     -          CMIL_MAD||CDII/858-891 -            1.3e-05   18.4   0.5   4.5e-05   16.7   0.1   2.2   2   0   0   2   2   1   1 -
-#
-# Program:         jackhmmer
-# Version:         3.1b2 (February 2015)
-# Pipeline mode:   SEARCH
-# Query file:      TromerBuBR1_CMI.fasta
-# Target file:     ../../../Sequences/Human_BUB1B_orthologues_2018_12_05.fa
-# Option settings: jackhmmer --tblout EnsemblBuBR1_CMI_jackhmmer.tblout --domtblout EnsemblBuBR1_CMI_jackhmmer.domtblout --qformat fasta --tformat fasta TromerBuBR1_CMI.fasta ../../../Sequences/Human_BUB1B_orthologues_2018_12_05.fa 
-# Current dir:     /media/axelle/Angel_backup/Dropbox/BuBR1/BuBR1_coevolution/Data/Domain_annotation/BuBR1/CMI
-# Date:            Thu Aug  8 09:53:09 2019
-# [ok]";
-
-    const PHMMER_FILE: &str = "#                                                               --- full sequence ---- --- best 1 domain ---- --- domain number estimation ----
-# target name        accession  query name           accession    E-value  score  bias   E-value  score  bias   exp reg clu  ov env dom rep inc description of target
-#------------------- ---------- -------------------- ---------- --------- ------ ----- --------- ------ -----   --- --- --- --- --- --- --- --- ---------------------
-HBB_HUMAN            -          MYG_ESCGI            -            2.3e-11   30.3   0.1   2.5e-11   30.2   0.1   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          MYG_HORSE            -            5.1e-12   32.3   0.1   6.1e-12   32.1   0.1   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          MYG_PROGU            -            9.3e-13   34.6   0.0   1.1e-12   34.4   0.0   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          MYG_SAISC            -            7.4e-13   35.1   0.0     8e-13   35.0   0.0   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          HBB_LARRI            -            1.5e-70  222.2   0.1   1.6e-70  222.0   0.1   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          HBB1_VAREX           -              7e-67  210.6   0.2   7.7e-67  210.5   0.2   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          HBB2_XENTR           -            1.2e-52  163.9   0.1   1.4e-52  163.7   0.1   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          HBBL_RANCA           -            1.5e-57  180.0   0.1   1.7e-57  179.9   0.1   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-HBB_HUMAN            -          HBB2_TRICR           -            3.4e-46  143.0   0.0   3.7e-46  142.8   0.0   1.0   1   0   0   1   1   1   1 Human beta hemoglobin.
-#
-# Program:         phmmer
-# Version:         3.2 (June 2018)
-# Pipeline mode:   SEARCH
-# Query file:      /var/lib/cwl/stgc72dd3bc-8891-4b83-83e7-215178692be8/globins45.fa
-# Target file:     /var/lib/cwl/stgf46f4fb0-f7ec-4fbf-b608-74ca6d8a20a0/HBB_HUMAN
-# Option settings: phmmer -o globins45.fa.phmmer_matches.out --tblout globins45.fa.phmmer_matches.tblout /var/lib/cwl/stgc72dd3bc-8891-4b83-83e7-215178692be8/globins45.fa /var/lib/cwl/stgf46f4fb0-f7ec-4fbf-b608-74ca6d8a20a0/HBB_HUMAN 
-# Current dir:     /tmp/cwl
-# Date:            Tue Jul 17 13:01:19 2018
-# [ok]";
-
-    const NHMMSCAN_FILE: &str = "# target name        accession  query name           accession  hmmfrom hmm to alifrom  ali to envfrom  env to  modlen strand   E-value  score  bias  description of target
-#------------------- ---------- -------------------- ---------- ------- ------- ------- ------- ------- ------- ------- ------ --------- ------ ----- ---------------------
-CLASSA_ARL           -          ARL-1                -                1     845       1     845       1     846     846    +    1.5e-275  909.7  88.2  -
-CLASSA_BLAZ          -          ARL-1                -                1     828       1     828       1     842     843    +    6.7e-108  355.9  83.7  -
-CLASSA_PC1           -          ARL-1                -                1     825       1     825       1     840     843    +    3.7e-104  343.6  81.8  -
-CLASSA_TLA           -          ARL-1                -                2     809       2     764       1     785     903    +     1.3e-10   34.5  67.0  -
-CLASSA_CEPA          -          ARL-1                -               25     468      13     441       2     461     900    +     1.6e-10   34.1  32.0  -
-CLASSD_LCR           -          ARL-1                -               14     642      26     714       5     735     783    +     1.3e-09   31.0  58.1  -
-CLASSA_PER           -          ARL-1                -               25     882      13     822       2     842     924    +     1.6e-09   30.7  73.1  -
-CLASSA_CFXA          -          ARL-1                -              322     869     256     770     235     791     963    +     2.8e-09   29.7  48.7  -
-#
-# Program:         hmmscan
-# Version:         3.2 (June 2018)
-# Pipeline mode:   SCAN
-# Query file:      example_gene_seqs.fasta
-# Target file:     nARGhmm/Total_95_families_nucleotide.hmm
-# Option settings: nhmmscan --tblout nhmmscan_output -E 1e-6 nARGhmm/Total_95_families_nucleotide.hmm example_gene_seqs.fasta 
-# Current dir:     /Volumes/DATA/Ph.D.Works/BETALACTAMSE_WORK_12-NOV-18/Beta-lactamase_NEW_WORK/Standalone_Version/blacfampred_standalone
-# Date:            Sat Jul 24 16:56:59 2021
-# [ok]";
-
-    const HMMSCAN_FILE: &str = "#                                                               --- full sequence ---- --- best 1 domain ---- --- domain number estimation ----
-# target name        accession  query name           accession    E-value  score  bias   E-value  score  bias   exp reg clu  ov env dom rep inc description of target
-#------------------- ---------- -------------------- ---------- --------- ------ ----- --------- ------ -----   --- --- --- --- --- --- --- --- ---------------------
-ABC_membrane_2       PF06472.14 11LoS11_3_18_3       -              2e-74  250.4   5.3     2e-74  250.4   5.3   1.4   2   0   0   2   2   2   1 ABC transporter transmembrane region 2
-SbmA_BacA            PF05992.11 11LoS11_3_18_3       -            3.3e-41  141.7   9.4   4.4e-41  141.2   9.4   1.1   1   0   0   1   1   1   1 SbmA/BacA-like family
-ABC_tran             PF00005.26 11LoS11_3_18_3       -              6e-17   62.3   0.0   1.8e-16   60.8   0.0   1.8   1   1   0   1   1   1   1 ABC transporter
-AAA_29               PF13555.5  11LoS11_3_18_3       -            6.1e-06   25.8   0.5   2.1e-05   24.1   0.1   2.1   2   0   0   2   2   2   1 P-loop containing region of AAA domain
-ABC_membrane_2       PF06472.14 11LoS18_3_1_2        -            4.2e-87  292.0   2.1   5.3e-87  291.7   2.1   1.1   1   0   0   1   1   1   1 ABC transporter transmembrane region 2
-HisKA                PF00512.24 11LoS6_2_10_2        -            6.2e-07   29.3   0.3     2e-06   27.7   0.1   2.0   2   0   0   2   2   2   1 His Kinase A (phospho-acceptor) domain
-Esterase_phd         PF10503.8  11LoS7_1_2_1         -            3.9e-16   59.1   0.8   6.9e-16   58.3   0.7   1.5   1   1   0   1   1   1   1 Esterase PHB depolymerase
-Peptidase_S9         PF00326.20 11LoS7_1_2_1         -            3.5e-09   36.3   0.4   1.3e-07   31.3   0.2   2.4   1   1   1   2   2   2   1 Prolyl oligopeptidase family
-Esterase             PF00756.19 11LoS7_1_2_1         -            7.3e-06   25.7   0.0     1e-05   25.2   0.0   1.3   1   0   0   1   1   1   1 Putative esterase
-Abhydrolase_2        PF02230.15 11LoS7_1_2_1         -            1.8e-05   24.6   0.0   0.00012   21.8   0.0   2.0   1   1   0   1   1   1   1 Phospholipase/Carboxylesterase
-Abhydrolase_6        PF12697.6  11LoS7_1_2_1         -            2.1e-05   25.2   2.1     4e-05   24.2   2.1   1.5   1   0   0   1   1   1   1 Alpha/beta hydrolase family
-Ribonuc_L-PSP        PF01042.20 11LoS7_1_2_2         -            1.9e-23   82.6   0.6   2.3e-23   82.4   0.6   1.1   1   0   0   1   1   1   1 Endoribonuclease L-PSP
-ABC_membrane_2       PF06472.14 13LoS28_1_10_2       -            4.2e-87  292.0   2.1   5.3e-87  291.7   2.1   1.1   1   0   0   1   1   1   1 ABC transporter transmembrane region 2
-AAA_29               PF13555.5  CW1_7_2              -            3.2e-05   23.5   0.0   0.00011   21.8   0.0   1.9   1   0   0   1   1   1   1 P-loop containing region of AAA domain
-#
-# Program:         hmmscan
-# Version:         3.1b2 (February 2015)
-# Pipeline mode:   SCAN
-# Query file:      orf.out.txt
-# Target file:     /srv/projects/db/pfam/2017-06-11-Pfam31.0/Pfam-A.hmm
-# Option settings: hmmscan --tblout hmmscan.tblout.txt -E 0.0001 --cpu 4 /srv/projects/db/pfam/2017-06-11-Pfam31.0/Pfam-A.hmm orf.out.txt 
-# Current dir:     /rhome/arahm010/project220/src
-# Date:            Fri Dec 14 01:07:39 2018
-# [ok]";
-
-    const HMMSEARCH_FILE: &str = "#                                                               --- full sequence ---- --- best 1 domain ---- --- domain number estimation ----
-# target name        accession  query name           accession    E-value  score  bias   E-value  score  bias   exp reg clu  ov env dom rep inc description of target
-#------------------- ---------- -------------------- ---------- --------- ------ ----- --------- ------ -----   --- --- --- --- --- --- --- --- ---------------------
-sp|P29082|SOR_ACIAM  -          SOR                  PF07682.13  1.5e-152  492.8   0.8  1.7e-152  492.6   0.8   1.0   1   0   0   1   1   1   1 Sulfur oxygenase/reductase OS=Acidianus ambivalens OX=2283 GN=sor PE=1 SV=3
-#
-# Program:         hmmsearch
-# Version:         3.2.1 (June 2018)
-# Pipeline mode:   SEARCH
-# Query file:      SOR.hmm
-# Target file:     sor.faa
-# Option settings: hmmsearch --tblout sor.sor.tblout SOR.hmm sor.faa 
-# Current dir:     /Users/arkadiygarber/MagicLamp/hmms/litho
-# Date:            Mon May 24 13:53:50 2021
-# [ok]";
+    const NHMMER_FILE: &str = include_str!("../data/corpus/nhmmer.tbl");
+    const JACKHMMER_FILE: &str = include_str!("../data/corpus/jackhmmer.tbl");
+    const PHMMER_FILE: &str = include_str!("../data/corpus/phmmer.tbl");
+    const NHMMSCAN_FILE: &str = include_str!("../data/corpus/nhmmscan.tbl");
+    const HMMSCAN_FILE: &str = include_str!("../data/corpus/hmmscan.tbl");
+    const HMMSEARCH_FILE: &str = include_str!("../data/corpus/hmmsearch.tbl");
 
     #[test]
     fn test_whole_file() {
@@ -257,4 +268,812 @@ sp|P29082|SOR_ACIAM  -          SOR                  PF07682.13  1.5e-152  492.8
         assert_eq!(first.e_value_full().unwrap(), 2.3e-11);
         assert_eq!(third.e_value_full().unwrap(), 9.3e-13);
     }
+
+    #[test]
+    fn test_nhmmscan_meta() {
+        let reader = Reader::from_reader(b(NHMMSCAN_FILE));
+        let r = reader.unwrap();
+        let meta = r.meta();
+        assert_eq!(meta.pipeline_mode(), "SCAN".to_string());
+        assert_eq!(meta.version(), "3.2 (June 2018)".to_string());
+        // written as hmmscan by nhmmscan 3.2
+        assert_eq!(meta.program(), Program::Nhmmscan);
+    }
+
+    #[test]
+    fn test_hmmscan_records() {
+        let reader = Reader::from_reader(b(HMMSCAN_FILE));
+        let mut r = reader.unwrap();
+        assert_eq!(r.meta().program(), Program::Hmmscan);
+
+        let records = r.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(records.len(), 14);
+        assert_eq!(records[0].target_accession(), "PF06472.14".to_string());
+        assert_eq!(records[13].query_name(), "CW1_7_2".to_string());
+    }
+
+    #[test]
+    fn test_hmmsearch_records() {
+        let reader = Reader::from_reader(b(HMMSEARCH_FILE));
+        let mut r = reader.unwrap();
+        assert_eq!(r.meta().program(), Program::Hmmsearch);
+
+        let first = r.records().next().unwrap().unwrap();
+        assert_eq!(first.target_name(), "sp|P29082|SOR_ACIAM".to_string());
+        assert_eq!(first.score_full().unwrap(), 492.8);
+    }
+
+    #[test]
+    fn test_typed_records() {
+        let mut r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let expected = r.records().collect::<Result<Vec<_>>>().unwrap();
+
+        let mut r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let dna = r.dna_records().unwrap().collect::<Result<Vec<_>>>();
+        let dna = dna
+            .unwrap()
+            .into_iter()
+            .map(Record::Dna)
+            .collect::<Vec<_>>();
+        assert_eq!(dna, expected);
+
+        let r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let first = r.into_dna_records().unwrap().next().unwrap().unwrap();
+        assert_eq!(first.target_name(), "SUPER_1");
+
+        let mut r = Reader::from_reader(b(HMMSEARCH_FILE)).unwrap();
+        let first = r.protein_records().unwrap().next().unwrap().unwrap();
+        assert_eq!(first.score_full(), 492.8);
+        let r = Reader::from_reader(b(HMMSEARCH_FILE)).unwrap();
+        assert_eq!(r.into_protein_records().unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_unknown_program() {
+        let file = NHMMER_FILE.replace(
+            "# Program:         nhmmer",
+            "# Program:         /usr/bin/blastn",
+        );
+        let err = Reader::from_reader(b(&file)).err().unwrap();
+        assert!(matches!(err.kind(), ErrorKind::Meta(_)));
+        assert_eq!(
+            err.to_string(),
+            "metadata error - The program \"/usr/bin/blastn\" is not supported."
+        );
+    }
+
+    #[test]
+    fn test_records_only() {
+        let file = NHMMER_FILE
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(|l| format!("{}\n", l))
+            .collect::<String>();
+        // the absence is reported, not an empty header and metadata
+        let mut r = Reader::from_reader(b(&file)).unwrap();
+        assert!(r.meta().is_empty());
+        assert_eq!((r.meta_opt(), r.header_opt()), (None, None));
+        let err = r.records().next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "metadata error - the file has no metadata, so the program which produced it is unknown"
+        );
+        assert!(r.dna_records().is_err());
+
+        // which metadata naming the program is not
+        let mut r = Reader::with_program(b(&file), Program::Nhmmer);
+        assert_eq!(r.meta_opt().map(|m| m.program()), Some(Program::Nhmmer));
+        assert_eq!(r.header_opt(), None);
+        assert_eq!(r.records().count(), file.lines().count());
+        let err = Reader::from_reader(b(&NHMMER_FILE.replace("# Program:         nhmmer\n", "")))
+            .unwrap()
+            .records()
+            .next()
+            .unwrap()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("the program which produced the file is unknown"));
+
+        let r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        assert!(r.meta_opt().is_some() && r.header_opt().is_some());
+        assert_eq!(Meta::default().to_string(), "");
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let file = NHMMER_FILE.replacen("SUPER_3 ", "SUPER_3 x", 1);
+        let mut r = Reader::from_reader(b(&file)).unwrap();
+        let mut records = r.records();
+        assert!(records.next().unwrap().is_ok());
+        assert!(records.next().unwrap().is_err());
+        // the two header lines and two records
+        assert_eq!(r.line(), 4);
+    }
+
+    #[test]
+    fn test_peek_first() {
+        let all = Reader::from_reader(b(NHMMER_FILE))
+            .unwrap()
+            .into_records()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        let mut r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let first = r.peek_first().unwrap().cloned().unwrap();
+        assert_eq!(first, all[0]);
+        // peeking again doesn't read further
+        assert_eq!(r.peek_first().unwrap(), Some(&all[0]));
+        assert_eq!(r.line(), 3);
+        // nothing lost or repeated, by any of the iterators
+        let records = r.records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(records, all);
+        assert_eq!(r.peek_first().unwrap(), None);
+
+        let mut r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        r.peek_first().unwrap();
+        let records = r.into_dna_records().unwrap().collect::<Result<Vec<_>>>();
+        assert_eq!(records.unwrap().len(), all.len());
+
+        let mut r = Reader::from_reader(b(HMMSEARCH_FILE)).unwrap();
+        assert_eq!(r.peek_first().unwrap().unwrap().query_name(), "SOR");
+        let records = r.into_records().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].query_name(), "SOR");
+    }
+
+    #[test]
+    fn test_records_with_lines() {
+        for (file, program) in [
+            (NHMMER_FILE, Program::Nhmmer),
+            (HMMSCAN_FILE, Program::Hmmscan),
+            (JACKHMMER_FILE, Program::Jackhmmer),
+        ] {
+            let mut r = Reader::from_reader(b(file)).unwrap();
+            let pairs = r.records_with_lines().collect::<Result<Vec<_>>>().unwrap();
+            // the lines of the file, without the comments
+            let lines = pairs
+                .iter()
+                .map(|(_, line)| line.as_str())
+                .collect::<Vec<_>>();
+            let original = file
+                .lines()
+                .filter(|l| !l.starts_with('#'))
+                .collect::<Vec<_>>();
+            assert_eq!(lines, original);
+            for (record, line) in &pairs {
+                // which the records were parsed from
+                let mut again = Reader::with_program(b(line), program);
+                assert_eq!(again.records().next().unwrap().unwrap(), *record);
+                assert_eq!(record.raw_line(), None);
+            }
+        }
+
+        // a peeked record comes with its line
+        let mut r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        r.peek_first().unwrap();
+        let (_, line) = r.records_with_lines().next().unwrap().unwrap();
+        assert_eq!(line, NHMMER_FILE.lines().nth(2).unwrap());
+
+        let mut r = Reader::new(b(NHMMER_FILE), Meta::default());
+        assert_eq!(r.records_with_lines().count(), 1);
+    }
+
+    #[test]
+    fn test_into_parts() {
+        use std::io::{Read, Seek};
+
+        let file = std::fs::read_to_string("./data/test.tbl").unwrap();
+        let mut records = Reader::from_path("./data/test.tbl").unwrap().into_records();
+        for _ in 0..3 {
+            records.next().unwrap().unwrap();
+        }
+        let (mut inner, header, meta, buffered) = records.into_parts();
+        assert_eq!(meta.program(), Program::Nhmmer);
+        assert!(header.columns().starts_with("# target name"));
+
+        // the next line starts where the file is, less what was buffered
+        let next = file.lines().take(5).map(|l| l.len() + 1).sum::<usize>();
+        let position = inner.stream_position().unwrap();
+        assert_eq!(position as usize - buffered.len(), next);
+        let mut rest = buffered;
+        inner.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, &file.as_bytes()[next..]);
+
+        // from memory, the rest is what was buffered and what was not
+        let mut r = Reader::with_program(b(NHMMER_FILE), Program::Nhmmer);
+        r.records().next().unwrap().unwrap();
+        let (inner, _, _, mut buffered) = r.into_parts();
+        buffered.extend_from_slice(inner);
+        let next = NHMMER_FILE
+            .lines()
+            .take(3)
+            .map(|l| l.len() + 1)
+            .sum::<usize>();
+        assert_eq!(buffered, &NHMMER_FILE.as_bytes()[next..]);
+    }
+
+    #[test]
+    fn test_peek_first_with_options() {
+        // the record peeked is read as the iterators would read it
+        let mut r = Reader::from_reader(b(NHMMER_FILE))
+            .unwrap()
+            .keep_raw_lines(true);
+        let line = NHMMER_FILE.lines().nth(2).unwrap();
+        assert_eq!(r.peek_first().unwrap().unwrap().raw_line(), Some(line));
+        let first = r.records().next().unwrap().unwrap();
+        assert!(first.raw_line().is_some());
+
+        // an error is given once, and reading goes on
+        let file = NHMMER_FILE.replacen("SUPER_3 ", "SUPER_3 x", 1);
+        let mut r = Reader::from_reader(b(&file)).unwrap();
+        let n = r.records().count();
+        let mut r = Reader::from_reader(b(&file)).unwrap();
+        r.records().next().unwrap().unwrap();
+        assert!(r.peek_first().is_err());
+        assert_eq!(r.records().count(), n - 2);
+    }
+
+    fn assert_send<T: Send>() {}
+    fn assert_sync<T: Sync>() {}
+
+    #[test]
+    fn test_send_sync() {
+        assert_send::<Record>();
+        assert_sync::<Record>();
+        assert_send::<DNARecord>();
+        assert_sync::<DNARecord>();
+        assert_send::<ProteinRecord>();
+        assert_sync::<ProteinRecord>();
+        assert_send::<Meta>();
+        assert_sync::<Meta>();
+        assert_send::<Header>();
+        assert_sync::<Header>();
+        assert_send::<Error>();
+        assert_sync::<Error>();
+        assert_send::<Reader<std::fs::File>>();
+        assert_send::<RecordsIntoIter<std::fs::File>>();
+        assert_send::<DNARecordsIntoIter<std::fs::File>>();
+        assert_send::<ProteinRecordsIntoIter<std::fs::File>>();
+        assert_send::<RecordsIter<'static, std::fs::File>>();
+        assert_send::<Writer<std::fs::File>>();
+    }
+
+    #[test]
+    fn test_records_across_threads() {
+        let (tx, rx) = std::sync::mpsc::sync_channel(4);
+        let parser = std::thread::spawn(move || {
+            let reader = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+            for record in reader.into_records() {
+                tx.send(record.unwrap()).unwrap();
+            }
+        });
+        let rx = std::sync::Mutex::new(rx);
+        let total: f32 = std::thread::scope(|s| {
+            let workers = (0..3)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut sum = 0.0;
+                        while let Ok(record) = rx.lock().unwrap().recv() {
+                            sum += record.score_opt().unwrap();
+                        }
+                        sum
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers.into_iter().map(|w| w.join().unwrap()).sum()
+        });
+        parser.join().unwrap();
+
+        let expected: f32 = Reader::from_reader(b(NHMMER_FILE))
+            .unwrap()
+            .into_records()
+            .map(|r| r.unwrap().score_opt().unwrap())
+            .sum();
+        assert!((total - expected).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_read_all() {
+        // bad lines 4 and 9
+        let file =
+            NHMMER_FILE
+                .replacen("SUPER_3 ", "SUPER_3 x", 1)
+                .replacen("SUPER_8 ", "SUPER_8 x", 1);
+        let mut r = Reader::from_reader(b(&file)).unwrap();
+        let (records, errors) = r.read_all();
+        assert_eq!(records.len(), 14);
+        assert!(records.capacity() >= 16);
+        let lines = errors.iter().map(|(line, _)| *line).collect::<Vec<_>>();
+        assert_eq!(lines, [4, 9]);
+
+        let mut r = Reader::from_reader(b(&file)).unwrap();
+        assert!(r.read_all_strict().is_err());
+        assert_eq!(r.line(), 4);
+
+        let mut r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let all = Reader::from_reader(b(NHMMER_FILE))
+            .unwrap()
+            .into_records()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(r.read_all_strict().unwrap(), all);
+        assert_eq!(r.read_all().0.len(), 0);
+
+        // an unknown program gives one error, not one for every line
+        let mut r = Reader::new(b(NHMMER_FILE), Meta::default());
+        let (records, errors) = r.read_all();
+        assert!(records.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].1.kind(), ErrorKind::Meta(_)));
+    }
+
+    #[test]
+    fn test_orientation() {
+        let mut r = Reader::from_reader(b(HMMSEARCH_FILE)).unwrap();
+        let o = r.orientation();
+        assert_eq!(o, ScanOrientation::Search);
+        let first = r.records().next().unwrap().unwrap();
+        assert_eq!(first.profile_name(o), "SOR");
+        assert_eq!(first.profile_accession(o), "PF07682.13");
+        assert_eq!(first.sequence_name(o), "sp|P29082|SOR_ACIAM");
+        assert_eq!(first.sequence_accession(o), "-");
+
+        let mut r = Reader::from_reader(b(HMMSCAN_FILE)).unwrap();
+        let o = r.orientation();
+        assert_eq!(o, ScanOrientation::Scan);
+        let first = r.records().next().unwrap().unwrap();
+        assert_eq!(first.profile_name(o), "ABC_membrane_2");
+        assert_eq!(first.profile_accession(o), "PF06472.14");
+        assert_eq!(first.sequence_name(o), "11LoS11_3_18_3");
+        assert_eq!(first.sequence_accession(o), "-");
+    }
+
+    #[test]
+    fn test_typed_records_mismatch() {
+        let mut r = Reader::from_reader(b(HMMSEARCH_FILE)).unwrap();
+        let err = r.dna_records().err().unwrap();
+        assert!(matches!(err.kind(), ErrorKind::Meta(_)));
+        assert_eq!(
+            err.to_string(),
+            "metadata error - cannot read DNA records from hmmsearch output"
+        );
+
+        let r = Reader::from_reader(b(NHMMSCAN_FILE)).unwrap();
+        let err = r.into_protein_records().err().unwrap();
+        assert!(matches!(err.kind(), ErrorKind::Meta(_)));
+
+        let mut r = Reader::new(b(NHMMER_FILE), Meta::default());
+        assert!(r.dna_records().is_err());
+        assert!(r.protein_records().is_err());
+        // the records of an unknown program give one error, then end
+        let mut records = r.records();
+        let err = records.next().unwrap().unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Meta(_)));
+        assert!(records.next().is_none());
+        let mut records = Reader::from_reader(b("not a tblout file\n"))
+            .unwrap()
+            .into_records();
+        assert!(records.next().unwrap().is_err());
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_tblout_record() {
+        fn summary<R: TbloutRecord>(r: &R) -> (String, String, Option<f64>, Option<(i64, i64)>) {
+            (
+                r.target_name(),
+                r.query_name(),
+                r.evalue_opt(),
+                r.interval_opt(),
+            )
+        }
+
+        let mut r = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let dna = r.dna_records().unwrap().next().unwrap().unwrap();
+        let expected = (
+            "SUPER_1".to_string(),
+            "TR".to_string(),
+            Some(dna.e_value()),
+            Some((dna.ali_from(), dna.ali_to())),
+        );
+        assert_eq!(summary(&dna), expected);
+        assert_eq!(summary(&Record::Dna(dna)), expected);
+
+        let mut r = Reader::from_reader(b(HMMSEARCH_FILE)).unwrap();
+        let protein = r.protein_records().unwrap().next().unwrap().unwrap();
+        let expected = (
+            "sp|P29082|SOR_ACIAM".to_string(),
+            protein.query_name(),
+            Some(protein.e_value_full()),
+            None,
+        );
+        assert_eq!(summary(&protein), expected);
+        assert_eq!(summary(&Record::Protein(protein)), expected);
+    }
+
+    #[test]
+    fn test_helpers_take_concrete_records() {
+        let dna = || {
+            Reader::from_path("./data/test.tbl")
+                .unwrap()
+                .into_dna_records()
+                .unwrap()
+        };
+        let records = || Reader::from_path("./data/test.tbl").unwrap().into_records();
+
+        assert_eq!(
+            coverage_per_target(dna(), None).unwrap(),
+            coverage_per_target(records(), None).unwrap()
+        );
+        let merged = merge([dna(), dna()], None).unwrap();
+        let expected = merge([records(), records()], None).unwrap();
+        assert_eq!(merged.len(), 62);
+        assert!(merged.into_iter().map(Record::Dna).eq(expected.into_iter()));
+    }
+
+    #[test]
+    fn test_spawn_records() {
+        let mut reader = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let expected = reader
+            .records()
+            .map(|r| r.unwrap().ali_from())
+            .collect::<Vec<_>>();
+
+        let reader = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let (handle, rx) = reader.spawn_records(2);
+        let received = rx.iter().map(|r| r.unwrap().ali_from()).collect::<Vec<_>>();
+
+        assert_eq!(received, expected);
+        assert_eq!(handle.join().unwrap().unwrap().program(), Program::Nhmmer);
+    }
+
+    #[test]
+    fn test_spawn_records_early_drop() {
+        let reader = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let (handle, rx) = reader.spawn_records(1);
+
+        let first = rx.recv().unwrap().unwrap();
+        assert_eq!(first.target_name(), "SUPER_1".to_string());
+        drop(rx);
+
+        // the worker must notice the hang up rather than block on send
+        let res = handle.join().unwrap();
+        assert!(matches!(
+            res.map_err(|e| e.into_kind()),
+            Err(ErrorKind::Cancelled)
+        ));
+    }
+
+    #[test]
+    fn test_write_all_dna() {
+        let mut reader = Reader::from_reader(b(NHMMER_FILE)).unwrap();
+        let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+
+        let mut writer = Writer::new(Vec::new());
+        let n = writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+        assert_eq!(n, 16);
+        let out = writer.into_inner().unwrap();
+
+        let mut reparsed = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(reparsed.header(), reader.header());
+        assert_eq!(reparsed.meta().program(), Program::Nhmmer);
+        assert_eq!(reparsed.meta().date(), reader.meta().date());
+        let again = reparsed.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(again, records);
+    }
+
+    #[test]
+    fn test_write_all_protein() {
+        let mut reader = Reader::from_reader(b(JACKHMMER_FILE)).unwrap();
+        let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+        let out = writer.into_inner().unwrap();
+
+        let mut reparsed = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(reparsed.header().group(), reader.header().group());
+        assert_eq!(reparsed.meta().options(), reader.meta().options());
+        let again = reparsed.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(again, records);
+    }
+
+    #[test]
+    fn test_records_without_header() {
+        // as left by grepping out the comment lines
+        let extract = NHMMER_FILE
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| format!("{}\n", line))
+            .collect::<String>();
+        let expected = Reader::from_reader(b(NHMMER_FILE))
+            .unwrap()
+            .into_records()
+            .collect::<Result<Vec<Record>>>()
+            .unwrap();
+
+        let mut reader = Reader::with_program(b(&extract), Program::Nhmmer);
+        assert!(reader.header().is_empty());
+        let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(records, expected);
+
+        // written back under the header nhmmer would have written
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let mut reparsed = Reader::from_reader(out.as_bytes()).unwrap();
+        assert_eq!(reparsed.header().record_kind(), Some(RecordKind::Dna));
+        assert_eq!(reparsed.meta().program(), Program::Nhmmer);
+        let widths = reparsed.header().calculate_dashes();
+        for line in out.lines().filter(|line| !line.starts_with('#')) {
+            // every column ends where its dashes do
+            let mut end = 0;
+            for (width, field) in widths.iter().zip(line.split_whitespace()).take(4) {
+                assert!(field.len() <= *width);
+                end += width + 1;
+                assert_eq!(line.as_bytes()[end - 1], b' ');
+            }
+        }
+        let again = reparsed.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(again, records);
+    }
+
+    #[test]
+    fn test_protein_header_without_group() {
+        // as left by tools which strip the first comment line
+        let (group, stripped) = HMMSEARCH_FILE.split_once('\n').unwrap();
+        let mut reader = Reader::from_reader(b(stripped)).unwrap();
+        assert_eq!(reader.header().group(), None);
+        assert_eq!(reader.header().record_kind(), Some(RecordKind::Protein));
+        let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].query_name(), "SOR");
+
+        // the group line is written back
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out.lines().next(), Some(group));
+        let mut reparsed = Reader::from_reader(out.as_bytes()).unwrap();
+        assert_eq!(reparsed.header().group(), Some(group));
+        let again = reparsed.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(again, records);
+    }
+
+    #[test]
+    fn test_protein_display() {
+        // hmmsearch and hmmscan files with HMMER's default column widths
+        for file in [HMMSEARCH_FILE, HMMSCAN_FILE] {
+            let mut reader = Reader::from_reader(b(file)).unwrap();
+            let original = file.lines().filter(|l| !l.starts_with('#'));
+            for (record, original) in reader.records().zip(original) {
+                let Record::Protein(mut record) = record.unwrap() else {
+                    panic!("expected a protein record");
+                };
+                let line = record.to_string();
+                assert_eq!(line, original);
+
+                // only the changed column differs
+                record.set_score_full(1234.5);
+                let start = record::PROTEIN_WIDTHS[..5].iter().sum::<usize>() + 5;
+                let end = start + record::PROTEIN_WIDTHS[5];
+                let changed = record.to_string();
+                assert_eq!(&changed[start..end], "1234.5");
+                assert_eq!(changed[..start], line[..start]);
+                assert_eq!(changed[end..], line[end..]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_description_whitespace() {
+        let protein = HMMSEARCH_FILE.replace(
+            "Sulfur oxygenase/reductase OS=Acidianus",
+            "  Sulfur  oxygenase/reductase   OS=Acidianus",
+        );
+        let protein = protein.replace("SV=3\n", "SV=3  \n");
+        let dna = NHMMER_FILE.replacen("1.2  -\n", "1.2   SUPER 1  scaffold \n", 1);
+        for (file, description) in [
+            (
+                protein.as_str(),
+                "  Sulfur  oxygenase/reductase   OS=Acidianus ambivalens OX=2283 GN=sor PE=1 SV=3  ",
+            ),
+            (dna.as_str(), " SUPER 1  scaffold "),
+        ] {
+            let mut reader = Reader::from_reader(b(file)).unwrap();
+            let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+            assert_eq!(records[0].description(), description);
+
+            let mut writer = Writer::new(Vec::new());
+            writer
+                .write_all(reader.header(), &records, reader.meta())
+                .unwrap();
+            let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+            let line = |s: &str| s.lines().find(|l| !l.starts_with('#')).unwrap().to_string();
+            assert_eq!(line(&out), line(file));
+
+            let mut reparsed = Reader::from_reader(out.as_bytes()).unwrap();
+            let again = reparsed.records().collect::<Result<Vec<Record>>>().unwrap();
+            assert_eq!(again[0].description(), description);
+        }
+    }
+
+    #[test]
+    fn test_tab_delimited() {
+        let test_file = std::fs::read_to_string("./data/test.tbl").unwrap();
+        let mut reader = Reader::from_reader(b(&test_file)).unwrap();
+        let expected = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert!(!reader.tab_delimited());
+
+        let tabs = std::fs::read_to_string("./data/tabs.tbl").unwrap();
+        let mut reader = Reader::from_reader(b(&tabs)).unwrap();
+        assert_eq!(reader.meta().program(), Program::Nhmmer);
+        let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert!(reader.tab_delimited());
+        assert_eq!(records, expected[..4]);
+        // once, for the first record
+        assert_eq!(reader.warnings(), [Warning::TabDelimited { line: 3 }]);
+
+        let mixed = std::fs::read_to_string("./data/mixed_whitespace.tbl").unwrap();
+        let mut reader = Reader::from_reader(b(&mixed)).unwrap();
+        let mut records = reader.records();
+        // tabs between some of the columns
+        let first = records.next().unwrap().unwrap();
+        assert_eq!(first, expected[0]);
+        assert!(records.reader().tab_delimited());
+        // a tab in the description is kept
+        let second = records.next().unwrap().unwrap();
+        assert_eq!(
+            second.description(),
+            expected[1].description().replacen(' ', "\t", 1)
+        );
+        // spaces after the tab belong to the description
+        let third = records.next().unwrap().unwrap();
+        assert_eq!(
+            third.description(),
+            format!("  {}", expected[2].description())
+        );
+        assert_eq!(records.next().unwrap().unwrap(), expected[3]);
+        assert_eq!(reader.warnings(), [Warning::TabDelimited { line: 3 }]);
+
+        // a tab in the description alone is HMMER's own output
+        let line = test_file.lines().nth(3).unwrap();
+        let file = test_file.replacen(line, &line.replacen(" primary", "\tprimary", 1), 1);
+        let mut reader = Reader::from_reader(b(&file)).unwrap();
+        assert_eq!(reader.records().count(), expected.len());
+        assert!(!reader.tab_delimited());
+        assert!(reader.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_strand_column() {
+        let test_file = std::fs::read_to_string("./data/test.tbl").unwrap();
+        for file in [NHMMER_FILE, NHMMSCAN_FILE, test_file.as_str()] {
+            let mut reader = Reader::from_reader(b(file)).unwrap();
+            let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+            let widths = reader.header().calculate_dashes();
+            let start = widths[..11].iter().sum::<usize>() + 11;
+
+            let mut writer = WriterBuilder::new().write_header(false).build(Vec::new());
+            writer.write_header(reader.header()).unwrap();
+            writer.write_records(&records).unwrap();
+            let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+            let original = file.lines().filter(|l| !l.starts_with('#'));
+            for (line, original) in out.lines().zip(original) {
+                let strand = &original[start..start + 6];
+                assert!(strand == "   +  " || strand == "   -  ");
+                assert_eq!(&line[start..start + 6], strand);
+            }
+        }
+    }
+
+    #[test]
+    fn test_coordinates_beyond_i32() {
+        // a hit near the end of a 5 Gb scaffold, on the minus strand
+        let line = "scaffold_1           -          TR                   -                2     317 4294967296 4294966948 4294967297 4294966948 5000000000    -     1.9e-37  136.7   1.4  -";
+        let record = crate::reader::parse_dna_line(line).unwrap();
+        assert_eq!(record.ali_from(), 4294967296);
+        assert_eq!(record.sq_len(), 5_000_000_000);
+        assert_eq!(record.interval(), (4294966948, 4294967296));
+        assert_eq!(record.check_coordinates(), Ok(()));
+        assert!(record.validate().is_empty());
+
+        let mut meta = Meta::for_program(Program::Nhmmer);
+        meta.set_version("3.4 (Aug 2023)".to_string());
+        let mut writer = Writer::new(Vec::new());
+        let records = [Record::Dna(record)];
+        writer.write_aligned(Program::Nhmmer, &records).unwrap();
+        let out = String::from_utf8(writer.finish(&meta).unwrap()).unwrap();
+        assert!(out.contains(" 4294967296 4294966948 4294967297 4294966948 5000000000    -  "));
+
+        let mut reader = Reader::from_reader(out.as_bytes()).unwrap();
+        let again = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+        assert_eq!(again, records);
+        assert_eq!(again[0].ali_from(), Some(4294967296));
+        assert_eq!(again[0].interval_opt(), Some((4294967296, 4294966948)));
+    }
+
+    #[test]
+    fn test_write_to() {
+        let test_file = std::fs::read_to_string("./data/test.tbl").unwrap();
+        for file in [
+            NHMMER_FILE,
+            NHMMSCAN_FILE,
+            JACKHMMER_FILE,
+            PHMMER_FILE,
+            HMMSCAN_FILE,
+            HMMSEARCH_FILE,
+            test_file.as_str(),
+        ] {
+            let mut reader = Reader::from_reader(b(file)).unwrap();
+            let widths = reader.header().calculate_dashes();
+            let original = file.lines().filter(|l| !l.starts_with('#'));
+            for (record, original) in reader.records().zip(original) {
+                let record = record.unwrap();
+                // the same as Display with the default widths
+                let defaults = match &record {
+                    Record::Protein(_) => record::PROTEIN_WIDTHS.as_slice(),
+                    Record::Dna(_) => record::DNA_WIDTHS.as_slice(),
+                };
+                let mut out = Vec::new();
+                record.write_to(&mut out, defaults).unwrap();
+                assert_eq!(String::from_utf8(out).unwrap(), record.to_string());
+
+                // and as HMMER wrote it with the widths of the header
+                let mut out = Vec::new();
+                record.write_to(&mut out, &widths).unwrap();
+                assert_eq!(String::from_utf8(out).unwrap(), original);
+            }
+        }
+    }
+
+    #[test]
+    fn test_golden_lines() {
+        // records written under the header they were read with are
+        // formatted exactly as HMMER formatted them
+        for file in [
+            NHMMER_FILE,
+            NHMMSCAN_FILE,
+            JACKHMMER_FILE,
+            PHMMER_FILE,
+            HMMSCAN_FILE,
+            HMMSEARCH_FILE,
+        ] {
+            let mut reader = Reader::from_reader(b(file)).unwrap();
+            let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+
+            let mut writer = WriterBuilder::new().write_header(false).build(Vec::new());
+            writer.write_header(reader.header()).unwrap();
+            writer.write_records(&records).unwrap();
+            let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+            let original: Vec<&str> = file.lines().filter(|l| !l.starts_with('#')).collect();
+            assert_eq!(out.lines().collect::<Vec<_>>(), original);
+        }
+    }
+
+    #[test]
+    fn test_write_records_count() {
+        let mut reader = Reader::from_reader(b(PHMMER_FILE)).unwrap();
+        let records = reader.records().collect::<Result<Vec<Record>>>().unwrap();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_header(reader.header()).unwrap();
+        assert_eq!(writer.write_records(&records[..3]).unwrap(), 3);
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out.lines().count(), 6);
+        assert!(out
+            .lines()
+            .nth(3)
+            .unwrap()
+            .starts_with("HBB_HUMAN            -          MYG_ESCGI"));
+    }
 }
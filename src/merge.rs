@@ -0,0 +1,43 @@
+use crate::{
+    ord::cmp_by_evalue,
+    progress::{report, Progress, ProgressFn},
+    record::TbloutRecord,
+    Result,
+};
+
+/// Merge the records of several searches (e.g. the same query
+/// run against chunks of a genome) into a single list, ordered
+/// by E-value as HMMER would report them. Records with equal
+/// E-values keep their input order. The optional `progress`
+/// callback is called periodically and may cancel the merge.
+///
+/// Any kind of record can be merged, e.g. `Record`s from
+/// `Reader::records` or `DNARecord`s from `Reader::dna_records`.
+pub fn merge<I, T, R>(inputs: I, progress: Option<ProgressFn>) -> Result<Vec<R>>
+where
+    I: IntoIterator<Item = T>,
+    T: IntoIterator<Item = Result<R>>,
+    R: TbloutRecord,
+{
+    let mut merged = Vec::new();
+    for input in inputs {
+        for record in input {
+            merged.push(record?);
+            report(
+                progress,
+                Progress {
+                    records: merged.len() as u64,
+                    bytes: None,
+                },
+            )?;
+        }
+    }
+
+    merged.sort_by(cmp_by_evalue);
+    Ok(merged)
+}
+
+/// The E-value of the whole hit, with records which have none last.
+pub(crate) fn e_value<R: TbloutRecord>(record: &R) -> f64 {
+    record.evalue_opt().unwrap_or(f64::INFINITY)
+}
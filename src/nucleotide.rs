@@ -0,0 +1,99 @@
+use crate::{
+    record::{DNARecord, Record, Strand},
+    Error, ErrorKind,
+};
+
+/// A hit of a profile on a nucleotide sequence, holding only the
+/// values any nucleotide search reports, so hits from different
+/// programs can be handled alike.
+///
+/// Converting a `DNARecord` drops the envelope coordinates, the
+/// sequence length and the description.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NucleotideHit {
+    /// The name of the target sequence or profile.
+    pub target_name: String,
+    /// The name of the query sequence or profile.
+    pub query_name: String,
+    /// The start of the alignment on the profile.
+    pub model_from: i32,
+    /// The end of the alignment on the profile.
+    pub model_to: i32,
+    /// The start of the alignment on the sequence. On the minus strand
+    /// this is greater than `seq_to`.
+    pub seq_from: i64,
+    /// The end of the alignment on the sequence.
+    pub seq_to: i64,
+    /// The strand of the hit.
+    pub strand: Strand,
+    /// The E-value of the hit.
+    pub e_value: f64,
+    /// The bit score of the hit.
+    pub score: f32,
+    /// The bias correction applied to the score.
+    pub bias: f32,
+}
+
+impl From<&DNARecord> for NucleotideHit {
+    fn from(r: &DNARecord) -> NucleotideHit {
+        NucleotideHit {
+            target_name: r.target_name(),
+            query_name: r.query_name(),
+            model_from: r.hmm_from(),
+            model_to: r.hmm_to(),
+            seq_from: r.ali_from(),
+            seq_to: r.ali_to(),
+            strand: r.strand(),
+            e_value: r.e_value(),
+            score: r.score(),
+            bias: r.bias(),
+        }
+    }
+}
+
+/// Protein records are not nucleotide hits, so converting one is an
+/// `ErrorKind::Unsupported` error.
+impl TryFrom<&Record> for NucleotideHit {
+    type Error = Error;
+
+    fn try_from(r: &Record) -> Result<NucleotideHit, Error> {
+        match r {
+            Record::Dna(r) => Ok(NucleotideHit::from(r)),
+            Record::Protein(r) => Err(Error::new(ErrorKind::Unsupported(format!(
+                "protein record for target {} is not a nucleotide hit",
+                r.target_name()
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    #[test]
+    fn test_nucleotide_hit() {
+        let records = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+
+        let hit = NucleotideHit::try_from(&records[1]).unwrap();
+        assert_eq!(hit.strand, Strand::Negative);
+        assert_eq!(hit.seq_from, 61501865);
+        assert!(hit.seq_from > hit.seq_to);
+        assert_eq!(hit.target_name, records[1].target_name());
+        assert_eq!(Some(hit.model_from), records[1].hmm_from());
+        assert_eq!(Some(hit.e_value), records[1].e_value());
+
+        let protein = crate::reader::parse_protein_line(
+            "sp|P29082|SOR_ACIAM  -          SOR                  PF07682.13  1.5e-152  492.8   0.8  1.7e-152  492.6   0.8   1.0   1   0   0   1   1   1   1 Sulfur oxygenase/reductase",
+        )
+        .map(Record::Protein)
+        .unwrap();
+        let err = NucleotideHit::try_from(&protein).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Unsupported(_)));
+    }
+}
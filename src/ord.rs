@@ -0,0 +1,280 @@
+//! Orderings of records by E-value and bit score which never panic.
+//!
+//! E-values and scores are floats, so a NaN, from a corrupt file or
+//! arithmetic downstream, makes `partial_cmp(..).unwrap()` panic. These
+//! orderings are total: hits are ordered best first, then those whose
+//! value is NaN, then those which have none. Everything in the crate
+//! which sorts or ranks records by E-value or score uses them.
+//!
+//! ```
+//! use hmm_tblout::{ord::cmp_by_evalue, Reader};
+//!
+//! let mut reader = Reader::from_path("./data/test.tbl")?;
+//! let mut records = reader.records().collect::<hmm_tblout::Result<Vec<_>>>()?;
+//! records.sort_by(cmp_by_evalue);
+//! # Ok::<(), hmm_tblout::Error>(())
+//! ```
+
+use crate::{
+    record::{Record, TbloutRecord},
+    Result,
+};
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+/// Compare two records by E-value, smallest (most significant) first,
+/// then those whose E-value is NaN, then those which have none.
+pub fn cmp_by_evalue<R: TbloutRecord>(a: &R, b: &R) -> Ordering {
+    cmp_keys(a.evalue_opt(), b.evalue_opt())
+}
+
+/// Compare two records by bit score, highest first, then those whose
+/// score is NaN, then those which have none.
+pub fn cmp_by_score<R: TbloutRecord>(a: &R, b: &R) -> Ordering {
+    // negated, so the highest are least
+    let key = |r: &R| r.score_opt().map(|s| -f64::from(s));
+    cmp_keys(key(a), key(b))
+}
+
+/// Compare values, least first, with NaN after every number and no
+/// value after NaN.
+fn cmp_keys(a: Option<f64>, b: Option<f64>) -> Ordering {
+    let rank = |v: Option<f64>| match v {
+        Some(v) if !v.is_nan() => 0,
+        Some(_) => 1,
+        None => 2,
+    };
+    match (a, b) {
+        (Some(x), Some(y)) if rank(a) == 0 && rank(b) == 0 => x.total_cmp(&y),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// A record ordered by `cmp_by_evalue`, e.g. to keep records in a
+/// `BinaryHeap` or `BTreeSet`. Records with equal E-values are equal,
+/// whatever their other fields, so this is not the record's own
+/// equality.
+#[derive(Debug, Clone)]
+pub struct EValueKey<R = Record>(pub R);
+
+/// A record ordered by `cmp_by_score`, so highest scores first, e.g.
+/// to keep records in a `BinaryHeap` or `BTreeSet`. Records with equal
+/// scores are equal, whatever their other fields, so this is not the
+/// record's own equality.
+#[derive(Debug, Clone)]
+pub struct ScoreKey<R = Record>(pub R);
+
+macro_rules! ord_by {
+    ($key:ident, $cmp:ident) => {
+        impl<R: TbloutRecord> Ord for $key<R> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                $cmp(&self.0, &other.0)
+            }
+        }
+
+        impl<R: TbloutRecord> PartialOrd for $key<R> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl<R: TbloutRecord> PartialEq for $key<R> {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl<R: TbloutRecord> Eq for $key<R> {}
+    };
+}
+
+ord_by!(EValueKey, cmp_by_evalue);
+ord_by!(ScoreKey, cmp_by_score);
+
+/// The `n` records with the smallest E-values, best first, holding no
+/// more than `n` at a time. Records with equal E-values keep their
+/// input order.
+pub fn top_n_by_evalue<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    n: usize,
+) -> Result<Vec<R>> {
+    Ok(top_n(records, n, EValueKey)?
+        .into_iter()
+        .map(|key| key.0)
+        .collect())
+}
+
+/// The `n` records with the highest bit scores, best first, holding no
+/// more than `n` at a time. Records with equal scores keep their input
+/// order.
+pub fn top_n_by_score<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    n: usize,
+) -> Result<Vec<R>> {
+    Ok(top_n(records, n, ScoreKey)?
+        .into_iter()
+        .map(|key| key.0)
+        .collect())
+}
+
+/// The `n` least records by their keys, least first, with ties in
+/// input order. A max-heap holds the best so far, so the worst of them
+/// is the one dropped.
+fn top_n<R, K: Ord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    n: usize,
+    key: impl Fn(R) -> K,
+) -> Result<Vec<K>> {
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    // the place of each record breaks ties, so later records are worse
+    let mut heap = BinaryHeap::with_capacity(n + 1);
+    for (i, record) in records.into_iter().enumerate() {
+        heap.push((key(record?), i));
+        if heap.len() > n {
+            heap.pop();
+        }
+    }
+    Ok(heap.into_sorted_vec().into_iter().map(|(k, _)| k).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// A hit with only an E-value and score, either of which may be
+    /// missing.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Hit(Option<f64>, Option<f32>);
+
+    impl TbloutRecord for Hit {
+        fn target_name(&self) -> String {
+            "target".into()
+        }
+        fn target_accession(&self) -> String {
+            "-".into()
+        }
+        fn query_name(&self) -> String {
+            "query".into()
+        }
+        fn query_accession(&self) -> String {
+            "-".into()
+        }
+        fn description(&self) -> String {
+            "-".into()
+        }
+        fn evalue_opt(&self) -> Option<f64> {
+            self.0
+        }
+        fn score_opt(&self) -> Option<f32> {
+            self.1
+        }
+    }
+
+    fn e_values(hits: &[Hit]) -> Vec<String> {
+        hits.iter().map(|h| format!("{:?}", h.0)).collect()
+    }
+
+    fn scores(hits: &[Hit]) -> Vec<String> {
+        hits.iter().map(|h| format!("{:?}", h.1)).collect()
+    }
+
+    fn some_hits() -> Vec<Hit> {
+        vec![
+            Hit(None, Some(10.0)),
+            Hit(Some(f64::NAN), Some(f32::NAN)),
+            Hit(Some(1e-5), None),
+            Hit(Some(0.0), Some(-5.0)),
+            Hit(Some(-f64::NAN), Some(50.0)),
+            Hit(Some(1e-30), Some(f32::INFINITY)),
+        ]
+    }
+
+    #[test]
+    fn test_sort_by_evalue() {
+        let mut hits = some_hits();
+        hits.sort_by(cmp_by_evalue);
+        // numbers, then NaN of either sign, then none
+        assert_eq!(
+            e_values(&hits),
+            [
+                "Some(0.0)",
+                "Some(1e-30)",
+                "Some(1e-5)",
+                "Some(NaN)",
+                "Some(NaN)",
+                "None"
+            ]
+        );
+        // the NaNs are equal, so stay in input order
+        assert!(hits[3].1.unwrap().is_nan());
+        assert_eq!(hits[4].1, Some(50.0));
+    }
+
+    #[test]
+    fn test_sort_by_score() {
+        let mut hits = some_hits();
+        hits.sort_by(cmp_by_score);
+        // highest first, then NaN, then none
+        assert_eq!(
+            scores(&hits),
+            [
+                "Some(inf)",
+                "Some(50.0)",
+                "Some(10.0)",
+                "Some(-5.0)",
+                "Some(NaN)",
+                "None"
+            ]
+        );
+
+        let mut keys = some_hits().into_iter().map(ScoreKey).collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys[0].0 .1, Some(f32::INFINITY));
+        assert_eq!(keys[5].0 .1, None);
+    }
+
+    #[test]
+    fn test_keys() {
+        let key = |e| EValueKey(Hit(e, None));
+        assert!(key(Some(1.0)) < key(Some(2.0)));
+        assert!(key(Some(f64::INFINITY)) < key(Some(f64::NAN)));
+        assert!(key(Some(f64::NAN)) < key(None));
+        assert_eq!(key(Some(f64::NAN)), key(Some(-f64::NAN)));
+        assert_eq!(key(None), key(None));
+        // the two NaNs are one key of a map
+        let mut map = BTreeMap::new();
+        for hit in some_hits() {
+            *map.entry(EValueKey(hit)).or_insert(0) += 1;
+        }
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), [1, 1, 1, 2, 1]);
+    }
+
+    #[test]
+    fn test_top_n() {
+        let best = top_n_by_evalue(some_hits().into_iter().map(Ok), 3).unwrap();
+        assert_eq!(e_values(&best), ["Some(0.0)", "Some(1e-30)", "Some(1e-5)"]);
+        let best = top_n_by_score(some_hits().into_iter().map(Ok), 2).unwrap();
+        assert_eq!(scores(&best), ["Some(inf)", "Some(50.0)"]);
+        // more than there are
+        assert_eq!(
+            top_n_by_evalue(some_hits().into_iter().map(Ok), 10)
+                .unwrap()
+                .len(),
+            6
+        );
+        assert!(top_n_by_evalue(some_hits().into_iter().map(Ok), 0)
+            .unwrap()
+            .is_empty());
+
+        // ties in input order
+        let tied = vec![
+            Hit(Some(1.0), Some(1.0)),
+            Hit(Some(1.0), Some(2.0)),
+            Hit(Some(1.0), Some(3.0)),
+        ];
+        let best = top_n_by_evalue(tied.into_iter().map(Ok), 2).unwrap();
+        assert_eq!(scores(&best), ["Some(1.0)", "Some(2.0)"]);
+    }
+}
@@ -0,0 +1,35 @@
+//! The types most programs need, to be glob imported.
+//!
+//! ```
+//! use hmm_tblout::prelude::*;
+//!
+//! fn strong_hits(path: &str, max_evalue: f64) -> Result<Vec<Record>, Error> {
+//!     let mut reader = Reader::from_path(path)?;
+//!     let mut hits = Vec::new();
+//!     for record in reader.records() {
+//!         let record = record?;
+//!         if record.evalue_opt().is_some_and(|e| e <= max_evalue) {
+//!             hits.push(record);
+//!         }
+//!     }
+//!     Ok(hits)
+//! }
+//!
+//! fn write_hits(hits: &[Record], header: &Header, meta: &Meta) -> Result<Vec<u8>, Error> {
+//!     let mut writer = WriterBuilder::new().build(Vec::new());
+//!     writer.write_all(header, hits, meta)?;
+//!     writer.into_inner()
+//! }
+//! ```
+//!
+//! `Result` is left out so it doesn't hide the standard library's.
+
+#[doc(inline)]
+pub use crate::{
+    chunks::RecordsIterExt,
+    error::{Error, ErrorKind},
+    header::Header,
+    reader::{Reader, RecordsIntoIter, RecordsIter},
+    record::{DNARecord, Meta, Program, ProteinRecord, Record, RecordKind, Strand, TbloutRecord},
+    writer::{WritableRecord, Writer, WriterBuilder},
+};
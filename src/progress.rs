@@ -0,0 +1,163 @@
+use crate::{Error, ErrorKind, Result};
+use std::ops::ControlFlow;
+
+/// How often (in records) long running operations report progress.
+pub const PROGRESS_INTERVAL: u64 = 1024;
+
+/// A progress report from a long running operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of records processed so far.
+    pub records: u64,
+    /// The number of bytes processed so far, if known.
+    pub bytes: Option<u64>,
+}
+
+/// A callback which receives progress reports, returning
+/// `ControlFlow::Break` to cancel the operation.
+pub type ProgressFn<'a> = &'a dyn Fn(Progress) -> ControlFlow<()>;
+
+/// Report progress every `PROGRESS_INTERVAL` records, returning
+/// an `ErrorKind::Cancelled` error if the callback asks to stop.
+// `is_multiple_of` needs Rust 1.87, newer than the crate otherwise needs
+#[allow(clippy::manual_is_multiple_of)]
+pub(crate) fn report(callback: Option<ProgressFn>, progress: Progress) -> Result<()> {
+    match callback {
+        Some(f) if progress.records % PROGRESS_INTERVAL == 0 => match f(progress) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(Error::new(ErrorKind::Cancelled)),
+        },
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{coverage_per_target, merge, Reader, Record, TbloutIndex};
+    use std::{
+        cell::Cell,
+        sync::{atomic::AtomicBool, mpsc, Arc},
+        thread,
+    };
+
+    const LINE: &str = "SUPER_1              -          TR                   -                1     315 10988331 10987997 10988331 10987995 52766903    -     6.5e-34  124.1   1.2  -\n";
+
+    fn big_file() -> String {
+        let mut s = String::from("# target name\n#----\n");
+        for _ in 0..10 * PROGRESS_INTERVAL {
+            s.push_str(LINE);
+        }
+        s.push_str("#\n# Program:         nhmmer\n# [ok]\n");
+        s
+    }
+
+    fn records(file: &str) -> Vec<crate::Result<Record>> {
+        Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .into_records()
+            .collect()
+    }
+
+    /// A callback which counts calls and cancels on the first.
+    fn cancel_first(calls: &Cell<u64>) -> impl Fn(Progress) -> ControlFlow<()> + '_ {
+        move |p: Progress| {
+            calls.set(calls.get() + 1);
+            assert_eq!(p.records, PROGRESS_INTERVAL);
+            ControlFlow::Break(())
+        }
+    }
+
+    fn is_cancelled<T>(res: crate::Result<T>) -> bool {
+        matches!(res.map_err(|e| e.into_kind()), Err(ErrorKind::Cancelled))
+    }
+
+    #[test]
+    fn test_cancel_index() {
+        let file = big_file();
+        let calls = Cell::new(0);
+        let cb = cancel_first(&calls);
+        assert!(is_cancelled(TbloutIndex::build(file.as_bytes(), Some(&cb))));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_cancel_merge() {
+        let file = big_file();
+        let calls = Cell::new(0);
+        let cb = cancel_first(&calls);
+        assert!(is_cancelled(merge(vec![records(&file)], Some(&cb))));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_cancel_coverage() {
+        let file = big_file();
+        let calls = Cell::new(0);
+        let cb = cancel_first(&calls);
+        assert!(is_cancelled(coverage_per_target(records(&file), Some(&cb))));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_cancel_token() {
+        let file = big_file();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let canceller = {
+            let cancel = cancel.clone();
+            thread::spawn(move || {
+                rx.recv().unwrap();
+                cancel.store(true, std::sync::atomic::Ordering::Relaxed);
+            })
+        };
+
+        let reader = Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .cancel_token(cancel)
+            .cancel_interval(100);
+        let mut records = reader.into_records();
+        for record in records.by_ref().take(1000) {
+            record.unwrap();
+        }
+        tx.send(()).unwrap();
+        canceller.join().unwrap();
+
+        let rest = records.by_ref().collect::<Vec<_>>();
+        // after two header lines and 1000 records, the records up to
+        // the next line checked, line 1100, then the error
+        assert_eq!(rest.len(), 97 + 1);
+        assert!(matches!(rest.last(), Some(Err(e)) if matches!(e.kind(), ErrorKind::Cancelled)));
+        assert_eq!(records.reader().line(), 1100);
+        assert!(records.next().is_none());
+    }
+
+    #[test]
+    fn test_cancel_first_pass() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, big_file().as_bytes()).unwrap();
+        let cancel = Arc::new(AtomicBool::new(true));
+        assert!(is_cancelled(Reader::from_path_cancellable(
+            file.path(),
+            cancel.clone()
+        )));
+
+        cancel.store(false, std::sync::atomic::Ordering::Relaxed);
+        let reader = Reader::from_path_cancellable(file.path(), cancel).unwrap();
+        assert_eq!(reader.into_records().count() as u64, 10 * PROGRESS_INTERVAL);
+    }
+
+    #[test]
+    fn test_progress_continue() {
+        let file = big_file();
+        let calls = Cell::new(0);
+        let cb = |p: Progress| {
+            calls.set(calls.get() + 1);
+            assert!(p.bytes.unwrap() > 0);
+            ControlFlow::Continue(())
+        };
+        let index = TbloutIndex::build(file.as_bytes(), Some(&cb)).unwrap();
+        assert_eq!(index.len(), 10 * PROGRESS_INTERVAL);
+        assert_eq!(calls.get(), 10);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{record::TbloutRecord, Result};
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, Write},
+};
+
+/// The column names of `write_qc_points`.
+const QC_COLUMNS: [&str; 6] = [
+    "query",
+    "target",
+    "score",
+    "neg_log10_evalue",
+    "bias",
+    "bias_ratio",
+];
+
+/// Options for extracting QC points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QcOptions {
+    /// The largest `-log10(E-value)`, which an E-value of zero maps to.
+    neg_log10_cap: f64,
+}
+
+impl Default for QcOptions {
+    fn default() -> QcOptions {
+        QcOptions {
+            neg_log10_cap: 300.0,
+        }
+    }
+}
+
+impl QcOptions {
+    /// Set the largest `-log10(E-value)`, 300 by default. HMMER writes
+    /// E-values too small for a double as zero, which would be
+    /// infinite, so they are given this value instead, and any larger
+    /// values are capped to it, so the points sit on one line at the
+    /// edge of a plot.
+    pub fn neg_log10_cap(mut self, cap: f64) -> QcOptions {
+        self.neg_log10_cap = cap;
+        self
+    }
+}
+
+/// The statistics of a hit, for plotting the bit score against the
+/// E-value and spotting hits whose score is mostly bias.
+///
+/// For protein records these are the full sequence values, which
+/// HMMER ranks hits by, rather than those of the best domain.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QcPoint {
+    /// The name of the query.
+    pub query: String,
+    /// The name of the target.
+    pub target: String,
+    /// The bit score of the hit.
+    pub score: f32,
+    /// `-log10` of the E-value of the hit, capped by
+    /// `QcOptions::neg_log10_cap`.
+    pub neg_log10_evalue: f64,
+    /// The biased-composition correction of the score.
+    pub bias: f32,
+    /// The bias as a fraction of the score, or `None` if the score is
+    /// not positive. Near one, the hit may be a false positive.
+    pub bias_ratio: Option<f32>,
+}
+
+/// Writes the point as a TSV row in the order of `write_qc_points`'
+/// columns, with `-` for no bias ratio.
+impl Display for QcPoint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{:.1}\t{:.3}\t{:.1}\t",
+            self.query, self.target, self.score, self.neg_log10_evalue, self.bias
+        )?;
+        match self.bias_ratio {
+            Some(ratio) => write!(f, "{:.3}", ratio),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+/// The QC point of each record, in order. Records without an E-value,
+/// bit score and bias are skipped, though every kind of record this
+/// crate reads has them.
+///
+/// ```no_run
+/// use hmm_tblout::{qc_points, QcOptions, Reader};
+///
+/// let reader = Reader::from_path("hits.tbl")?;
+/// for point in qc_points(reader.into_records(), &QcOptions::default()) {
+///     let point = point?;
+///     if point.bias_ratio.is_some_and(|r| r > 0.5) {
+///         println!("{} on {} is mostly bias", point.query, point.target);
+///     }
+/// }
+/// # Ok::<(), hmm_tblout::Error>(())
+/// ```
+pub fn qc_points<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    options: &QcOptions,
+) -> impl Iterator<Item = Result<QcPoint>> {
+    let cap = options.neg_log10_cap;
+    records.into_iter().filter_map(move |record| {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => return Some(Err(e)),
+        };
+        let e_value = record.evalue_opt()?;
+        let score = record.score_opt()?;
+        let bias = record.bias_opt()?;
+        Some(Ok(QcPoint {
+            query: record.query_name(),
+            target: record.target_name(),
+            score,
+            neg_log10_evalue: (-e_value.log10()).min(cap),
+            bias,
+            bias_ratio: if score > 0.0 {
+                Some(bias / score)
+            } else {
+                None
+            },
+        }))
+    })
+}
+
+/// Write QC points as TSV with a header line, for loading into a
+/// notebook. Returns the number of points written, and stops at the
+/// first error.
+pub fn write_qc_points<W: Write>(
+    points: impl IntoIterator<Item = Result<QcPoint>>,
+    w: W,
+) -> Result<usize> {
+    let mut w = io::BufWriter::new(w);
+    writeln!(w, "{}", QC_COLUMNS.join("\t"))?;
+    let mut n = 0;
+    for point in points {
+        writeln!(w, "{}", point?)?;
+        n += 1;
+    }
+    w.flush()?;
+    Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Record};
+
+    fn read_points(path: &str, options: &QcOptions) -> Vec<QcPoint> {
+        let reader = Reader::from_path(path).unwrap();
+        qc_points(reader.into_records(), options)
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_qc_points_dna() {
+        let points = read_points("./data/test.tbl", &QcOptions::default());
+        assert_eq!(points.len(), 31);
+        let point = &points[0];
+        assert_eq!(point.query, "TR");
+        assert_eq!(point.target, "2");
+        assert_eq!(point.score, 136.7);
+        assert!((point.neg_log10_evalue - 36.7212).abs() < 1e-4);
+        assert_eq!(point.bias, 1.4);
+        assert_eq!(point.to_string(), "TR\t2\t136.7\t36.721\t1.4\t0.010");
+    }
+
+    #[test]
+    fn test_qc_points_protein() {
+        // the full sequence values
+        let points = read_points("./data/corpus/hmmscan.tbl", &QcOptions::default());
+        let point = &points[0];
+        assert_eq!(point.query, "11LoS11_3_18_3");
+        assert_eq!(point.target, "ABC_membrane_2");
+        assert_eq!(point.score, 250.4);
+        assert!((point.neg_log10_evalue - 73.699).abs() < 1e-3);
+        assert_eq!(point.bias, 5.3);
+        assert_eq!(point.bias_ratio, Some(5.3 / 250.4));
+    }
+
+    #[test]
+    fn test_zero_e_value() {
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let mut records = reader
+            .records()
+            .take(2)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        for record in &mut records {
+            if let Record::Dna(r) = record {
+                r.set_e_value(0.0);
+                r.set_score(-0.5);
+            }
+        }
+        let options = QcOptions::default().neg_log10_cap(250.0);
+        let points = qc_points(records.into_iter().map(Ok), &options)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(points[0].neg_log10_evalue, 250.0);
+        // no ratio for a score which is not positive
+        assert_eq!(points[0].bias_ratio, None);
+
+        // values beyond the cap are capped too
+        let points = read_points("./data/test.tbl", &QcOptions::default().neg_log10_cap(10.0));
+        assert!(points.iter().all(|p| p.neg_log10_evalue <= 10.0));
+        assert_eq!(points[0].neg_log10_evalue, 10.0);
+    }
+
+    #[test]
+    fn test_write_qc_points() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let mut out = Vec::new();
+        let n = write_qc_points(
+            qc_points(reader.into_records(), &QcOptions::default()),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(n, 31);
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "query\ttarget\tscore\tneg_log10_evalue\tbias\tbias_ratio"
+        );
+        assert_eq!(lines[1], "TR\t2\t136.7\t36.721\t1.4\t0.010");
+        assert_eq!(lines.len(), 32);
+    }
+}
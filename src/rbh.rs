@@ -0,0 +1,294 @@
+use crate::{
+    ord::{cmp_by_evalue, cmp_by_score},
+    record::Record,
+    record::TbloutRecord,
+    Result,
+};
+use std::{cmp::Ordering, collections::HashMap};
+
+/// Options for finding reciprocal best hits.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RbhOptions {
+    /// Whether queries and targets are paired by accession, not name.
+    by_accession: bool,
+}
+
+impl RbhOptions {
+    /// Pair queries and targets by their accessions rather than their
+    /// names, e.g. for Pfam families whose names have changed. Records
+    /// without an accession (`-`) are then left out. Names are used by
+    /// default.
+    pub fn by_accession(mut self, yes: bool) -> RbhOptions {
+        self.by_accession = yes;
+        self
+    }
+
+    /// The query and target keys of a record, if it has them.
+    fn keys<R: TbloutRecord>(&self, record: &R) -> Option<(String, String)> {
+        if !self.by_accession {
+            return Some((record.query_name(), record.target_name()));
+        }
+        let (query, target) = (record.query_accession(), record.target_accession());
+        if query == "-" || target == "-" {
+            None
+        } else {
+            Some((query, target))
+        }
+    }
+}
+
+/// The records supporting a reciprocal best hit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RbhEvidence<R = Record> {
+    /// The best hit of the query in the first search.
+    pub a: R,
+    /// The best hit of the target in the second search, back to the
+    /// query.
+    pub b: R,
+}
+
+/// The best hit of a query so far.
+struct Best<R> {
+    target: String,
+    record: R,
+    /// Whether another target has a hit just as good, so the query has
+    /// no single best hit.
+    tied: bool,
+}
+
+/// The best hit of each query of a search.
+struct BestHits<R> {
+    /// The queries, in the order they are first seen.
+    queries: Vec<String>,
+    by_query: HashMap<String, Best<R>>,
+}
+
+/// Find the reciprocal best hits between two searches run in opposite
+/// directions, e.g. library A against library B and B against A.
+/// Returns a `(query, target)` pair, keyed by the first search, for
+/// each query of `a` whose best hit is a target whose own best hit in
+/// `b` is the query, with the records behind both. Pairs are in the
+/// order their queries are first seen in `a`.
+///
+/// Hits are ranked by E-value, then by bit score. A query whose best
+/// hits on two targets are equal on both has no best hit, so is left
+/// out, as is a query whose best hit is not reciprocated. Several
+/// hits of a query on the same target, as `nhmmer` gives, are not a
+/// tie: the best of them is taken.
+///
+/// ```no_run
+/// use hmm_tblout::{reciprocal_best_hits, RbhOptions, Reader};
+///
+/// let a = Reader::from_path("a_vs_b.tbl")?;
+/// let b = Reader::from_path("b_vs_a.tbl")?;
+/// let options = RbhOptions::default().by_accession(true);
+/// for (query, target, _) in reciprocal_best_hits(a.into_records(), b.into_records(), &options)? {
+///     println!("{}\t{}", query, target);
+/// }
+/// # Ok::<(), hmm_tblout::Error>(())
+/// ```
+pub fn reciprocal_best_hits<R: TbloutRecord>(
+    a: impl IntoIterator<Item = Result<R>>,
+    b: impl IntoIterator<Item = Result<R>>,
+    options: &RbhOptions,
+) -> Result<Vec<(String, String, RbhEvidence<R>)>> {
+    let BestHits {
+        queries,
+        by_query: mut best_a,
+    } = best_hits(a, options)?;
+    let mut best_b = best_hits(b, options)?.by_query;
+
+    let mut pairs = Vec::new();
+    for query in queries {
+        let forward = &best_a[&query];
+        if forward.tied {
+            continue;
+        }
+        let reciprocated = match best_b.get(&forward.target) {
+            Some(back) => !back.tied && back.target == query,
+            None => false,
+        };
+        if reciprocated {
+            let forward = best_a.remove(&query).expect("a best hit for the query");
+            let back = best_b
+                .remove(&forward.target)
+                .expect("a best hit for the target");
+            pairs.push((
+                query,
+                forward.target,
+                RbhEvidence {
+                    a: forward.record,
+                    b: back.record,
+                },
+            ));
+        }
+    }
+    Ok(pairs)
+}
+
+/// The best hit of each query.
+fn best_hits<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    options: &RbhOptions,
+) -> Result<BestHits<R>> {
+    let mut queries = Vec::new();
+    let mut best: HashMap<String, Best<R>> = HashMap::new();
+    for record in records {
+        let record = record?;
+        let (query, target) = match options.keys(&record) {
+            Some(keys) => keys,
+            None => continue,
+        };
+        let current = match best.get_mut(&query) {
+            Some(current) => current,
+            None => {
+                queries.push(query.clone());
+                best.insert(
+                    query,
+                    Best {
+                        target,
+                        record,
+                        tied: false,
+                    },
+                );
+                continue;
+            }
+        };
+        match rank(&record, &current.record) {
+            Ordering::Less => {
+                *current = Best {
+                    target,
+                    record,
+                    tied: false,
+                }
+            }
+            Ordering::Equal if target != current.target => current.tied = true,
+            _ => {}
+        }
+    }
+    Ok(BestHits {
+        queries,
+        by_query: best,
+    })
+}
+
+/// Order hits best first: by E-value, then by bit score, highest first.
+fn rank<R: TbloutRecord>(a: &R, b: &R) -> Ordering {
+    cmp_by_evalue(a, b).then_with(|| cmp_by_score(a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_protein_line, ProteinRecord};
+
+    /// A record for a hit of `query` on `target`.
+    fn hit(query: &str, target: &str, e_value: f64, score: f32) -> Result<ProteinRecord> {
+        let line = format!(
+            "{} PF{}.1 {} PF{}.1 {:e} {} 0.1 {:e} {} 0.1 1.0 1 0 0 1 1 1 1 -",
+            target,
+            target.len(),
+            query,
+            query.len(),
+            e_value,
+            score,
+            e_value,
+            score
+        );
+        Ok(parse_protein_line(&line).unwrap())
+    }
+
+    fn pairs(
+        a: Vec<Result<ProteinRecord>>,
+        b: Vec<Result<ProteinRecord>>,
+        options: &RbhOptions,
+    ) -> Vec<(String, String)> {
+        reciprocal_best_hits(a, b, options)
+            .unwrap()
+            .into_iter()
+            .map(|(q, t, _)| (q, t))
+            .collect()
+    }
+
+    #[test]
+    fn test_reciprocal_best_hits() {
+        let a = vec![
+            hit("a1", "b1", 1e-30, 100.0),
+            hit("a1", "b2", 1e-10, 40.0),
+            hit("a2", "b2", 1e-20, 70.0),
+            // a3's best hit is b2, whose best hit is a2
+            hit("a3", "b2", 1e-5, 20.0),
+            hit("a3", "b3", 1e-3, 12.0),
+            hit("a4", "b4", 1e-8, 30.0),
+        ];
+        let b = vec![
+            hit("b4", "a4", 1e-8, 30.0),
+            hit("b1", "a1", 1e-29, 98.0),
+            hit("b2", "a3", 1e-6, 25.0),
+            hit("b2", "a2", 1e-19, 69.0),
+            hit("b3", "a1", 1e-2, 10.0),
+        ];
+        let found = reciprocal_best_hits(a, b, &RbhOptions::default()).unwrap();
+        let names = found
+            .iter()
+            .map(|(q, t, _)| (q.as_str(), t.as_str()))
+            .collect::<Vec<_>>();
+        // in the order of the first search
+        assert_eq!(names, vec![("a1", "b1"), ("a2", "b2"), ("a4", "b4")]);
+
+        let evidence = &found[1].2;
+        assert_eq!(evidence.a.query_name(), "a2");
+        assert_eq!(evidence.a.target_name(), "b2");
+        assert_eq!(evidence.b.query_name(), "b2");
+        assert_eq!(evidence.b.target_name(), "a2");
+        assert_eq!(evidence.b.evalue_opt(), Some(1e-19));
+    }
+
+    #[test]
+    fn test_ties() {
+        // the score breaks a tie on E-value
+        let a = vec![hit("a1", "b1", 1e-10, 40.0), hit("a1", "b2", 1e-10, 41.0)];
+        let b = vec![hit("b2", "a1", 1e-10, 41.0)];
+        assert_eq!(
+            pairs(a, b, &RbhOptions::default()),
+            vec![("a1".to_string(), "b2".to_string())]
+        );
+
+        // a tie on both leaves the query out, whichever order the hits are in
+        for a in [
+            vec![hit("a1", "b1", 1e-10, 40.0), hit("a1", "b2", 1e-10, 40.0)],
+            vec![hit("a1", "b2", 1e-10, 40.0), hit("a1", "b1", 1e-10, 40.0)],
+        ] {
+            let b = vec![hit("b1", "a1", 1e-10, 40.0), hit("b2", "a1", 1e-10, 40.0)];
+            assert!(pairs(a, b, &RbhOptions::default()).is_empty());
+        }
+
+        // a better hit after a tie settles it
+        let a = vec![
+            hit("a1", "b1", 1e-10, 40.0),
+            hit("a1", "b2", 1e-10, 40.0),
+            hit("a1", "b2", 1e-12, 45.0),
+        ];
+        let b = vec![hit("b2", "a1", 1e-12, 45.0)];
+        assert_eq!(pairs(a, b, &RbhOptions::default()).len(), 1);
+
+        // a tie in the other search leaves it out too
+        let a = vec![hit("a1", "b1", 1e-10, 40.0)];
+        let b = vec![hit("b1", "a1", 1e-10, 40.0), hit("b1", "a2", 1e-10, 40.0)];
+        assert!(pairs(a, b, &RbhOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn test_by_accession() {
+        // the names differ between the searches, the accessions do not
+        let a = || vec![hit("a1", "bb1", 1e-10, 40.0)];
+        let b = || vec![hit("b1x", "aa", 1e-10, 40.0)];
+        assert!(pairs(a(), b(), &RbhOptions::default()).is_empty());
+
+        let options = RbhOptions::default().by_accession(true);
+        assert_eq!(
+            pairs(a(), b(), &options),
+            vec![("PF2.1".to_string(), "PF3.1".to_string())]
+        );
+    }
+}
@@ -1,20 +1,42 @@
+#[cfg(all(feature = "std-fs", feature = "bgzf"))]
+use crate::bgzf::BgzfReader;
+#[cfg(feature = "std-fs")]
+use crate::follow::FollowIter;
 use crate::{
-    record::{Meta, Program, Record, Strand},
+    header::Header,
+    index::TbloutIndex,
+    progress::PROGRESS_INTERVAL,
+    record::{Meta, Program, Record, RecordKind, ScanOrientation, Strand},
+    table::TableFormat,
+    validate::{CoordinateError, ValidationIssue, Warning},
     DNARecord, Error, ErrorKind, ProteinRecord, Result,
 };
 
 use std::{
-    collections::VecDeque,
     fs::File,
-    io::{self, BufRead},
-    path::{Path, PathBuf},
+    io::{self, BufRead, Seek, SeekFrom},
+    path::PathBuf,
     str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    thread::{self, JoinHandle},
 };
+#[cfg(feature = "std-fs")]
+use std::{path::Path, time::Duration};
 
 /// A reader over the metadata of a HMM tblout file.
 pub struct MetaReader<R> {
     rdr: io::BufReader<R>,
     line: u64,
+    /// The number of lines which aren't comments, so hold records.
+    records: u64,
+    /// A flag which cancels the pass when set.
+    cancel: Option<Arc<AtomicBool>>,
+    /// The warnings found in the pass, handed on to the reader.
+    warnings: Vec<Warning>,
 }
 
 impl<R: io::Read> MetaReader<R> {
@@ -23,23 +45,41 @@ impl<R: io::Read> MetaReader<R> {
         MetaReader {
             rdr: io::BufReader::new(rdr),
             line: 0,
+            records: 0,
+            cancel: None,
+            warnings: Vec::new(),
         }
     }
-    /// Read the metadata from the input reader.
-    fn read_meta(&mut self) -> Result<Meta> {
+    /// Read the header and the metadata from the input reader.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn read_header_and_meta(&mut self) -> Result<(Header, Meta)> {
         // read the metadata into the meta struct
         // we skip the first three #'s that we come across
         // and the fourth should be where the metadata starts
+        let mut header = Header::default();
         let mut metadata = Meta::default();
 
         let mut line = String::new();
         let mut hash_counter = 0;
+        // the header is the comment lines before the first record
+        // or the separating `#` line.
+        let mut in_header = true;
+        let mut crlf = false;
         loop {
             line.clear();
             match self.rdr.read_line(&mut line) {
                 Ok(0) => break,
                 Ok(_) => {
                     self.line += 1;
+                    check_cancel(self.cancel.as_deref(), self.line, PROGRESS_INTERVAL)?;
+
+                    if !crlf && line.ends_with("\r\n") {
+                        crlf = true;
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(line = self.line, "the file has CRLF line endings");
+                        self.warnings
+                            .push(Warning::CrlfLineEndings { line: self.line });
+                    }
 
                     // increment the hash counter
                     if line.starts_with('#') {
@@ -47,24 +87,30 @@ impl<R: io::Read> MetaReader<R> {
                     }
 
                     if !line.starts_with('#') {
+                        in_header = false;
+                        self.records += 1;
                         continue;
                     }
 
+                    if in_header {
+                        if line.trim() == "#" || line.starts_with("# Program:") {
+                            in_header = false;
+                        } else {
+                            header.push_line(&line);
+                        }
+                    }
+
                     // once we hit the fourth hash we can start reading
                     if hash_counter >= 4 {
-                        // match on the starting substring of the line
-                        let mut split_line = line
-                            .split(':')
-                            .collect::<Vec<&str>>()
-                            .into_iter()
-                            .map(|e| e.trim())
-                            .collect::<VecDeque<&str>>();
-
-                        let first = split_line.pop_front().unwrap();
-                        let rest = split_line.into_iter().collect::<Vec<&str>>().join(" ");
+                        // match on the starting substring of the line,
+                        // the value may contain colons itself (e.g. the date)
+                        let (first, rest) = match line.split_once(':') {
+                            Some((first, rest)) => (first.trim(), rest.trim()),
+                            None => continue,
+                        };
 
                         match first {
-                            "# Program" => metadata.set_program(Program::from_str(&rest).unwrap()),
+                            "# Program" => metadata.set_program(Program::from_str(rest)?),
                             "# Version" => metadata.set_version(rest.to_string()),
                             "# Pipeline mode" => metadata.set_pipeline_mode(rest.to_string()),
                             "# Query file" => {
@@ -73,7 +119,15 @@ impl<R: io::Read> MetaReader<R> {
                             "# Target file" => {
                                 metadata.set_target_file(PathBuf::from(rest.to_string()))
                             }
-                            "# Option settings" => metadata.set_options(rest.to_string()),
+                            // HMMER follows each option with a space, which is kept
+                            // so the line can be written back as it was
+                            "# Option settings" => metadata.set_options(
+                                line.split_once(':')
+                                    .map(|(_, options)| options.trim_start())
+                                    .unwrap_or_default()
+                                    .trim_end_matches(['\n', '\r'])
+                                    .to_string(),
+                            ),
                             "# Current dir" => {
                                 metadata.set_current_dir(PathBuf::from(rest.to_string()))
                             }
@@ -87,11 +141,53 @@ impl<R: io::Read> MetaReader<R> {
             }
         }
 
-        Ok(metadata)
+        // nhmmscan from HMMER 3.2 names itself hmmscan in the metadata,
+        // but its columns are those of a DNA search
+        if metadata.program() == Program::Hmmscan && header.record_kind() == Some(RecordKind::Dna) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                "hmmscan metadata with DNA columns, reading the file as nhmmscan output"
+            );
+            metadata.set_program(Program::Nhmmscan);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            if metadata.is_empty() {
+                tracing::warn!("the file has no metadata");
+            } else if metadata.program() == Program::None {
+                tracing::warn!("the metadata does not name the program");
+            }
+            tracing::debug!(
+                lines = self.line,
+                program = %metadata.program(),
+                "read the header and metadata"
+            );
+        }
+
+        Ok((header, metadata))
+    }
+}
+
+/// Return an `ErrorKind::Cancelled` error if `flag` is set, checking
+/// it only every `interval` lines.
+// `is_multiple_of` needs Rust 1.87, newer than the crate otherwise needs
+#[allow(clippy::manual_is_multiple_of)]
+fn check_cancel(flag: Option<&AtomicBool>, line: u64, interval: u64) -> Result<()> {
+    match flag {
+        Some(flag) if line % interval == 0 && flag.load(Ordering::Relaxed) => {
+            Err(Error::new(ErrorKind::Cancelled))
+        }
+        _ => Ok(()),
     }
 }
 
 /// A reader over the records of a HMM tblout file.
+///
+/// A reader is `Send` when its input is, as a `File` is, so it can be
+/// moved to a thread which parses while others process the records.
+/// The owned iterators, such as `RecordsIntoIter`, are `Send` on the
+/// same terms.
 pub struct Reader<R> {
     /// The underlying reader.
     rdr: io::BufReader<R>,
@@ -99,24 +195,131 @@ pub struct Reader<R> {
     line: u64,
     /// The metadata from the first pass.
     meta: Meta,
+    /// The header from the first pass.
+    header: Header,
+    /// Whether each record keeps the line it was parsed from.
+    keep_raw_lines: bool,
+    /// Whether each record keeps its E-value tokens.
+    keep_raw_numerics: bool,
+    /// Whether the strand of a DNA record is taken from its coordinates
+    /// when the strand column disagrees with them.
+    trust_coordinates: bool,
+    /// Whether records which fail validation are errors.
+    strict: bool,
+    /// Whether records are validated, with their issues kept as
+    /// warnings unless the reader is strict.
+    validate_records: bool,
+    /// The warnings found so far.
+    warnings: Vec<Warning>,
+    /// Whether a record with tabs between its columns has been read.
+    tabs: bool,
+    /// Whether the input is BGZF compressed, so positions in it are
+    /// virtual offsets.
+    bgzf: bool,
+    /// The number of record lines found by the first pass, if there was
+    /// one, to size the vector `read_all` collects into.
+    records_hint: Option<u64>,
+    /// The record read by `peek_first` and not yet yielded, or `None`
+    /// within if the records had run out.
+    peeked: Option<Option<Record>>,
+    /// A flag which cancels reading when set.
+    cancel: Option<Arc<AtomicBool>>,
+    /// How often, in lines, the cancel flag is checked.
+    cancel_interval: u64,
+    /// Whether reading was cancelled, so no more records are read.
+    cancelled: bool,
+    /// The last record line read, with its line ending. The buffer is
+    /// reused for each line.
+    buf: String,
+    /// The number of records and comment lines read, reported when the
+    /// records run out.
+    #[cfg(feature = "tracing")]
+    counts: (u64, u64),
 }
 
 impl Reader<File> {
     /// Construct a new reader from a file path.
+    #[cfg(feature = "std-fs")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+    )]
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Reader<File>> {
         let mut metareader = MetaReader::new(File::open(path.as_ref())?);
-        let meta = metareader.read_meta()?;
+        let (header, meta) = metareader.read_header_and_meta()?;
 
-        Ok(Reader::new(File::open(path)?, meta))
+        let mut reader = Reader::new(File::open(path)?, meta).with_header(header);
+        reader.records_hint = Some(metareader.records);
+        reader.warnings = metareader.warnings;
+        Ok(reader)
+    }
+
+    /// Construct a new reader from a file path, which stops reading
+    /// when `cancel` is set, e.g. by another thread when the user gives
+    /// up waiting. The first pass over the file checks the flag every
+    /// `PROGRESS_INTERVAL` lines, and returns an `ErrorKind::Cancelled`
+    /// error if it is set. Reading the records then checks it as
+    /// `cancel_token` sets out.
+    #[cfg(feature = "std-fs")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+    )]
+    pub fn from_path_cancellable<P: AsRef<Path>>(
+        path: P,
+        cancel: Arc<AtomicBool>,
+    ) -> Result<Reader<File>> {
+        let mut metareader = MetaReader::new(File::open(path.as_ref())?);
+        metareader.cancel = Some(cancel.clone());
+        let (header, meta) = metareader.read_header_and_meta()?;
+
+        let mut reader = Reader::new(File::open(path)?, meta)
+            .with_header(header)
+            .cancel_token(cancel);
+        reader.records_hint = Some(metareader.records);
+        reader.warnings = metareader.warnings;
+        Ok(reader)
     }
 
     /// Construct a new reader from anything that implements `io::Read`
     /// and clone.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
     pub fn from_reader<R: io::Read + Clone>(rdr: R) -> Result<Reader<R>> {
         let mut metareader = MetaReader::new(rdr.clone());
-        let meta = metareader.read_meta()?;
+        let (header, meta) = metareader.read_header_and_meta()?;
 
-        Ok(Reader::new(rdr, meta))
+        let mut reader = Reader::new(rdr, meta).with_header(header);
+        reader.records_hint = Some(metareader.records);
+        reader.warnings = metareader.warnings;
+        Ok(reader)
+    }
+
+    /// Construct a new reader from the path of a BGZF compressed file,
+    /// as written by `bgzip`. Plain gzip files are rejected, as they
+    /// cannot be indexed.
+    #[cfg(all(feature = "std-fs", feature = "bgzf"))]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip_all, fields(path = %path.as_ref().display()))
+    )]
+    pub fn from_bgzf_path<P: AsRef<Path>>(path: P) -> Result<Reader<BgzfReader<File>>> {
+        let mut metareader = MetaReader::new(BgzfReader::new(File::open(path.as_ref())?)?);
+        let (header, meta) = metareader.read_header_and_meta()?;
+
+        let mut reader = Reader::new(BgzfReader::new(File::open(path)?)?, meta).with_header(header);
+        reader.bgzf = true;
+        reader.records_hint = Some(metareader.records);
+        reader.warnings = metareader.warnings;
+        Ok(reader)
+    }
+
+    /// Follow a tblout file which is still being written, e.g. by a
+    /// running `nhmmer` job. Records are yielded as complete lines are
+    /// appended, and `poll_interval` is how long to wait at the end of
+    /// the file before checking again.
+    #[cfg(feature = "std-fs")]
+    pub fn follow<P: AsRef<Path>>(path: P, poll_interval: Duration) -> Result<FollowIter> {
+        FollowIter::new(path, poll_interval)
     }
 }
 
@@ -126,6 +329,14 @@ pub enum RecordsIter<'a, R> {
     Dna(DNARecordsIter<'a, R>),
     /// An iterator over protein records.
     Protein(ProteinRecordsIter<'a, R>),
+    /// The program which produced the file is unknown, so the records
+    /// cannot be parsed. A single `ErrorKind::Meta` error is yielded.
+    Unknown {
+        /// The underlying reader.
+        rdr: &'a mut Reader<R>,
+        /// Whether the error has been yielded.
+        done: bool,
+    },
 }
 
 impl<'r, R: io::Read> Iterator for RecordsIter<'r, R> {
@@ -135,10 +346,31 @@ impl<'r, R: io::Read> Iterator for RecordsIter<'r, R> {
         match self {
             RecordsIter::Dna(e) => e.next().map(|rec| rec.map(Record::Dna)),
             RecordsIter::Protein(e) => e.next().map(|rec| rec.map(Record::Protein)),
+            RecordsIter::Unknown { rdr, done } => unknown_program(&rdr.meta, done),
         }
     }
 }
 
+/// The error for reading the records of a file from an unknown
+/// program, the first time it is asked for.
+fn unknown_program<T>(meta: &Meta, done: &mut bool) -> Option<Result<T>> {
+    if std::mem::replace(done, true) {
+        return None;
+    }
+    Some(Err(unknown_program_error(meta)))
+}
+
+/// The error for a file from an unknown program, saying whether the
+/// file had no metadata at all or metadata without the program.
+fn unknown_program_error(meta: &Meta) -> Error {
+    let msg = if meta.is_empty() {
+        "the file has no metadata, so the program which produced it is unknown"
+    } else {
+        "the program which produced the file is unknown"
+    };
+    Error::new(ErrorKind::Meta(msg.into()))
+}
+
 /// A borrowed iterator over the records of a refer file.
 pub struct DNARecordsIter<'r, R: 'r> {
     /// The underlying reader
@@ -149,14 +381,7 @@ impl<'r, R: io::Read> Iterator for DNARecordsIter<'r, R> {
     type Item = Result<DNARecord>;
 
     fn next(&mut self) -> Option<Result<DNARecord>> {
-        match self.rdr.read_dna_record() {
-            Ok(Some(r)) => {
-                self.rdr.line += 1;
-                Some(Ok(r))
-            }
-            Ok(None) => None,
-            Err(e) => Some(Err(e)),
-        }
+        self.rdr.read_dna_record().transpose()
     }
 }
 
@@ -170,25 +395,16 @@ impl<'r, R: io::Read> Iterator for ProteinRecordsIter<'r, R> {
     type Item = Result<ProteinRecord>;
 
     fn next(&mut self) -> Option<Result<ProteinRecord>> {
-        match self.rdr.read_protein_record() {
-            Ok(Some(r)) => {
-                self.rdr.line += 1;
-                Some(Ok(r))
-            }
-            Ok(None) => None,
-            Err(e) => Some(Err(e)),
-        }
+        self.rdr.read_protein_record().transpose()
     }
 }
 
 impl<'r, R: io::Read> RecordsIter<'r, R> {
     fn new(rdr: &'r mut Reader<R>, program: Program) -> RecordsIter<'r, R> {
-        match program {
-            Program::Nhmmer | Program::Nhmmscan => RecordsIter::Dna(DNARecordsIter { rdr }),
-            Program::Jackhmmer | Program::Hmmscan | Program::Hmmsearch | Program::Phmmer => {
-                RecordsIter::Protein(ProteinRecordsIter { rdr })
-            }
-            Program::None => unreachable!(),
+        match program.record_kind() {
+            Some(RecordKind::Dna) => RecordsIter::Dna(DNARecordsIter { rdr }),
+            Some(RecordKind::Protein) => RecordsIter::Protein(ProteinRecordsIter { rdr }),
+            None => RecordsIter::Unknown { rdr, done: false },
         }
     }
     /// Return a reference to the underlying reader.
@@ -196,6 +412,7 @@ impl<'r, R: io::Read> RecordsIter<'r, R> {
         match self {
             RecordsIter::Dna(r) => r.rdr,
             RecordsIter::Protein(r) => r.rdr,
+            RecordsIter::Unknown { rdr, .. } => rdr,
         }
     }
 
@@ -204,6 +421,7 @@ impl<'r, R: io::Read> RecordsIter<'r, R> {
         match self {
             RecordsIter::Dna(r) => r.rdr,
             RecordsIter::Protein(r) => r.rdr,
+            RecordsIter::Unknown { rdr, .. } => rdr,
         }
     }
 }
@@ -214,6 +432,14 @@ pub enum RecordsIntoIter<R> {
     Dna(DNARecordsIntoIter<R>),
     /// An iterator over protein records.
     Protein(ProteinRecordsIntoIter<R>),
+    /// The program which produced the file is unknown, so the records
+    /// cannot be parsed. A single `ErrorKind::Meta` error is yielded.
+    Unknown {
+        /// The underlying reader.
+        rdr: Reader<R>,
+        /// Whether the error has been yielded.
+        done: bool,
+    },
 }
 
 impl<R: io::Read> Iterator for RecordsIntoIter<R> {
@@ -223,6 +449,7 @@ impl<R: io::Read> Iterator for RecordsIntoIter<R> {
         match self {
             RecordsIntoIter::Dna(e) => e.next().map(|rec| rec.map(Record::Dna)),
             RecordsIntoIter::Protein(e) => e.next().map(|rec| rec.map(Record::Protein)),
+            RecordsIntoIter::Unknown { rdr, done } => unknown_program(&rdr.meta, done),
         }
     }
 }
@@ -234,12 +461,10 @@ pub struct DNARecordsIntoIter<R> {
 
 impl<R: io::Read> RecordsIntoIter<R> {
     fn new(rdr: Reader<R>, program: Program) -> RecordsIntoIter<R> {
-        match program {
-            Program::Nhmmer | Program::Nhmmscan => RecordsIntoIter::Dna(DNARecordsIntoIter { rdr }),
-            Program::Jackhmmer | Program::Hmmscan | Program::Hmmsearch | Program::Phmmer => {
-                RecordsIntoIter::Protein(ProteinRecordsIntoIter { rdr })
-            }
-            Program::None => unreachable!(),
+        match program.record_kind() {
+            Some(RecordKind::Dna) => RecordsIntoIter::Dna(DNARecordsIntoIter { rdr }),
+            Some(RecordKind::Protein) => RecordsIntoIter::Protein(ProteinRecordsIntoIter { rdr }),
+            None => RecordsIntoIter::Unknown { rdr, done: false },
         }
     }
     /// Return a reference to the underlying reader.
@@ -247,6 +472,7 @@ impl<R: io::Read> RecordsIntoIter<R> {
         match self {
             RecordsIntoIter::Dna(r) => &r.rdr,
             RecordsIntoIter::Protein(r) => &r.rdr,
+            RecordsIntoIter::Unknown { rdr, .. } => rdr,
         }
     }
 
@@ -255,6 +481,7 @@ impl<R: io::Read> RecordsIntoIter<R> {
         match self {
             RecordsIntoIter::Dna(r) => &mut r.rdr,
             RecordsIntoIter::Protein(r) => &mut r.rdr,
+            RecordsIntoIter::Unknown { rdr, .. } => rdr,
         }
     }
 
@@ -263,22 +490,22 @@ impl<R: io::Read> RecordsIntoIter<R> {
         match self {
             RecordsIntoIter::Dna(r) => r.rdr,
             RecordsIntoIter::Protein(r) => r.rdr,
+            RecordsIntoIter::Unknown { rdr, .. } => rdr,
         }
     }
+
+    /// Drop this iterator and return the parts of the underlying
+    /// reader. See `Reader::into_parts`.
+    pub fn into_parts(self) -> (R, Header, Meta, Vec<u8>) {
+        self.into_reader().into_parts()
+    }
 }
 
 impl<R: io::Read> Iterator for DNARecordsIntoIter<R> {
     type Item = Result<DNARecord>;
 
     fn next(&mut self) -> Option<Result<DNARecord>> {
-        match self.rdr.read_dna_record() {
-            Ok(Some(r)) => {
-                self.rdr.line += 1;
-                Some(Ok(r))
-            }
-            Ok(None) => None,
-            Err(e) => Some(Err(e)),
-        }
+        self.rdr.read_dna_record().transpose()
     }
 }
 
@@ -292,10 +519,32 @@ impl<R: io::Read> Iterator for ProteinRecordsIntoIter<R> {
     type Item = Result<ProteinRecord>;
 
     fn next(&mut self) -> Option<Result<ProteinRecord>> {
-        match self.rdr.read_protein_record() {
-            Ok(Some(r)) => {
-                self.rdr.line += 1;
-                Some(Ok(r))
+        self.rdr.read_protein_record().transpose()
+    }
+}
+
+/// A borrowed iterator over the records of a file with their lines,
+/// made by `Reader::records_with_lines`.
+pub struct RecordsWithLines<'r, R: 'r> {
+    /// The underlying reader.
+    rdr: &'r mut Reader<R>,
+    /// Whether the error for an unknown program has been yielded.
+    done: bool,
+}
+
+impl<'r, R: io::Read> Iterator for RecordsWithLines<'r, R> {
+    type Item = Result<(Record, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rdr.meta.program().record_kind().is_none() {
+            return unknown_program(&self.rdr.meta, &mut self.done);
+        }
+        match self.rdr.read_record() {
+            // the buffer holds the line of the record, even one peeked
+            // at, as nothing has been read since
+            Ok(Some(record)) => {
+                let line = self.rdr.buf.trim_end_matches(['\n', '\r']);
+                Some(Ok((record, line.to_string())))
             }
             Ok(None) => None,
             Err(e) => Some(Err(e)),
@@ -310,144 +559,790 @@ impl<R: io::Read> Reader<R> {
             rdr: io::BufReader::new(rdr),
             line: 0,
             meta,
+            header: Header::default(),
+            keep_raw_lines: false,
+            keep_raw_numerics: false,
+            trust_coordinates: false,
+            strict: false,
+            validate_records: false,
+            warnings: Vec::new(),
+            tabs: false,
+            bgzf: false,
+            records_hint: None,
+            peeked: None,
+            cancel: None,
+            cancel_interval: PROGRESS_INTERVAL,
+            cancelled: false,
+            buf: String::new(),
+            #[cfg(feature = "tracing")]
+            counts: (0, 0),
         }
     }
 
+    /// Construct a new reader for the records of a program, for a file
+    /// with no header or metadata, such as an extract of the records
+    /// alone. The header is left empty, so `Writer::write_all`
+    /// generates one fitted to the records.
+    pub fn with_program(rdr: R, program: Program) -> Reader<R> {
+        Reader::new(rdr, Meta::for_program(program))
+    }
+
+    /// Set the header of the file, which is otherwise read in the
+    /// first pass by the `from_*` constructors.
+    pub fn with_header(mut self, header: Header) -> Reader<R> {
+        self.header = header;
+        self
+    }
+
+    /// Set whether each record keeps its E-value tokens as they were
+    /// written, e.g. to tell a true `0` from an E-value too small for
+    /// an `f64` (see `DNARecord::e_value_raw`). Writers write the
+    /// tokens back in place of the values, unless the values have been
+    /// changed. Off by default, when records carry nothing for them.
+    pub fn keep_raw_numerics(mut self, yes: bool) -> Reader<R> {
+        self.keep_raw_numerics = yes;
+        self
+    }
+
+    /// Set whether a DNA record whose strand column disagrees with the
+    /// order of its alignment coordinates, as in a file with shifted
+    /// columns, takes its strand from the coordinates (see
+    /// `DNARecord::coordinate_strand`). Either way, the disagreement is
+    /// kept as a `Warning::StrandMismatch` naming the line, and with the
+    /// `tracing` feature is logged too. Off by default, so the strand
+    /// column is kept, and a strict reader makes the disagreement an
+    /// error.
+    pub fn trust_coordinates(mut self, yes: bool) -> Reader<R> {
+        self.trust_coordinates = yes;
+        self
+    }
+
+    /// Whether any record read so far had tabs between its columns.
+    /// HMMER only ever separates them with spaces, so the file has been
+    /// rewritten by another tool. Such records are read as usual, and
+    /// the first is kept as a `Warning::TabDelimited` naming its line,
+    /// and with the `tracing` feature logged too.
+    pub fn tab_delimited(&self) -> bool {
+        self.tabs
+    }
+
+    /// Set whether records which are inconsistent with themselves (see
+    /// `Record::validate`) are errors, of kind `ErrorKind::Validation`.
+    /// The coordinates of DNA records are checked first, with
+    /// `DNARecord::check_coordinates`, giving errors of kind
+    /// `ErrorKind::Coordinates`. Both name the line of the record.
+    ///
+    /// Off by default, so such records are read as they are and callers
+    /// can collect their issues with `validate`. For nhmmscan, whose
+    /// length column is the model length, coordinates running past it
+    /// are not errors.
+    pub fn strict(mut self, yes: bool) -> Reader<R> {
+        self.strict = yes;
+        self
+    }
+
+    /// Set whether each record is validated as it is read (see
+    /// `Record::validate`), with each issue kept as a
+    /// `Warning::Validation` naming the line of the record, e.g. "line
+    /// 1234: envelope 1..5000 is outside the sequence of length 4800".
+    /// The record is read as it is. A strict reader validates records
+    /// whether this is set or not, and its issues are errors instead.
+    ///
+    /// Off by default. For nhmmscan, envelopes running past the length
+    /// column, which is the model length, are not issues.
+    pub fn validate_records(mut self, yes: bool) -> Reader<R> {
+        self.validate_records = yes;
+        self
+    }
+
+    /// The warnings found so far, in the order of their lines. Those
+    /// of the first pass of the `from_*` constructors come first.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Take the warnings found so far, leaving none, e.g. to report
+    /// them as reading goes on without holding them all.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Set whether each record keeps a copy of the line it was parsed
+    /// from, so a `Writer` with `round_trip` set can write it back
+    /// exactly. Off by default, as it doubles the memory each record
+    /// takes.
+    pub fn keep_raw_lines(mut self, yes: bool) -> Reader<R> {
+        self.keep_raw_lines = yes;
+        self
+    }
+
+    /// Set a flag which cancels reading when set, e.g. by another
+    /// thread when the user gives up waiting. The flag is checked every
+    /// `cancel_interval` lines, and once it is found set the next read
+    /// returns an `ErrorKind::Cancelled` error, after which there are
+    /// no more records. To cancel the first pass of a file too, build
+    /// the reader with `from_path_cancellable`.
+    pub fn cancel_token(mut self, cancel: Arc<AtomicBool>) -> Reader<R> {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Set how often, in lines, the flag of `cancel_token` is checked,
+    /// `PROGRESS_INTERVAL` by default. Smaller intervals stop sooner,
+    /// and larger ones check less.
+    ///
+    /// # Panics
+    ///
+    /// If `lines` is zero.
+    pub fn cancel_interval(mut self, lines: u64) -> Reader<R> {
+        assert!(lines > 0, "cancel interval must be positive");
+        self.cancel_interval = lines;
+        self
+    }
+
     /// Return the metadata from the first pass.
     pub fn meta(&self) -> &Meta {
         &self.meta
     }
 
+    /// The number of lines read so far, so after an error the number
+    /// of the line it was in. Seeking makes it meaningless.
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    /// Which of the query and target of each record is the profile,
+    /// from the program in the metadata.
+    pub fn orientation(&self) -> ScanOrientation {
+        self.meta.program().orientation()
+    }
+
+    /// Return the header from the first pass.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Return the metadata from the first pass, or `None` if the file
+    /// had none, e.g. an extract of the records alone. `meta` gives an
+    /// empty `Meta` for such files.
+    pub fn meta_opt(&self) -> Option<&Meta> {
+        match self.meta.is_empty() {
+            true => None,
+            false => Some(&self.meta),
+        }
+    }
+
+    /// Return the header from the first pass, or `None` if the file had
+    /// none. `header` gives an empty `Header` for such files.
+    pub fn header_opt(&self) -> Option<&Header> {
+        match self.header.is_empty() {
+            true => None,
+            false => Some(&self.header),
+        }
+    }
+
+    /// The layout of the table, from the program in the metadata and
+    /// the column names in the header. See `TableFormat::detect`.
+    pub fn format(&self) -> TableFormat {
+        TableFormat::detect(self.meta.program(), &self.header)
+    }
+
+    /// Drop the reader and return the underlying reader, the header and
+    /// the metadata, e.g. to seek in a file, or hand a section after the
+    /// records to another parser.
+    ///
+    /// The underlying reader has been read ahead of the records, so the
+    /// bytes read into the buffer but not yet parsed are returned too.
+    /// The unread input is those bytes, then the rest of the underlying
+    /// reader, so for a file the position of the next line is its
+    /// position less their length. A record read by `peek_first` and
+    /// not yet yielded has been parsed, so is not among them.
+    pub fn into_parts(self) -> (R, Header, Meta, Vec<u8>) {
+        let buffered = self.rdr.buffer().to_vec();
+        (self.rdr.into_inner(), self.header, self.meta, buffered)
+    }
+
+    /// Read the next record without consuming it, so before any
+    /// records are read, the first. The record is kept and yielded
+    /// first by the next iterator over the records, so a file can be
+    /// checked, e.g. that its query is the one expected, before all of
+    /// it is read. Returns `None` if there are no records left.
+    ///
+    /// The record is read with the reader's options, as the iterators
+    /// would read it, so this must be called after they are set. An
+    /// error is returned once, as the iterators would yield it, and
+    /// reading goes on from the next line. Seeking drops the record.
+    pub fn peek_first(&mut self) -> Result<Option<&Record>> {
+        if self.peeked.is_none() {
+            let record = self.read_record()?;
+            self.peeked = Some(record);
+        }
+        Ok(self.peeked.as_ref().and_then(Option::as_ref))
+    }
+
+    /// Read the rest of the records, carrying on past records which
+    /// can't be read. Returns the records read, and the errors with the
+    /// numbers of the lines they were found on.
+    ///
+    /// Reading stops at an I/O error, or if the program which produced
+    /// the file is unknown, as no more records could be read. For
+    /// readers from `from_path`, `from_reader` and `from_bgzf_path`,
+    /// the first pass counts the record lines, so the vector of records
+    /// is allocated once.
+    pub fn read_all(&mut self) -> (Vec<Record>, Vec<(u64, Error)>) {
+        let mut records = self.presized();
+        let mut errors = Vec::new();
+        loop {
+            match self.read_record() {
+                Ok(Some(record)) => records.push(record),
+                Ok(None) => break,
+                Err(e) => {
+                    let fatal = matches!(e.kind(), ErrorKind::Io(_) | ErrorKind::Meta(_));
+                    errors.push((self.line, e));
+                    if fatal {
+                        break;
+                    }
+                }
+            }
+        }
+        (records, errors)
+    }
+
+    /// Read the rest of the records, stopping at the first error. The
+    /// same as collecting `records` into a `Result<Vec<Record>>`, but
+    /// allocating the vector once as `read_all` does.
+    pub fn read_all_strict(&mut self) -> Result<Vec<Record>> {
+        let mut records = self.presized();
+        while let Some(record) = self.read_record()? {
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    /// An empty vector with room for the records the first pass found.
+    fn presized(&self) -> Vec<Record> {
+        Vec::with_capacity(self.records_hint.unwrap_or(0) as usize)
+    }
+
     /// A borrowed iterator over the records of a refer file.
-    pub fn records(&mut self) -> RecordsIter<R> {
+    pub fn records(&mut self) -> RecordsIter<'_, R> {
         RecordsIter::new(self, self.meta.program())
     }
 
+    /// A borrowed iterator over the records of a file, each with the
+    /// line it was parsed from, unchanged but for its line ending, e.g.
+    /// to pass lines through a filter untouched. Comment lines are
+    /// skipped, as they hold no record.
+    ///
+    /// The lines are those the records were parsed from, not read
+    /// again, so records are read with the reader's options as usual
+    /// and need not keep their lines (see `keep_raw_lines`).
+    pub fn records_with_lines(&mut self) -> RecordsWithLines<'_, R> {
+        RecordsWithLines {
+            rdr: self,
+            done: false,
+        }
+    }
+
     /// An owned iterator over the records of a refer file.
     pub fn into_records(self) -> RecordsIntoIter<R> {
         let program = self.meta.program();
         RecordsIntoIter::new(self, program)
     }
 
+    /// A borrowed iterator over the records of an `nhmmer` or `nhmmscan`
+    /// file, yielding `DNARecord`s directly. An `ErrorKind::Meta` error
+    /// is returned if the file was written by another program.
+    pub fn dna_records(&mut self) -> Result<DNARecordsIter<'_, R>> {
+        self.check_kind(RecordKind::Dna)?;
+        Ok(DNARecordsIter { rdr: self })
+    }
+
+    /// A borrowed iterator over the records of a protein search file,
+    /// yielding `ProteinRecord`s directly. An `ErrorKind::Meta` error
+    /// is returned if the file was written by another program.
+    pub fn protein_records(&mut self) -> Result<ProteinRecordsIter<'_, R>> {
+        self.check_kind(RecordKind::Protein)?;
+        Ok(ProteinRecordsIter { rdr: self })
+    }
+
+    /// An owned iterator over the records of an `nhmmer` or `nhmmscan`
+    /// file. See `dna_records`.
+    pub fn into_dna_records(self) -> Result<DNARecordsIntoIter<R>> {
+        self.check_kind(RecordKind::Dna)?;
+        Ok(DNARecordsIntoIter { rdr: self })
+    }
+
+    /// An owned iterator over the records of a protein search file.
+    /// See `protein_records`.
+    pub fn into_protein_records(self) -> Result<ProteinRecordsIntoIter<R>> {
+        self.check_kind(RecordKind::Protein)?;
+        Ok(ProteinRecordsIntoIter { rdr: self })
+    }
+
+    /// Check the file holds records of the kind asked for.
+    fn check_kind(&self, kind: RecordKind) -> Result<()> {
+        let found = self
+            .meta
+            .program()
+            .record_kind()
+            .ok_or_else(|| unknown_program_error(&self.meta))?;
+        if found != kind {
+            let kind = match kind {
+                RecordKind::Dna => "DNA",
+                RecordKind::Protein => "protein",
+            };
+            return Err(Error::new(ErrorKind::Meta(format!(
+                "cannot read {} records from {} output",
+                kind,
+                self.meta.program()
+            ))));
+        }
+        Ok(())
+    }
+
+    /// Parse the records on a background thread, sending them over a
+    /// bounded channel holding at most `capacity` records so that I/O
+    /// and downstream work can overlap.
+    ///
+    /// The join handle returns the metadata once every record has been
+    /// sent. If the receiver is dropped early, the worker stops at the
+    /// next record and the join handle returns an `ErrorKind::Cancelled`
+    /// error instead.
+    pub fn spawn_records(
+        self,
+        capacity: usize,
+    ) -> (JoinHandle<Result<Meta>>, Receiver<Result<Record>>)
+    where
+        R: Send + 'static,
+    {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        let handle = thread::spawn(move || {
+            let mut records = self.into_records();
+            for record in records.by_ref() {
+                if tx.send(record).is_err() {
+                    return Err(Error::new(ErrorKind::Cancelled));
+                }
+            }
+            Ok(records.into_reader().meta)
+        });
+
+        (handle, rx)
+    }
+
+    /// Read a single record of the kind produced by the program.
+    fn read_record(&mut self) -> Result<Option<Record>> {
+        match self.meta.program().record_kind() {
+            Some(RecordKind::Dna) => Ok(self.read_dna_record()?.map(Record::Dna)),
+            Some(RecordKind::Protein) => Ok(self.read_protein_record()?.map(Record::Protein)),
+            None => Err(Error::new(ErrorKind::Meta(
+                "the program which produced the file is unknown".into(),
+            ))),
+        }
+    }
+
     /// Read a single record from an input reader.
     fn read_dna_record(&mut self) -> Result<Option<DNARecord>> {
-        // for this function, we read a single line and parse
-        // on whitespace, returning a record. We skip lines
-        // starting with a comment character '#'.
-        let mut line = String::new();
-        loop {
-            line.clear();
-            match self.rdr.read_line(&mut line) {
-                Ok(0) => return Ok(None),
-                Ok(_) => {
-                    self.line += 1;
-                    if line.starts_with('#') {
-                        continue;
-                    }
-                    let l_vec = line.split_whitespace().collect::<Vec<&str>>();
-
-                    let target_name = l_vec[0].to_string();
-                    let target_accession = l_vec[1].to_string();
-                    let query_name = l_vec[2].to_string();
-                    let query_accession = l_vec[3].to_string();
-                    let hmm_from = l_vec[4].parse::<i32>()?;
-                    let hmm_to = l_vec[5].parse::<i32>()?;
-                    let ali_from = l_vec[6].parse::<i32>()?;
-                    let ali_to = l_vec[7].parse::<i32>()?;
-                    let env_from = l_vec[8].parse::<i32>()?;
-                    let env_to = l_vec[9].parse::<i32>()?;
-                    let sq_len = l_vec[10].parse::<i32>()?;
-                    let strand = l_vec[11].parse::<Strand>()?;
-                    let e_value = l_vec[12].parse::<f32>()?;
-                    let score = l_vec[13].parse::<f32>()?;
-                    let bias = l_vec[14].parse::<f32>()?;
-                    // note we omit description column
-
-                    let record = DNARecord::new(
-                        target_name,
-                        target_accession,
-                        query_name,
-                        query_accession,
-                        hmm_from,
-                        hmm_to,
-                        ali_from,
-                        ali_to,
-                        env_from,
-                        env_to,
-                        sq_len,
-                        strand,
-                        e_value,
-                        score,
-                        bias,
-                    );
+        match self.peeked.take() {
+            Some(Some(Record::Dna(record))) => return Ok(Some(record)),
+            Some(None) => return Ok(None),
+            _ => (),
+        }
+        let mut line = std::mem::take(&mut self.buf);
+        let record = self.read_dna_record_into(&mut line);
+        self.buf = line;
+        record
+    }
 
-                    return Ok(Some(record));
+    /// Read a single DNA record, with its line read into `line`.
+    fn read_dna_record_into(&mut self, line: &mut String) -> Result<Option<DNARecord>> {
+        if !self.read_record_line(line)? {
+            return Ok(None);
+        }
+        self.check_tabs(line, 15);
+        let mut record = parse_dna_line(line)?;
+        if self.keep_raw_lines {
+            record = record.with_raw_line(line);
+        }
+        if self.keep_raw_numerics {
+            let (fields, _) = split_fields(line, 13);
+            record = record.with_raw_e_value(fields[12]);
+        }
+        if let Some(strand) = record.coordinate_strand().filter(|&s| s != record.strand()) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                line = self.line,
+                column = %record.strand(),
+                coordinates = %strand,
+                trusted = if self.trust_coordinates { "coordinates" } else { "column" },
+                "the strand disagrees with the coordinates"
+            );
+            self.warnings.push(Warning::StrandMismatch {
+                line: self.line,
+                column: record.strand(),
+                coordinates: strand,
+            });
+            if self.trust_coordinates {
+                // which drops the line, as the record has changed
+                record.set_strand(strand);
+            }
+        }
+        if self.strict {
+            let nhmmscan = self.meta.program() == Program::Nhmmscan;
+            match record.check_coordinates() {
+                Err(CoordinateError::AlignmentOutsideSequence { .. }) if nhmmscan => (),
+                Err(error) => {
+                    let line = self.line;
+                    return Err(Error::new(ErrorKind::Coordinates { line, error }));
                 }
-                Err(e) => return Err(Error::new(ErrorKind::Io(e))),
+                Ok(()) => (),
             }
         }
+        if self.strict || self.validate_records {
+            let nhmmscan = self.meta.program() == Program::Nhmmscan;
+            let issues = record
+                .validate()
+                .into_iter()
+                .filter(|issue| {
+                    !(nhmmscan && matches!(issue, ValidationIssue::EnvelopeOutsideSequence { .. }))
+                })
+                .collect();
+            self.handle_issues(issues)?;
+        }
+        Ok(Some(record))
     }
 
     /// Read a single record from an input reader as a protein record.
     fn read_protein_record(&mut self) -> Result<Option<ProteinRecord>> {
-        // for this function, we read a single line and parse
-        // on whitespace, returning a record. We skip lines
-        // starting with a comment character '#'.
-        let mut line = String::new();
+        match self.peeked.take() {
+            Some(Some(Record::Protein(record))) => return Ok(Some(record)),
+            Some(None) => return Ok(None),
+            _ => (),
+        }
+        let mut line = std::mem::take(&mut self.buf);
+        let record = self.read_protein_record_into(&mut line);
+        self.buf = line;
+        record
+    }
+
+    /// Read a single protein record, with its line read into `line`.
+    fn read_protein_record_into(&mut self, line: &mut String) -> Result<Option<ProteinRecord>> {
+        if !self.read_record_line(line)? {
+            return Ok(None);
+        }
+        self.check_tabs(line, 18);
+        let mut record = parse_protein_line(line)?;
+        if self.keep_raw_numerics {
+            let (fields, _) = split_fields(line, 8);
+            record = record.with_raw_e_values(fields[4], fields[7]);
+        }
+        if self.strict || self.validate_records {
+            self.handle_issues(record.validate())?;
+        }
+        if self.keep_raw_lines {
+            return Ok(Some(record.with_raw_line(line)));
+        }
+        Ok(Some(record))
+    }
+
+    /// Raise the validation issues of the record just read as an error
+    /// if the reader is strict, or keep them as warnings.
+    fn handle_issues(&mut self, issues: Vec<ValidationIssue>) -> Result<()> {
+        if self.strict {
+            return check_issues(self.line, issues);
+        }
+        for issue in issues {
+            let warning = Warning::Validation {
+                line: self.line,
+                issue,
+            };
+            #[cfg(feature = "tracing")]
+            tracing::warn!(line = self.line, "{}", warning);
+            self.warnings.push(warning);
+        }
+        Ok(())
+    }
+
+    /// Note whether a record line of `n` columns and a description has
+    /// tabs between its columns, keeping a warning for the first such
+    /// line.
+    /// Tabs within the description don't count.
+    fn check_tabs(&mut self, line: &str, n: usize) {
+        if self.tabs || !line.contains('\t') {
+            return;
+        }
+        let (_, rest) = split_fields(line, n);
+        let columns = &line[..line.len() - rest.len()];
+        if columns.contains('\t') || rest.starts_with('\t') {
+            self.tabs = true;
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                line = self.line,
+                "the file has tabs between its columns, so was not written by HMMER"
+            );
+            self.warnings
+                .push(Warning::TabDelimited { line: self.line });
+        }
+    }
+
+    /// Read the next line holding a record into `line`, skipping lines
+    /// starting with the comment character `#`. Returns false at the
+    /// end of the input.
+    fn read_record_line(&mut self, line: &mut String) -> Result<bool> {
+        if self.cancelled {
+            return Ok(false);
+        }
         loop {
             line.clear();
-            match self.rdr.read_line(&mut line) {
-                Ok(0) => return Ok(None),
+            match self.rdr.read_line(line) {
+                Ok(0) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        records = self.counts.0,
+                        skipped = self.counts.1,
+                        lines = self.line,
+                        "finished reading records"
+                    );
+                    return Ok(false);
+                }
                 Ok(_) => {
                     self.line += 1;
+                    if let Err(e) =
+                        check_cancel(self.cancel.as_deref(), self.line, self.cancel_interval)
+                    {
+                        self.cancelled = true;
+                        return Err(e);
+                    }
                     if line.starts_with('#') {
+                        #[cfg(feature = "tracing")]
+                        {
+                            self.counts.1 += 1;
+                        }
                         continue;
                     }
-                    let l_vec = line.split_whitespace().collect::<Vec<&str>>();
-                    let target_name = l_vec[0].to_string();
-                    let target_accession = l_vec[1].to_string();
-                    let query_name = l_vec[2].to_string();
-                    let query_accession = l_vec[3].to_string();
-                    let e_value_full = l_vec[4].parse::<f32>()?;
-                    let score_full = l_vec[5].parse::<f32>()?;
-                    let bias_full = l_vec[6].parse::<f32>()?;
-                    let e_value_best = l_vec[7].parse::<f32>()?;
-                    let score_best = l_vec[8].parse::<f32>()?;
-                    let bias_best = l_vec[9].parse::<f32>()?;
-                    let exp = l_vec[10].parse::<f32>()?;
-                    let reg = l_vec[11].parse::<i32>()?;
-                    let clu = l_vec[12].parse::<i32>()?;
-                    let ov = l_vec[13].parse::<i32>()?;
-                    let env = l_vec[14].parse::<i32>()?;
-                    let dom = l_vec[15].parse::<i32>()?;
-                    let rep = l_vec[16].parse::<i32>()?;
-                    let inc = l_vec[17].parse::<i32>()?;
-
-                    let record = ProteinRecord::new(
-                        target_name,
-                        target_accession,
-                        query_name,
-                        query_accession,
-                        e_value_full,
-                        score_full,
-                        bias_full,
-                        e_value_best,
-                        score_best,
-                        bias_best,
-                        exp,
-                        reg,
-                        clu,
-                        ov,
-                        env,
-                        dom,
-                        rep,
-                        inc,
-                    );
-
-                    return Ok(Some(record));
+                    #[cfg(feature = "tracing")]
+                    {
+                        self.counts.0 += 1;
+                        // once every so often, so large files don't
+                        // give an event for every record
+                        #[allow(clippy::manual_is_multiple_of)]
+                        if self.counts.0 % PROGRESS_INTERVAL == 0 {
+                            tracing::debug!(
+                                records = self.counts.0,
+                                line = self.line,
+                                "reading records"
+                            );
+                        }
+                    }
+                    return Ok(true);
                 }
                 Err(e) => return Err(Error::new(ErrorKind::Io(e))),
             }
         }
     }
 }
+
+/// An error for a record on `line` with validation issues, if it has
+/// any.
+fn check_issues(line: u64, issues: Vec<ValidationIssue>) -> Result<()> {
+    match issues.is_empty() {
+        true => Ok(()),
+        false => Err(Error::new(ErrorKind::Validation { line, issues })),
+    }
+}
+
+/// Split the first `n` whitespace separated fields from a line, and
+/// return them with the rest of the line after the last of them.
+fn split_fields(line: &str, n: usize) -> (Vec<&str>, &str) {
+    let mut fields = Vec::with_capacity(n);
+    let mut rest = line;
+    while fields.len() < n {
+        let trimmed = rest.trim_start();
+        let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+        if end == 0 {
+            break;
+        }
+        fields.push(&trimmed[..end]);
+        rest = &trimmed[end..];
+    }
+    (fields, rest)
+}
+
+/// The description ending a record line, exactly as written. HMMER
+/// separates it from the last column with `sep` spaces, so any more
+/// spaces belong to the description. A file rewritten with tabs
+/// between the columns has a single tab there instead. A missing
+/// description is `-`.
+fn description(rest: &str, sep: usize) -> String {
+    let rest = rest.trim_end_matches(['\n', '\r']);
+    let description = match rest.strip_prefix('\t') {
+        Some(description) => description,
+        None => {
+            let n = rest.len() - rest.trim_start_matches(' ').len();
+            &rest[n.min(sep)..]
+        }
+    };
+    if description.trim().is_empty() {
+        "-".to_string()
+    } else {
+        description.to_string()
+    }
+}
+
+/// Parse a single, non-comment line of DNA search output.
+pub(crate) fn parse_dna_line(line: &str) -> Result<DNARecord> {
+    let (l_vec, rest) = split_fields(line, 15);
+    if l_vec.len() < 15 {
+        return Err(Error::new(ErrorKind::ReadRecord(format!(
+            "expected 15 columns, found {}",
+            l_vec.len()
+        ))));
+    }
+
+    let target_name = l_vec[0].to_string();
+    let target_accession = l_vec[1].to_string();
+    let query_name = l_vec[2].to_string();
+    let query_accession = l_vec[3].to_string();
+    let hmm_from = l_vec[4].parse::<i32>()?;
+    let hmm_to = l_vec[5].parse::<i32>()?;
+    let ali_from = l_vec[6].parse::<i64>()?;
+    let ali_to = l_vec[7].parse::<i64>()?;
+    let env_from = l_vec[8].parse::<i64>()?;
+    let env_to = l_vec[9].parse::<i64>()?;
+    let sq_len = l_vec[10].parse::<i64>()?;
+    let strand = l_vec[11].parse::<Strand>()?;
+    let e_value = l_vec[12].parse::<f64>()?;
+    let score = l_vec[13].parse::<f32>()?;
+    let bias = l_vec[14].parse::<f32>()?;
+    // two spaces separate the description in DNA searches
+    let description = description(rest, 2);
+
+    let mut record = DNARecord::new(
+        target_name,
+        target_accession,
+        query_name,
+        query_accession,
+        hmm_from,
+        hmm_to,
+        ali_from,
+        ali_to,
+        env_from,
+        env_to,
+        sq_len,
+        strand,
+        e_value,
+        score,
+        bias,
+    );
+    record.set_description(description);
+    Ok(record)
+}
+
+/// Parse a single, non-comment line of protein search output.
+pub(crate) fn parse_protein_line(line: &str) -> Result<ProteinRecord> {
+    let (l_vec, rest) = split_fields(line, 18);
+    if l_vec.len() < 18 {
+        return Err(Error::new(ErrorKind::ReadRecord(format!(
+            "expected 18 columns, found {}",
+            l_vec.len()
+        ))));
+    }
+
+    let target_name = l_vec[0].to_string();
+    let target_accession = l_vec[1].to_string();
+    let query_name = l_vec[2].to_string();
+    let query_accession = l_vec[3].to_string();
+    let e_value_full = l_vec[4].parse::<f64>()?;
+    let score_full = l_vec[5].parse::<f32>()?;
+    let bias_full = l_vec[6].parse::<f32>()?;
+    let e_value_best = l_vec[7].parse::<f64>()?;
+    let score_best = l_vec[8].parse::<f32>()?;
+    let bias_best = l_vec[9].parse::<f32>()?;
+    let exp = l_vec[10].parse::<f32>()?;
+    let reg = l_vec[11].parse::<i32>()?;
+    let clu = l_vec[12].parse::<i32>()?;
+    let ov = l_vec[13].parse::<i32>()?;
+    let env = l_vec[14].parse::<i32>()?;
+    let dom = l_vec[15].parse::<i32>()?;
+    let rep = l_vec[16].parse::<i32>()?;
+    let inc = l_vec[17].parse::<i32>()?;
+    // a single space separates the description in protein searches
+    let description = description(rest, 1);
+
+    let mut record = ProteinRecord::new(
+        target_name,
+        target_accession,
+        query_name,
+        query_accession,
+        e_value_full,
+        score_full,
+        bias_full,
+        e_value_best,
+        score_best,
+        bias_best,
+        exp,
+        reg,
+        clu,
+        ov,
+        env,
+        dom,
+        rep,
+        inc,
+    );
+    record.set_description(description);
+    Ok(record)
+}
+
+impl<R: io::Read + io::Seek> Reader<R> {
+    /// Seek to the first record of a query, using the offsets in
+    /// `index`. Returns false if the query is not in the index.
+    /// As HMMER groups its output by query, iterating the records
+    /// afterwards yields the records of that query first.
+    ///
+    /// An index of a BGZF file can only be used with a reader from
+    /// `from_bgzf_path`, and an index of a plain file only with other
+    /// readers; otherwise an `ErrorKind::Unsupported` error is returned.
+    pub fn seek_to_query(&mut self, index: &TbloutIndex, query: &str) -> Result<bool> {
+        self.check_index(index)?;
+        self.peeked = None;
+        match index.query_offsets(query).first() {
+            Some(offset) => {
+                self.rdr.seek(SeekFrom::Start(*offset))?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Read all of the records for a target, seeking to each of the
+    /// offsets in `index`. The index must be of the same kind of file
+    /// as the reader, as for `seek_to_query`.
+    pub fn records_for_target(&mut self, index: &TbloutIndex, target: &str) -> Result<Vec<Record>> {
+        self.check_index(index)?;
+        self.peeked = None;
+        let mut records = Vec::new();
+        for offset in index.target_offsets(target) {
+            self.rdr.seek(SeekFrom::Start(*offset))?;
+            if let Some(record) = self.read_record()? {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Check the offsets in an index are of the kind the reader uses.
+    fn check_index(&self, index: &TbloutIndex) -> Result<()> {
+        if index.is_bgzf() == self.bgzf {
+            return Ok(());
+        }
+        let (offsets, file) = if index.is_bgzf() {
+            ("BGZF virtual", "a plain")
+        } else {
+            ("plain byte", "a BGZF")
+        };
+        Err(Error::new(ErrorKind::Unsupported(format!(
+            "the index holds {} offsets, which cannot be used to read {} file",
+            offsets, file
+        ))))
+    }
+}
@@ -1,12 +1,24 @@
-use crate::{Error, ErrorKind, Result};
+use crate::{writer::FloatStyle, Error, ErrorKind, Result};
 use std::{
+    cmp::Ordering,
     fmt::{Display, Formatter},
+    io,
     path::PathBuf,
     str::FromStr,
 };
 
 /// A record in a HMMER tblout file. Can either be a protein
 /// record or a DNA record.
+///
+/// Records compare equal when their fields are equal. The line a
+/// record was read from (see `raw_line`) is not compared.
+///
+/// Records own all their data, so they are `Send` and `Sync` and can be
+/// handed to other threads as they are read.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "lowercase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Record {
     /// A protein record.
     Protein(ProteinRecord),
@@ -26,6 +38,14 @@ impl Record {
             Record::Dna(record) => record.target_name(),
         }
     }
+    /// The description of the target, exactly as written by HMMER,
+    /// or `-` if there is none.
+    pub fn description(&self) -> String {
+        match self {
+            Record::Protein(record) => record.description(),
+            Record::Dna(record) => record.description(),
+        }
+    }
     /// The accession of the target sequence or profile, or ’-’ if none.
     pub fn target_accession(&self) -> String {
         match self {
@@ -55,7 +75,7 @@ impl Record {
     /// the overall false positive rate of that search rather than the false
     /// positive rate per query, you will want to multiply this per query
     /// E-value by how many queries you’re doing. Protein (like) records only.
-    pub fn e_value_full(&self) -> Option<f32> {
+    pub fn e_value_full(&self) -> Option<f64> {
         match self {
             Record::Protein(record) => Some(record.e_value_full()),
             Record::Dna(_) => None,
@@ -93,7 +113,7 @@ impl Record {
     /// domains, or it might contain a repetitive sequence that is hitting by
     /// chance (i.e. once one repeat hits, all the repeats hit). Protein (like)
     /// records only.
-    pub fn e_value_best(&self) -> Option<f32> {
+    pub fn e_value_best(&self) -> Option<f64> {
         match self {
             Record::Protein(record) => Some(record.e_value_best()),
             Record::Dna(_) => None,
@@ -223,7 +243,7 @@ impl Record {
 
     /// The position in the target sequence at which the hit starts.
     /// DNA records only.
-    pub fn ali_from(&self) -> Option<i32> {
+    pub fn ali_from(&self) -> Option<i64> {
         match self {
             Record::Protein(_) => None,
             Record::Dna(record) => Some(record.ali_from()),
@@ -231,7 +251,7 @@ impl Record {
     }
     /// The position in the target sequence at which the hit ends. DNA
     /// records only.
-    pub fn ali_to(&self) -> Option<i32> {
+    pub fn ali_to(&self) -> Option<i64> {
         match self {
             Record::Protein(_) => None,
             Record::Dna(record) => Some(record.ali_to()),
@@ -239,7 +259,7 @@ impl Record {
     }
     /// The position in the target sequence where the surrounding envelope starts.
     /// DNA records only.
-    pub fn env_from(&self) -> Option<i32> {
+    pub fn env_from(&self) -> Option<i64> {
         match self {
             Record::Protein(_) => None,
             Record::Dna(record) => Some(record.env_from()),
@@ -247,14 +267,14 @@ impl Record {
     }
     /// The position in the target sequence at which the surrounding envelope ends.
     /// DNA records only.
-    pub fn env_to(&self) -> Option<i32> {
+    pub fn env_to(&self) -> Option<i64> {
         match self {
             Record::Protein(_) => None,
             Record::Dna(record) => Some(record.env_to()),
         }
     }
     /// The length of the target sequence. DNA records only.
-    pub fn sq_len(&self) -> Option<i32> {
+    pub fn sq_len(&self) -> Option<i64> {
         match self {
             Record::Protein(_) => None,
             Record::Dna(record) => Some(record.sq_len()),
@@ -270,7 +290,7 @@ impl Record {
     }
     /// The expectation value (statistical significance) of the target
     /// as above. DNA records only.
-    pub fn e_value(&self) -> Option<f32> {
+    pub fn e_value(&self) -> Option<f64> {
         match self {
             Record::Protein(_) => None,
             Record::Dna(record) => Some(record.e_value()),
@@ -278,7 +298,322 @@ impl Record {
     }
 }
 
+impl Record {
+    /// Write the record as a line of a tblout file, without the line
+    /// ending, padding the columns to `widths` (see
+    /// `Header::calculate_dashes`). The output is the same as `Display`
+    /// gives with the default widths, but is written straight to `w`.
+    pub fn write_to<W: io::Write>(&self, w: &mut W, widths: &[usize]) -> io::Result<()> {
+        self.write_aligned(w, widths, FloatStyle::Hmmer, None)
+    }
+
+    /// Write the record padding the columns to `widths`, with floating
+    /// point values in the given style, and `description` in place of
+    /// the record's own if given.
+    pub(crate) fn write_aligned<W: io::Write>(
+        &self,
+        w: &mut W,
+        widths: &[usize],
+        style: FloatStyle,
+        description: Option<&str>,
+    ) -> io::Result<()> {
+        match self {
+            Record::Protein(record) => record.write_aligned(w, widths, style, description),
+            Record::Dna(record) => record.write_aligned(w, widths, style, description),
+        }
+    }
+
+    /// The formatted fields of the record in column order,
+    /// ending with the description.
+    pub(crate) fn fields(&self, style: FloatStyle) -> Vec<String> {
+        match self {
+            Record::Protein(record) => record.fields(style),
+            Record::Dna(record) => record.fields(style),
+        }
+    }
+
+    /// The fields of the record in column order, ending with the
+    /// description, with numbers formatted as HMMER formats them but
+    /// without padding. The columns are named by `field_names`.
+    pub fn to_fields(&self) -> Vec<String> {
+        self.fields(FloatStyle::Hmmer)
+    }
+}
+
+/// An implementation of `Display` for `Record`, using the default
+/// HMMER column widths.
+impl Display for Record {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Record::Protein(record) => record.fmt(f),
+            Record::Dna(record) => record.fmt(f),
+        }
+    }
+}
+
+impl Record {
+    /// The line the record was parsed from, without its line ending.
+    /// `None` unless the record was read by a reader keeping its lines
+    /// (see `Reader::keep_raw_lines`), and hasn't been changed since.
+    pub fn raw_line(&self) -> Option<&str> {
+        match self {
+            Record::Protein(record) => record.raw_line(),
+            Record::Dna(record) => record.raw_line(),
+        }
+    }
+
+    /// The E-value as it was written in the file (see
+    /// `DNARecord::e_value_raw`). DNA records only.
+    pub fn e_value_raw(&self) -> Option<&str> {
+        match self {
+            Record::Protein(_) => None,
+            Record::Dna(record) => record.e_value_raw(),
+        }
+    }
+
+    /// The full sequence E-value as it was written in the file (see
+    /// `ProteinRecord::e_value_full_raw`). Protein records only.
+    pub fn e_value_full_raw(&self) -> Option<&str> {
+        match self {
+            Record::Protein(record) => record.e_value_full_raw(),
+            Record::Dna(_) => None,
+        }
+    }
+
+    /// The best domain E-value as it was written in the file (see
+    /// `ProteinRecord::e_value_best_raw`). Protein records only.
+    pub fn e_value_best_raw(&self) -> Option<&str> {
+        match self {
+            Record::Protein(record) => record.e_value_best_raw(),
+            Record::Dna(_) => None,
+        }
+    }
+
+    /// The kind of this record.
+    pub fn kind(&self) -> RecordKind {
+        match self {
+            Record::Protein(_) => RecordKind::Protein,
+            Record::Dna(_) => RecordKind::Dna,
+        }
+    }
+
+    /// The name of the profile, the query of a search and the target
+    /// of a scan.
+    pub fn profile_name(&self, orientation: ScanOrientation) -> String {
+        match orientation {
+            ScanOrientation::Search => self.query_name(),
+            ScanOrientation::Scan => self.target_name(),
+        }
+    }
+
+    /// The accession of the profile, or `-` if none.
+    pub fn profile_accession(&self, orientation: ScanOrientation) -> String {
+        match orientation {
+            ScanOrientation::Search => self.query_accession(),
+            ScanOrientation::Scan => self.target_accession(),
+        }
+    }
+
+    /// The name of the sequence, the target of a search and the query
+    /// of a scan.
+    pub fn sequence_name(&self, orientation: ScanOrientation) -> String {
+        match orientation {
+            ScanOrientation::Search => self.target_name(),
+            ScanOrientation::Scan => self.query_name(),
+        }
+    }
+
+    /// The accession of the sequence, or `-` if none.
+    pub fn sequence_accession(&self, orientation: ScanOrientation) -> String {
+        match orientation {
+            ScanOrientation::Search => self.target_accession(),
+            ScanOrientation::Scan => self.query_accession(),
+        }
+    }
+}
+
+/// Which of the query and target of a hit is the profile, which
+/// depends on the program (see `Program::orientation`).
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum ScanOrientation {
+    /// Profiles searched against a sequence database, as by
+    /// `hmmsearch` and `nhmmer`: the query is the profile and the
+    /// target is the sequence. `phmmer` and `jackhmmer` build a
+    /// profile from their query sequence, so they search too.
+    Search,
+    /// A sequence scanned against a profile database, as by `hmmscan`
+    /// and `nhmmscan`: the query is the sequence and the target is the
+    /// profile.
+    Scan,
+}
+
+/// The accessors every kind of record has, so generic code can take
+/// a `Record` or one of the concrete records it wraps.
+///
+/// The values only some kinds of record have return `None` by default.
+pub trait TbloutRecord {
+    /// The name of the target sequence or profile.
+    fn target_name(&self) -> String;
+    /// The accession of the target sequence or profile, or `-` if none.
+    fn target_accession(&self) -> String;
+    /// The name of the query sequence or profile.
+    fn query_name(&self) -> String;
+    /// The accession of the query sequence or profile, or `-` if none.
+    fn query_accession(&self) -> String;
+    /// The description of the target, or `-` if there is none.
+    fn description(&self) -> String;
+    /// The E-value of the whole hit.
+    fn evalue_opt(&self) -> Option<f64> {
+        None
+    }
+    /// The bit score of the whole hit.
+    fn score_opt(&self) -> Option<f32> {
+        None
+    }
+    /// The biased-composition correction of the bit score of the whole
+    /// hit.
+    fn bias_opt(&self) -> Option<f32> {
+        None
+    }
+    /// The alignment coordinates on the target, as `(from, to)`. On
+    /// the minus strand `from` is greater than `to`.
+    fn interval_opt(&self) -> Option<(i64, i64)> {
+        None
+    }
+    /// Whether HMMER included the hit by its reporting thresholds, if
+    /// the record says. Protein records count the domains included, so
+    /// the hit is included if any are; DNA records don't say.
+    fn included_opt(&self) -> Option<bool> {
+        None
+    }
+}
+
+impl TbloutRecord for Record {
+    fn target_name(&self) -> String {
+        Record::target_name(self)
+    }
+    fn target_accession(&self) -> String {
+        Record::target_accession(self)
+    }
+    fn query_name(&self) -> String {
+        Record::query_name(self)
+    }
+    fn query_accession(&self) -> String {
+        Record::query_accession(self)
+    }
+    fn description(&self) -> String {
+        Record::description(self)
+    }
+    fn evalue_opt(&self) -> Option<f64> {
+        match self {
+            Record::Protein(record) => record.evalue_opt(),
+            Record::Dna(record) => record.evalue_opt(),
+        }
+    }
+    fn score_opt(&self) -> Option<f32> {
+        match self {
+            Record::Protein(record) => record.score_opt(),
+            Record::Dna(record) => record.score_opt(),
+        }
+    }
+    fn bias_opt(&self) -> Option<f32> {
+        match self {
+            Record::Protein(record) => record.bias_opt(),
+            Record::Dna(record) => record.bias_opt(),
+        }
+    }
+    fn interval_opt(&self) -> Option<(i64, i64)> {
+        match self {
+            Record::Protein(record) => record.interval_opt(),
+            Record::Dna(record) => record.interval_opt(),
+        }
+    }
+    fn included_opt(&self) -> Option<bool> {
+        match self {
+            Record::Protein(record) => record.included_opt(),
+            Record::Dna(record) => record.included_opt(),
+        }
+    }
+}
+
+/// Protein records have no coordinates on the target.
+impl TbloutRecord for ProteinRecord {
+    fn target_name(&self) -> String {
+        ProteinRecord::target_name(self)
+    }
+    fn target_accession(&self) -> String {
+        ProteinRecord::target_accession(self)
+    }
+    fn query_name(&self) -> String {
+        ProteinRecord::query_name(self)
+    }
+    fn query_accession(&self) -> String {
+        ProteinRecord::query_accession(self)
+    }
+    fn description(&self) -> String {
+        ProteinRecord::description(self)
+    }
+    fn evalue_opt(&self) -> Option<f64> {
+        Some(self.e_value_full())
+    }
+    fn score_opt(&self) -> Option<f32> {
+        Some(self.score_full())
+    }
+    fn bias_opt(&self) -> Option<f32> {
+        Some(self.bias_full())
+    }
+    fn included_opt(&self) -> Option<bool> {
+        Some(self.inc() > 0)
+    }
+}
+
+impl TbloutRecord for DNARecord {
+    fn target_name(&self) -> String {
+        DNARecord::target_name(self)
+    }
+    fn target_accession(&self) -> String {
+        DNARecord::target_accession(self)
+    }
+    fn query_name(&self) -> String {
+        DNARecord::query_name(self)
+    }
+    fn query_accession(&self) -> String {
+        DNARecord::query_accession(self)
+    }
+    fn description(&self) -> String {
+        DNARecord::description(self)
+    }
+    fn evalue_opt(&self) -> Option<f64> {
+        Some(self.e_value())
+    }
+    fn score_opt(&self) -> Option<f32> {
+        Some(self.score())
+    }
+    fn bias_opt(&self) -> Option<f32> {
+        Some(self.bias())
+    }
+    fn interval_opt(&self) -> Option<(i64, i64)> {
+        Some((self.ali_from(), self.ali_to()))
+    }
+}
+
+/// The kind of record in a HMMER tblout file, which depends
+/// on the program used.
+#[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum RecordKind {
+    /// A protein record, from `hmmsearch`, `hmmscan`, `phmmer`
+    /// or `jackhmmer`.
+    Protein,
+    /// A DNA record, from `nhmmer` or `nhmmscan`.
+    Dna,
+}
+
 #[derive(Default, PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 /// The program used to generate the output.
 pub enum Program {
     #[default]
@@ -298,11 +633,26 @@ pub enum Program {
     Phmmer, // test done
 }
 
+/// Parse the name of a program, as HMMER writes it. Other tools
+/// sometimes rewrite the name, so any directories before it and a
+/// version after it (as in `nhmmer3.4` or `nhmmer (hmmer 3.4)`) are
+/// ignored, as is case.
 impl FromStr for Program {
     type Err = Error;
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-        match s {
+        let name = s.trim();
+        let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+        let name = name.split('(').next().unwrap_or(name);
+        // no program name contains a digit, so one starts the version
+        let name = match name.find(|c: char| c.is_ascii_digit()) {
+            Some(i) => &name[..i],
+            None => name,
+        };
+        let name = name
+            .trim_end_matches(|c: char| c.is_whitespace() || c == '-' || c == '_')
+            .to_lowercase();
+        match name.as_str() {
             "nhmmer" => Ok(Program::Nhmmer),
             "nhmmscan" => Ok(Program::Nhmmscan),
             "jackhmmer" => Ok(Program::Jackhmmer),
@@ -317,7 +667,158 @@ impl FromStr for Program {
     }
 }
 
-#[derive(Default)]
+impl Program {
+    /// Whether the program searches DNA, `nhmmer` or `nhmmscan`.
+    pub fn is_dna_search(&self) -> bool {
+        matches!(self, Program::Nhmmer | Program::Nhmmscan)
+    }
+
+    /// Whether the program searches proteins, `hmmsearch`, `hmmscan`,
+    /// `phmmer` or `jackhmmer`.
+    pub fn is_protein_search(&self) -> bool {
+        matches!(
+            self,
+            Program::Jackhmmer | Program::Hmmscan | Program::Hmmsearch | Program::Phmmer
+        )
+    }
+
+    /// Whether the program searches a sequence against a database of
+    /// profiles, `hmmscan` or `nhmmscan`, so the target of each hit is
+    /// the profile and the query is the sequence.
+    pub fn is_scan(&self) -> bool {
+        matches!(self, Program::Hmmscan | Program::Nhmmscan)
+    }
+
+    /// Which of the query and target of each hit is the profile.
+    /// Unknown programs are taken to search.
+    pub fn orientation(&self) -> ScanOrientation {
+        if self.is_scan() {
+            ScanOrientation::Scan
+        } else {
+            ScanOrientation::Search
+        }
+    }
+
+    /// The kind of record the program writes, or `None` if the program
+    /// is unknown.
+    pub fn record_kind(&self) -> Option<RecordKind> {
+        if self.is_dna_search() {
+            Some(RecordKind::Dna)
+        } else if self.is_protein_search() {
+            Some(RecordKind::Protein)
+        } else {
+            None
+        }
+    }
+}
+
+/// An implementation of `Display` for `Program`, giving the
+/// name of the program as HMMER writes it.
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let name = match self {
+            Program::None => "unknown",
+            Program::Nhmmer => "nhmmer",
+            Program::Nhmmscan => "nhmmscan",
+            Program::Jackhmmer => "jackhmmer",
+            Program::Hmmscan => "hmmscan",
+            Program::Hmmsearch => "hmmsearch",
+            Program::Phmmer => "phmmer",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The version of the program which wrote a file, parsed from the
+/// metadata, e.g. `3.4 (Aug 2023)` or `3.1b2 (February 2015)`.
+///
+/// Versions are ordered by their numbers. Versions with the same
+/// numbers but different text (`3.1b2` and `3.1`) cannot be ordered.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    /// The major version number.
+    major: u32,
+    /// The minor version number.
+    minor: u32,
+    /// The patch number, zero if there is none.
+    patch: u32,
+    /// Whatever follows the numbers, e.g. `b2 (February 2015)`.
+    text: String,
+}
+
+impl Version {
+    /// Construct a version from its numbers, without any text.
+    pub fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+            text: String::new(),
+        }
+    }
+
+    /// Parse a version as HMMER or Infernal writes it: two or three
+    /// numbers separated by dots, then any text. Returns `None` if the
+    /// string does not start with a major and minor version.
+    pub fn parse(s: &str) -> Option<Version> {
+        let s = s.trim();
+        let mut numbers = [0; 3];
+        let mut rest = s;
+        for (i, n) in numbers.iter_mut().enumerate() {
+            if i > 0 {
+                match rest.strip_prefix('.') {
+                    Some(r) if r.starts_with(|c: char| c.is_ascii_digit()) => rest = r,
+                    _ if i == 2 => break,
+                    _ => return None,
+                }
+            }
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            *n = rest[..end].parse().ok()?;
+            rest = &rest[end..];
+        }
+        Some(Version {
+            major: numbers[0],
+            minor: numbers[1],
+            patch: numbers[2],
+            text: rest.trim().to_string(),
+        })
+    }
+
+    /// Get the major version number.
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    /// Get the minor version number.
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Get the patch number.
+    pub fn patch(&self) -> u32 {
+        self.patch
+    }
+
+    /// Get the text following the numbers.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<Ordering> {
+        let numbers = |v: &Version| (v.major, v.minor, v.patch);
+        match numbers(self).cmp(&numbers(other)) {
+            Ordering::Equal if self.text != other.text => None,
+            ordering => Some(ordering),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Metadata about the search that produced the HMMER tblout file.
 pub struct Meta {
     /// The program used to generate the output.
@@ -339,6 +840,21 @@ pub struct Meta {
 }
 
 impl Meta {
+    /// Metadata for a program, with all other fields left empty.
+    pub fn for_program(program: Program) -> Meta {
+        Meta {
+            program,
+            ..Default::default()
+        }
+    }
+
+    /// Whether every field is empty, as for a file without a metadata
+    /// block, such as an extract of the records alone. Metadata from
+    /// `for_program` names a program, so is not empty.
+    pub fn is_empty(&self) -> bool {
+        *self == Meta::default()
+    }
+
     /// Get the program information.
     pub fn program(&self) -> Program {
         self.program
@@ -354,6 +870,12 @@ impl Meta {
         self.version.clone()
     }
 
+    /// Get the version, parsed into its numbers. Returns `None` if
+    /// it cannot be parsed.
+    pub fn version_parsed(&self) -> Option<Version> {
+        Version::parse(&self.version)
+    }
+
     /// Set version.
     pub fn set_version(&mut self, version: String) {
         self.version = version;
@@ -420,16 +942,290 @@ impl Meta {
     }
 }
 
-#[derive(Debug)]
+/// An implementation of `Display` for `Meta`, writing the
+/// metadata block found at the end of a HMMER tblout file. Empty
+/// metadata is written as nothing, as it was read from a file without
+/// the block, rather than as a block naming an unknown program.
+impl Display for Meta {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "# Program:         {}", self.program)?;
+        writeln!(f, "# Version:         {}", self.version)?;
+        writeln!(f, "# Pipeline mode:   {}", self.pipeline_mode)?;
+        writeln!(f, "# Query file:      {}", self.query_file.display())?;
+        writeln!(f, "# Target file:     {}", self.target_file.display())?;
+        writeln!(f, "# Option settings: {}", self.options)?;
+        writeln!(f, "# Current dir:     {}", self.current_dir.display())?;
+        writeln!(f, "# Date:            {}", self.date)?;
+        writeln!(f, "# [ok]")
+    }
+}
+
+/// The column widths HMMER uses for protein search output
+/// when all of the values fit.
+pub(crate) const PROTEIN_WIDTHS: [usize; 18] =
+    [20, 10, 20, 10, 9, 6, 5, 9, 6, 5, 5, 3, 3, 3, 3, 3, 3, 3];
+
+/// The column widths HMMER uses for DNA search output
+/// when all of the values fit.
+pub(crate) const DNA_WIDTHS: [usize; 15] = [20, 10, 20, 10, 7, 7, 7, 7, 7, 7, 7, 6, 9, 6, 5];
+
+/// Look up a column width, falling back to the default if the
+/// widths given are for a different kind of record, i.e. there is
+/// not one for each column, with or without the description. HMMER
+/// only ever widens its columns, so a narrower width (e.g. from a
+/// header with a single dash under a column) is taken to be the
+/// default.
+fn width(widths: &[usize], defaults: &[usize], i: usize) -> usize {
+    if widths.len() == defaults.len() || widths.len() == defaults.len() + 1 {
+        widths[i].max(defaults[i])
+    } else {
+        defaults[i]
+    }
+}
+
+/// The text a record was parsed from, kept so that it can be written
+/// back exactly: the whole line (see `Reader::keep_raw_lines`) and the
+/// E-value tokens (see `Reader::keep_raw_numerics`). It is boxed, so a
+/// record keeping nothing only has a null pointer. It is not part of
+/// the record's value, so all raw text compares equal.
+#[derive(Debug, Clone, Default)]
+struct Raw(Option<Box<RawText>>);
+
+#[derive(Debug, Clone, Default)]
+struct RawText {
+    line: Option<String>,
+    /// The E-value tokens, in column order.
+    e_values: [Option<String>; 2],
+}
+
+impl Raw {
+    fn text(&mut self) -> &mut RawText {
+        self.0.get_or_insert_with(Default::default)
+    }
+
+    fn line(&self) -> Option<&str> {
+        self.0.as_ref()?.line.as_deref()
+    }
+
+    fn set_line(&mut self, line: &str) {
+        self.text().line = Some(line.trim_end_matches(['\n', '\r']).to_string());
+    }
+
+    /// The `i`th E-value token.
+    fn e_value(&self, i: usize) -> Option<&str> {
+        self.0.as_ref()?.e_values[i].as_deref()
+    }
+
+    fn set_e_value(&mut self, i: usize, token: &str) {
+        self.text().e_values[i] = Some(token.to_string());
+    }
+
+    /// Forget the line, as a field of the record has changed.
+    fn changed(&mut self) {
+        if let Some(text) = &mut self.0 {
+            text.line = None;
+        }
+    }
+
+    /// Forget the line and the `i`th E-value token, as the E-value has
+    /// changed.
+    fn e_value_changed(&mut self, i: usize) {
+        if let Some(text) = &mut self.0 {
+            text.line = None;
+            text.e_values[i] = None;
+        }
+    }
+}
+
+impl PartialEq for Raw {
+    fn eq(&self, _: &Raw) -> bool {
+        true
+    }
+}
+
+/// Format an E-value in the given style, or as the token it was read
+/// from if that was kept.
+pub(crate) fn fmt_e_value(value: f64, raw: Option<&str>, style: FloatStyle) -> String {
+    if let Some(raw) = raw {
+        return raw.to_string();
+    }
+    match style {
+        FloatStyle::Hmmer => fmt_g(value, 2),
+        FloatStyle::Raw => format!("{:?}", value),
+    }
+}
+
+/// Format a value to `sig` significant figures like printf's `%g`,
+/// which HMMER uses for E-values: scientific notation for small and
+/// large values, fixed notation otherwise, and no trailing zeros.
+/// Exponents have at least two digits, as with printf, but positive
+/// ones have no `+`.
+fn fmt_g(value: f64, sig: usize) -> String {
+    if !value.is_finite() {
+        return format!("{}", value).to_lowercase();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let sig = sig.max(1);
+    // the exponent after rounding to the significant figures,
+    // so 9.96 to two figures is 1.0e1 rather than 9.96e0
+    let sci = format!("{:.*e}", sig - 1, value);
+    let (mantissa, exp) = sci.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    if exp < -4 || exp >= sig as i32 {
+        // at least two exponent digits and no plus sign, e.g. 1e-05 and 1e05
+        let sign = if exp < 0 { "-" } else { "" };
+        format!("{}e{}{:02}", trim_zeros(mantissa), sign, exp.abs())
+    } else {
+        let precision = (sig as i32 - 1 - exp) as usize;
+        trim_zeros(&format!("{:.*}", precision, value)).to_string()
+    }
+}
+
+/// Remove the trailing zeros after a decimal point, and the point
+/// itself if nothing is left after it.
+fn trim_zeros(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
+}
+
+/// Format a score, bias or expected number of domains in the
+/// given style.
+pub(crate) fn fmt_score(value: f32, style: FloatStyle) -> String {
+    let mut s = Vec::new();
+    write_score(&mut s, value, 0, style).expect("writing to a Vec cannot fail");
+    String::from_utf8(s).expect("scores are ASCII")
+}
+
+/// Write an E-value in the given style, right justified in `width`.
+fn write_e_value<W: io::Write>(
+    w: &mut W,
+    value: f64,
+    raw: Option<&str>,
+    width: usize,
+    style: FloatStyle,
+) -> io::Result<()> {
+    match raw {
+        Some(raw) => write_column(w, raw, width, false),
+        None => write_column(w, &fmt_e_value(value, None, style), width, false),
+    }
+}
+
+/// Write a score, bias or expected number of domains in the given
+/// style, right justified in `width`. HMMER's one decimal place is
+/// written without going through `format!`, rounding halves to even
+/// as `{:.1}` does.
+fn write_score<W: io::Write>(
+    w: &mut W,
+    value: f32,
+    width: usize,
+    style: FloatStyle,
+) -> io::Result<()> {
+    // larger values would overflow the tenths
+    if style == FloatStyle::Raw || !value.is_finite() || value.abs() >= 1e15 {
+        let s = match style {
+            FloatStyle::Hmmer => format!("{:.1}", value),
+            FloatStyle::Raw => format!("{:?}", value),
+        };
+        return write_column(w, &s, width, false);
+    }
+    // exact, as an f32 times ten fits in an f64
+    let tenths = (value as f64 * 10.0).round_ties_even().abs() as u64;
+    let mut int = itoa::Buffer::new();
+    let whole = int.format(tenths / 10);
+    let sign = value.is_sign_negative();
+    let len = sign as usize + whole.len() + 2;
+    write_spaces(w, width.saturating_sub(len))?;
+    if sign {
+        w.write_all(b"-")?;
+    }
+    w.write_all(whole.as_bytes())?;
+    w.write_all(&[b'.', b'0' + (tenths % 10) as u8])
+}
+
+/// Map `pos` from the range `from` linearly onto the range `to`,
+/// rounding to the nearest position. Either range may run downwards.
+/// A range of one position maps everything to the start of `to`.
+fn interpolate(from: (i64, i64), to: (i64, i64), pos: i64) -> i64 {
+    if from.0 == from.1 {
+        return to.0;
+    }
+    let t = (pos - from.0) as f64 / (from.1 - from.0) as f64;
+    to.0 + (t * (to.1 - to.0) as f64).round() as i64
+}
+
+/// Write a column, padded with spaces to `width` on the right if
+/// it is left justified, or on the left otherwise.
+pub(crate) fn write_column<W: io::Write>(
+    w: &mut W,
+    value: &str,
+    width: usize,
+    left: bool,
+) -> io::Result<()> {
+    let pad = width.saturating_sub(value.chars().count());
+    if !left {
+        write_spaces(w, pad)?;
+    }
+    w.write_all(value.as_bytes())?;
+    if left {
+        write_spaces(w, pad)?;
+    }
+    Ok(())
+}
+
+/// Write `n` spaces.
+fn write_spaces<W: io::Write>(w: &mut W, mut n: usize) -> io::Result<()> {
+    const SPACES: [u8; 32] = [b' '; 32];
+    while n > 0 {
+        let chunk = n.min(SPACES.len());
+        w.write_all(&SPACES[..chunk])?;
+        n -= chunk;
+    }
+    Ok(())
+}
+
+/// Lets `Display` share the formatting of `write_to` by writing to a
+/// `Formatter` through `io::Write`. Only whole strings are written, so
+/// every buffer is valid UTF-8.
+struct FmtWriter<'a, 'b>(&'a mut Formatter<'b>);
+
+impl io::Write for FmtWriter<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let s =
+            std::str::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.0
+            .write_str(s)
+            .map_err(|_| io::Error::other("formatter error"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A record in a HMMER tblout file specific to protein searches.
+///
+/// Records compare equal when their fields are equal. The line a
+/// record was read from (see `raw_line`) is not compared.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProteinRecord {
     target_name: String,
     target_accession: String,
     query_name: String,
     query_accession: String,
-    e_value_full: f32,
+    e_value_full: f64,
     score_full: f32,
     bias_full: f32,
-    e_value_best: f32,
+    e_value_best: f64,
     score_best: f32,
     bias_best: f32,
     exp: f32,
@@ -440,18 +1236,22 @@ pub struct ProteinRecord {
     dom: i32,
     rep: i32,
     inc: i32,
+    description: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raw: Raw,
 }
 
 impl ProteinRecord {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         target_name: String,
         target_accession: String,
         query_name: String,
         query_accession: String,
-        e_value_full: f32,
+        e_value_full: f64,
         score_full: f32,
         bias_full: f32,
-        e_value_best: f32,
+        e_value_best: f64,
         score_best: f32,
         bias_best: f32,
         exp: f32,
@@ -482,6 +1282,8 @@ impl ProteinRecord {
             dom,
             rep,
             inc,
+            description: "-".to_string(),
+            raw: Raw::default(),
         }
     }
 
@@ -501,7 +1303,7 @@ impl ProteinRecord {
         self.query_accession.clone()
     }
 
-    pub fn e_value_full(&self) -> f32 {
+    pub fn e_value_full(&self) -> f64 {
         self.e_value_full
     }
 
@@ -513,7 +1315,7 @@ impl ProteinRecord {
         self.bias_full
     }
 
-    pub fn e_value_best(&self) -> f32 {
+    pub fn e_value_best(&self) -> f64 {
         self.e_value_best
     }
 
@@ -557,82 +1359,247 @@ impl ProteinRecord {
         self.inc
     }
 
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+
     pub fn set_target_name(&mut self, target_name: String) {
         self.target_name = target_name;
+        self.raw.changed();
     }
 
     pub fn set_target_accession(&mut self, target_accession: String) {
         self.target_accession = target_accession;
+        self.raw.changed();
     }
 
     pub fn set_query_name(&mut self, query_name: String) {
         self.query_name = query_name;
+        self.raw.changed();
     }
 
     pub fn set_query_accession(&mut self, query_accession: String) {
         self.query_accession = query_accession;
+        self.raw.changed();
     }
 
-    pub fn set_e_value_full(&mut self, e_value_full: f32) {
+    pub fn set_e_value_full(&mut self, e_value_full: f64) {
         self.e_value_full = e_value_full;
+        self.raw.e_value_changed(0);
     }
 
     pub fn set_score_full(&mut self, score_full: f32) {
         self.score_full = score_full;
+        self.raw.changed();
     }
 
     pub fn set_bias_full(&mut self, bias_full: f32) {
         self.bias_full = bias_full;
+        self.raw.changed();
     }
 
-    pub fn set_e_value_best(&mut self, e_value_best: f32) {
+    pub fn set_e_value_best(&mut self, e_value_best: f64) {
         self.e_value_best = e_value_best;
+        self.raw.e_value_changed(1);
     }
 
     pub fn set_score_best(&mut self, score_best: f32) {
         self.score_best = score_best;
+        self.raw.changed();
     }
 
     pub fn set_bias_best(&mut self, bias_best: f32) {
         self.bias_best = bias_best;
+        self.raw.changed();
     }
 
     pub fn set_exp(&mut self, exp: f32) {
         self.exp = exp;
+        self.raw.changed();
     }
 
     pub fn set_reg(&mut self, reg: i32) {
         self.reg = reg;
+        self.raw.changed();
     }
 
     pub fn set_clu(&mut self, clu: i32) {
         self.clu = clu;
+        self.raw.changed();
     }
 
     pub fn set_ov(&mut self, ov: i32) {
         self.ov = ov;
+        self.raw.changed();
     }
 
     pub fn set_env(&mut self, env: i32) {
         self.env = env;
+        self.raw.changed();
     }
 
     pub fn set_dom(&mut self, dom: i32) {
         self.dom = dom;
+        self.raw.changed();
     }
 
     pub fn set_rep(&mut self, rep: i32) {
         self.rep = rep;
+        self.raw.changed();
     }
 
     pub fn set_inc(&mut self, inc: i32) {
         self.inc = inc;
+        self.raw.changed();
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        self.description = description;
+        self.raw.changed();
+    }
+}
+
+impl ProteinRecord {
+    /// The line the record was parsed from, without its line ending.
+    /// `None` unless the record was read by a reader keeping its lines
+    /// (see `Reader::keep_raw_lines`), and hasn't been changed since.
+    pub fn raw_line(&self) -> Option<&str> {
+        self.raw.line()
+    }
+
+    /// Keep the line the record was parsed from.
+    pub(crate) fn with_raw_line(mut self, line: &str) -> ProteinRecord {
+        self.raw.set_line(line);
+        self
+    }
+
+    /// The full sequence E-value as it was written in the file. `None`
+    /// unless the record was read by a reader keeping its E-value tokens
+    /// (see `Reader::keep_raw_numerics`), and the E-value hasn't been
+    /// changed since.
+    pub fn e_value_full_raw(&self) -> Option<&str> {
+        self.raw.e_value(0)
+    }
+
+    /// The best domain E-value as it was written in the file. See
+    /// `e_value_full_raw`.
+    pub fn e_value_best_raw(&self) -> Option<&str> {
+        self.raw.e_value(1)
+    }
+
+    /// Keep the E-value tokens the record was parsed from.
+    pub(crate) fn with_raw_e_values(mut self, full: &str, best: &str) -> ProteinRecord {
+        self.raw.set_e_value(0, full);
+        self.raw.set_e_value(1, best);
+        self
+    }
+
+    /// Write the record as a line of a tblout file, without the line
+    /// ending, padding the columns to `widths` (see
+    /// `Header::calculate_dashes`). The output is the same as `Display`
+    /// gives with the default widths, but is written straight to `w`.
+    pub fn write_to<W: io::Write>(&self, w: &mut W, widths: &[usize]) -> io::Result<()> {
+        self.write_aligned(w, widths, FloatStyle::Hmmer, None)
+    }
+
+    /// Write the record padding the columns to `widths`, with floating
+    /// point values in the given style, and `description` in place of
+    /// the record's own if given.
+    pub(crate) fn write_aligned<W: io::Write>(
+        &self,
+        w: &mut W,
+        widths: &[usize],
+        style: FloatStyle,
+        description: Option<&str>,
+    ) -> io::Result<()> {
+        let width = |i| width(widths, &PROTEIN_WIDTHS, i);
+        // names are left justified, numbers right justified
+        let names = [
+            &self.target_name,
+            &self.target_accession,
+            &self.query_name,
+            &self.query_accession,
+        ];
+        for (i, name) in names.into_iter().enumerate() {
+            write_column(w, name, width(i), true)?;
+            w.write_all(b" ")?;
+        }
+        write_e_value(w, self.e_value_full, self.raw.e_value(0), width(4), style)?;
+        w.write_all(b" ")?;
+        write_score(w, self.score_full, width(5), style)?;
+        w.write_all(b" ")?;
+        write_score(w, self.bias_full, width(6), style)?;
+        w.write_all(b" ")?;
+        write_e_value(w, self.e_value_best, self.raw.e_value(1), width(7), style)?;
+        w.write_all(b" ")?;
+        write_score(w, self.score_best, width(8), style)?;
+        w.write_all(b" ")?;
+        write_score(w, self.bias_best, width(9), style)?;
+        w.write_all(b" ")?;
+        write_score(w, self.exp, width(10), style)?;
+        let counts = [
+            self.reg, self.clu, self.ov, self.env, self.dom, self.rep, self.inc,
+        ];
+        let mut int = itoa::Buffer::new();
+        for (i, count) in counts.into_iter().enumerate() {
+            w.write_all(b" ")?;
+            write_column(w, int.format(count), width(i + 11), false)?;
+        }
+        // the description ends the line, so is not padded
+        w.write_all(b" ")?;
+        w.write_all(description.unwrap_or(&self.description).as_bytes())
+    }
+
+    /// The formatted fields of the record in column order,
+    /// ending with the description.
+    pub(crate) fn fields(&self, style: FloatStyle) -> Vec<String> {
+        vec![
+            self.target_name.clone(),
+            self.target_accession.clone(),
+            self.query_name.clone(),
+            self.query_accession.clone(),
+            fmt_e_value(self.e_value_full, self.raw.e_value(0), style),
+            fmt_score(self.score_full, style),
+            fmt_score(self.bias_full, style),
+            fmt_e_value(self.e_value_best, self.raw.e_value(1), style),
+            fmt_score(self.score_best, style),
+            fmt_score(self.bias_best, style),
+            fmt_score(self.exp, style),
+            self.reg.to_string(),
+            self.clu.to_string(),
+            self.ov.to_string(),
+            self.env.to_string(),
+            self.dom.to_string(),
+            self.rep.to_string(),
+            self.inc.to_string(),
+            self.description.clone(),
+        ]
+    }
+
+    /// The fields of the record in column order, ending with the
+    /// description, with numbers formatted as HMMER formats them but
+    /// without padding. The columns are named by `field_names`.
+    pub fn to_fields(&self) -> Vec<String> {
+        self.fields(FloatStyle::Hmmer)
+    }
+}
+
+/// An implementation of `Display` for `ProteinRecord`, using the
+/// default HMMER column widths.
+impl Display for ProteinRecord {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        self.write_aligned(&mut FmtWriter(f), &PROTEIN_WIDTHS, FloatStyle::Hmmer, None)
+            .map_err(|_| std::fmt::Error)
     }
 }
 
 /// A record in a HMMER tblout file
 /// specific to DNA related searches.
-#[derive(Debug)]
+///
+/// Records compare equal when their fields are equal. The line a
+/// record was read from (see `raw_line`) is not compared.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DNARecord {
     target_name: String,
     target_accession: String,
@@ -640,18 +1607,22 @@ pub struct DNARecord {
     query_accession: String,
     hmm_from: i32,
     hmm_to: i32,
-    ali_from: i32,
-    ali_to: i32,
-    env_from: i32,
-    env_to: i32,
-    sq_len: i32,
+    ali_from: i64,
+    ali_to: i64,
+    env_from: i64,
+    env_to: i64,
+    sq_len: i64,
     strand: Strand,
-    e_value: f32,
+    e_value: f64,
     score: f32,
     bias: f32,
+    description: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    raw: Raw,
 }
 
 impl DNARecord {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         target_name: String,
         target_accession: String,
@@ -659,13 +1630,13 @@ impl DNARecord {
         query_accession: String,
         hmm_from: i32,
         hmm_to: i32,
-        ali_from: i32,
-        ali_to: i32,
-        env_from: i32,
-        env_to: i32,
-        sq_len: i32,
+        ali_from: i64,
+        ali_to: i64,
+        env_from: i64,
+        env_to: i64,
+        sq_len: i64,
         strand: Strand,
-        e_value: f32,
+        e_value: f64,
         score: f32,
         bias: f32,
     ) -> Self {
@@ -685,6 +1656,8 @@ impl DNARecord {
             e_value,
             score,
             bias,
+            description: "-".to_string(),
+            raw: Raw::default(),
         }
     }
 
@@ -711,23 +1684,23 @@ impl DNARecord {
         self.hmm_to
     }
 
-    pub fn ali_from(&self) -> i32 {
+    pub fn ali_from(&self) -> i64 {
         self.ali_from
     }
 
-    pub fn ali_to(&self) -> i32 {
+    pub fn ali_to(&self) -> i64 {
         self.ali_to
     }
 
-    pub fn env_from(&self) -> i32 {
+    pub fn env_from(&self) -> i64 {
         self.env_from
     }
 
-    pub fn env_to(&self) -> i32 {
+    pub fn env_to(&self) -> i64 {
         self.env_to
     }
 
-    pub fn sq_len(&self) -> i32 {
+    pub fn sq_len(&self) -> i64 {
         self.sq_len
     }
 
@@ -735,7 +1708,7 @@ impl DNARecord {
         self.strand
     }
 
-    pub fn e_value(&self) -> f32 {
+    pub fn e_value(&self) -> f64 {
         self.e_value
     }
 
@@ -746,12 +1719,470 @@ impl DNARecord {
     pub fn bias(&self) -> f32 {
         self.bias
     }
+
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    pub fn set_target_name(&mut self, target_name: String) {
+        self.target_name = target_name;
+        self.raw.changed();
+    }
+
+    pub fn set_target_accession(&mut self, target_accession: String) {
+        self.target_accession = target_accession;
+        self.raw.changed();
+    }
+
+    pub fn set_query_name(&mut self, query_name: String) {
+        self.query_name = query_name;
+        self.raw.changed();
+    }
+
+    pub fn set_query_accession(&mut self, query_accession: String) {
+        self.query_accession = query_accession;
+        self.raw.changed();
+    }
+
+    pub fn set_hmm_from(&mut self, hmm_from: i32) {
+        self.hmm_from = hmm_from;
+        self.raw.changed();
+    }
+
+    pub fn set_hmm_to(&mut self, hmm_to: i32) {
+        self.hmm_to = hmm_to;
+        self.raw.changed();
+    }
+
+    pub fn set_ali_from(&mut self, ali_from: i64) {
+        self.ali_from = ali_from;
+        self.raw.changed();
+    }
+
+    pub fn set_ali_to(&mut self, ali_to: i64) {
+        self.ali_to = ali_to;
+        self.raw.changed();
+    }
+
+    pub fn set_env_from(&mut self, env_from: i64) {
+        self.env_from = env_from;
+        self.raw.changed();
+    }
+
+    pub fn set_env_to(&mut self, env_to: i64) {
+        self.env_to = env_to;
+        self.raw.changed();
+    }
+
+    pub fn set_sq_len(&mut self, sq_len: i64) {
+        self.sq_len = sq_len;
+        self.raw.changed();
+    }
+
+    pub fn set_strand(&mut self, strand: Strand) {
+        self.strand = strand;
+        self.raw.changed();
+    }
+
+    pub fn set_e_value(&mut self, e_value: f64) {
+        self.e_value = e_value;
+        self.raw.e_value_changed(0);
+    }
+
+    pub fn set_score(&mut self, score: f32) {
+        self.score = score;
+        self.raw.changed();
+    }
+
+    pub fn set_bias(&mut self, bias: f32) {
+        self.bias = bias;
+        self.raw.changed();
+    }
+
+    pub fn set_description(&mut self, description: String) {
+        self.description = description;
+        self.raw.changed();
+    }
+}
+
+impl DNARecord {
+    /// The line the record was parsed from, without its line ending.
+    /// `None` unless the record was read by a reader keeping its lines
+    /// (see `Reader::keep_raw_lines`), and hasn't been changed since.
+    pub fn raw_line(&self) -> Option<&str> {
+        self.raw.line()
+    }
+
+    /// Keep the line the record was parsed from.
+    pub(crate) fn with_raw_line(mut self, line: &str) -> DNARecord {
+        self.raw.set_line(line);
+        self
+    }
+
+    /// The E-value as it was written in the file, which tells a true
+    /// `0` from a value too small for an `f64`. `None` unless the record
+    /// was read by a reader keeping its E-value tokens (see
+    /// `Reader::keep_raw_numerics`), and the E-value hasn't been changed
+    /// since.
+    pub fn e_value_raw(&self) -> Option<&str> {
+        self.raw.e_value(0)
+    }
+
+    /// Keep the E-value token the record was parsed from.
+    pub(crate) fn with_raw_e_value(mut self, token: &str) -> DNARecord {
+        self.raw.set_e_value(0, token);
+        self
+    }
+
+    /// The strand the alignment coordinates put the hit on: minus when
+    /// `ali_from` is greater than `ali_to`, as HMMER writes them, and
+    /// plus when it is less. `None` for an alignment of one position,
+    /// which could be on either.
+    pub fn coordinate_strand(&self) -> Option<Strand> {
+        match self.ali_from.cmp(&self.ali_to) {
+            Ordering::Greater => Some(Strand::Negative),
+            Ordering::Less => Some(Strand::Positive),
+            Ordering::Equal => None,
+        }
+    }
+
+    /// The alignment on the target as 1-based, inclusive `(start, end)`
+    /// coordinates with `start <= end`, whatever the strand.
+    ///
+    /// The coordinates win over the strand column: they are ordered by
+    /// value, so a record whose strand disagrees with them (see
+    /// `coordinate_strand`) still gives the positions it covers.
+    pub fn interval(&self) -> (i64, i64) {
+        let (from, to) = (self.ali_from, self.ali_to);
+        (from.min(to), from.max(to))
+    }
+
+    /// Estimate where a position of the model falls on the target, or
+    /// `None` if it is outside the model positions of the hit.
+    ///
+    /// This is approximate. A tblout file gives only the ends of the
+    /// alignment, not the alignment itself, so positions are
+    /// interpolated linearly from `hmm_from`..`hmm_to` onto
+    /// `ali_from`..`ali_to`, and rounded to the nearest. Insertions and
+    /// deletions between the ends shift the true position. On the
+    /// minus strand `ali_from` is the greater, so later model positions
+    /// map to lower target positions.
+    pub fn map_model_to_seq(&self, model_pos: i32) -> Option<i64> {
+        if model_pos < self.hmm_from || model_pos > self.hmm_to {
+            return None;
+        }
+        Some(interpolate(
+            (self.hmm_from.into(), self.hmm_to.into()),
+            (self.ali_from, self.ali_to),
+            model_pos.into(),
+        ))
+    }
+
+    /// Estimate which position of the model a position on the target
+    /// aligns to, or `None` if it is outside the alignment. The inverse
+    /// of `map_model_to_seq`, and as approximate.
+    pub fn map_seq_to_model(&self, seq_pos: i64) -> Option<i32> {
+        let (start, end) = self.interval();
+        if seq_pos < start || seq_pos > end {
+            return None;
+        }
+        let model = interpolate(
+            (self.ali_from, self.ali_to),
+            (self.hmm_from.into(), self.hmm_to.into()),
+            seq_pos,
+        );
+        // between hmm_from and hmm_to, so fits
+        Some(model as i32)
+    }
+
+    /// Write the record as a line of a tblout file, without the line
+    /// ending, padding the columns to `widths` (see
+    /// `Header::calculate_dashes`). The output is the same as `Display`
+    /// gives with the default widths, but is written straight to `w`.
+    pub fn write_to<W: io::Write>(&self, w: &mut W, widths: &[usize]) -> io::Result<()> {
+        self.write_aligned(w, widths, FloatStyle::Hmmer, None)
+    }
+
+    /// Write the record padding the columns to `widths`, with floating
+    /// point values in the given style, and `description` in place of
+    /// the record's own if given.
+    pub(crate) fn write_aligned<W: io::Write>(
+        &self,
+        w: &mut W,
+        widths: &[usize],
+        style: FloatStyle,
+        description: Option<&str>,
+    ) -> io::Result<()> {
+        let width = |i| width(widths, &DNA_WIDTHS, i);
+        // names are left justified, numbers right justified
+        let names = [
+            &self.target_name,
+            &self.target_accession,
+            &self.query_name,
+            &self.query_accession,
+        ];
+        for (i, name) in names.into_iter().enumerate() {
+            write_column(w, name, width(i), true)?;
+            w.write_all(b" ")?;
+        }
+        // the model positions widened to go with those on the sequence
+        let positions = [
+            self.hmm_from.into(),
+            self.hmm_to.into(),
+            self.ali_from,
+            self.ali_to,
+            self.env_from,
+            self.env_to,
+            self.sq_len,
+        ];
+        let mut int = itoa::Buffer::new();
+        for (i, position) in positions.into_iter().enumerate() {
+            write_column(w, int.format(position), width(i + 4), false)?;
+            w.write_all(b" ")?;
+        }
+        // HMMER writes the strand as "   +  " in its six wide column
+        let strand = match self.strand {
+            Strand::Positive => "+",
+            Strand::Negative => "-",
+        };
+        write_column(w, strand, width(11).saturating_sub(2), false)?;
+        w.write_all(b"   ")?;
+        write_e_value(w, self.e_value, self.raw.e_value(0), width(12), style)?;
+        w.write_all(b" ")?;
+        write_score(w, self.score, width(13), style)?;
+        w.write_all(b" ")?;
+        write_score(w, self.bias, width(14), style)?;
+        w.write_all(b"  ")?;
+        w.write_all(description.unwrap_or(&self.description).as_bytes())
+    }
+
+    /// The formatted fields of the record in column order,
+    /// ending with the description.
+    pub(crate) fn fields(&self, style: FloatStyle) -> Vec<String> {
+        vec![
+            self.target_name.clone(),
+            self.target_accession.clone(),
+            self.query_name.clone(),
+            self.query_accession.clone(),
+            self.hmm_from.to_string(),
+            self.hmm_to.to_string(),
+            self.ali_from.to_string(),
+            self.ali_to.to_string(),
+            self.env_from.to_string(),
+            self.env_to.to_string(),
+            self.sq_len.to_string(),
+            self.strand.to_string(),
+            fmt_e_value(self.e_value, self.raw.e_value(0), style),
+            fmt_score(self.score, style),
+            fmt_score(self.bias, style),
+            self.description.clone(),
+        ]
+    }
+
+    /// The fields of the record in column order, ending with the
+    /// description, with numbers formatted as HMMER formats them but
+    /// without padding. The columns are named by `field_names`.
+    pub fn to_fields(&self) -> Vec<String> {
+        self.fields(FloatStyle::Hmmer)
+    }
+}
+
+/// An implementation of `Display` for `DNARecord`, using the
+/// default HMMER column widths.
+impl Display for DNARecord {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        self.write_aligned(&mut FmtWriter(f), &DNA_WIDTHS, FloatStyle::Hmmer, None)
+            .map_err(|_| std::fmt::Error)
+    }
+}
+
+/// The fields of a `ProteinRecord`, for moving them out of a record
+/// without cloning, or building one field by field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProteinParts {
+    pub target_name: String,
+    pub target_accession: String,
+    pub query_name: String,
+    pub query_accession: String,
+    pub e_value_full: f64,
+    pub score_full: f32,
+    pub bias_full: f32,
+    pub e_value_best: f64,
+    pub score_best: f32,
+    pub bias_best: f32,
+    pub exp: f32,
+    pub reg: i32,
+    pub clu: i32,
+    pub ov: i32,
+    pub env: i32,
+    pub dom: i32,
+    pub rep: i32,
+    pub inc: i32,
+    /// The description of the target, or `-` if there is none.
+    pub description: String,
+}
+
+/// The fields of a `DNARecord`, for moving them out of a record
+/// without cloning, or building one field by field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DNAParts {
+    pub target_name: String,
+    pub target_accession: String,
+    pub query_name: String,
+    pub query_accession: String,
+    pub hmm_from: i32,
+    pub hmm_to: i32,
+    pub ali_from: i64,
+    pub ali_to: i64,
+    pub env_from: i64,
+    pub env_to: i64,
+    pub sq_len: i64,
+    pub strand: Strand,
+    pub e_value: f64,
+    pub score: f32,
+    pub bias: f32,
+    /// The description of the target, or `-` if there is none.
+    pub description: String,
+}
+
+/// The fields of a `Record`, of either kind.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordParts {
+    /// The fields of a protein record.
+    Protein(ProteinParts),
+    /// The fields of a DNA record.
+    Dna(DNAParts),
+}
+
+impl ProteinRecord {
+    /// Take the fields out of the record. The line it was read from,
+    /// if kept, is dropped.
+    pub fn into_parts(self) -> ProteinParts {
+        ProteinParts {
+            target_name: self.target_name,
+            target_accession: self.target_accession,
+            query_name: self.query_name,
+            query_accession: self.query_accession,
+            e_value_full: self.e_value_full,
+            score_full: self.score_full,
+            bias_full: self.bias_full,
+            e_value_best: self.e_value_best,
+            score_best: self.score_best,
+            bias_best: self.bias_best,
+            exp: self.exp,
+            reg: self.reg,
+            clu: self.clu,
+            ov: self.ov,
+            env: self.env,
+            dom: self.dom,
+            rep: self.rep,
+            inc: self.inc,
+            description: self.description,
+        }
+    }
+
+    /// Build a record from its fields.
+    pub fn from_parts(parts: ProteinParts) -> ProteinRecord {
+        ProteinRecord {
+            target_name: parts.target_name,
+            target_accession: parts.target_accession,
+            query_name: parts.query_name,
+            query_accession: parts.query_accession,
+            e_value_full: parts.e_value_full,
+            score_full: parts.score_full,
+            bias_full: parts.bias_full,
+            e_value_best: parts.e_value_best,
+            score_best: parts.score_best,
+            bias_best: parts.bias_best,
+            exp: parts.exp,
+            reg: parts.reg,
+            clu: parts.clu,
+            ov: parts.ov,
+            env: parts.env,
+            dom: parts.dom,
+            rep: parts.rep,
+            inc: parts.inc,
+            description: parts.description,
+            raw: Raw::default(),
+        }
+    }
+}
+
+impl DNARecord {
+    /// Take the fields out of the record. The line it was read from,
+    /// if kept, is dropped.
+    pub fn into_parts(self) -> DNAParts {
+        DNAParts {
+            target_name: self.target_name,
+            target_accession: self.target_accession,
+            query_name: self.query_name,
+            query_accession: self.query_accession,
+            hmm_from: self.hmm_from,
+            hmm_to: self.hmm_to,
+            ali_from: self.ali_from,
+            ali_to: self.ali_to,
+            env_from: self.env_from,
+            env_to: self.env_to,
+            sq_len: self.sq_len,
+            strand: self.strand,
+            e_value: self.e_value,
+            score: self.score,
+            bias: self.bias,
+            description: self.description,
+        }
+    }
+
+    /// Build a record from its fields.
+    pub fn from_parts(parts: DNAParts) -> DNARecord {
+        DNARecord {
+            target_name: parts.target_name,
+            target_accession: parts.target_accession,
+            query_name: parts.query_name,
+            query_accession: parts.query_accession,
+            hmm_from: parts.hmm_from,
+            hmm_to: parts.hmm_to,
+            ali_from: parts.ali_from,
+            ali_to: parts.ali_to,
+            env_from: parts.env_from,
+            env_to: parts.env_to,
+            sq_len: parts.sq_len,
+            strand: parts.strand,
+            e_value: parts.e_value,
+            score: parts.score,
+            bias: parts.bias,
+            description: parts.description,
+            raw: Raw::default(),
+        }
+    }
+}
+
+impl Record {
+    /// Take the fields out of the record. The line it was read from,
+    /// if kept, is dropped.
+    pub fn into_parts(self) -> RecordParts {
+        match self {
+            Record::Protein(record) => RecordParts::Protein(record.into_parts()),
+            Record::Dna(record) => RecordParts::Dna(record.into_parts()),
+        }
+    }
+
+    /// Build a record from its fields.
+    pub fn from_parts(parts: RecordParts) -> Record {
+        match parts {
+            RecordParts::Protein(parts) => Record::Protein(ProteinRecord::from_parts(parts)),
+            RecordParts::Dna(parts) => Record::Dna(DNARecord::from_parts(parts)),
+        }
+    }
 }
 
 /// The strandedness of the HMM hit in the genome.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 pub enum Strand {
+    #[cfg_attr(feature = "serde", serde(rename = "+"))]
     Positive,
+    #[cfg_attr(feature = "serde", serde(rename = "-"))]
     Negative,
 }
 
@@ -774,8 +2205,337 @@ impl FromStr for Strand {
 impl Display for Strand {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match &self {
-            Strand::Positive => write!(f, "+"),
-            Strand::Negative => write!(f, "-"),
+            Strand::Positive => f.pad("+"),
+            Strand::Negative => f.pad("-"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_model_to_seq() {
+        // 100 model positions onto 200 bases
+        let line = "chr1 - TR - 1 101 1001 1201 1000 1205 50000 + 1.9e-37 136.7 1.4 -";
+        let plus = crate::reader::parse_dna_line(line).unwrap();
+        assert_eq!(plus.map_model_to_seq(1), Some(1001));
+        assert_eq!(plus.map_model_to_seq(101), Some(1201));
+        // halfway along the model, halfway along the alignment
+        assert_eq!(plus.map_model_to_seq(51), Some(1101));
+        assert_eq!(plus.map_model_to_seq(52), Some(1103));
+        assert_eq!(plus.map_model_to_seq(0), None);
+        assert_eq!(plus.map_model_to_seq(102), None);
+
+        assert_eq!(plus.map_seq_to_model(1001), Some(1));
+        assert_eq!(plus.map_seq_to_model(1201), Some(101));
+        assert_eq!(plus.map_seq_to_model(1101), Some(51));
+        assert_eq!(plus.map_seq_to_model(1104), Some(53));
+        // in the envelope but not the alignment
+        assert_eq!(plus.map_seq_to_model(1000), None);
+        assert_eq!(plus.map_seq_to_model(1202), None);
+
+        // on the minus strand the model runs down the target
+        let line = "chr1 - TR - 1 101 1201 1001 1205 1000 50000 - 1.9e-37 136.7 1.4 -";
+        let minus = crate::reader::parse_dna_line(line).unwrap();
+        assert_eq!(minus.map_model_to_seq(1), Some(1201));
+        assert_eq!(minus.map_model_to_seq(101), Some(1001));
+        assert_eq!(minus.map_model_to_seq(51), Some(1101));
+        assert_eq!(minus.map_model_to_seq(52), Some(1099));
+        assert_eq!(minus.map_seq_to_model(1201), Some(1));
+        assert_eq!(minus.map_seq_to_model(1001), Some(101));
+        assert_eq!(minus.map_seq_to_model(1101), Some(51));
+        assert_eq!(minus.map_seq_to_model(1098), Some(53));
+        assert_eq!(minus.map_seq_to_model(1202), None);
+
+        // a single model position
+        let line = "chr1 - TR - 5 5 100 100 100 100 1000 + 1.9e-37 136.7 1.4 -";
+        let one = crate::reader::parse_dna_line(line).unwrap();
+        assert_eq!(one.map_model_to_seq(5), Some(100));
+        assert_eq!(one.map_seq_to_model(100), Some(5));
+    }
+
+    #[test]
+    fn test_raw_size() {
+        // records keeping nothing pay for a pointer
+        assert_eq!(std::mem::size_of::<Raw>(), std::mem::size_of::<usize>());
+    }
+
+    #[test]
+    fn test_into_parts() {
+        let line = "chr1                 -          TR                   -                2     317    338944    339292    338943    339292 133811386    +     1.9e-37  136.7   1.4  Tandem repeat";
+        let record = Record::Dna(crate::reader::parse_dna_line(line).unwrap());
+        let ptr = match &record {
+            Record::Dna(r) => r.description.as_ptr(),
+            Record::Protein(_) => unreachable!(),
+        };
+
+        let parts = record.clone().into_parts();
+        let RecordParts::Dna(mut dna) = record.into_parts() else {
+            panic!("expected DNA parts");
+        };
+        // the description is moved, not copied
+        assert_eq!(dna.description.as_ptr(), ptr);
+        assert_eq!(dna.description, "Tandem repeat");
+
+        dna.strand = Strand::Negative;
+        let changed = DNARecord::from_parts(dna);
+        assert_eq!(changed.strand(), Strand::Negative);
+        assert_eq!(changed.description.as_ptr(), ptr);
+        assert_eq!(
+            Record::from_parts(parts),
+            Record::Dna(crate::reader::parse_dna_line(line).unwrap())
+        );
+
+        let line = "sp|P29082|SOR_ACIAM  -          SOR                  PF07682.13  1.5e-152  492.8   0.8  1.7e-152  492.6   0.8   1.0   1   0   0   1   1   1   1 Sulfur oxygenase/reductase";
+        let protein = crate::reader::parse_protein_line(line).unwrap();
+        let ptr = protein.description.as_ptr();
+        let parts = protein.clone().into_parts();
+        assert_eq!(ProteinRecord::from_parts(parts), protein);
+        assert_eq!(protein.into_parts().description.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn test_to_fields() {
+        use crate::{
+            reader::{parse_dna_line, parse_protein_line},
+            tsv::field_names,
+        };
+
+        let dna = parse_dna_line("chr1                 -          TR                   -                2     317    338944    339292    338943    339292 133811386    +     1.9e-37  136.7   1.4  Tandem  repeat").unwrap();
+        let fields = dna.to_fields();
+        assert_eq!(fields.len(), field_names(RecordKind::Dna).len());
+        assert_eq!(fields[12], "1.9e-37");
+        assert_eq!(fields[15], "Tandem  repeat");
+        assert_eq!(parse_dna_line(&fields.join(" ")).unwrap(), dna);
+
+        let protein = Record::Protein(parse_protein_line("sp|P29082|SOR_ACIAM  -          SOR                  PF07682.13  1.5e-152  492.8   0.8  1.7e-152  492.6   0.8   1.0   1   0   0   1   1   1   1 Sulfur oxygenase/reductase").unwrap());
+        let fields = protein.to_fields();
+        assert_eq!(fields.len(), field_names(RecordKind::Protein).len());
+        assert_eq!(
+            Record::Protein(parse_protein_line(&fields.join(" ")).unwrap()),
+            protein
+        );
+    }
+
+    #[test]
+    fn test_widths_of_other_kind() {
+        let line = "chr1                 -          TR                   -                2     317    338944    339292    338943    339292 133811386    +     1.9e-37  136.7   1.4  -";
+        let record = crate::reader::parse_dna_line(line).unwrap();
+        let dna = crate::Header::for_program(Program::Nhmmer)
+            .unwrap()
+            .calculate_dashes();
+        let protein = crate::Header::for_program(Program::Hmmsearch)
+            .unwrap()
+            .calculate_dashes();
+        assert_eq!((dna.len(), protein.len()), (16, 19));
+
+        // protein widths are ignored for a DNA record
+        let mut out = Vec::new();
+        record.write_to(&mut out, &protein).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), record.to_string());
+        let mut out = Vec::new();
+        record.write_to(&mut out, &dna[..15]).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), record.to_string());
+    }
+
+    #[test]
+    fn test_version() {
+        let v = Version::parse("3.1b2 (February 2015)").unwrap();
+        assert_eq!((v.major(), v.minor(), v.patch()), (3, 1, 0));
+        assert_eq!(v.text(), "b2 (February 2015)");
+        let hmmer = Version::parse("3.4 (Aug 2023)").unwrap();
+        assert!(v < hmmer);
+        assert!(hmmer >= Version::new(3, 2, 0));
+        assert!(v < Version::new(3, 2, 0));
+
+        // Infernal has a patch number
+        let infernal = Version::parse("1.1.5 (Sep 2023)").unwrap();
+        assert_eq!(
+            (infernal.major(), infernal.minor(), infernal.patch()),
+            (1, 1, 5)
+        );
+        assert_eq!(infernal.text(), "(Sep 2023)");
+        assert!(infernal > Version::new(1, 1, 4));
+
+        // a beta cannot be ordered against the release
+        let release = Version::parse("3.1").unwrap();
+        assert_eq!(v.partial_cmp(&release), None);
+        assert_eq!(release, Version::new(3, 1, 0));
+
+        for s in ["", "unknown", "3", "3.", "v3.4", ".3.4"] {
+            assert_eq!(Version::parse(s), None, "{}", s);
+        }
+
+        let mut meta = Meta::default();
+        assert_eq!(meta.version_parsed(), None);
+        meta.set_version("3.4 (Aug 2023)".into());
+        assert_eq!(meta.version_parsed(), Some(hmmer));
+    }
+
+    #[test]
+    fn test_program_from_str() {
+        let parse = |s: &str| Program::from_str(s).unwrap();
+        assert_eq!(parse("nhmmer"), Program::Nhmmer);
+        assert_eq!(parse("/usr/local/bin/nhmmer"), Program::Nhmmer);
+        assert_eq!(parse("C:\\hmmer\\bin\\hmmscan"), Program::Hmmscan);
+        assert_eq!(parse("NHMMER"), Program::Nhmmer);
+        assert_eq!(parse("/opt/HMMER/bin/HmmSearch"), Program::Hmmsearch);
+        assert_eq!(parse("nhmmer3.4"), Program::Nhmmer);
+        assert_eq!(parse("jackhmmer-3.1b2"), Program::Jackhmmer);
+        assert_eq!(parse("phmmer (hmmer 3.4)"), Program::Phmmer);
+        assert_eq!(parse("/usr/bin/NHMMSCAN 3.2"), Program::Nhmmscan);
+
+        let err = Program::from_str("blastn").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "metadata error - The program \"blastn\" is not supported."
+        );
+        assert!(Program::from_str("/usr/bin/").is_err());
+
+        // written back as HMMER writes it
+        assert_eq!(parse("/usr/local/bin/NHMMER3.4").to_string(), "nhmmer");
+    }
+
+    #[test]
+    fn test_program_predicates() {
+        // (program, dna, protein, scan, kind), listing every variant
+        let programs = [
+            (Program::None, false, false, false, None),
+            (Program::Nhmmer, true, false, false, Some(RecordKind::Dna)),
+            (Program::Nhmmscan, true, false, true, Some(RecordKind::Dna)),
+            (
+                Program::Jackhmmer,
+                false,
+                true,
+                false,
+                Some(RecordKind::Protein),
+            ),
+            (
+                Program::Hmmscan,
+                false,
+                true,
+                true,
+                Some(RecordKind::Protein),
+            ),
+            (
+                Program::Hmmsearch,
+                false,
+                true,
+                false,
+                Some(RecordKind::Protein),
+            ),
+            (
+                Program::Phmmer,
+                false,
+                true,
+                false,
+                Some(RecordKind::Protein),
+            ),
+        ];
+        for (program, dna, protein, scan, kind) in programs {
+            // fails to compile when a program is added without a row above
+            match program {
+                Program::None
+                | Program::Nhmmer
+                | Program::Nhmmscan
+                | Program::Jackhmmer
+                | Program::Hmmscan
+                | Program::Hmmsearch
+                | Program::Phmmer => {}
+            }
+            assert_eq!(program.is_dna_search(), dna, "{}", program);
+            assert_eq!(program.is_protein_search(), protein, "{}", program);
+            assert_eq!(program.is_scan(), scan, "{}", program);
+            assert_eq!(program.record_kind(), kind, "{}", program);
+        }
+    }
+
+    #[test]
+    fn test_fmt_g() {
+        // fixed notation, without trailing zeros
+        assert_eq!(fmt_g(0.23, 2), "0.23");
+        assert_eq!(fmt_g(0.00041, 2), "0.00041");
+        assert_eq!(fmt_g(1.6, 2), "1.6");
+        assert_eq!(fmt_g(5.0, 2), "5");
+        assert_eq!(fmt_g(0.3, 2), "0.3");
+        assert_eq!(fmt_g(12.0, 2), "12");
+        // scientific notation for small and large values
+        assert_eq!(fmt_g(1.9e-37, 2), "1.9e-37");
+        assert_eq!(fmt_g(2e-74, 2), "2e-74");
+        assert_eq!(fmt_g(123.0, 2), "1.2e02");
+        // rounding can move a value into scientific notation
+        assert_eq!(fmt_g(99.7, 2), "1e02");
+        assert_eq!(fmt_g(0.000099996, 2), "0.0001");
+        // other numbers of significant figures
+        assert_eq!(fmt_g(230.40000915527344, 4), "230.4");
+        assert_eq!(fmt_g(0.30000001, 6), "0.3");
+        assert_eq!(fmt_g(1234.5, 1), "1e03");
+        assert_eq!(fmt_g(-0.5, 2), "-0.5");
+        assert_eq!(fmt_g(0.0, 2), "0");
+        assert_eq!(fmt_g(f64::INFINITY, 2), "inf");
+        assert_eq!(fmt_g(f64::NAN, 2), "nan");
+    }
+
+    #[test]
+    fn test_fmt_g_exponents() {
+        // one, two and three digit exponents
+        assert_eq!(fmt_g(1e-5, 2), "1e-05");
+        assert_eq!(fmt_g(6.6e-7, 2), "6.6e-07");
+        assert_eq!(fmt_g(1.9e-37, 2), "1.9e-37");
+        assert_eq!(fmt_g(3.2e-105, 2), "3.2e-105");
+        // positive exponents have no sign
+        assert_eq!(fmt_g(4.5e7, 2), "4.5e07");
+        assert_eq!(fmt_g(2e12, 2), "2e12");
+        assert_eq!(fmt_g(1.5e300, 2), "1.5e300");
+        assert_eq!(fmt_g(0.0, 2), "0");
+
+        // either style is read
+        assert_eq!(
+            "1e-05".parse::<f64>().unwrap(),
+            "1e-5".parse::<f64>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_write_score() {
+        let mut values = vec![
+            0.0, -0.0, 0.05, 0.15, 0.25, 0.35, -0.04, -0.25, 1.25, 2.5, 99.95, 136.7, 1e14, -3e9,
+        ];
+        values.extend((0..20_000).map(|i| i as f32 * 0.0137 - 50.0));
+        for value in values {
+            assert_eq!(fmt_score(value, FloatStyle::Hmmer), format!("{:.1}", value));
+            let mut s = Vec::new();
+            write_score(&mut s, value, 8, FloatStyle::Hmmer).unwrap();
+            assert_eq!(String::from_utf8(s).unwrap(), format!("{:>8.1}", value));
         }
+        assert_eq!(fmt_score(f32::NAN, FloatStyle::Hmmer), "NaN");
+        assert_eq!(fmt_score(0.3, FloatStyle::Raw), "0.3");
+    }
+
+    #[test]
+    fn test_display_precision() {
+        let record = DNARecord::new(
+            "chr1".to_string(),
+            "-".to_string(),
+            "TR".to_string(),
+            "-".to_string(),
+            1,
+            100,
+            1000,
+            1099,
+            1000,
+            1099,
+            5000,
+            Strand::Positive,
+            0.30000001,
+            230.4,
+            0.3,
+        );
+        assert_eq!(
+            record.to_string(),
+            "chr1                 -          TR                   -                1     100    1000    1099    1000    1099    5000    +         0.3  230.4   0.3  -"
+        );
     }
 }
@@ -0,0 +1,973 @@
+//! Small tables of the best hits for each query, and summaries of
+//! each query's hits, of each model's, of what each query reported
+//! and included, and of the lengths of the hits, for run summaries.
+
+use crate::{
+    coverage::union_length,
+    merge::e_value,
+    ord::cmp_by_evalue,
+    record::{write_column, Record, TbloutRecord},
+    Result,
+};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::Write,
+};
+
+/// HMMER's default inclusion E-value, `--incE`.
+const INCLUSION_E_VALUE: f64 = 0.01;
+
+/// The column names of a report table.
+const COLUMNS: [&str; 6] = [
+    "query",
+    "target",
+    "E-value",
+    "score",
+    "coordinates",
+    "description",
+];
+
+/// Write the best `n_per_query` hits by E-value for each query as a
+/// GitHub flavored markdown table. Descriptions are cut down to
+/// `description_width` characters. Queries are listed in the order
+/// they are first seen.
+///
+/// Only the best hits for each query are held in memory, so records
+/// can be streamed straight from a reader.
+pub fn top_hits_markdown<W: Write, R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    n_per_query: usize,
+    description_width: usize,
+    mut w: W,
+) -> Result<()> {
+    let rows = top_hits(records, n_per_query, description_width)?;
+    writeln!(w, "| {} |", COLUMNS.join(" | "))?;
+    writeln!(w, "|---|---|--:|--:|---|---|")?;
+    for row in rows {
+        let row = row
+            .iter()
+            .map(|f| f.replace('|', "\\|"))
+            .collect::<Vec<_>>();
+        writeln!(w, "| {} |", row.join(" | "))?;
+    }
+    Ok(())
+}
+
+/// Write the best `n_per_query` hits by E-value for each query as a
+/// plain text table, with the columns padded to line up. Otherwise
+/// the same as `top_hits_markdown`.
+pub fn top_hits_text<W: Write, R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    n_per_query: usize,
+    description_width: usize,
+    mut w: W,
+) -> Result<()> {
+    let rows = top_hits(records, n_per_query, description_width)?;
+    let header = COLUMNS.map(String::from).to_vec();
+    let mut widths = COLUMNS.map(|c| c.chars().count());
+    for row in &rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+
+    let dashes = widths.map(|n| "-".repeat(n)).to_vec();
+    for row in [&header, &dashes].into_iter().chain(&rows) {
+        let mut line = Vec::new();
+        for (i, (field, width)) in row.iter().zip(widths).enumerate() {
+            if i > 0 {
+                line.extend_from_slice(b"  ");
+            }
+            // names are left justified, numbers right justified
+            write_column(&mut line, field, width, !matches!(i, 2 | 3))?;
+        }
+        let end = line.iter().rposition(|&b| b != b' ').map_or(0, |i| i + 1);
+        w.write_all(&line[..end])?;
+        w.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Fold the records of each query into a value, starting from the
+/// default. Returns the queries in the order they are first seen, with
+/// their values, so records for a query need not be together.
+fn fold_by_query<R: TbloutRecord, S: Default>(
+    records: impl IntoIterator<Item = Result<R>>,
+    mut f: impl FnMut(&mut S, R),
+) -> Result<Vec<(String, S)>> {
+    let mut queries: Vec<String> = Vec::new();
+    let mut values: HashMap<String, S> = HashMap::new();
+    for record in records {
+        let record = record?;
+        let value = match values.get_mut(&record.query_name()) {
+            Some(value) => value,
+            None => {
+                queries.push(record.query_name());
+                values.entry(record.query_name()).or_default()
+            }
+        };
+        f(value, record);
+    }
+    Ok(queries
+        .into_iter()
+        .map(|query| {
+            let value = values.remove(&query).unwrap_or_default();
+            (query, value)
+        })
+        .collect())
+}
+
+/// Collect the best hits for each query as rows of formatted fields.
+fn top_hits<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    n_per_query: usize,
+    description_width: usize,
+) -> Result<Vec<Vec<String>>> {
+    let best = fold_by_query(records, |hits: &mut Vec<R>, record| {
+        // keep the hits sorted, with ties in input order
+        let i = hits.partition_point(|h| cmp_by_evalue(h, &record) != Ordering::Greater);
+        if i < n_per_query {
+            hits.insert(i, record);
+            hits.truncate(n_per_query);
+        }
+    })?;
+
+    let mut rows = Vec::new();
+    for (query, hits) in best {
+        for hit in &hits {
+            rows.push(vec![
+                query.clone(),
+                hit.target_name(),
+                format!("{:.1e}", e_value(hit)),
+                format!("{:.1}", hit.score_opt().unwrap_or_default()),
+                coordinates(hit),
+                truncate(&hit.description(), description_width),
+            ]);
+        }
+    }
+    Ok(rows)
+}
+
+/// The alignment coordinates of a hit as `from-to`, or `-` if it has
+/// none.
+fn coordinates<R: TbloutRecord>(hit: &R) -> String {
+    match hit.interval_opt() {
+        Some((from, to)) => format!("{}-{}", from, to),
+        None => "-".to_string(),
+    }
+}
+
+/// How `per_query_summary` lays out each query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummaryLayout {
+    /// A block of labelled lines for each query, with a blank line
+    /// between blocks.
+    Block,
+    /// A row for each query in a tab separated table, after a row of
+    /// column names.
+    Row,
+}
+
+/// Options for `per_query_summary`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SummaryOptions {
+    /// How each query is laid out.
+    layout: SummaryLayout,
+    /// The E-value at or below which a hit is included.
+    inclusion_e_value: f64,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> SummaryOptions {
+        SummaryOptions {
+            layout: SummaryLayout::Block,
+            inclusion_e_value: INCLUSION_E_VALUE,
+        }
+    }
+}
+
+impl SummaryOptions {
+    /// Set how each query is laid out, `SummaryLayout::Block` by
+    /// default.
+    pub fn layout(mut self, layout: SummaryLayout) -> SummaryOptions {
+        self.layout = layout;
+        self
+    }
+
+    /// Set the E-value at or below which a hit counts as included.
+    /// Protein records say which hits HMMER included (see
+    /// `TbloutRecord::included_opt`), but DNA records don't, so their
+    /// inclusion is judged again here. The default of 0.01 is HMMER's
+    /// default `--incE`.
+    pub fn inclusion_e_value(mut self, e_value: f64) -> SummaryOptions {
+        self.inclusion_e_value = e_value;
+        self
+    }
+}
+
+/// The aggregates of one query for `per_query_summary`.
+struct Summary<R> {
+    hits: u64,
+    included: u64,
+    /// The first hit with the lowest E-value.
+    best: Option<R>,
+    /// The alignments on each target.
+    intervals: HashMap<String, Vec<(i64, i64)>>,
+}
+
+impl<R> Default for Summary<R> {
+    fn default() -> Summary<R> {
+        Summary {
+            hits: 0,
+            included: 0,
+            best: None,
+            intervals: HashMap::new(),
+        }
+    }
+}
+
+/// Write a summary of the hits of each query: the number of hits, the
+/// number included (see `SummaryOptions::inclusion_e_value`), the best
+/// hit by E-value, and the number of bases of the targets covered by
+/// the hits, counting overlaps once as `coverage_per_target` does.
+/// Protein records have no coordinates, so their bases covered are
+/// `-`. Queries are listed in the order they are first seen.
+///
+/// The records are read once, holding the best hit and the alignment
+/// coordinates of each query rather than every record.
+pub fn per_query_summary<W: Write, R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    mut w: W,
+    options: &SummaryOptions,
+) -> Result<()> {
+    let summaries = fold_by_query(records, |summary: &mut Summary<R>, record| {
+        summary.hits += 1;
+        if included(&record, options.inclusion_e_value) {
+            summary.included += 1;
+        }
+        if let Some((from, to)) = record.interval_opt() {
+            summary
+                .intervals
+                .entry(record.target_name())
+                .or_default()
+                .push((from.min(to), from.max(to)));
+        }
+        let better = match &summary.best {
+            Some(best) => e_value(&record) < e_value(best),
+            None => true,
+        };
+        if better {
+            summary.best = Some(record);
+        }
+    })?;
+
+    if options.layout == SummaryLayout::Row {
+        writeln!(
+            w,
+            "query\thits\tincluded\tbest_target\tbest_coordinates\tbest_e_value\tbest_score\tbases_covered"
+        )?;
+    }
+    for (i, (query, summary)) in summaries.into_iter().enumerate() {
+        // every query has a hit, so a best one
+        let best = summary.best.as_ref().expect("a query with hits");
+        let best_e_value = format!("{:.1e}", e_value(best));
+        let best_score = format!("{:.1}", best.score_opt().unwrap_or_default());
+        let covered = match summary.intervals.is_empty() {
+            true => "-".to_string(),
+            false => summary
+                .intervals
+                .into_values()
+                .map(union_length)
+                .sum::<u64>()
+                .to_string(),
+        };
+        match options.layout {
+            SummaryLayout::Block => {
+                if i > 0 {
+                    writeln!(w)?;
+                }
+                writeln!(w, "Query:          {}", query)?;
+                writeln!(w, "Hits:           {}", summary.hits)?;
+                writeln!(w, "Included:       {}", summary.included)?;
+                writeln!(
+                    w,
+                    "Best hit:       {} {} E-value {} score {}",
+                    best.target_name(),
+                    coordinates(best),
+                    best_e_value,
+                    best_score
+                )?;
+                writeln!(w, "Bases covered:  {}", covered)?;
+            }
+            SummaryLayout::Row => writeln!(
+                w,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                query,
+                summary.hits,
+                summary.included,
+                best.target_name(),
+                coordinates(best),
+                best_e_value,
+                best_score,
+                covered
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Whether a hit is included: as the record says, or for records
+/// which don't, if its E-value is at most `threshold`.
+fn included<R: TbloutRecord>(record: &R, threshold: f64) -> bool {
+    record
+        .included_opt()
+        .unwrap_or_else(|| e_value(record) <= threshold)
+}
+
+/// The hits of one query (model), as `per_model_table` counts them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSummary {
+    /// The name of the query.
+    pub query_name: String,
+    /// The number of hits.
+    pub hits: u64,
+    /// The number of hits included, as HMMER's inclusion threshold
+    /// would have.
+    pub included: u64,
+    /// The highest bit score of the hits.
+    pub best_score: f32,
+    /// The lowest E-value of the hits.
+    pub best_e_value: f64,
+    /// The total length of the alignments on the targets, overlaps
+    /// counted each time, or `None` for records without coordinates.
+    pub aligned_length: Option<u64>,
+}
+
+/// The column names of the model table, as `write_model_table` writes
+/// them.
+const MODEL_COLUMNS: [&str; 6] = [
+    "query",
+    "hits",
+    "included",
+    "best_score",
+    "best_e_value",
+    "aligned_length",
+];
+
+/// A row of the model table, tab separated, with `-` for an aligned
+/// length which isn't known.
+impl Display for ModelSummary {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{:.1}\t{:.1e}\t",
+            self.query_name, self.hits, self.included, self.best_score, self.best_e_value
+        )?;
+        match self.aligned_length {
+            Some(length) => write!(f, "{}", length),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+/// Count the hits of each query (model), to see which profiles of a
+/// library found anything. Returns a summary for each query, the most
+/// hits first, with ties in the order the queries are first seen.
+/// The query is the model for searches such as `hmmsearch` and
+/// `nhmmer`; for scans it is the sequence.
+///
+/// A hit is included if the record says so, as protein records do, or
+/// otherwise if its E-value is at most HMMER's default inclusion
+/// threshold of 0.01. The records are read once, holding only the
+/// counts for each query, so they needn't be grouped by query.
+pub fn per_model_table<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+) -> Result<Vec<ModelSummary>> {
+    let models = fold_by_query(records, |summary: &mut Option<ModelSummary>, record| {
+        let summary = summary.get_or_insert_with(|| ModelSummary {
+            query_name: record.query_name(),
+            hits: 0,
+            included: 0,
+            best_score: f32::NEG_INFINITY,
+            best_e_value: f64::INFINITY,
+            aligned_length: None,
+        });
+        summary.hits += 1;
+        if included(&record, INCLUSION_E_VALUE) {
+            summary.included += 1;
+        }
+        if let Some(score) = record.score_opt() {
+            summary.best_score = summary.best_score.max(score);
+        }
+        summary.best_e_value = summary.best_e_value.min(e_value(&record));
+        if let Some(length) = alignment_length(&record) {
+            *summary.aligned_length.get_or_insert(0) += length;
+        }
+    })?;
+    let mut table = models
+        .into_iter()
+        .filter_map(|(_, summary)| summary)
+        .collect::<Vec<_>>();
+    // stable, so ties keep their order
+    table.sort_by_key(|s| Reverse(s.hits));
+    Ok(table)
+}
+
+/// Write the model table from `per_model_table` as tab separated
+/// values, after a row of column names.
+pub fn write_model_table<W: Write>(table: &[ModelSummary], mut w: W) -> Result<()> {
+    writeln!(w, "{}", MODEL_COLUMNS.join("\t"))?;
+    for summary in table {
+        writeln!(w, "{}", summary)?;
+    }
+    Ok(())
+}
+
+/// What a query reported and included, as `inclusion_summary` counts
+/// them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct InclusionCounts {
+    /// The number of records (hits) of the query.
+    pub hits: u64,
+    /// The number reported: domains for protein records, hits for DNA.
+    pub reported: u64,
+    /// The number included: domains for protein records, hits for DNA.
+    pub included: u64,
+}
+
+/// Count what each query reported and included, to compare the runs
+/// of different programs.
+///
+/// **The counts mean different things for each kind of record.**
+/// HMMER's thresholds work the same way for all of them, but the files
+/// say different things about which hits met them:
+///
+/// - Protein records give the number of domains of each hit which were
+///   reported and included, the `rep` and `inc` columns, and these are
+///   summed. They count domains, not hits, so can be more than `hits`.
+/// - DNA records say nothing of inclusion, so every hit counts as
+///   reported, and those with an E-value at most
+///   `dna_inclusion_e_value` as included. HMMER's default `--incE` is
+///   0.01, but the threshold of the search can't be read from the file,
+///   so it must be given.
+///
+/// `cmsearch` and `cmscan` files, which flag included hits with `!`,
+/// are not read by this crate.
+pub fn inclusion_summary(
+    records: impl IntoIterator<Item = Result<Record>>,
+    dna_inclusion_e_value: f64,
+) -> Result<HashMap<String, InclusionCounts>> {
+    let mut counts: HashMap<String, InclusionCounts> = HashMap::new();
+    for record in records {
+        let record = record?;
+        let query = counts.entry(record.query_name()).or_default();
+        query.hits += 1;
+        match &record {
+            Record::Protein(r) => {
+                // never negative in HMMER's output
+                query.reported += r.rep().max(0) as u64;
+                query.included += r.inc().max(0) as u64;
+            }
+            Record::Dna(r) => {
+                query.reported += 1;
+                if r.e_value() <= dna_inclusion_e_value {
+                    query.included += 1;
+                }
+            }
+        }
+    }
+    Ok(counts)
+}
+
+/// Options for `length_distribution`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthOptions {
+    /// Whether to give the lengths of each query, or of all hits.
+    per_query: bool,
+    /// The length below which a hit counts as short.
+    short_threshold: u64,
+}
+
+impl LengthOptions {
+    /// Options counting hits shorter than `short_threshold` bases as
+    /// short, with the lengths of all hits together.
+    pub fn new(short_threshold: u64) -> LengthOptions {
+        LengthOptions {
+            per_query: false,
+            short_threshold,
+        }
+    }
+
+    /// Give the lengths of the hits of each query (model) apart, rather
+    /// than of all the hits together.
+    pub fn per_query(mut self, yes: bool) -> LengthOptions {
+        self.per_query = yes;
+        self
+    }
+}
+
+/// The distribution of the lengths of a set of hits, as
+/// `length_distribution` finds it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LengthStats {
+    /// The name of the query, or `None` for the hits of all queries.
+    pub query_name: Option<String>,
+    /// The number of hits.
+    pub hits: u64,
+    /// The shortest length.
+    pub min: u64,
+    /// The lower quartile.
+    pub q25: f64,
+    /// The median length.
+    pub median: f64,
+    /// The upper quartile.
+    pub q75: f64,
+    /// The longest length.
+    pub max: u64,
+    /// The mean length.
+    pub mean: f64,
+    /// The fraction of the hits shorter than
+    /// `LengthOptions::new`'s threshold.
+    pub short_fraction: f64,
+}
+
+/// The column names of the length table, as `write_length_table`
+/// writes them.
+const LENGTH_COLUMNS: [&str; 9] = [
+    "query",
+    "hits",
+    "min",
+    "q25",
+    "median",
+    "q75",
+    "max",
+    "mean",
+    "short_fraction",
+];
+
+/// A row of the length table, tab separated, with `-` for the query
+/// of all hits.
+impl Display for LengthStats {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}\t{}\t{}\t{:.1}\t{:.1}\t{:.1}\t{}\t{:.1}\t{:.3}",
+            self.query_name.as_deref().unwrap_or("-"),
+            self.hits,
+            self.min,
+            self.q25,
+            self.median,
+            self.q75,
+            self.max,
+            self.mean,
+            self.short_fraction
+        )
+    }
+}
+
+impl LengthStats {
+    /// The statistics of a set of lengths, which must not be empty.
+    fn new(query_name: Option<String>, mut lengths: Vec<u64>, short_threshold: u64) -> LengthStats {
+        lengths.sort_unstable();
+        let n = lengths.len();
+        let short = lengths.partition_point(|&l| l < short_threshold);
+        LengthStats {
+            query_name,
+            hits: n as u64,
+            min: lengths[0],
+            q25: quantile(&lengths, 0.25),
+            median: quantile(&lengths, 0.5),
+            q75: quantile(&lengths, 0.75),
+            max: lengths[n - 1],
+            mean: lengths.iter().sum::<u64>() as f64 / n as f64,
+            short_fraction: short as f64 / n as f64,
+        }
+    }
+}
+
+/// The `p` quantile of sorted values, interpolating linearly between
+/// the closest two as R's and numpy's defaults do.
+fn quantile(sorted: &[u64], p: f64) -> f64 {
+    let h = (sorted.len() - 1) as f64 * p;
+    let lo = h.floor() as usize;
+    let hi = h.ceil() as usize;
+    sorted[lo] as f64 + (h - lo as f64) * (sorted[hi] as f64 - sorted[lo] as f64)
+}
+
+/// Find the distribution of the lengths of the hits on their targets:
+/// the quartiles, extremes and mean, and the fraction of short hits,
+/// which many of suggest fragmented annotations. Lengths count the
+/// bases of the alignment on either strand. Records without
+/// coordinates, i.e. protein records, are skipped.
+///
+/// With `LengthOptions::per_query`, there are statistics for each
+/// query with hits, in the order the queries are first seen; otherwise
+/// there are statistics of all the hits, or none if there are no hits.
+/// The quantiles are exact, so every length is held, 8 bytes a hit.
+pub fn length_distribution<R: TbloutRecord>(
+    records: impl IntoIterator<Item = Result<R>>,
+    options: &LengthOptions,
+) -> Result<Vec<LengthStats>> {
+    let threshold = options.short_threshold;
+    if !options.per_query {
+        let mut lengths = Vec::new();
+        for record in records {
+            if let Some(length) = alignment_length(&record?) {
+                lengths.push(length);
+            }
+        }
+        return Ok(match lengths.is_empty() {
+            true => Vec::new(),
+            false => vec![LengthStats::new(None, lengths, threshold)],
+        });
+    }
+
+    let lengths = fold_by_query(records, |lengths: &mut Vec<u64>, record| {
+        if let Some(length) = alignment_length(&record) {
+            lengths.push(length);
+        }
+    })?;
+    Ok(lengths
+        .into_iter()
+        .filter(|(_, lengths)| !lengths.is_empty())
+        .map(|(query, lengths)| LengthStats::new(Some(query), lengths, threshold))
+        .collect())
+}
+
+/// Write the length table from `length_distribution` as tab separated
+/// values, after a row of column names.
+pub fn write_length_table<W: Write>(table: &[LengthStats], mut w: W) -> Result<()> {
+    writeln!(w, "{}", LENGTH_COLUMNS.join("\t"))?;
+    for stats in table {
+        writeln!(w, "{}", stats)?;
+    }
+    Ok(())
+}
+
+/// The number of bases of a hit's alignment on its target, whichever
+/// strand it is on, or `None` if it has no coordinates.
+fn alignment_length<R: TbloutRecord>(hit: &R) -> Option<u64> {
+    hit.interval_opt().map(|(from, to)| from.abs_diff(to) + 1)
+}
+
+/// Cut a string down to `width` characters, ending with `…` if cut.
+fn truncate(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        s.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        let mut out: String = s.chars().take(width - 1).collect();
+        out.push('…');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader, Record};
+
+    fn records() -> impl Iterator<Item = Result<Record>> {
+        Reader::from_path("./data/test.tbl").unwrap().into_records()
+    }
+
+    #[test]
+    fn test_top_hits_markdown() {
+        let mut out = Vec::new();
+        top_hits_markdown(records(), 3, 20, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "| query | target | E-value | score | coordinates | description |\n",
+                "|---|---|--:|--:|---|---|\n",
+                "| TR | 2 | 1.9e-37 | 136.7 | 338944-339292 | unmasked:chromosome… |\n",
+                "| TR | 6 | 4.7e-33 | 122.3 | 61501865-61501510 | unmasked:chromosome… |\n",
+                "| TR | 6 | 4.9e-16 | 66.5 | 62615451-62615652 | unmasked:chromosome… |\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_top_hits_text() {
+        let mut out = Vec::new();
+        top_hits_text(records(), 3, 20, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "query  target  E-value  score  coordinates        description\n",
+                "-----  ------  -------  -----  -----------------  --------------------\n",
+                "TR     2       1.9e-37  136.7  338944-339292      unmasked:chromosome…\n",
+                "TR     6       4.7e-33  122.3  61501865-61501510  unmasked:chromosome…\n",
+                "TR     6       4.9e-16   66.5  62615451-62615652  unmasked:chromosome…\n",
+            )
+        );
+    }
+
+    /// Two queries, the second of which has no hits with an E-value
+    /// under 0.01, with their hits interleaved.
+    fn two_queries() -> impl Iterator<Item = Result<Record>> {
+        Reader::from_path("./data/two_queries.tbl")
+            .unwrap()
+            .into_records()
+    }
+
+    #[test]
+    fn test_per_query_summary_block() {
+        let mut out = Vec::new();
+        per_query_summary(two_queries(), &mut out, &SummaryOptions::default()).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "Query:          TR\n",
+                "Hits:           4\n",
+                "Included:       4\n",
+                "Best hit:       2 338944-339292 E-value 1.9e-37 score 136.7\n",
+                "Bases covered:  1137\n",
+                "\n",
+                "Query:          TR2\n",
+                "Hits:           2\n",
+                "Included:       0\n",
+                "Best hit:       6 46018286-46018364 E-value 9.2e0 score 13.1\n",
+                "Bases covered:  184\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_per_query_summary_row() {
+        let options = SummaryOptions::default()
+            .layout(SummaryLayout::Row)
+            .inclusion_e_value(1e-20);
+        let mut out = Vec::new();
+        per_query_summary(two_queries(), &mut out, &options).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "query\thits\tincluded\tbest_target\tbest_coordinates\tbest_e_value\tbest_score\tbases_covered\n",
+                "TR\t4\t2\t2\t338944-339292\t1.9e-37\t136.7\t1137\n",
+                "TR2\t2\t0\t6\t46018286-46018364\t9.2e0\t13.1\t184\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_per_query_summary_protein() {
+        let records = Reader::from_path("./data/corpus/hmmsearch.tbl")
+            .unwrap()
+            .into_records();
+        let options = SummaryOptions::default().layout(SummaryLayout::Row);
+        let mut out = Vec::new();
+        per_query_summary(records, &mut out, &options).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        // no coordinates, so no bases covered
+        assert!(out.lines().count() > 1);
+        for line in out.lines().skip(1) {
+            let fields = line.split('\t').collect::<Vec<_>>();
+            assert_eq!(fields[4], "-");
+            assert_eq!(fields[7], "-");
+        }
+    }
+
+    #[test]
+    fn test_per_model_table_dna() {
+        let table = per_model_table(two_queries()).unwrap();
+        assert_eq!(
+            table,
+            vec![
+                ModelSummary {
+                    query_name: "TR".to_string(),
+                    hits: 4,
+                    included: 4,
+                    best_score: 136.7,
+                    best_e_value: 1.9e-37,
+                    aligned_length: Some(349 + 356 + 202 + 230),
+                },
+                ModelSummary {
+                    query_name: "TR2".to_string(),
+                    hits: 2,
+                    included: 0,
+                    best_score: 13.1,
+                    best_e_value: 9.2,
+                    aligned_length: Some(79 + 105),
+                },
+            ]
+        );
+
+        let mut out = Vec::new();
+        write_model_table(&table, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "query\thits\tincluded\tbest_score\tbest_e_value\taligned_length\n",
+                "TR\t4\t4\t136.7\t1.9e-37\t1137\n",
+                "TR2\t2\t0\t13.1\t9.2e0\t184\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_per_model_table_protein() {
+        // two hits of the second query have no domains included
+        let records = Reader::from_path("./data/mixed_inc.tbl")
+            .unwrap()
+            .into_records();
+        let table = per_model_table(records).unwrap();
+        let rows = table.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                "CMIL_MAD||KEN1/4-29\t8\t8\t66.2\t9.6e-21\t-",
+                "CMIL_MAD||CDII/858-891\t4\t2\t32.2\t6.4e-10\t-",
+            ]
+        );
+    }
+
+    /// A record for a hit of `query` from `from` to `to`.
+    fn hit(query: &str, from: i64, to: i64) -> Result<Record> {
+        let strand = if from <= to { "+" } else { "-" };
+        let line = format!(
+            "chr1 - {} - 2 317 {} {} {} {} 133811386 {} 1.9e-37 136.7 1.4 -",
+            query, from, to, from, to, strand
+        );
+        Ok(Record::Dna(parse_dna_line(&line).unwrap()))
+    }
+
+    /// Hits of lengths 10 to 50 for query A, one on the minus strand,
+    /// and a single hit of length 7 for query B.
+    fn lengths() -> Vec<Result<Record>> {
+        vec![
+            hit("A", 1, 30),
+            hit("B", 101, 107),
+            hit("A", 1, 10),
+            hit("A", 140, 101),
+            hit("A", 1, 50),
+            hit("A", 1, 20),
+        ]
+    }
+
+    #[test]
+    fn test_length_distribution_per_query() {
+        let options = LengthOptions::new(25).per_query(true);
+        let table = length_distribution(lengths(), &options).unwrap();
+        assert_eq!(
+            table,
+            vec![
+                LengthStats {
+                    query_name: Some("A".to_string()),
+                    hits: 5,
+                    min: 10,
+                    q25: 20.0,
+                    median: 30.0,
+                    q75: 40.0,
+                    max: 50,
+                    mean: 30.0,
+                    short_fraction: 0.4,
+                },
+                LengthStats {
+                    query_name: Some("B".to_string()),
+                    hits: 1,
+                    min: 7,
+                    q25: 7.0,
+                    median: 7.0,
+                    q75: 7.0,
+                    max: 7,
+                    mean: 7.0,
+                    short_fraction: 1.0,
+                },
+            ]
+        );
+
+        let mut out = Vec::new();
+        write_length_table(&table, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                "query\thits\tmin\tq25\tmedian\tq75\tmax\tmean\tshort_fraction\n",
+                "A\t5\t10\t20.0\t30.0\t40.0\t50\t30.0\t0.400\n",
+                "B\t1\t7\t7.0\t7.0\t7.0\t7\t7.0\t1.000\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_length_distribution_all() {
+        // lengths 7, 10, 20, 30, 40 and 50, interpolated between
+        let table = length_distribution(lengths(), &LengthOptions::new(25)).unwrap();
+        assert_eq!(table.len(), 1);
+        let stats = &table[0];
+        assert_eq!(stats.query_name, None);
+        assert_eq!((stats.hits, stats.min, stats.max), (6, 7, 50));
+        assert_eq!((stats.q25, stats.median, stats.q75), (12.5, 25.0, 37.5));
+        assert!((stats.mean - 157.0 / 6.0).abs() < 1e-12);
+        assert_eq!(stats.short_fraction, 0.5);
+        assert!(stats
+            .to_string()
+            .starts_with("-\t6\t7\t12.5\t25.0\t37.5\t50\t26.2\t"));
+
+        // protein records have no lengths
+        let reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let table = length_distribution(reader.into_records(), &LengthOptions::new(25)).unwrap();
+        assert!(table.is_empty());
+    }
+
+    #[test]
+    fn test_inclusion_summary_dna() {
+        // TR's four hits are significant, TR2's two are not
+        let reader = Reader::from_path("./data/two_queries.tbl").unwrap();
+        let counts = inclusion_summary(reader.into_records(), 0.01).unwrap();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(
+            counts["TR"],
+            InclusionCounts {
+                hits: 4,
+                reported: 4,
+                included: 4
+            }
+        );
+        assert_eq!(
+            counts["TR2"],
+            InclusionCounts {
+                hits: 2,
+                reported: 2,
+                included: 0
+            }
+        );
+
+        // a threshold between the E-values of 9.2 and 9.9
+        let reader = Reader::from_path("./data/two_queries.tbl").unwrap();
+        let counts = inclusion_summary(reader.into_records(), 9.5).unwrap();
+        assert_eq!(counts["TR2"].included, 1);
+    }
+
+    #[test]
+    fn test_inclusion_summary_protein() {
+        // the rep and inc columns summed, counting domains
+        let reader = Reader::from_path("./data/mixed_inc.tbl").unwrap();
+        let counts = inclusion_summary(reader.into_records(), 0.01).unwrap();
+        assert_eq!(
+            counts["CMIL_MAD||KEN1/4-29"],
+            InclusionCounts {
+                hits: 8,
+                reported: 10,
+                included: 8
+            }
+        );
+        assert_eq!(
+            counts["CMIL_MAD||CDII/858-891"],
+            InclusionCounts {
+                hits: 4,
+                reported: 5,
+                included: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("unmasked:chromosome", 8), "unmaske…");
+        assert_eq!(truncate("short", 8), "short");
+    }
+}
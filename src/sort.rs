@@ -0,0 +1,209 @@
+use crate::{
+    header::Header,
+    ord::{cmp_by_evalue, cmp_by_score},
+    record::{Meta, Record, TbloutRecord},
+    writer::{WritableRecord, Writer},
+    Result,
+};
+use std::{cmp::Ordering, io};
+
+/// The order a `SortedWriter` writes records in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// By E-value of the whole hit, most significant first.
+    EValue,
+    /// By score of the whole hit, highest first.
+    ScoreDesc,
+    /// By target name, then by the start and end of the alignment
+    /// on the target. Records without alignment coordinates, i.e.
+    /// protein records, are ordered by target name alone.
+    Genomic,
+}
+
+impl SortKey {
+    /// Compare two records by this key.
+    fn compare<R: TbloutRecord>(self, a: &R, b: &R) -> Ordering {
+        match self {
+            SortKey::EValue => cmp_by_evalue(a, b),
+            SortKey::ScoreDesc => cmp_by_score(a, b),
+            SortKey::Genomic => a
+                .target_name()
+                .cmp(&b.target_name())
+                .then_with(|| span(a).cmp(&span(b))),
+        }
+    }
+}
+
+/// The alignment coordinates on the target, lowest first, so hits
+/// on either strand sort by where they start on the target.
+fn span<R: TbloutRecord>(record: &R) -> Option<(i64, i64)> {
+    record
+        .interval_opt()
+        .map(|(from, to)| (from.min(to), from.max(to)))
+}
+
+/// A writer which holds records in memory until `finish`, then
+/// writes the header, the records sorted by a `SortKey` and the
+/// metadata in one go.
+///
+/// Records which compare equal keep the order they were written in.
+/// Any kind of record can be sorted, `Record` by default.
+pub struct SortedWriter<W: io::Write, T = Record> {
+    /// The writer the sorted output is written to.
+    inner: Writer<W>,
+    /// The order to write the records in.
+    key: SortKey,
+    /// The header to write, if one has been given.
+    header: Option<Header>,
+    /// The records written so far.
+    records: Vec<T>,
+}
+
+impl<W: io::Write, T: TbloutRecord + WritableRecord + Clone> SortedWriter<W, T> {
+    /// Construct a new sorted writer around a writer.
+    pub fn new(inner: Writer<W>, key: SortKey) -> SortedWriter<W, T> {
+        SortedWriter {
+            inner,
+            key,
+            header: None,
+            records: Vec::new(),
+        }
+    }
+
+    /// Set the header to write before the sorted records.
+    pub fn write_header(&mut self, header: &Header) {
+        self.header = Some(header.clone());
+    }
+
+    /// Add a record to be written.
+    pub fn write_record(&mut self, record: &T) {
+        self.records.push(record.clone());
+    }
+
+    /// Add each of the records to be written.
+    pub fn write_records<'a>(&mut self, records: impl IntoIterator<Item = &'a T>)
+    where
+        T: 'a,
+    {
+        self.records.extend(records.into_iter().cloned());
+    }
+
+    /// The number of records held.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether no records are held.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Sort the records, then write the header, the records and the
+    /// metadata, returning the underlying writer.
+    pub fn finish(mut self, meta: &Meta) -> Result<W> {
+        let key = self.key;
+        self.records.sort_by(|a, b| key.compare(a, b));
+
+        if let Some(header) = &self.header {
+            self.inner.write_header(header)?;
+        }
+        self.inner.write_records(&self.records)?;
+        self.inner.finish(meta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    /// The records of the test file, shuffled deterministically.
+    fn shuffled() -> (Reader<std::fs::File>, Vec<Record>) {
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let mut records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        // 31 records, and 7 is coprime with 31
+        let n = records.len();
+        records = (0..n).map(|i| records[(i * 7) % n].clone()).collect();
+        (reader, records)
+    }
+
+    fn sorted(key: SortKey) -> Vec<Record> {
+        let (reader, records) = shuffled();
+        let mut writer = SortedWriter::new(Writer::to_vec(), key);
+        writer.write_header(reader.header());
+        writer.write_records(&records);
+        assert_eq!(writer.len(), 31);
+        let out = writer.finish(reader.meta()).unwrap();
+
+        let mut reparsed = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(reparsed.meta(), reader.meta());
+        reparsed
+            .records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sort_e_value() {
+        let records = sorted(SortKey::EValue);
+        assert_eq!(records.len(), 31);
+        assert!(records
+            .windows(2)
+            .all(|w| w[0].e_value().unwrap() <= w[1].e_value().unwrap()));
+    }
+
+    #[test]
+    fn test_sort_score() {
+        let records = sorted(SortKey::ScoreDesc);
+        assert!(records
+            .windows(2)
+            .all(|w| w[0].score_full().unwrap() >= w[1].score_full().unwrap()));
+    }
+
+    #[test]
+    fn test_sort_nan_last() {
+        let (_, mut records) = shuffled();
+        if let Record::Dna(r) = &mut records[3] {
+            r.set_score(f32::NAN);
+            r.set_e_value(f64::NAN);
+        }
+        for key in [SortKey::EValue, SortKey::ScoreDesc] {
+            records.sort_by(|a, b| key.compare(a, b));
+            assert!(records[30].score_opt().unwrap().is_nan());
+        }
+    }
+
+    #[test]
+    fn test_sort_genomic() {
+        let records = sorted(SortKey::Genomic);
+        assert!(records
+            .windows(2)
+            .all(|w| { (w[0].target_name(), span(&w[0])) <= (w[1].target_name(), span(&w[1])) }));
+        assert_eq!(records[0].target_name(), "1");
+        assert_eq!(records[30].target_name(), "6");
+    }
+
+    #[test]
+    fn test_sort_concrete_records() {
+        let (reader, records) = shuffled();
+        let dna = records
+            .iter()
+            .map(|r| match r {
+                Record::Dna(r) => r.clone(),
+                Record::Protein(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        let mut writer = SortedWriter::new(Writer::to_vec(), SortKey::Genomic);
+        writer.write_header(reader.header());
+        writer.write_records(&dna);
+        let out = writer.finish(reader.meta()).unwrap();
+        assert_eq!(
+            Reader::from_reader(out.as_slice())
+                .unwrap()
+                .records()
+                .collect::<crate::Result<Vec<_>>>()
+                .unwrap(),
+            sorted(SortKey::Genomic)
+        );
+    }
+}
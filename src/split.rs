@@ -0,0 +1,242 @@
+use crate::{
+    record::{Meta, Program, TbloutRecord},
+    writer::{WritableRecord, Writer},
+    Result,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    path::{Path, PathBuf},
+};
+
+/// The default number of files a `SplitWriter` keeps open.
+const DEFAULT_MAX_OPEN: usize = 64;
+
+/// A writer which routes each record into a file for its query, in
+/// an output directory. Each file gets the header for the program
+/// before its first record, and the metadata when the writer is
+/// finished.
+///
+/// File names come from a function of the query name. Path separators
+/// and other characters which are unsafe in file names are replaced
+/// with `_`, and if two queries end up with the same name, the later
+/// one gets a numbered suffix (`name.2.tbl`), so the same records
+/// written in the same order always give the same files.
+pub struct SplitWriter<F> {
+    /// The directory the files are written to.
+    out_dir: PathBuf,
+    /// The program the records come from.
+    program: Program,
+    /// The metadata written at the end of every file.
+    meta: Meta,
+    /// Names the file for a query.
+    name_fn: F,
+    /// The file for each query seen so far.
+    paths: HashMap<String, PathBuf>,
+    /// The queries seen so far, in the order they were seen.
+    queries: Vec<String>,
+    /// The open writers, keyed by query.
+    open: HashMap<String, Writer<File>>,
+    /// The queries with open writers, least recently used first.
+    lru: VecDeque<String>,
+    /// The most files to keep open at once.
+    max_open: usize,
+}
+
+impl<F: Fn(&str) -> String> SplitWriter<F> {
+    /// Construct a new writer into `out_dir`, which must exist. The
+    /// file for each query is named by `name_fn`, e.g.
+    /// `|q| format!("{}.tbl", q)`.
+    pub fn new<P: AsRef<Path>>(
+        out_dir: P,
+        program: Program,
+        meta_template: Meta,
+        name_fn: F,
+    ) -> SplitWriter<F> {
+        SplitWriter {
+            out_dir: out_dir.as_ref().to_path_buf(),
+            program,
+            meta: meta_template,
+            name_fn,
+            paths: HashMap::new(),
+            queries: Vec::new(),
+            open: HashMap::new(),
+            lru: VecDeque::new(),
+            max_open: DEFAULT_MAX_OPEN,
+        }
+    }
+
+    /// Set the most files to keep open at once (at least one). Files
+    /// closed to stay under the limit are reopened to append to them.
+    pub fn max_open(mut self, max_open: usize) -> SplitWriter<F> {
+        self.max_open = max_open.max(1);
+        self
+    }
+
+    /// Write a record to the file for its query. This may be a `Record`
+    /// or one of the concrete records it wraps.
+    pub fn write_record<T: WritableRecord + TbloutRecord>(&mut self, record: &T) -> Result<()> {
+        self.writer(&record.query_name())?.write_record(record)
+    }
+
+    /// Write each of the records, returning how many were written.
+    pub fn write_records<'a, T: WritableRecord + TbloutRecord + 'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a T>,
+    ) -> Result<usize> {
+        let mut n = 0;
+        for record in records {
+            self.write_record(record)?;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    /// Write the metadata to the end of every file, returning the
+    /// paths of the files in the order their queries were first seen.
+    pub fn finish(mut self) -> Result<Vec<PathBuf>> {
+        for query in std::mem::take(&mut self.queries) {
+            let writer = match self.open.remove(&query) {
+                Some(writer) => writer,
+                None => self.reopen(&query)?,
+            };
+            writer.finish(&self.meta)?;
+            self.queries.push(query);
+        }
+        Ok(self.queries.iter().map(|q| self.paths[q].clone()).collect())
+    }
+
+    /// Get the writer for a query, creating its file or reopening it
+    /// as needed.
+    fn writer(&mut self, query: &str) -> Result<&mut Writer<File>> {
+        if self.open.contains_key(query) {
+            // move to the back of the queue, as the most recently used
+            if let Some(i) = self.lru.iter().position(|q| q == query) {
+                let q = self.lru.remove(i).unwrap();
+                self.lru.push_back(q);
+            }
+        } else {
+            if self.open.len() >= self.max_open {
+                if let Some(oldest) = self.lru.pop_front() {
+                    if let Some(writer) = self.open.remove(&oldest) {
+                        writer.into_inner()?;
+                    }
+                }
+            }
+            let writer = if self.paths.contains_key(query) {
+                self.reopen(query)?
+            } else {
+                let path = self.new_path(query);
+                let writer = Writer::with_program(File::create(&path)?, self.program);
+                self.paths.insert(query.to_string(), path);
+                self.queries.push(query.to_string());
+                writer
+            };
+            self.open.insert(query.to_string(), writer);
+            self.lru.push_back(query.to_string());
+        }
+        Ok(self.open.get_mut(query).unwrap())
+    }
+
+    /// Reopen the file for a query to append to it.
+    fn reopen(&self, query: &str) -> Result<Writer<File>> {
+        let file = OpenOptions::new().append(true).open(&self.paths[query])?;
+        Ok(Writer::resume(file, self.program))
+    }
+
+    /// Choose a path for a new query which no other query is using.
+    fn new_path(&self, query: &str) -> PathBuf {
+        let name = sanitize(&(self.name_fn)(query));
+        let mut path = self.out_dir.join(&name);
+        let mut n = 1;
+        while self.paths.values().any(|p| p == &path) {
+            n += 1;
+            path = self.out_dir.join(numbered(&name, n));
+        }
+        path
+    }
+}
+
+/// Make a file name safe to join to a directory, replacing path
+/// separators and control characters, and refusing `.` and `..`.
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '\0' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match name.as_str() {
+        "" | "." | ".." => format!("_{}", name),
+        _ => name,
+    }
+}
+
+/// Add a number before the extension of a file name.
+fn numbered(name: &str, n: usize) -> String {
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}.{}.{}", stem, n, ext),
+        _ => format!("{}.{}", name, n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Reader, Record};
+
+    #[test]
+    fn test_split_three_queries() {
+        let mut records = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        // give the records three queries, one with a path separator
+        let queries = ["TR", "LINE/L1", "LINE_L1"];
+        let mut text = String::new();
+        for (i, record) in records.iter().enumerate() {
+            let line = record
+                .to_string()
+                .replacen("TR ", &format!("{} ", queries[i % 3]), 1);
+            text.push_str(&line);
+            text.push('\n');
+        }
+        records = text
+            .lines()
+            .map(|l| crate::reader::parse_dna_line(l).map(Record::Dna))
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let meta = Meta::for_program(Program::Nhmmer);
+        let mut writer = SplitWriter::new(dir.path(), Program::Nhmmer, meta.clone(), |q| {
+            format!("{}.tbl", q)
+        })
+        .max_open(2);
+        assert_eq!(writer.write_records(&records).unwrap(), 31);
+        let paths = writer.finish().unwrap();
+
+        let names = paths
+            .iter()
+            .map(|p| p.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["TR.tbl", "LINE_L1.tbl", "LINE_L1.2.tbl"]);
+
+        for (i, path) in paths.iter().enumerate() {
+            let mut reader = Reader::from_path(path).unwrap();
+            assert_eq!(reader.meta(), &meta);
+            let got = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+            let expected = records
+                .iter()
+                .skip(i)
+                .step_by(3)
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(got, expected);
+            assert!(got.iter().all(|r| r.query_name() == queries[i]));
+        }
+    }
+}
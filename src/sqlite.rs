@@ -0,0 +1,254 @@
+use crate::{
+    record::{Record, RecordKind},
+    tsv::{DNA_COLUMNS, PROTEIN_COLUMNS},
+    writer::FloatStyle,
+    Error, ErrorKind, Reader, Result,
+};
+use rusqlite::{params, types::Value, Connection};
+use std::{io::Read, path::Path};
+
+/// The number of records inserted in each transaction.
+const BATCH_SIZE: usize = 10_000;
+
+/// The SQLite types of the DNA columns.
+const DNA_TYPES: [&str; 16] = [
+    "TEXT", "TEXT", "TEXT", "TEXT", "INTEGER", "INTEGER", "INTEGER", "INTEGER", "INTEGER",
+    "INTEGER", "INTEGER", "TEXT", "REAL", "REAL", "REAL", "TEXT",
+];
+
+/// The SQLite types of the protein columns.
+const PROTEIN_TYPES: [&str; 19] = [
+    "TEXT", "TEXT", "TEXT", "TEXT", "REAL", "REAL", "REAL", "REAL", "REAL", "REAL", "REAL",
+    "INTEGER", "INTEGER", "INTEGER", "INTEGER", "INTEGER", "INTEGER", "INTEGER", "TEXT",
+];
+
+/// Export the records of a reader into a table of a SQLite database,
+/// creating the database if it does not exist. Returns the number of
+/// records exported.
+///
+/// The columns of the table are named as in `TsvWriter` output, and
+/// depend on whether the program writes DNA or protein records. The
+/// metadata is stored in a `meta` table of keys and values for each
+/// table, so several files can be exported into one database, and the
+/// table is indexed on `target_name` and `query_name`.
+///
+/// The table name must be a plain identifier other than `meta`, or an
+/// `ErrorKind::InvalidInput` error is returned. The table must not
+/// already exist. Errors from SQLite are returned as `ErrorKind::Sqlite`.
+pub fn export_sqlite<R: Read, P: AsRef<Path>>(
+    reader: Reader<R>,
+    db_path: P,
+    table_name: &str,
+) -> Result<u64> {
+    let valid = table_name
+        .chars()
+        .enumerate()
+        .all(|(i, c)| c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()));
+    if table_name.is_empty() || !valid || table_name == "meta" {
+        return Err(Error::new(ErrorKind::InvalidInput(format!(
+            "`{}` cannot be used as a table name",
+            table_name
+        ))));
+    }
+
+    let (columns, types): (&[&str], &[&str]) = match reader.meta().program().record_kind() {
+        Some(RecordKind::Dna) => (&DNA_COLUMNS, &DNA_TYPES),
+        Some(RecordKind::Protein) => (&PROTEIN_COLUMNS, &PROTEIN_TYPES),
+        None => {
+            return Err(Error::new(ErrorKind::Meta(
+                "the program is unknown, so the records cannot be exported".into(),
+            )))
+        }
+    };
+
+    let mut conn = Connection::open(db_path).map_err(sql_error)?;
+    let schema = columns
+        .iter()
+        .zip(types)
+        .map(|(c, t)| format!("{} {}", c, t))
+        .collect::<Vec<_>>()
+        .join(", ");
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS meta (
+             table_name TEXT, key TEXT, value TEXT, PRIMARY KEY (table_name, key)
+         );
+         CREATE TABLE {} ({});",
+        table_name, schema
+    ))
+    .map_err(sql_error)?;
+
+    let meta = reader.meta();
+    let entries = [
+        ("program", meta.program().to_string()),
+        ("version", meta.version()),
+        ("pipeline_mode", meta.pipeline_mode()),
+        ("query_file", meta.query_file().display().to_string()),
+        ("target_file", meta.target_file().display().to_string()),
+        ("options", meta.options()),
+        ("current_dir", meta.current_dir().display().to_string()),
+        ("date", meta.date()),
+    ];
+    for (key, value) in entries {
+        conn.execute(
+            "INSERT OR REPLACE INTO meta (table_name, key, value) VALUES (?1, ?2, ?3)",
+            params![table_name, key, value],
+        )
+        .map_err(sql_error)?;
+    }
+
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table_name,
+        columns.join(", "),
+        vec!["?"; columns.len()].join(", ")
+    );
+    let mut records = reader.into_records().peekable();
+    let mut n = 0;
+    while records.peek().is_some() {
+        let tx = conn.transaction().map_err(sql_error)?;
+        {
+            let mut stmt = tx.prepare_cached(&insert).map_err(sql_error)?;
+            for record in records.by_ref().take(BATCH_SIZE) {
+                stmt.execute(rusqlite::params_from_iter(values(&record?, types)))
+                    .map_err(sql_error)?;
+                n += 1;
+            }
+        }
+        tx.commit().map_err(sql_error)?;
+    }
+
+    conn.execute_batch(&format!(
+        "CREATE INDEX {0}_target_name ON {0} (target_name);
+         CREATE INDEX {0}_query_name ON {0} (query_name);",
+        table_name
+    ))
+    .map_err(sql_error)?;
+
+    Ok(n)
+}
+
+/// The values of a record's fields, typed for their columns.
+fn values(record: &Record, types: &[&str]) -> Vec<Value> {
+    record
+        .fields(FloatStyle::Raw)
+        .into_iter()
+        .zip(types)
+        .map(|(field, ty)| match *ty {
+            "INTEGER" => field.parse().map(Value::Integer).unwrap_or(Value::Null),
+            "REAL" => field.parse().map(Value::Real).unwrap_or(Value::Null),
+            _ => Value::Text(field),
+        })
+        .collect()
+}
+
+/// Convert a SQLite error into a crate error.
+fn sql_error(err: rusqlite::Error) -> Error {
+    Error::new(ErrorKind::Sqlite(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &str = "SUPER_1              -          TR                   -                1     315 10988331 10987997 10988331 10987995 52766903    -     6.5e-34  124.1   1.2  -\n";
+
+    /// A synthetic file of `n` records over ten targets.
+    fn synthetic(n: usize) -> String {
+        let mut s = String::from("# target name\n#----\n");
+        for i in 0..n {
+            s.push_str(&LINE.replacen("SUPER_1", &format!("SUPER_{}", i % 10), 1));
+        }
+        s.push_str("#\n# Program:         nhmmer\n# Version:         3.4 (Aug 2023)\n# [ok]\n");
+        s
+    }
+
+    fn export(n: usize) {
+        let file = synthetic(n);
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("hits.db");
+
+        let reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(export_sqlite(reader, &db, "hits").unwrap(), n as u64);
+
+        let conn = Connection::open(&db).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT count(*) FROM hits", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(count, n as i64);
+        let count: i64 = conn
+            .query_row(
+                "SELECT count(*) FROM hits WHERE target_name = 'SUPER_3'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, (n / 10) as i64);
+        let (ali_from, e_value): (i64, f64) = conn
+            .query_row("SELECT ali_from, e_value FROM hits LIMIT 1", [], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((ali_from, e_value), (10988331, 6.5e-34));
+        let version: String = conn
+            .query_row(
+                "SELECT value FROM meta WHERE table_name = 'hits' AND key = 'version'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(version, "3.4 (Aug 2023)");
+
+        // the table already exists
+        let reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let err = export_sqlite(reader, &db, "hits").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::Sqlite(_)));
+    }
+
+    #[test]
+    fn test_export_sqlite() {
+        export(25_000);
+    }
+
+    #[test]
+    #[ignore = "slow in debug builds"]
+    fn test_export_sqlite_million() {
+        export(1_000_000);
+    }
+
+    #[test]
+    fn test_bad_table_name() {
+        let file = synthetic(1);
+        let reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let err = export_sqlite(reader, "unused.db", "hits; DROP TABLE meta").unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+        assert!(err.to_string().contains("table name"));
+    }
+
+    #[test]
+    fn test_meta_per_table() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("hits.db");
+
+        let first = synthetic(1);
+        let second = first.replace("3.4 (Aug 2023)", "3.3.2 (Nov 2020)");
+        export_sqlite(Reader::from_reader(first.as_bytes()).unwrap(), &db, "first").unwrap();
+        export_sqlite(
+            Reader::from_reader(second.as_bytes()).unwrap(),
+            &db,
+            "second",
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db).unwrap();
+        let version = |table: &str| -> String {
+            conn.query_row(
+                "SELECT value FROM meta WHERE table_name = ?1 AND key = 'version'",
+                [table],
+                |r| r.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(version("first"), "3.4 (Aug 2023)");
+        assert_eq!(version("second"), "3.3.2 (Nov 2020)");
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    header::Header,
+    record::{Program, RecordKind},
+    tsv::field_names,
+};
+
+/// The layout of the table in a file, i.e. which columns its records
+/// have, from the program in its metadata and the column names in its
+/// header.
+///
+/// This crate reads HMMER's per-sequence tables, so these are the
+/// layouts it knows. Per-domain tables (`--domtblout`), Dfam tables
+/// and Infernal's are not read, so are `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableFormat {
+    /// The per-sequence table of the protein searches, `hmmsearch`,
+    /// `hmmscan`, `phmmer` and `jackhmmer`, with full sequence and best
+    /// domain scores and the domain counts.
+    HmmerSeqTbl,
+    /// The table of the DNA searches, `nhmmer` and `nhmmscan`, with the
+    /// model, alignment and envelope coordinates and the strand. For
+    /// `nhmmscan` the length column is the model's, not the sequence's.
+    NhmmerTbl,
+    /// The layout could not be told, as neither the program nor the
+    /// header says, or they disagree.
+    Unknown,
+}
+
+impl TableFormat {
+    /// The layout of a file written by `program` under `header`.
+    ///
+    /// When both say which kind of records the file has, they must
+    /// agree. When only one does, as for a file of records alone read
+    /// with `Reader::with_program`, or one whose metadata was lost or
+    /// does not name the program, that one is taken. A header is only
+    /// taken to say if its column names are HMMER's, so a truncated or
+    /// rewritten one is as good as none. Otherwise the format is
+    /// `Unknown`.
+    pub fn detect(program: Program, header: &Header) -> TableFormat {
+        let kind = match (program.record_kind(), header.record_kind()) {
+            (Some(a), Some(b)) if a != b => None,
+            (Some(kind), _) | (None, Some(kind)) => Some(kind),
+            (None, None) => None,
+        };
+        match kind {
+            Some(RecordKind::Protein) => TableFormat::HmmerSeqTbl,
+            Some(RecordKind::Dna) => TableFormat::NhmmerTbl,
+            None => TableFormat::Unknown,
+        }
+    }
+
+    /// The kind of records in a table of this layout, or `None` if it
+    /// is unknown.
+    pub fn record_kind(&self) -> Option<RecordKind> {
+        match self {
+            TableFormat::HmmerSeqTbl => Some(RecordKind::Protein),
+            TableFormat::NhmmerTbl => Some(RecordKind::Dna),
+            TableFormat::Unknown => None,
+        }
+    }
+
+    /// The columns of a table of this layout, in order, named as the
+    /// accessors of its records are (see `field_names`). Empty if the
+    /// layout is unknown.
+    pub fn columns(&self) -> &'static [&'static str] {
+        match self.record_kind() {
+            Some(kind) => field_names(kind),
+            None => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Reader;
+
+    #[test]
+    fn test_corpus_formats() {
+        for (path, format) in [
+            ("./data/corpus/hmmscan.tbl", TableFormat::HmmerSeqTbl),
+            ("./data/corpus/hmmsearch.tbl", TableFormat::HmmerSeqTbl),
+            ("./data/corpus/jackhmmer.tbl", TableFormat::HmmerSeqTbl),
+            ("./data/corpus/phmmer.tbl", TableFormat::HmmerSeqTbl),
+            ("./data/corpus/nhmmer.tbl", TableFormat::NhmmerTbl),
+            ("./data/corpus/nhmmscan.tbl", TableFormat::NhmmerTbl),
+            ("./data/test.tbl", TableFormat::NhmmerTbl),
+            ("./data/two_queries.tbl", TableFormat::NhmmerTbl),
+            ("./data/tabs.tbl", TableFormat::NhmmerTbl),
+            ("./data/invalid_envelope.tbl", TableFormat::NhmmerTbl),
+        ] {
+            let reader = Reader::from_path(path).unwrap();
+            assert_eq!(reader.format(), format, "{}", path);
+            assert_eq!(
+                reader.format().record_kind(),
+                reader.meta().program().record_kind()
+            );
+        }
+        assert_eq!(TableFormat::NhmmerTbl.columns()[11], "strand");
+        assert_eq!(TableFormat::HmmerSeqTbl.columns().len(), 19);
+    }
+
+    #[test]
+    fn test_fallback_formats() {
+        let dna = Header::for_program(Program::Nhmmer).unwrap();
+        let protein = Header::for_program(Program::Hmmsearch).unwrap();
+        // either alone
+        assert_eq!(
+            TableFormat::detect(Program::Phmmer, &Header::default()),
+            TableFormat::HmmerSeqTbl
+        );
+        assert_eq!(
+            TableFormat::detect(Program::None, &dna),
+            TableFormat::NhmmerTbl
+        );
+        // disagreeing, or neither
+        assert_eq!(
+            TableFormat::detect(Program::Nhmmer, &protein),
+            TableFormat::Unknown
+        );
+        assert_eq!(
+            TableFormat::detect(Program::None, &Header::default()),
+            TableFormat::Unknown
+        );
+        assert!(TableFormat::Unknown.columns().is_empty());
+
+        // a truncated header says nothing, so the program decides
+        let truncated = Header::new(None, "# target name  accession".into(), "#---- ----".into());
+        assert_eq!(
+            TableFormat::detect(Program::Nhmmscan, &truncated),
+            TableFormat::NhmmerTbl
+        );
+
+        // a file without metadata, with the header alone
+        let file = std::fs::read_to_string("./data/test.tbl").unwrap();
+        let records = file.split("\n#\n").next().unwrap();
+        let reader = Reader::from_reader(records.as_bytes()).unwrap();
+        assert_eq!(reader.meta().program(), Program::None);
+        assert_eq!(reader.format(), TableFormat::NhmmerTbl);
+    }
+}
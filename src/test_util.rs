@@ -0,0 +1,387 @@
+use crate::{Header, Meta, Reader, Record, Result};
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Assert that two tblout files hold the same search results, as
+/// `assert_tblout_semantically_eq` does. An optional third argument is
+/// a `TbloutComparison` saying what to compare.
+///
+/// On failure, every difference is listed, a line for each field of
+/// each record, rather than the two files.
+///
+/// ```
+/// use hmm_tblout::assert_tblout_eq;
+///
+/// let expected = std::fs::read_to_string("./data/test.tbl").unwrap();
+/// // an E-value in full, which HMMER would have rounded
+/// let actual = expected.replace("1.9e-37", "1.9000000001e-37");
+/// assert_tblout_eq!(expected, actual);
+/// ```
+#[macro_export]
+macro_rules! assert_tblout_eq {
+    ($expected:expr, $actual:expr $(,)?) => {
+        $crate::assert_tblout_eq!($expected, $actual, $crate::TbloutComparison::new())
+    };
+    ($expected:expr, $actual:expr, $comparison:expr $(,)?) => {
+        $crate::assert_tblout_with(
+            &$comparison,
+            ::core::convert::AsRef::<str>::as_ref(&$expected),
+            ::core::convert::AsRef::<str>::as_ref(&$actual),
+        )
+    };
+}
+
+/// The most differences a failed assertion lists.
+const MAX_LISTED: usize = 50;
+
+/// Assert that two tblout files hold the same search results.
+///
+/// Both files are parsed and their records compared field by field.
+/// Numbers only have to agree to the precision HMMER writes them
+/// with, two significant figures for E-values and one decimal place
+/// for scores, biases and the expected number of domains. Of the
+/// metadata only the program, version and pipeline mode are compared,
+/// as the paths, options and date change from run to run. Headers
+/// are compared by their column names, whatever their widths.
+///
+/// # Panics
+///
+/// If either file cannot be parsed, or they differ, listing the
+/// differences.
+pub fn assert_tblout_semantically_eq(a: &str, b: &str) {
+    assert_tblout_with(&TbloutComparison::new(), a, b)
+}
+
+/// Assert that two tblout files hold the same search results, as
+/// `comparison` compares them. `assert_tblout_eq!` calls this.
+///
+/// # Panics
+///
+/// If either file cannot be parsed, or they differ, listing the
+/// differences.
+pub fn assert_tblout_with(comparison: &TbloutComparison, a: &str, b: &str) {
+    let differences = comparison
+        .differences(a, b)
+        .unwrap_or_else(|e| panic!("could not read the files: {}", e));
+    if differences.is_empty() {
+        return;
+    }
+    let mut msg = format!("the files differ in {} places:", differences.len());
+    for difference in differences.iter().take(MAX_LISTED) {
+        msg.push_str(&format!("\n  {}", difference));
+    }
+    if differences.len() > MAX_LISTED {
+        msg.push_str(&format!("\n  and {} more", differences.len() - MAX_LISTED));
+    }
+    panic!("{}", msg);
+}
+
+/// The differences between two tblout files, with the default
+/// `TbloutComparison`. Empty if they hold the same search results.
+pub fn tblout_differences(a: &str, b: &str) -> Result<Vec<Difference>> {
+    TbloutComparison::new().differences(a, b)
+}
+
+/// A way in which two tblout files differ, as found by
+/// `TbloutComparison::differences`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// The index of the record which differs, or `None` for the
+    /// metadata, header and number of records.
+    pub record: Option<usize>,
+    /// The name of the field which differs, as its accessor is named.
+    pub field: &'static str,
+    /// The value in the left file.
+    pub left: String,
+    /// The value in the right file.
+    pub right: String,
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(i) = self.record {
+            write!(f, "record {}: ", i)?;
+        }
+        write!(f, "{} differs: {} != {}", self.field, self.left, self.right)
+    }
+}
+
+/// What to compare of two tblout files. By default it is the search
+/// results: the records, to the precision HMMER writes them with, the
+/// column names, and the program, version and pipeline mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TbloutComparison {
+    /// Whether the paths, options and date of the metadata are
+    /// compared too.
+    all_meta: bool,
+}
+
+impl TbloutComparison {
+    /// A comparison of the search results alone.
+    pub fn new() -> TbloutComparison {
+        TbloutComparison::default()
+    }
+
+    /// Set whether the query and target files, options, current
+    /// directory and date are compared too. Off by default, as they
+    /// change from run to run.
+    pub fn all_meta(mut self, yes: bool) -> TbloutComparison {
+        self.all_meta = yes;
+        self
+    }
+
+    /// Parse both files and list the ways they differ. Records are
+    /// compared in order, and as many as both files have.
+    pub fn differences(&self, a: &str, b: &str) -> Result<Vec<Difference>> {
+        let (meta_a, header_a, records_a) = parse(a)?;
+        let (meta_b, header_b, records_b) = parse(b)?;
+        let mut diff = Diff::default();
+
+        diff.same("program", meta_a.program(), meta_b.program());
+        diff.same("version", meta_a.version(), meta_b.version());
+        diff.same(
+            "pipeline_mode",
+            meta_a.pipeline_mode(),
+            meta_b.pipeline_mode(),
+        );
+        if self.all_meta {
+            diff.same("query_file", meta_a.query_file(), meta_b.query_file());
+            diff.same("target_file", meta_a.target_file(), meta_b.target_file());
+            diff.same("options", meta_a.options(), meta_b.options());
+            diff.same("current_dir", meta_a.current_dir(), meta_b.current_dir());
+            diff.same("date", meta_a.date(), meta_b.date());
+        }
+        diff.same("columns", column_names(&header_a), column_names(&header_b));
+        diff.same("records", records_a.len(), records_b.len());
+
+        for (i, (a, b)) in records_a.iter().zip(&records_b).enumerate() {
+            diff.record = Some(i);
+            diff.records(a, b);
+        }
+        Ok(diff.differences)
+    }
+}
+
+/// Parse a whole file.
+fn parse(s: &str) -> Result<(Meta, Header, Vec<Record>)> {
+    let mut reader = Reader::from_reader(s.as_bytes())?;
+    let records = reader.records().collect::<Result<Vec<_>>>()?;
+    Ok((reader.meta().clone(), reader.header().clone(), records))
+}
+
+/// The column names of a header, with single spaces between words
+/// whatever the widths of the columns.
+fn column_names(header: &Header) -> String {
+    header
+        .columns()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The differences found so far, and the record being compared.
+#[derive(Default)]
+struct Diff {
+    differences: Vec<Difference>,
+    record: Option<usize>,
+}
+
+impl Diff {
+    fn records(&mut self, a: &Record, b: &Record) {
+        match (a, b) {
+            (Record::Dna(a), Record::Dna(b)) => {
+                self.same("target_name", a.target_name(), b.target_name());
+                self.same(
+                    "target_accession",
+                    a.target_accession(),
+                    b.target_accession(),
+                );
+                self.same("query_name", a.query_name(), b.query_name());
+                self.same("query_accession", a.query_accession(), b.query_accession());
+                self.same("hmm_from", a.hmm_from(), b.hmm_from());
+                self.same("hmm_to", a.hmm_to(), b.hmm_to());
+                self.same("ali_from", a.ali_from(), b.ali_from());
+                self.same("ali_to", a.ali_to(), b.ali_to());
+                self.same("env_from", a.env_from(), b.env_from());
+                self.same("env_to", a.env_to(), b.env_to());
+                self.same("sq_len", a.sq_len(), b.sq_len());
+                self.same("strand", a.strand(), b.strand());
+                self.e_value("e_value", a.e_value(), b.e_value());
+                self.score("score", a.score(), b.score());
+                self.score("bias", a.bias(), b.bias());
+                self.same("description", a.description(), b.description());
+            }
+            (Record::Protein(a), Record::Protein(b)) => {
+                self.same("target_name", a.target_name(), b.target_name());
+                self.same(
+                    "target_accession",
+                    a.target_accession(),
+                    b.target_accession(),
+                );
+                self.same("query_name", a.query_name(), b.query_name());
+                self.same("query_accession", a.query_accession(), b.query_accession());
+                self.e_value("e_value_full", a.e_value_full(), b.e_value_full());
+                self.score("score_full", a.score_full(), b.score_full());
+                self.score("bias_full", a.bias_full(), b.bias_full());
+                self.e_value("e_value_best", a.e_value_best(), b.e_value_best());
+                self.score("score_best", a.score_best(), b.score_best());
+                self.score("bias_best", a.bias_best(), b.bias_best());
+                self.score("exp", a.exp(), b.exp());
+                self.same("reg", a.reg(), b.reg());
+                self.same("clu", a.clu(), b.clu());
+                self.same("ov", a.ov(), b.ov());
+                self.same("env", a.env(), b.env());
+                self.same("dom", a.dom(), b.dom());
+                self.same("rep", a.rep(), b.rep());
+                self.same("inc", a.inc(), b.inc());
+                self.same("description", a.description(), b.description());
+            }
+            _ => self.push("kind", &a.kind(), &b.kind()),
+        }
+    }
+
+    fn same<T: PartialEq + Debug>(&mut self, field: &'static str, a: T, b: T) {
+        if a != b {
+            self.push(field, &a, &b);
+        }
+    }
+
+    /// E-values are written to two significant figures.
+    fn e_value(&mut self, field: &'static str, a: f64, b: f64) {
+        let close = a == b || (a - b).abs() <= 0.05 * a.abs().max(b.abs());
+        if !close {
+            self.push(field, &a, &b);
+        }
+    }
+
+    /// Scores are written to one decimal place, which large scores read
+    /// back as the nearest `f32`, so may be further off.
+    fn score(&mut self, field: &'static str, a: f32, b: f32) {
+        let ulp = a.abs().max(b.abs()) * f32::EPSILON;
+        let close = (a - b).abs() <= 0.05 + 1e-4 + ulp;
+        if !close {
+            self.push(field, &a, &b);
+        }
+    }
+
+    /// Note a difference in the record being compared, if any.
+    fn push(&mut self, field: &'static str, a: &dyn Debug, b: &dyn Debug) {
+        self.differences.push(Difference {
+            record: self.record,
+            field,
+            left: format!("{:?}", a),
+            right: format!("{:?}", b),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{FloatStyle, Writer, WriterBuilder};
+
+    const CORPUS: [&str; 7] = [
+        include_str!("../data/corpus/nhmmer.tbl"),
+        include_str!("../data/corpus/nhmmscan.tbl"),
+        include_str!("../data/corpus/hmmsearch.tbl"),
+        include_str!("../data/corpus/hmmscan.tbl"),
+        include_str!("../data/corpus/phmmer.tbl"),
+        include_str!("../data/corpus/jackhmmer.tbl"),
+        include_str!("../data/test.tbl"),
+    ];
+
+    fn rewrite(file: &str, mut writer: Writer<Vec<u8>>) -> String {
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+        String::from_utf8(writer.into_inner().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_corpus_round_trip() {
+        for file in CORPUS {
+            let written = rewrite(file, Writer::to_vec());
+            assert_tblout_semantically_eq(file, &written);
+
+            // full precision values are within HMMER's rounding
+            let raw = WriterBuilder::new()
+                .float_style(FloatStyle::Raw)
+                .build(Vec::new());
+            assert_tblout_semantically_eq(file, &rewrite(file, raw));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "record 1: ali_to differs")]
+    fn test_records_differ() {
+        let file = CORPUS[0];
+        assert_tblout_semantically_eq(file, &file.replacen("17951472", "17951473", 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "record 0: e_value differs")]
+    fn test_e_values_differ() {
+        let file = CORPUS[0];
+        assert_tblout_semantically_eq(file, &file.replacen("6.5e-34", "6.5e-33", 1));
+    }
+
+    #[test]
+    fn test_assert_tblout_eq() {
+        let file = CORPUS[0];
+        assert_tblout_eq!(file, rewrite(file, Writer::to_vec()));
+        // the column names, whatever their widths
+        let header = file.lines().next().unwrap();
+        let wider = header.replacen("target name", "target name     ", 1);
+        assert_tblout_eq!(file, file.replacen(header, &wider, 1));
+    }
+
+    #[test]
+    #[should_panic(expected = "record 2: score differs: 66.5 != 56.5")]
+    fn test_assert_tblout_eq_fails() {
+        let file = CORPUS[6];
+        assert_tblout_eq!(file, file.replacen(" 66.5 ", " 56.5 ", 1));
+    }
+
+    #[test]
+    fn test_differences() {
+        let file = CORPUS[0];
+        let changed = file
+            .replacen("17951472", "17951473", 1)
+            .replacen("6.5e-34", "6.5e-33", 1)
+            .replacen("target name", "target", 1);
+        let differences = tblout_differences(file, &changed).unwrap();
+        let lines = differences
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("columns differs: "));
+        assert_eq!(lines[1], "record 0: e_value differs: 6.5e-34 != 6.5e-33");
+        assert_eq!(lines[2], "record 1: ali_to differs: 17951472 != 17951473");
+        assert_eq!(differences[2].record, Some(1));
+        assert_eq!(differences[2].field, "ali_to");
+
+        assert!(tblout_differences(file, "not a tblout file").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "the files differ in 1 places:\n  date differs")]
+    fn test_all_meta() {
+        let file = CORPUS[0];
+        let later = file.replace("Fri May  3 10:07:36 2024", "Sat May  4 09:00:00 2024");
+        assert_tblout_eq!(file, later, TbloutComparison::new().all_meta(true));
+    }
+
+    #[test]
+    fn test_meta_paths_ignored() {
+        let file = CORPUS[0];
+        let moved = file
+            .replace(
+                "# Query file:      TR.hmm",
+                "# Query file:      /elsewhere/TR.hmm",
+            )
+            .replace("Fri May  3 10:07:36 2024", "Sat May  4 09:00:00 2024");
+        assert_tblout_semantically_eq(file, &moved);
+    }
+}
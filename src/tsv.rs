@@ -0,0 +1,369 @@
+use crate::{
+    hit::Hit,
+    record::{fmt_e_value, fmt_score, RecordKind},
+    writer::{FloatStyle, WritableRecord},
+    Error, ErrorKind, Result,
+};
+use std::io::{self, Write};
+
+/// The column names for DNA records, matching the accessor names.
+pub(crate) const DNA_COLUMNS: [&str; 16] = [
+    "target_name",
+    "target_accession",
+    "query_name",
+    "query_accession",
+    "hmm_from",
+    "hmm_to",
+    "ali_from",
+    "ali_to",
+    "env_from",
+    "env_to",
+    "sq_len",
+    "strand",
+    "e_value",
+    "score",
+    "bias",
+    "description",
+];
+
+/// The column names for protein records, matching the accessor names.
+pub(crate) const PROTEIN_COLUMNS: [&str; 19] = [
+    "target_name",
+    "target_accession",
+    "query_name",
+    "query_accession",
+    "e_value_full",
+    "score_full",
+    "bias_full",
+    "e_value_best",
+    "score_best",
+    "bias_best",
+    "exp",
+    "reg",
+    "clu",
+    "ov",
+    "env",
+    "dom",
+    "rep",
+    "inc",
+    "description",
+];
+
+/// The column names for hits, matching the field names of `Hit`, with
+/// its interval as `start` and `end`.
+pub(crate) const HIT_COLUMNS: [&str; 11] = [
+    "target_name",
+    "target_accession",
+    "query_name",
+    "query_accession",
+    "start",
+    "end",
+    "strand",
+    "e_value",
+    "score",
+    "bias",
+    "description",
+];
+
+/// The names of the columns of a kind of record, in the order of
+/// `Record::to_fields`, matching the accessor names.
+pub fn field_names(kind: RecordKind) -> &'static [&'static str] {
+    match kind {
+        RecordKind::Dna => &DNA_COLUMNS,
+        RecordKind::Protein => &PROTEIN_COLUMNS,
+    }
+}
+
+/// A writer of records as a delimited table, tab separated by
+/// default, with a row of column names and a row per record.
+///
+/// The column names are written before the first record and depend
+/// on its kind, so all the records must be of the same kind. Records
+/// of any kind can be written as `Hit`s instead, with the same columns
+/// for all, but a table holds records or hits, not both. Fields
+/// containing the delimiter, a quote or a newline are quoted as in
+/// RFC 4180.
+pub struct TsvWriter<W: io::Write> {
+    /// The underlying buffered writer.
+    wtr: io::BufWriter<W>,
+    /// The field delimiter.
+    delimiter: u8,
+    /// How floating point values are written.
+    float_style: FloatStyle,
+    /// What the rows are, once the column names are written.
+    columns: Option<Columns>,
+}
+
+/// What the rows of a table are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Columns {
+    /// Records of a kind, with all their fields.
+    Records(RecordKind),
+    /// Hits, of records of any kind.
+    Hits,
+}
+
+impl<W: io::Write> TsvWriter<W> {
+    /// Construct a new tab separated writer.
+    pub fn new(wtr: W) -> TsvWriter<W> {
+        TsvWriter {
+            wtr: io::BufWriter::new(wtr),
+            delimiter: b'\t',
+            float_style: FloatStyle::Hmmer,
+            columns: None,
+        }
+    }
+
+    /// Set the field delimiter, e.g. `b','` for CSV.
+    pub fn delimiter(mut self, delimiter: u8) -> TsvWriter<W> {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Write E-values and the other floating point values in full,
+    /// rather than rounded as HMMER writes them.
+    pub fn full_precision(mut self, yes: bool) -> TsvWriter<W> {
+        self.float_style = if yes {
+            FloatStyle::Raw
+        } else {
+            FloatStyle::Hmmer
+        };
+        self
+    }
+
+    /// Write a record, preceded by the column names if it is the first.
+    /// This may be a `Record` or one of the concrete records it wraps.
+    pub fn write_record<T: WritableRecord>(&mut self, record: &T) -> Result<()> {
+        match self.columns {
+            None => {
+                self.write_row(field_names(record.kind()).iter().copied())?;
+                self.columns = Some(Columns::Records(record.kind()));
+            }
+            Some(Columns::Records(kind)) if kind == record.kind() => (),
+            Some(Columns::Records(kind)) => {
+                return Err(Error::new(ErrorKind::InvalidInput(format!(
+                    "cannot write a {:?} record for target {} to a table of {:?} records",
+                    record.kind(),
+                    record.target_name(),
+                    kind
+                ))))
+            }
+            Some(Columns::Hits) => {
+                return Err(Error::new(ErrorKind::InvalidInput(format!(
+                    "cannot write a record for target {} to a table of hits",
+                    record.target_name()
+                ))))
+            }
+        }
+        let fields = record.fields(self.float_style);
+        self.write_row(fields.iter().map(|f| f.as_str()))
+            .map_err(|error| {
+                Error::new(ErrorKind::WriteRecord {
+                    target: record.target_name(),
+                    error,
+                })
+            })
+    }
+
+    /// Write the records as they are read, so the whole file is never
+    /// held in memory. Stops at the first error, returning the number
+    /// of records written otherwise.
+    pub fn write_records<T: WritableRecord>(
+        &mut self,
+        records: impl IntoIterator<Item = Result<T>>,
+    ) -> Result<usize> {
+        let mut n = 0;
+        for record in records {
+            self.write_record(&record?)?;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    /// Write a hit, preceded by the column names if it is the first.
+    /// Coordinates and strand are written as `-` for hits without them.
+    pub fn write_hit(&mut self, hit: &Hit) -> Result<()> {
+        match self.columns {
+            None => {
+                self.write_row(HIT_COLUMNS.iter().copied())?;
+                self.columns = Some(Columns::Hits);
+            }
+            Some(Columns::Hits) => (),
+            Some(Columns::Records(kind)) => {
+                return Err(Error::new(ErrorKind::InvalidInput(format!(
+                    "cannot write a hit for target {} to a table of {:?} records",
+                    hit.target_name, kind
+                ))))
+            }
+        }
+        let (start, end) = match hit.interval {
+            Some((start, end)) => (start.to_string(), end.to_string()),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        let strand = match hit.strand {
+            Some(strand) => strand.to_string(),
+            None => "-".to_string(),
+        };
+        let fields = [
+            hit.target_name.clone(),
+            hit.target_accession.clone(),
+            hit.query_name.clone(),
+            hit.query_accession.clone(),
+            start,
+            end,
+            strand,
+            fmt_e_value(hit.e_value, None, self.float_style),
+            fmt_score(hit.score, self.float_style),
+            fmt_score(hit.bias, self.float_style),
+            hit.description.clone(),
+        ];
+        self.write_row(fields.iter().map(|f| f.as_str()))
+            .map_err(|error| {
+                Error::new(ErrorKind::WriteRecord {
+                    target: hit.target_name.clone(),
+                    error,
+                })
+            })
+    }
+
+    /// Write records of any kind as hits, as they are read. Stops at
+    /// the first error, returning the number of hits written otherwise.
+    pub fn write_hits<R: Into<Hit>>(
+        &mut self,
+        records: impl IntoIterator<Item = Result<R>>,
+    ) -> Result<usize> {
+        let mut n = 0;
+        for record in records {
+            self.write_hit(&record?.into())?;
+            n += 1;
+        }
+        Ok(n)
+    }
+
+    /// Flush any buffered output to the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.wtr.flush()?)
+    }
+
+    /// Flush the buffered output and return the underlying writer.
+    pub fn into_inner(self) -> Result<W> {
+        Ok(self.wtr.into_inner().map_err(|e| e.into_error())?)
+    }
+
+    /// Write a row of fields, quoting where needed.
+    fn write_row<'a>(&mut self, fields: impl Iterator<Item = &'a str>) -> io::Result<()> {
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                self.wtr.write_all(&[self.delimiter])?;
+            }
+            let quote = field
+                .bytes()
+                .any(|b| b == self.delimiter || b == b'"' || b == b'\n' || b == b'\r');
+            if quote {
+                write!(self.wtr, "\"{}\"", field.replace('"', "\"\""))?;
+            } else {
+                self.wtr.write_all(field.as_bytes())?;
+            }
+        }
+        self.wtr.write_all(b"\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader, Record};
+
+    #[test]
+    fn test_tsv_read_back() {
+        let reader = Reader::from_path("./data/test.tbl").unwrap();
+        let mut writer = TsvWriter::new(Vec::new());
+        assert_eq!(writer.write_records(reader.into_records()).unwrap(), 31);
+        let out = writer.into_inner().unwrap();
+
+        let mut tsv = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_reader(out.as_slice());
+        assert_eq!(tsv.headers().unwrap(), DNA_COLUMNS.as_slice());
+        let rows = tsv
+            .records()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rows.len(), 31);
+        assert_eq!(&rows[0][0], "2");
+        assert_eq!(&rows[0][6], "338944");
+        assert_eq!(&rows[0][11], "+");
+        assert_eq!(rows[0][12].parse::<f64>().unwrap(), 1.9e-37);
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let line = "chr1,alt             -          TR                   -                2     317    338944    339292    338943    339292 133811386    +     1.9e-37  136.7   1.4  -";
+        let record = Record::Dna(parse_dna_line(line).unwrap());
+
+        let mut writer = TsvWriter::new(Vec::new())
+            .delimiter(b',')
+            .full_precision(true);
+        writer.write_record(&record).unwrap();
+        let out = writer.into_inner().unwrap();
+        assert!(String::from_utf8_lossy(&out).contains("\"chr1,alt\""));
+
+        let mut csv = csv::Reader::from_reader(out.as_slice());
+        let row = csv.records().next().unwrap().unwrap();
+        assert_eq!(&row[0], "chr1,alt");
+        assert_eq!(&row[13], "136.7");
+        assert_eq!(row.len(), DNA_COLUMNS.len());
+    }
+
+    #[test]
+    fn test_tsv_hits() {
+        let dna = Reader::from_path("./data/test.tbl").unwrap().into_records();
+        let protein = Reader::from_path("./data/corpus/hmmsearch.tbl")
+            .unwrap()
+            .into_records();
+        let mut writer = TsvWriter::new(Vec::new());
+        // records of both kinds in one table
+        let n = writer.write_hits(dna.chain(protein.take(1))).unwrap();
+        assert_eq!(n, 32);
+        let out = writer.into_inner().unwrap();
+
+        let mut tsv = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_reader(out.as_slice());
+        assert_eq!(tsv.headers().unwrap(), HIT_COLUMNS.as_slice());
+        let rows = tsv
+            .records()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rows.len(), 32);
+        // the second hit is on the minus strand, the lower first
+        assert_eq!(
+            (&rows[1][4], &rows[1][5], &rows[1][6]),
+            ("61501510", "61501865", "-")
+        );
+        let last = &rows[31];
+        assert_eq!(&last[2], "SOR");
+        assert_eq!((&last[4], &last[5], &last[6]), ("-", "-", "-"));
+        assert_eq!(last[7].parse::<f64>().unwrap(), 1.5e-152);
+        assert_eq!(&last[8], "492.8");
+    }
+
+    #[test]
+    fn test_tsv_records_or_hits() {
+        let record = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .into_records()
+            .next()
+            .unwrap()
+            .unwrap();
+        let mut writer = TsvWriter::new(io::sink());
+        writer.write_record(&record).unwrap();
+        let err = writer.write_hit(&Hit::from(&record)).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+
+        let mut writer = TsvWriter::new(io::sink());
+        writer.write_hit(&Hit::from(&record)).unwrap();
+        let err = writer.write_record(&record).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+    }
+}
@@ -0,0 +1,775 @@
+use crate::record::{DNARecord, ProteinRecord, Record, Strand};
+use std::{
+    error::Error as StdError,
+    fmt::{self, Display, Formatter},
+    result::Result as StdResult,
+};
+
+/// A way in which a record is inconsistent with itself, as found by
+/// `Record::validate`. HMMER never writes such records, but files
+/// edited by hand or generated by other tools can contain them.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationIssue {
+    /// The model coordinates are reversed or not 1-based.
+    ModelRangeInvalid { hmm_from: i32, hmm_to: i32 },
+    /// The alignment or envelope coordinates run the wrong way for the
+    /// strand: from low to high on the plus strand, and from high to
+    /// low on the minus strand.
+    StrandCoordinateMismatch { strand: Strand, from: i64, to: i64 },
+    /// The alignment is not within its envelope.
+    AlignmentOutsideEnvelope {
+        ali_from: i64,
+        ali_to: i64,
+        env_from: i64,
+        env_to: i64,
+    },
+    /// The envelope runs off either end of the sequence.
+    EnvelopeOutsideSequence {
+        env_from: i64,
+        env_to: i64,
+        sq_len: i64,
+    },
+    /// An E-value is negative or not a number.
+    EValueOutOfRange(f64),
+    /// A bias is negative or not a number. HMMER only ever lowers
+    /// scores for biased composition.
+    BiasOutOfRange(f32),
+    /// The expected number of domains is negative or not a number.
+    ExpOutOfRange(f32),
+    /// One of the domain counts of a protein record is negative.
+    NegativeCount { column: &'static str, value: i32 },
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ValidationIssue::ModelRangeInvalid { hmm_from, hmm_to } => {
+                write!(f, "model range {}..{} is not valid", hmm_from, hmm_to)
+            }
+            ValidationIssue::StrandCoordinateMismatch { strand, from, to } => write!(
+                f,
+                "coordinates {}..{} do not run the way of the {} strand",
+                from, to, strand
+            ),
+            ValidationIssue::AlignmentOutsideEnvelope {
+                ali_from,
+                ali_to,
+                env_from,
+                env_to,
+            } => write!(
+                f,
+                "alignment {}..{} is outside the envelope {}..{}",
+                ali_from, ali_to, env_from, env_to
+            ),
+            ValidationIssue::EnvelopeOutsideSequence {
+                env_from,
+                env_to,
+                sq_len,
+            } => write!(
+                f,
+                "envelope {}..{} is outside the sequence of length {}",
+                env_from, env_to, sq_len
+            ),
+            ValidationIssue::EValueOutOfRange(e) => write!(f, "E-value {} is out of range", e),
+            ValidationIssue::BiasOutOfRange(b) => write!(f, "bias {} is out of range", b),
+            ValidationIssue::ExpOutOfRange(e) => {
+                write!(f, "expected number of domains {} is out of range", e)
+            }
+            ValidationIssue::NegativeCount { column, value } => {
+                write!(f, "{} count {} is negative", column, value)
+            }
+        }
+    }
+}
+
+/// Something amiss with the input which did not stop a reader, kept by
+/// the reader for `Reader::warnings`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// The record on `line` has a validation issue, found with
+    /// `Reader::validate_records` set. A record with several issues
+    /// gives a warning for each.
+    Validation { line: u64, issue: ValidationIssue },
+    /// The record on `line`, the first such, has tabs between its
+    /// columns. HMMER only ever separates them with spaces, so the
+    /// file has been rewritten by another tool.
+    TabDelimited { line: u64 },
+    /// The file has CRLF line endings, from `line`, the first such. The
+    /// first pass of the `from_*` constructors looks for them.
+    CrlfLineEndings { line: u64 },
+    /// The strand column of the DNA record on `line` disagrees with
+    /// the order of its alignment coordinates, as in a file with
+    /// shifted columns (see `Reader::trust_coordinates`).
+    StrandMismatch {
+        line: u64,
+        column: Strand,
+        coordinates: Strand,
+    },
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Warning::Validation { line, issue } => write!(f, "line {}: {}", line, issue),
+            Warning::TabDelimited { line } => write!(
+                f,
+                "line {}: the file has tabs between its columns, so was not written by HMMER",
+                line
+            ),
+            Warning::CrlfLineEndings { line } => {
+                write!(f, "line {}: the file has CRLF line endings", line)
+            }
+            Warning::StrandMismatch {
+                line,
+                column,
+                coordinates,
+            } => write!(
+                f,
+                "line {}: the strand column is {}, but the coordinates run the way of the {} strand",
+                line, column, coordinates
+            ),
+        }
+    }
+}
+
+/// The first coordinate constraint a DNA record breaks, as found by
+/// `DNARecord::check_coordinates`, with the values breaking it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoordinateError {
+    /// `hmm_from <= hmm_to` does not hold.
+    ModelRangeReversed { hmm_from: i32, hmm_to: i32 },
+    /// The envelope does not contain the alignment.
+    AlignmentOutsideEnvelope {
+        ali_from: i64,
+        ali_to: i64,
+        env_from: i64,
+        env_to: i64,
+    },
+    /// `1 <= min(ali_from, ali_to)` and `max(ali_from, ali_to) <= sq_len`
+    /// do not both hold.
+    AlignmentOutsideSequence {
+        ali_from: i64,
+        ali_to: i64,
+        sq_len: i64,
+    },
+}
+
+impl Display for CoordinateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            CoordinateError::ModelRangeReversed { hmm_from, hmm_to } => write!(
+                f,
+                "hmm_from <= hmm_to does not hold (hmm_from {}, hmm_to {})",
+                hmm_from, hmm_to
+            ),
+            CoordinateError::AlignmentOutsideEnvelope {
+                ali_from,
+                ali_to,
+                env_from,
+                env_to,
+            } => write!(
+                f,
+                "the envelope does not contain the alignment (ali_from {}, ali_to {}, env_from {}, env_to {})",
+                ali_from, ali_to, env_from, env_to
+            ),
+            CoordinateError::AlignmentOutsideSequence {
+                ali_from,
+                ali_to,
+                sq_len,
+            } => write!(
+                f,
+                "1 <= min(ali_from, ali_to) and max(ali_from, ali_to) <= sq_len do not hold (ali_from {}, ali_to {}, sq_len {})",
+                ali_from, ali_to, sq_len
+            ),
+        }
+    }
+}
+
+impl StdError for CoordinateError {}
+
+/// Whether `from..to` runs the way of the strand.
+fn runs_with(strand: Strand, from: i64, to: i64) -> bool {
+    match strand {
+        Strand::Positive => from <= to,
+        Strand::Negative => from >= to,
+    }
+}
+
+/// The ordered bounds of `from..to`.
+fn bounds(from: i64, to: i64) -> (i64, i64) {
+    (from.min(to), from.max(to))
+}
+
+impl DNARecord {
+    /// Check the coordinates of the record are consistent: the model
+    /// range runs forwards, the envelope contains the alignment and the
+    /// alignment is within the sequence. Returns the first constraint
+    /// broken, in that order.
+    ///
+    /// As with `validate`, the length column is taken to be the length
+    /// of the sequence, which it is not for nhmmscan.
+    pub fn check_coordinates(&self) -> StdResult<(), CoordinateError> {
+        let (hmm_from, hmm_to) = (self.hmm_from(), self.hmm_to());
+        if hmm_from > hmm_to {
+            return Err(CoordinateError::ModelRangeReversed { hmm_from, hmm_to });
+        }
+        let (ali_from, ali_to) = (self.ali_from(), self.ali_to());
+        let (env_from, env_to) = (self.env_from(), self.env_to());
+        let (ali_lo, ali_hi) = bounds(ali_from, ali_to);
+        let (env_lo, env_hi) = bounds(env_from, env_to);
+        if ali_lo < env_lo || ali_hi > env_hi {
+            return Err(CoordinateError::AlignmentOutsideEnvelope {
+                ali_from,
+                ali_to,
+                env_from,
+                env_to,
+            });
+        }
+        let sq_len = self.sq_len();
+        if ali_lo < 1 || ali_hi > sq_len {
+            return Err(CoordinateError::AlignmentOutsideSequence {
+                ali_from,
+                ali_to,
+                sq_len,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the record is consistent with itself, returning every
+    /// issue found. An empty list means the record is valid.
+    ///
+    /// The length column is taken to be that of the sequence the
+    /// alignment is on, as it is for nhmmer. nhmmscan reports the
+    /// model length there instead, so `EnvelopeOutsideSequence` says
+    /// nothing about its records.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let (hmm_from, hmm_to) = (self.hmm_from(), self.hmm_to());
+        if hmm_from < 1 || hmm_from > hmm_to {
+            issues.push(ValidationIssue::ModelRangeInvalid { hmm_from, hmm_to });
+        }
+
+        let strand = self.strand();
+        let (ali_from, ali_to) = (self.ali_from(), self.ali_to());
+        let (env_from, env_to) = (self.env_from(), self.env_to());
+        for (from, to) in [(ali_from, ali_to), (env_from, env_to)] {
+            if !runs_with(strand, from, to) {
+                issues.push(ValidationIssue::StrandCoordinateMismatch { strand, from, to });
+            }
+        }
+
+        let (ali_lo, ali_hi) = bounds(ali_from, ali_to);
+        let (env_lo, env_hi) = bounds(env_from, env_to);
+        if ali_lo < env_lo || ali_hi > env_hi {
+            issues.push(ValidationIssue::AlignmentOutsideEnvelope {
+                ali_from,
+                ali_to,
+                env_from,
+                env_to,
+            });
+        }
+        let sq_len = self.sq_len();
+        if env_lo < 1 || env_hi > sq_len {
+            issues.push(ValidationIssue::EnvelopeOutsideSequence {
+                env_from,
+                env_to,
+                sq_len,
+            });
+        }
+
+        check_e_value(&mut issues, self.e_value());
+        check_bias(&mut issues, self.bias());
+        issues
+    }
+}
+
+impl ProteinRecord {
+    /// Check the record is consistent with itself, returning every
+    /// issue found. An empty list means the record is valid.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        check_e_value(&mut issues, self.e_value_full());
+        check_bias(&mut issues, self.bias_full());
+        check_e_value(&mut issues, self.e_value_best());
+        check_bias(&mut issues, self.bias_best());
+        if self.exp() < 0.0 || self.exp().is_nan() {
+            issues.push(ValidationIssue::ExpOutOfRange(self.exp()));
+        }
+        let counts = [
+            ("reg", self.reg()),
+            ("clu", self.clu()),
+            ("ov", self.ov()),
+            ("env", self.env()),
+            ("dom", self.dom()),
+            ("rep", self.rep()),
+            ("inc", self.inc()),
+        ];
+        for (column, value) in counts {
+            if value < 0 {
+                issues.push(ValidationIssue::NegativeCount { column, value });
+            }
+        }
+        issues
+    }
+}
+
+impl Record {
+    /// Check the record is consistent with itself, returning every
+    /// issue found. See `DNARecord::validate` and
+    /// `ProteinRecord::validate`.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        match self {
+            Record::Protein(record) => record.validate(),
+            Record::Dna(record) => record.validate(),
+        }
+    }
+}
+
+fn check_e_value(issues: &mut Vec<ValidationIssue>, e_value: f64) {
+    if e_value < 0.0 || e_value.is_nan() {
+        issues.push(ValidationIssue::EValueOutOfRange(e_value));
+    }
+}
+
+fn check_bias(issues: &mut Vec<ValidationIssue>, bias: f32) {
+    if bias < 0.0 || bias.is_nan() {
+        issues.push(ValidationIssue::BiasOutOfRange(bias));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DNAParts, ErrorKind, ProteinParts, Reader};
+
+    fn dna(f: impl FnOnce(&mut DNAParts)) -> DNARecord {
+        let mut parts = DNARecord::new(
+            "chr1".into(),
+            "-".into(),
+            "TR".into(),
+            "-".into(),
+            1,
+            315,
+            1000,
+            1314,
+            998,
+            1316,
+            50000,
+            Strand::Positive,
+            6.5e-34,
+            124.1,
+            1.2,
+        )
+        .into_parts();
+        f(&mut parts);
+        DNARecord::from_parts(parts)
+    }
+
+    fn protein(f: impl FnOnce(&mut ProteinParts)) -> ProteinRecord {
+        let mut parts = ProteinRecord::new(
+            "HBB_HUMAN".into(),
+            "-".into(),
+            "MYG_ESCGI".into(),
+            "-".into(),
+            2.3e-11,
+            30.3,
+            0.1,
+            2.5e-11,
+            30.2,
+            0.1,
+            1.0,
+            1,
+            0,
+            0,
+            1,
+            1,
+            1,
+            1,
+        )
+        .into_parts();
+        f(&mut parts);
+        ProteinRecord::from_parts(parts)
+    }
+
+    #[test]
+    fn test_valid_records() {
+        assert_eq!(dna(|_| ()).validate(), vec![]);
+        assert_eq!(protein(|_| ()).validate(), vec![]);
+        // negative scores are fine, only the bias can't be
+        assert_eq!(dna(|p| p.score = -3.5).validate(), vec![]);
+
+        for file in [
+            include_str!("../data/test.tbl"),
+            include_str!("../data/corpus/nhmmer.tbl"),
+            include_str!("../data/corpus/hmmsearch.tbl"),
+            include_str!("../data/corpus/jackhmmer.tbl"),
+            include_str!("../data/corpus/phmmer.tbl"),
+            include_str!("../data/corpus/hmmscan.tbl"),
+        ] {
+            let mut reader = Reader::from_reader(file.as_bytes()).unwrap().strict(true);
+            assert!(reader.records().all(|r| r.unwrap().validate().is_empty()));
+        }
+    }
+
+    #[test]
+    fn test_dna_issues() {
+        let record = dna(|p| p.hmm_to = 0);
+        assert_eq!(
+            record.validate(),
+            vec![ValidationIssue::ModelRangeInvalid {
+                hmm_from: 1,
+                hmm_to: 0
+            }]
+        );
+
+        let record = dna(|p| {
+            p.strand = Strand::Negative;
+            (p.ali_from, p.ali_to) = (1314, 1000);
+        });
+        assert_eq!(
+            record.validate(),
+            vec![ValidationIssue::StrandCoordinateMismatch {
+                strand: Strand::Negative,
+                from: 998,
+                to: 1316
+            }]
+        );
+
+        let record = dna(|p| p.env_from = 1001);
+        assert_eq!(
+            record.validate(),
+            vec![ValidationIssue::AlignmentOutsideEnvelope {
+                ali_from: 1000,
+                ali_to: 1314,
+                env_from: 1001,
+                env_to: 1316
+            }]
+        );
+
+        let record = dna(|p| p.sq_len = 1315);
+        assert_eq!(
+            record.validate(),
+            vec![ValidationIssue::EnvelopeOutsideSequence {
+                env_from: 998,
+                env_to: 1316,
+                sq_len: 1315
+            }]
+        );
+
+        let record = dna(|p| p.e_value = -1.0);
+        assert_eq!(
+            record.validate(),
+            vec![ValidationIssue::EValueOutOfRange(-1.0)]
+        );
+
+        let record = dna(|p| p.bias = f32::NAN);
+        assert!(matches!(
+            record.validate()[..],
+            [ValidationIssue::BiasOutOfRange(b)] if b.is_nan()
+        ));
+    }
+
+    #[test]
+    fn test_protein_issues() {
+        let record = protein(|p| {
+            p.e_value_best = f64::NAN;
+            p.bias_full = -0.1;
+            p.exp = -1.0;
+            p.rep = -2;
+        });
+        let issues = record.validate();
+        assert_eq!(issues.len(), 4);
+        assert_eq!(issues[0], ValidationIssue::BiasOutOfRange(-0.1));
+        assert!(matches!(issues[1], ValidationIssue::EValueOutOfRange(e) if e.is_nan()));
+        assert_eq!(issues[2], ValidationIssue::ExpOutOfRange(-1.0));
+        assert_eq!(
+            issues[3],
+            ValidationIssue::NegativeCount {
+                column: "rep",
+                value: -2
+            }
+        );
+        assert_eq!(
+            Record::Protein(record).validate().len(),
+            4,
+            "records validate as their kind"
+        );
+    }
+
+    #[test]
+    fn test_strict_reader() {
+        let file = include_str!("../data/test.tbl");
+        let mut lines = file.lines().collect::<Vec<_>>();
+        // the first record, with its envelope shrunk inside the alignment
+        let broken = lines[2].replacen("338943", "338950", 1);
+        assert_ne!(broken, lines[2]);
+        lines[2] = &broken;
+        let file = lines.join("\n") + "\n";
+
+        // lenient by default, so the issues can be collected
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let issues = reader
+            .records()
+            .flat_map(|r| r.unwrap().validate())
+            .collect::<Vec<_>>();
+        assert_eq!(issues.len(), 1);
+        assert!(matches!(
+            issues[0],
+            ValidationIssue::AlignmentOutsideEnvelope { .. }
+        ));
+
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap().strict(true);
+        let err = reader.records().next().unwrap().unwrap_err();
+        assert_eq!(reader.line(), 3);
+        match err.kind() {
+            ErrorKind::Coordinates { line: 3, error } => assert!(matches!(
+                error,
+                CoordinateError::AlignmentOutsideEnvelope { .. }
+            )),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_check_coordinates() {
+        assert_eq!(dna(|_| ()).check_coordinates(), Ok(()));
+        // minus strand coordinates are checked by their bounds
+        let record = dna(|p| {
+            p.strand = Strand::Negative;
+            (p.ali_from, p.ali_to, p.env_from, p.env_to) = (1314, 1000, 1316, 998);
+        });
+        assert_eq!(record.check_coordinates(), Ok(()));
+
+        let record = dna(|p| (p.hmm_from, p.hmm_to) = (316, 315));
+        assert_eq!(
+            record.check_coordinates(),
+            Err(CoordinateError::ModelRangeReversed {
+                hmm_from: 316,
+                hmm_to: 315
+            })
+        );
+        let record = dna(|p| p.env_to = 1300);
+        assert!(matches!(
+            record.check_coordinates(),
+            Err(CoordinateError::AlignmentOutsideEnvelope { env_to: 1300, .. })
+        ));
+        let record = dna(|p| (p.ali_from, p.env_from) = (0, 0));
+        assert!(matches!(
+            record.check_coordinates(),
+            Err(CoordinateError::AlignmentOutsideSequence { ali_from: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_corrupt_sq_len() {
+        let file = include_str!("../data/corrupt_sq_len.tbl");
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap().strict(true);
+        let mut records = reader.records();
+        assert!(records.next().unwrap().is_ok());
+        let err = records.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid coordinates on line 4 - 1 <= min(ali_from, ali_to) and max(ali_from, ali_to) <= sq_len do not hold (ali_from 61501865, ali_to 61501510, sq_len 11009245)"
+        );
+        // reading carries on after the record
+        assert!(records.next().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_validation_warnings() {
+        // the second record's envelope runs past the end of its sequence
+        let file = include_str!("../data/invalid_envelope.tbl");
+        let mut reader = Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .validate_records(true);
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            reader.warnings(),
+            &[Warning::Validation {
+                line: 4,
+                issue: ValidationIssue::EnvelopeOutsideSequence {
+                    env_from: 4490,
+                    env_to: 5000,
+                    sq_len: 4800
+                }
+            }]
+        );
+        assert_eq!(
+            reader.warnings()[0].to_string(),
+            "line 4: envelope 4490..5000 is outside the sequence of length 4800"
+        );
+        assert_eq!(reader.take_warnings().len(), 1);
+        assert!(reader.warnings().is_empty());
+
+        // none unless asked for
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(reader.records().count(), 3);
+        assert!(reader.warnings().is_empty());
+
+        // and errors if strict
+        let mut reader = Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .validate_records(true)
+            .strict(true);
+        let results = reader.records().collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        match results[1].as_ref().unwrap_err().kind() {
+            ErrorKind::Validation { line: 4, issues } => assert!(matches!(
+                issues[..],
+                [ValidationIssue::EnvelopeOutsideSequence { env_to: 5000, .. }]
+            )),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+        assert!(reader.warnings().is_empty());
+
+        // oddities of the file are warnings too, whether records are
+        // validated or not, in the order of their lines
+        let file = file
+            .replacen("    +     1.9e-37", "    -     1.9e-37", 1)
+            .replacen("6                    -          TR", "6\t-\tTR", 1)
+            .replace('\n', "\r\n");
+        let mut reader = Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .validate_records(true);
+        assert_eq!(reader.warnings(), &[Warning::CrlfLineEndings { line: 1 }]);
+        assert_eq!(reader.records().count(), 3);
+        let warnings = reader.take_warnings();
+        assert_eq!(warnings.len(), 6);
+        assert_eq!(
+            warnings[1],
+            Warning::StrandMismatch {
+                line: 3,
+                column: Strand::Negative,
+                coordinates: Strand::Positive
+            }
+        );
+        // for both the alignment and the envelope
+        assert!(warnings[2..4].iter().all(|w| matches!(
+            w,
+            Warning::Validation {
+                line: 3,
+                issue: ValidationIssue::StrandCoordinateMismatch { .. }
+            }
+        )));
+        assert!(matches!(
+            warnings[4..],
+            [
+                Warning::Validation { line: 4, .. },
+                Warning::TabDelimited { line: 5 }
+            ]
+        ));
+        let lines = warnings.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        assert_eq!(lines[0], "line 1: the file has CRLF line endings");
+        assert_eq!(
+            lines[1],
+            "line 3: the strand column is -, but the coordinates run the way of the + strand"
+        );
+        assert_eq!(
+            lines[5],
+            "line 5: the file has tabs between its columns, so was not written by HMMER"
+        );
+
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(reader.records().count(), 3);
+        assert_eq!(reader.warnings().len(), 3);
+    }
+
+    #[test]
+    fn test_strict_protein() {
+        let file = include_str!("../data/corpus/hmmsearch.tbl");
+        let record = file.lines().nth(3).unwrap();
+        let file = file.replacen(record, &record.replacen("   0.8", "  -0.8", 1), 1);
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap().strict(true);
+        let err = reader.records().next().unwrap().unwrap_err();
+        match err.kind() {
+            ErrorKind::Validation { line: 4, issues } => {
+                assert_eq!(issues, &[ValidationIssue::BiasOutOfRange(-0.8)])
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_strand_disagreement() {
+        let file = include_str!("../data/test.tbl");
+        // the first record, on the minus strand by its column alone
+        let file = file.replacen("    +     1.9e-37", "    -     1.9e-37", 1);
+
+        // the column is kept by default
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let record = reader.dna_records().unwrap().next().unwrap().unwrap();
+        assert_eq!(record.strand(), Strand::Negative);
+        assert_eq!(record.coordinate_strand(), Some(Strand::Positive));
+        assert_eq!(record.interval(), (338944, 339292));
+
+        // or the coordinates trusted over it
+        let mut reader = Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .keep_raw_lines(true)
+            .trust_coordinates(true);
+        let record = reader.dna_records().unwrap().next().unwrap().unwrap();
+        assert_eq!(record.strand(), Strand::Positive);
+        assert_eq!(record.raw_line(), None);
+
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap().strict(true);
+        let err = reader.records().next().unwrap().unwrap_err();
+        match err.kind() {
+            ErrorKind::Validation { line: 3, issues } => assert!(matches!(
+                issues[0],
+                ValidationIssue::StrandCoordinateMismatch {
+                    strand: Strand::Negative,
+                    ..
+                }
+            )),
+            kind => panic!("unexpected error {:?}", kind),
+        }
+
+        // a single position could be on either strand
+        let record = dna(|p| (p.ali_from, p.ali_to) = (1000, 1000));
+        assert_eq!(record.coordinate_strand(), None);
+        assert_eq!(record.interval(), (1000, 1000));
+
+        // with its strand from the coordinates the record is consistent
+        let mut reader = Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .trust_coordinates(true)
+            .strict(true);
+        assert!(reader.records().next().unwrap().is_ok());
+    }
+
+    #[test]
+    fn test_strand_mismatch_warning() {
+        let file = include_str!("../data/test.tbl");
+        let file = file.replacen("    +     1.9e-37", "    -     1.9e-37", 1);
+        let expected = Warning::StrandMismatch {
+            line: 3,
+            column: Strand::Negative,
+            coordinates: Strand::Positive,
+        };
+        // whichever strand is kept, the disagreement is recorded
+        for trust in [false, true] {
+            let mut reader = Reader::from_reader(file.as_bytes())
+                .unwrap()
+                .trust_coordinates(trust);
+            assert!(reader.records().all(|r| r.is_ok()));
+            assert_eq!(reader.warnings().to_vec(), vec![expected.clone()]);
+            assert!(reader.warnings()[0].to_string().starts_with("line 3: "));
+        }
+    }
+
+    #[test]
+    fn test_strict_nhmmscan() {
+        // the length column is the model length, so envelopes past it
+        // are not errors
+        let file = include_str!("../data/corpus/nhmmscan.tbl");
+        let file = file.replacen("846     846    +", "846     800    +", 1);
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let issues = reader.records().flat_map(|r| r.unwrap().validate());
+        assert_eq!(issues.count(), 1);
+
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap().strict(true);
+        assert!(reader.records().all(|r| r.is_ok()));
+    }
+}
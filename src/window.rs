@@ -0,0 +1,192 @@
+use crate::{
+    record::{Record, TbloutRecord},
+    Result,
+};
+use std::collections::BTreeMap;
+
+/// The hits on each target, sorted along it, for looking at each hit
+/// with its neighbours, e.g. to chain or cluster them.
+///
+/// All the hits are held, grouped by target, so memory grows with the
+/// number of hits. To bound it, pass the records of one target, or a
+/// few, at a time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetHits<R = Record> {
+    /// The hits of each target, sorted along it, in order of target
+    /// name.
+    targets: Vec<(String, Vec<R>)>,
+}
+
+impl<R: TbloutRecord> TargetHits<R> {
+    /// Group the records by target, and sort the hits of each by the
+    /// start of their alignment, then its end. Starts are the lower of
+    /// the coordinates, whatever the strand. Records without
+    /// coordinates, i.e. protein records, are skipped.
+    pub fn from_records(records: impl IntoIterator<Item = Result<R>>) -> Result<TargetHits<R>> {
+        let mut targets: BTreeMap<String, Vec<R>> = BTreeMap::new();
+        for record in records {
+            let record = record?;
+            if record.interval_opt().is_some() {
+                targets
+                    .entry(record.target_name())
+                    .or_default()
+                    .push(record);
+            }
+        }
+        let targets = targets
+            .into_iter()
+            .map(|(target, mut hits)| {
+                hits.sort_by_key(span);
+                (target, hits)
+            })
+            .collect();
+        Ok(TargetHits { targets })
+    }
+
+    /// The hits of each target, sorted along it.
+    pub fn targets(&self) -> impl Iterator<Item = (&str, &[R])> {
+        self.targets
+            .iter()
+            .map(|(target, hits)| (target.as_str(), hits.as_slice()))
+    }
+
+    /// Every run of `k` consecutive hits along each target, in order,
+    /// each overlapping the last by all but one hit. A target with
+    /// fewer than `k` hits has none.
+    ///
+    /// # Panics
+    ///
+    /// If `k` is zero.
+    pub fn windows_per_target(&self, k: usize) -> impl Iterator<Item = (&str, &[R])> {
+        assert!(k > 0, "window size must be positive");
+        self.targets
+            .iter()
+            .flat_map(move |(target, hits)| hits.windows(k).map(move |w| (target.as_str(), w)))
+    }
+
+    /// Every pair of hits on a target with at most `distance` bases
+    /// between them, whatever their strands, the first starting first.
+    /// Hits which overlap or abut have no bases between them, so are
+    /// always a pair.
+    pub fn pairs_within(&self, distance: u64) -> impl Iterator<Item = (&str, &R, &R)> {
+        self.targets.iter().flat_map(move |(target, hits)| {
+            hits.iter().enumerate().flat_map(move |(i, a)| {
+                let end = span(a).1;
+                // the hits after start no earlier, so the first too far
+                // off ends the pairs
+                hits[i + 1..]
+                    .iter()
+                    .take_while(move |b| gap(end, span(*b).0) <= distance)
+                    .map(move |b| (target.as_str(), a, b))
+            })
+        })
+    }
+}
+
+/// The start and end of a hit on its target, whatever its strand.
+fn span<R: TbloutRecord>(record: &R) -> (i64, i64) {
+    match record.interval_opt() {
+        Some((from, to)) => (from.min(to), from.max(to)),
+        None => unreachable!("hits without coordinates are skipped"),
+    }
+}
+
+/// The number of bases between the end of a hit and the start of
+/// another, zero if they overlap.
+fn gap(end: i64, start: i64) -> u64 {
+    (start - end - 1).max(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reader::parse_dna_line, Reader};
+
+    /// A record for a hit of `query` on `target` from `from` to `to`.
+    fn hit(target: &str, query: &str, from: i64, to: i64) -> Result<Record> {
+        let strand = if from <= to { "+" } else { "-" };
+        let line = format!(
+            "{} - {} - 2 317 {} {} {} {} 133811386 {} 1.9e-37 136.7 1.4 -",
+            target, query, from, to, from, to, strand
+        );
+        Ok(Record::Dna(parse_dna_line(&line).unwrap()))
+    }
+
+    /// Five hits on chr1, out of order, and one on chr2.
+    fn hits() -> TargetHits {
+        TargetHits::from_records(vec![
+            hit("chr1", "C", 400, 301),
+            hit("chr2", "F", 1, 100),
+            hit("chr1", "A", 1, 100),
+            hit("chr1", "E", 1000, 1100),
+            hit("chr1", "B", 151, 250),
+            hit("chr1", "D", 320, 380),
+        ])
+        .unwrap()
+    }
+
+    /// The queries of some hits, which name them in the tests.
+    fn names<'a>(hits: impl IntoIterator<Item = &'a Record>) -> String {
+        hits.into_iter().map(|r| r.query_name()).collect()
+    }
+
+    #[test]
+    fn test_sorted_per_target() {
+        let hits = hits();
+        let targets = hits
+            .targets()
+            .map(|(target, hits)| (target, names(hits)))
+            .collect::<Vec<_>>();
+        // by the lower coordinate, whatever the strand
+        assert_eq!(targets, [("chr1", "ABCDE".into()), ("chr2", "F".into())]);
+    }
+
+    #[test]
+    fn test_windows_per_target() {
+        let hits = hits();
+        let windows = hits
+            .windows_per_target(3)
+            .map(|(target, w)| (target, names(w)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            windows,
+            [
+                ("chr1", "ABC".into()),
+                ("chr1", "BCD".into()),
+                ("chr1", "CDE".into())
+            ]
+        );
+        assert_eq!(hits.windows_per_target(1).count(), 6);
+        assert_eq!(hits.windows_per_target(5).count(), 1);
+        assert_eq!(hits.windows_per_target(6).count(), 0);
+    }
+
+    #[test]
+    fn test_pairs_within() {
+        let hits = hits();
+        let pairs = |distance| {
+            hits.pairs_within(distance)
+                .map(|(target, a, b)| {
+                    assert_eq!(target, "chr1");
+                    names([a, b])
+                })
+                .collect::<Vec<_>>()
+        };
+        // C and D overlap, so are always a pair
+        assert_eq!(pairs(0), ["CD"]);
+        // 50 bases between A and B, and B and C
+        assert_eq!(pairs(49), ["CD"]);
+        assert_eq!(pairs(50), ["AB", "BC", "CD"]);
+        // 200 between A and C, 599 between C and E, 619 between D and E
+        assert_eq!(pairs(600), ["AB", "AC", "AD", "BC", "BD", "CD", "CE"]);
+        assert_eq!(pairs(619), ["AB", "AC", "AD", "BC", "BD", "CD", "CE", "DE"]);
+        assert_eq!(pairs(10_000).len(), 10);
+    }
+
+    #[test]
+    fn test_protein_records_skipped() {
+        let reader = Reader::from_path("./data/corpus/hmmsearch.tbl").unwrap();
+        let hits = TargetHits::from_records(reader.into_records()).unwrap();
+        assert_eq!(hits.targets().count(), 0);
+    }
+}
@@ -0,0 +1,1839 @@
+#[cfg(all(feature = "std-fs", any(feature = "gzip", feature = "zstd")))]
+use crate::compress::{CompressedFile, Format};
+use crate::{
+    error::{Error, ErrorKind, Result},
+    header::Header,
+    record::{
+        DNARecord, Meta, Program, ProteinRecord, Record, RecordKind, DNA_WIDTHS, PROTEIN_WIDTHS,
+    },
+};
+
+use std::io::{self, Write};
+#[cfg(feature = "std-fs")]
+use std::{
+    fs::{File, OpenOptions},
+    path::Path,
+};
+
+/// How the columns of each record are separated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Columns padded with spaces to line up under the header, as
+    /// HMMER writes them.
+    #[default]
+    Aligned,
+    /// Columns separated by a single space, without padding, so the
+    /// Nth column of a record is its Nth space separated field, as
+    /// `cut -d' ' -fN` finds it, however wide the values. Names never
+    /// contain spaces; the description is last and written as it is,
+    /// so takes the rest of the line. The output reads back as the
+    /// same records as the aligned form.
+    SingleSpace,
+    /// Columns separated by a tab. Tblout fields never contain tabs,
+    /// so nothing is quoted.
+    Tab,
+}
+
+/// How floating point values are written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FloatStyle {
+    /// Rounded as HMMER writes them, e.g. scores to one decimal place.
+    #[default]
+    Hmmer,
+    /// The shortest representation which reads back as the same value.
+    Raw,
+}
+
+/// How the description of each record is written. The records
+/// themselves are left unchanged.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum DescriptionMode {
+    /// The description as it was read.
+    #[default]
+    Keep,
+    /// `-` in place of the description, as HMMER writes for a target
+    /// without one.
+    Drop,
+    /// The description cut down to at most this many characters.
+    Truncate(usize),
+    /// This text in place of every description.
+    Replace(String),
+}
+
+impl DescriptionMode {
+    /// The description to write in place of `description`, or `None`
+    /// to write it as it is. An empty description would leave the
+    /// column out, so is written as `-`.
+    fn apply(&self, description: &str) -> Option<String> {
+        let replaced = match self {
+            DescriptionMode::Keep => return None,
+            DescriptionMode::Drop => "-",
+            DescriptionMode::Truncate(n) => match description.char_indices().nth(*n) {
+                Some((end, _)) => &description[..end],
+                None => return None,
+            },
+            DescriptionMode::Replace(s) => s,
+        };
+        if replaced.is_empty() {
+            Some("-".to_string())
+        } else {
+            Some(replaced.to_string())
+        }
+    }
+}
+
+/// The comment lines written between the records and the metadata.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FooterStyle {
+    /// A single `#` line, which every HMMER program writes before the
+    /// `# Program:` block.
+    #[default]
+    Hmmer,
+    /// The metadata alone, for output which already ends with the
+    /// separator.
+    MetaOnly,
+}
+
+/// A record which can be written by a `Writer`: a `Record`, or one
+/// of the concrete records it wraps.
+///
+/// This trait is sealed and cannot be implemented outside this crate.
+pub trait WritableRecord: private::Sealed {
+    /// The kind of the record.
+    fn kind(&self) -> RecordKind;
+}
+
+mod private {
+    use super::FloatStyle;
+    use std::io;
+
+    /// The formatting a `Writer` needs, kept out of the public API.
+    pub trait Sealed {
+        fn target_name(&self) -> String;
+        fn description(&self) -> String;
+        fn raw_line(&self) -> Option<&str>;
+        fn fields(&self, style: FloatStyle) -> Vec<String>;
+        fn write_aligned<W: io::Write>(
+            &self,
+            w: &mut W,
+            widths: &[usize],
+            style: FloatStyle,
+            description: Option<&str>,
+        ) -> io::Result<()>;
+    }
+}
+
+macro_rules! writable_record {
+    ($ty:ty, $kind:expr) => {
+        impl WritableRecord for $ty {
+            fn kind(&self) -> RecordKind {
+                $kind(self)
+            }
+        }
+
+        impl private::Sealed for $ty {
+            fn target_name(&self) -> String {
+                <$ty>::target_name(self)
+            }
+
+            fn description(&self) -> String {
+                <$ty>::description(self)
+            }
+
+            fn raw_line(&self) -> Option<&str> {
+                <$ty>::raw_line(self)
+            }
+
+            fn fields(&self, style: FloatStyle) -> Vec<String> {
+                <$ty>::fields(self, style)
+            }
+
+            fn write_aligned<W: io::Write>(
+                &self,
+                w: &mut W,
+                widths: &[usize],
+                style: FloatStyle,
+                description: Option<&str>,
+            ) -> io::Result<()> {
+                <$ty>::write_aligned(self, w, widths, style, description)
+            }
+        }
+    };
+}
+
+writable_record!(Record, Record::kind);
+writable_record!(DNARecord, |_| RecordKind::Dna);
+writable_record!(ProteinRecord, |_| RecordKind::Protein);
+
+/// A builder for configuring a `Writer`.
+///
+/// By default records are aligned under the header and numbers
+/// are formatted as HMMER formats them, which is what `Writer::new`
+/// does.
+#[derive(Debug, Clone)]
+pub struct WriterBuilder {
+    delimiter: Delimiter,
+    float_style: FloatStyle,
+    header: bool,
+    meta: bool,
+    round_trip: bool,
+    description: DescriptionMode,
+}
+
+impl Default for WriterBuilder {
+    fn default() -> WriterBuilder {
+        WriterBuilder {
+            delimiter: Delimiter::default(),
+            float_style: FloatStyle::default(),
+            header: true,
+            meta: true,
+            round_trip: false,
+            description: DescriptionMode::default(),
+        }
+    }
+}
+
+impl WriterBuilder {
+    /// Construct a new builder with the default configuration.
+    pub fn new() -> WriterBuilder {
+        WriterBuilder::default()
+    }
+
+    /// Set how the columns of each record are separated.
+    pub fn delimiter(&mut self, delimiter: Delimiter) -> &mut WriterBuilder {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Set whether the header lines are written. When disabled,
+    /// `Writer::write_header` still sets the column widths.
+    pub fn write_header(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.header = yes;
+        self
+    }
+
+    /// Set whether the metadata block ending the file is written.
+    pub fn write_meta(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.meta = yes;
+        self
+    }
+
+    /// Set how floating point values are written.
+    pub fn float_style(&mut self, style: FloatStyle) -> &mut WriterBuilder {
+        self.float_style = style;
+        self
+    }
+
+    /// Set whether records read from a file are written back exactly
+    /// as they were read, which needs the reader to keep the lines
+    /// (see `Reader::keep_raw_lines`). Records which have been changed
+    /// with a setter, or which were not read that way, are formatted
+    /// as usual. This only applies to aligned output.
+    pub fn round_trip(&mut self, yes: bool) -> &mut WriterBuilder {
+        self.round_trip = yes;
+        self
+    }
+
+    /// Set how the description of each record is written.
+    pub fn description(&mut self, mode: DescriptionMode) -> &mut WriterBuilder {
+        self.description = mode;
+        self
+    }
+
+    /// Build a writer with this configuration.
+    pub fn build<W: io::Write>(&self, wtr: W) -> Writer<W> {
+        Writer {
+            wtr: Some(io::BufWriter::new(Tracked {
+                inner: wtr,
+                failed: false,
+            })),
+            widths: Vec::new(),
+            delimiter: self.delimiter,
+            float_style: self.float_style,
+            header: self.header,
+            meta: self.meta,
+            round_trip: self.round_trip,
+            realign: false,
+            description: self.description.clone(),
+            program: None,
+            header_written: false,
+            records_written: false,
+            meta_written: false,
+            finisher: None,
+        }
+    }
+}
+
+/// A writer of HMMER tblout files.
+///
+/// Output is buffered. `finish` is the way to end a file: it writes
+/// the metadata, flushes the output and returns the underlying writer,
+/// or the first error. `flush` and `into_inner` flush too. A writer
+/// which is dropped instead still flushes its output, so nothing is
+/// lost, but any error doing so can't be returned, so is ignored in
+/// release builds and panics in debug builds.
+///
+/// A file is written in HMMER's order: the header, the records and
+/// then the metadata. Records cannot be written after the metadata,
+/// but further complete sections can be with `write_section`.
+///
+/// A writer is `Send` when its output is, so records can be written
+/// from another thread than the one they were read on.
+pub struct Writer<W: io::Write> {
+    /// The underlying buffered writer, only taken by `into_inner`.
+    wtr: Option<io::BufWriter<Tracked<W>>>,
+    /// The column widths from the last header written.
+    widths: Vec<usize>,
+    /// How the columns of each record are separated.
+    delimiter: Delimiter,
+    /// How floating point values are written.
+    float_style: FloatStyle,
+    /// Whether to write the header lines.
+    header: bool,
+    /// Whether to write the metadata block.
+    meta: bool,
+    /// Whether to write unchanged records as the lines they were read from.
+    round_trip: bool,
+    /// Whether the widths were fixed with `set_header`, so records are
+    /// aligned to them even in round trip mode.
+    realign: bool,
+    /// How the description of each record is written.
+    description: DescriptionMode,
+    /// The program the output is for, if set with `with_program`.
+    program: Option<Program>,
+    /// Whether a header has been written.
+    header_written: bool,
+    /// Whether records have been written since the last metadata.
+    records_written: bool,
+    /// Whether the metadata has been written, ending the file.
+    meta_written: bool,
+    /// Completes the underlying writer once the buffered output has
+    /// been flushed, e.g. writing the footer of a compressed stream.
+    finisher: Option<fn(&mut W) -> io::Result<()>>,
+}
+
+#[cfg(feature = "std-fs")]
+impl Writer<File> {
+    /// Construct a new writer to a file, creating it if it does
+    /// not exist and truncating it if it does.
+    pub fn to_path<P: AsRef<Path>>(path: P) -> Result<Writer<File>> {
+        Ok(Writer::new(File::create(path)?))
+    }
+
+    /// Construct a new writer to a file, creating it if it does
+    /// not exist and truncating it if it does.
+    #[deprecated(since = "0.3.0", note = "use `Writer::to_path` instead")]
+    pub fn to_file(path: &str) -> Result<Writer<File>> {
+        Writer::to_path(path)
+    }
+
+    /// Construct a new writer which appends to the end of a file,
+    /// creating it if it does not exist. Use `write_section` to add a
+    /// complete section after those already in the file.
+    pub fn append_to_path<P: AsRef<Path>>(path: P) -> Result<Writer<File>> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(Writer::new(file))
+    }
+}
+
+impl Writer<io::Stdout> {
+    /// Construct a new writer to standard output.
+    pub fn to_stdout() -> Writer<io::Stdout> {
+        Writer::new(io::stdout())
+    }
+}
+
+impl Writer<Vec<u8>> {
+    /// Construct a new writer to an in memory buffer, which
+    /// `into_inner` returns.
+    pub fn to_vec() -> Writer<Vec<u8>> {
+        Writer::new(Vec::new())
+    }
+}
+
+#[cfg(all(feature = "std-fs", any(feature = "gzip", feature = "zstd")))]
+impl Writer<CompressedFile> {
+    /// Construct a new writer to a gzip compressed file at compression
+    /// `level` (0 to 9), creating it if it does not exist and truncating
+    /// it if it does.
+    #[cfg(feature = "gzip")]
+    pub fn to_gz_file<P: AsRef<Path>>(path: P, level: u32) -> Result<Writer<CompressedFile>> {
+        Writer::to_compressed_file(path, Format::Gzip, level)
+    }
+
+    /// Construct a new writer to a file compressed with `format`,
+    /// creating it if it does not exist and truncating it if it does.
+    ///
+    /// The compressed stream is completed by `finish` or `into_inner`.
+    /// Dropping the writer also completes it, but can't return any
+    /// error in doing so, so prefer calling one of them.
+    pub fn to_compressed_file<P: AsRef<Path>>(
+        path: P,
+        format: Format,
+        level: u32,
+    ) -> Result<Writer<CompressedFile>> {
+        let file = CompressedFile::new(File::create(path)?, format, level)?;
+        let mut writer = Writer::new(file);
+        writer.finisher = Some(CompressedFile::try_finish);
+        Ok(writer)
+    }
+}
+
+impl<W: io::Write> Writer<W> {
+    /// Construct a new writer from anything that implements `io::Write`,
+    /// with the default configuration (see `WriterBuilder`).
+    pub fn new(wtr: W) -> Writer<W> {
+        WriterBuilder::new().build(wtr)
+    }
+
+    /// Construct a new writer for the output of a program. The header
+    /// HMMER writes for the program is written before the first record,
+    /// unless a header has been written already, and records of the
+    /// wrong kind for the program are rejected.
+    pub fn with_program(wtr: W, program: Program) -> Writer<W> {
+        let mut writer = Writer::new(wtr);
+        writer.program = Some(program);
+        writer
+    }
+
+    /// Construct a writer for the output of a program which carries on
+    /// from where a `with_program` writer left off, so the header has
+    /// already been written.
+    #[cfg(feature = "std-fs")]
+    pub(crate) fn resume(wtr: W, program: Program) -> Writer<W> {
+        let widths = Header::for_program(program)
+            .map(|h| h.calculate_dashes())
+            .unwrap_or_default();
+        let mut writer = Writer::with_program(wtr, program);
+        writer.widths = widths;
+        writer.header_written = true;
+        writer
+    }
+
+    /// Write the header lines. Records written afterwards are
+    /// aligned to the columns of this header.
+    pub fn write_header(&mut self, header: &Header) -> Result<()> {
+        self.widths = header.calculate_dashes();
+        self.header_written = true;
+        if !self.header {
+            return Ok(());
+        }
+        Ok(write!(self.wtr(), "{}", header)?)
+    }
+
+    /// Align the records written afterwards to the columns of
+    /// `header`, without writing it, e.g. when the header has been
+    /// written already or records from several files are gathered
+    /// under one header.
+    ///
+    /// Unlike `write_header`, this also overrides round trip mode (see
+    /// `WriterBuilder::round_trip`): lines read from a file are
+    /// aligned to that file's header, so records are formatted afresh
+    /// to line up under this one. The header counts as written, so a
+    /// `with_program` writer doesn't write its own.
+    pub fn set_header(&mut self, header: &Header) {
+        self.widths = header.calculate_dashes();
+        self.header_written = true;
+        self.realign = true;
+    }
+
+    /// Write a single record. If the writer was constructed with
+    /// `with_program`, an `ErrorKind::InvalidInput` error is returned
+    /// for records of the wrong kind. I/O errors writing the record
+    /// are returned as `ErrorKind::WriteRecord`, naming its target.
+    pub fn write_record<T: WritableRecord>(&mut self, record: &T) -> Result<()> {
+        if self.meta_written {
+            return Err(invalid_input("cannot write a record after the metadata"));
+        }
+        if let Some(program) = self.program {
+            self.check_program(program, record)?;
+        }
+        self.records_written = true;
+        self.write_line(record).map_err(|error| {
+            Error::new(ErrorKind::WriteRecord {
+                target: record.target_name(),
+                error,
+            })
+        })
+    }
+
+    /// Write a record as a line, in the configured layout.
+    fn write_line<T: WritableRecord>(&mut self, record: &T) -> io::Result<()> {
+        let description = match self.description {
+            DescriptionMode::Keep => None,
+            ref mode => mode.apply(&record.description()),
+        };
+        let sep = match self.delimiter {
+            Delimiter::Aligned => {
+                let raw_line = record
+                    .raw_line()
+                    .filter(|_| self.round_trip && !self.realign);
+                if let (Some(line), None) = (raw_line, &description) {
+                    return writeln!(self.wtr(), "{}", line);
+                }
+                let wtr = self.wtr.as_mut().expect(TAKEN);
+                record.write_aligned(
+                    wtr,
+                    &self.widths,
+                    self.float_style,
+                    description.as_deref(),
+                )?;
+                return wtr.write_all(b"\n");
+            }
+            Delimiter::SingleSpace => " ",
+            Delimiter::Tab => "\t",
+        };
+        let mut fields = record.fields(self.float_style);
+        if let Some(description) = description {
+            *fields.last_mut().unwrap() = description;
+        }
+        writeln!(self.wtr(), "{}", fields.join(sep))
+    }
+
+    /// Check a record is of the kind the program writes, writing the
+    /// program's header first if no header has been written yet.
+    fn check_program<T: WritableRecord>(&mut self, program: Program, record: &T) -> Result<()> {
+        check_kind(program, record)?;
+        if !self.header_written {
+            if let Some(header) = Header::for_program(program) {
+                self.write_header(&header)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the header for a program followed by the records, with
+    /// the columns widened to fit the longest values as HMMER does,
+    /// rather than aligned to a header written earlier. Returns the
+    /// number of records written.
+    ///
+    /// Records of the wrong kind for the program are rejected before
+    /// anything is written.
+    pub fn write_aligned<T: WritableRecord>(
+        &mut self,
+        program: Program,
+        records: &[T],
+    ) -> Result<usize> {
+        for record in records {
+            check_kind(program, record)?;
+        }
+        let header = Header::canonical(program, &column_widths(program, records))
+            .ok_or_else(unknown_program)?;
+        self.program = Some(program);
+        self.write_header(&header)?;
+        self.write_records(records)
+    }
+
+    /// Write each of the records, returning how many were written.
+    /// Stops at the first error.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn write_records<'a, T: WritableRecord + 'a>(
+        &mut self,
+        records: impl IntoIterator<Item = &'a T>,
+    ) -> Result<usize> {
+        let mut n = 0;
+        for record in records {
+            self.write_record(record)?;
+            n += 1;
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(records = n, "wrote records");
+        Ok(n)
+    }
+
+    /// Write the metadata block which ends a tblout file, preceded
+    /// by the blank comment line separating it from the records. This
+    /// is `write_footer` with `FooterStyle::Hmmer`, as `finish` and
+    /// `write_all` write it.
+    pub fn write_meta(&mut self, meta: &Meta) -> Result<()> {
+        self.write_footer(meta, FooterStyle::Hmmer)
+    }
+
+    /// Write the metadata block which ends a tblout file, preceded by
+    /// the comment lines of `style`.
+    ///
+    /// If the writer was constructed with `with_program` and nothing
+    /// has been written yet, the program's header is written first,
+    /// as HMMER does for a search without hits. Empty metadata, from a
+    /// file without any, writes nothing, so the output has none either.
+    /// Writing the metadata twice is an error.
+    pub fn write_footer(&mut self, meta: &Meta, style: FooterStyle) -> Result<()> {
+        if self.meta_written {
+            return Err(invalid_input("the metadata has already been written"));
+        }
+        if let (Some(program), false) = (self.program, self.header_written) {
+            if let Some(header) = Header::for_program(program) {
+                self.write_header(&header)?;
+            }
+        }
+        self.meta_written = true;
+        self.records_written = false;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(program = %meta.program(), written = self.meta, "finished writing");
+        if !self.meta || meta.is_empty() {
+            return Ok(());
+        }
+        if style == FooterStyle::Hmmer {
+            writeln!(self.wtr(), "#")?;
+        }
+        Ok(write!(self.wtr(), "{}", meta)?)
+    }
+
+    /// Write a complete file in HMMER's layout: the header, the
+    /// records, a blank comment line and the metadata. The writer
+    /// is flushed afterwards. Returns the number of records written.
+    ///
+    /// If the header is empty, as it is for a file of records alone,
+    /// the header HMMER would have written for the program in the
+    /// metadata is generated, with the columns fitted to the records.
+    pub fn write_all<'a, T: WritableRecord + 'a>(
+        &mut self,
+        header: &Header,
+        records: impl IntoIterator<Item = &'a T>,
+        meta: &Meta,
+    ) -> Result<usize> {
+        let n = match header.is_empty() {
+            true if meta.program() != Program::None => {
+                let records = records.into_iter().collect::<Vec<_>>();
+                let widths = column_widths(meta.program(), records.iter().copied());
+                let header =
+                    Header::canonical(meta.program(), &widths).ok_or_else(unknown_program)?;
+                self.write_header(&header)?;
+                self.write_records(records)?
+            }
+            _ => {
+                self.write_header(header)?;
+                self.write_records(records)?
+            }
+        };
+        self.write_meta(meta)?;
+        self.flush()?;
+        Ok(n)
+    }
+
+    /// Write a complete section, as `write_all` does. Unlike
+    /// `write_all` it can be called again to write another section
+    /// after this one, though records cannot be written on their own
+    /// in between. HMMER writes one section for each run, so this is
+    /// how the output of several runs is put in one file. Returns the
+    /// number of records written.
+    ///
+    /// An `ErrorKind::InvalidInput` error is returned, before
+    /// anything is written, if a header or records have been written
+    /// without the metadata ending their section, or if the writer
+    /// was constructed with `with_program` and a record is of the
+    /// wrong kind for the program.
+    pub fn write_section<'a, T: WritableRecord + 'a>(
+        &mut self,
+        header: &Header,
+        records: impl IntoIterator<Item = &'a T>,
+        meta: &Meta,
+    ) -> Result<usize> {
+        if (self.header_written || self.records_written) && !self.meta_written {
+            return Err(invalid_input(
+                "cannot start a section before the metadata of the last one",
+            ));
+        }
+        let records = records.into_iter().collect::<Vec<_>>();
+        if let Some(program) = self.program {
+            for record in &records {
+                check_kind(program, *record)?;
+            }
+        }
+        self.header_written = false;
+        self.meta_written = false;
+        self.write_all(header, records, meta)
+    }
+
+    /// Finish the file by writing the metadata, then flush the
+    /// buffered output and return the underlying writer.
+    pub fn finish(mut self, meta: &Meta) -> Result<W> {
+        self.write_meta(meta)?;
+        self.into_inner()
+    }
+
+    /// Flush any buffered output to the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.wtr().flush()?)
+    }
+
+    /// Flush the buffered output and return the underlying writer.
+    /// For compressed files the compressed stream is completed too.
+    pub fn into_inner(mut self) -> Result<W> {
+        let wtr = self.wtr.take().expect(TAKEN);
+        let mut wtr = wtr.into_inner().map_err(|e| e.into_error())?.inner;
+        if let Some(finish) = self.finisher {
+            finish(&mut wtr)?;
+        }
+        Ok(wtr)
+    }
+
+    /// The underlying buffered writer.
+    fn wtr(&mut self) -> &mut io::BufWriter<Tracked<W>> {
+        self.wtr.as_mut().expect(TAKEN)
+    }
+}
+
+/// Why the underlying writer is always there: it is only taken by
+/// `into_inner`, which consumes the writer.
+const TAKEN: &str = "the underlying writer is only taken when the writer is consumed";
+
+/// The writer under the buffer, noting whether writing to it has
+/// failed.
+struct Tracked<W> {
+    inner: W,
+    failed: bool,
+}
+
+impl<W: io::Write> io::Write for Tracked<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let result = self.inner.write(buf);
+        self.failed |= result.is_err();
+        result
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.inner.flush();
+        self.failed |= result.is_err();
+        result
+    }
+}
+
+/// Flush the output of a writer which wasn't finished, as `into_inner`
+/// would. Errors can't be returned from here, so those which weren't
+/// returned by an earlier call are caught by a debug assertion.
+impl<W: io::Write> Drop for Writer<W> {
+    fn drop(&mut self) {
+        let wtr = match self.wtr.as_mut() {
+            Some(wtr) => wtr,
+            None => return,
+        };
+        // an error which was returned before needn't be reported again
+        let failed = wtr.get_ref().failed;
+        let result = wtr.flush().and_then(|()| match self.finisher {
+            Some(finish) => finish(&mut wtr.get_mut().inner),
+            None => Ok(()),
+        });
+        debug_assert!(
+            result.is_ok() || failed || std::thread::panicking(),
+            "error flushing a dropped writer: {:?}",
+            result
+        );
+    }
+}
+
+/// Check a record is of the kind the program writes.
+fn check_kind<T: WritableRecord>(program: Program, record: &T) -> Result<()> {
+    let expected = program.record_kind().ok_or_else(unknown_program)?;
+    if record.kind() != expected {
+        return Err(invalid_input(&format!(
+            "cannot write a {:?} record for target {} as {} output",
+            record.kind(),
+            record.target_name(),
+            program
+        )));
+    }
+    Ok(())
+}
+
+/// The error for writing records without knowing the program.
+fn unknown_program() -> Error {
+    invalid_input("cannot write records for an unknown program")
+}
+
+/// The error for a call the writer can't carry out in its state.
+fn invalid_input(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidInput(msg.to_string()))
+}
+
+/// Calculate the column widths HMMER would use for the records. The
+/// names, accessions and (for DNA) sequence positions are widened to
+/// fit; the other columns keep HMMER's fixed widths. Widths are in
+/// characters, as columns are padded, so names which are not ASCII
+/// line up.
+fn column_widths<'a, T: WritableRecord + 'a>(
+    program: Program,
+    records: impl IntoIterator<Item = &'a T>,
+) -> Vec<usize> {
+    let mut widths = if program.is_dna_search() {
+        DNA_WIDTHS.to_vec()
+    } else {
+        PROTEIN_WIDTHS.to_vec()
+    };
+    for record in records {
+        let fields = record.fields(FloatStyle::Hmmer);
+        for i in 0..4 {
+            widths[i] = widths[i].max(fields[i].chars().count());
+        }
+        if record.kind() == RecordKind::Dna {
+            // HMMER uses one width for the alignment, envelope and
+            // sequence length columns
+            let posw = fields[6..11]
+                .iter()
+                .map(|f| f.chars().count())
+                .max()
+                .unwrap_or(0);
+            for w in &mut widths[6..11] {
+                *w = (*w).max(posw);
+            }
+        }
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DNARecord, ProteinRecord, Reader, Strand};
+
+    fn dna_record() -> Record {
+        Record::Dna(DNARecord::new(
+            "chr1".into(),
+            "-".into(),
+            "TR".into(),
+            "-".into(),
+            1,
+            315,
+            1000,
+            1314,
+            998,
+            1316,
+            50000,
+            Strand::Positive,
+            6.5e-34,
+            124.1,
+            1.2,
+        ))
+    }
+
+    #[test]
+    fn test_non_ascii_alignment() {
+        let record = |target: &str, description: &str| match dna_record() {
+            Record::Dna(mut r) => {
+                r.set_target_name(target.into());
+                r.set_description(description.into());
+                Record::Dna(r)
+            }
+            Record::Protein(_) => unreachable!(),
+        };
+        let records = vec![
+            record("α-amylase_BACSU_chromosome", "α-amylase, Bacillus subtilis"),
+            record("amyE_BACSU_chromosome_", "Alpha-amylase"),
+        ];
+        let mut writer = Writer::new(Vec::new());
+        writer.write_aligned(Program::Nhmmer, &records).unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+
+        // the columns start at the same character in every line
+        let column = |line: &str, value: &str| line[..line.find(value).unwrap()].chars().count();
+        let lines = out.lines().collect::<Vec<_>>();
+        let query = column(lines[0], "query name");
+        assert_eq!(column(lines[2], " TR ") + 1, query);
+        assert_eq!(column(lines[3], " TR ") + 1, query);
+        assert_eq!(
+            column(lines[2], "α-amylase,"),
+            column(lines[3], "Alpha-amylase")
+        );
+        let widths = Header::new(None, lines[0].into(), lines[1].into()).calculate_dashes();
+        assert_eq!(widths[0], "α-amylase_BACSU_chromosome".chars().count());
+
+        // and read back as they were
+        let mut reader = Reader::with_program(out.as_bytes(), Program::Nhmmer);
+        let read = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        assert_eq!(read, records);
+        assert_eq!(read[0].description(), "α-amylase, Bacillus subtilis");
+    }
+
+    #[test]
+    fn test_with_program_lazy_header() {
+        let mut writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        writer.flush().unwrap();
+        assert!(writer.wtr().get_ref().inner.is_empty());
+
+        writer.write_record(&dna_record()).unwrap();
+        writer.write_record(&dna_record()).unwrap();
+        writer
+            .write_meta(&Meta::for_program(Program::Nhmmer))
+            .unwrap();
+        let out = writer.into_inner().unwrap();
+
+        let header = Header::for_program(Program::Nhmmer).unwrap();
+        assert!(String::from_utf8_lossy(&out).starts_with(&header.to_string()));
+
+        let mut reader = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(reader.meta().program(), Program::Nhmmer);
+        assert_eq!(reader.header(), &header);
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        assert_eq!(records, vec![dna_record(), dna_record()]);
+    }
+
+    #[test]
+    fn test_empty_meta_not_written() {
+        let mut writer = Writer::new(Vec::new());
+        writer.write_record(&dna_record()).unwrap();
+        let out = String::from_utf8(writer.finish(&Meta::default()).unwrap()).unwrap();
+        // no separator or block for a file without metadata
+        assert_eq!(out.lines().count(), 1);
+        assert!(!out.contains('#'));
+    }
+
+    fn protein_record() -> Record {
+        Record::Protein(ProteinRecord::new(
+            "HBB_HUMAN".into(),
+            "-".into(),
+            "MYG_ESCGI".into(),
+            "-".into(),
+            2.3e-11,
+            30.3,
+            0.1,
+            2.5e-11,
+            30.2,
+            0.1,
+            1.0,
+            1,
+            0,
+            0,
+            1,
+            1,
+            1,
+            1,
+        ))
+    }
+
+    #[test]
+    fn test_with_program_kind_mismatch() {
+        let protein = protein_record();
+
+        let mut writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        let err = writer.write_record(&protein).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+        assert!(err.to_string().contains("HBB_HUMAN"));
+        // nothing, not even the header, is written
+        assert!(writer.into_inner().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_finish_layout() {
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_header(reader.header()).unwrap();
+        writer.write_records(&records).unwrap();
+        let out = String::from_utf8(writer.finish(reader.meta()).unwrap()).unwrap();
+
+        // the same sections in the same order as the file written by nhmmer
+        let sections = |s: &str| {
+            let mut sections: Vec<&str> = Vec::new();
+            for line in s.lines() {
+                let section = match line {
+                    "#" => "separator",
+                    l if l.starts_with("# Program:") => "meta",
+                    "# [ok]" => "ok",
+                    l if l.starts_with('#') && sections.last() == Some(&"meta") => "meta",
+                    l if l.starts_with('#') => "header",
+                    _ => "records",
+                };
+                if sections.last() != Some(&section) {
+                    sections.push(section);
+                }
+            }
+            sections
+        };
+        let original = std::fs::read_to_string("./data/test.tbl").unwrap();
+        assert_eq!(sections(&out), sections(&original));
+        assert_eq!(
+            sections(&out),
+            ["header", "records", "separator", "meta", "ok"]
+        );
+
+        let reparsed = Reader::from_reader(out.as_bytes()).unwrap();
+        assert_eq!(reparsed.meta(), reader.meta());
+    }
+
+    #[test]
+    fn test_meta_ordering() {
+        let meta = Meta::for_program(Program::Nhmmer);
+
+        // without hits the header still comes before the metadata
+        let writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        let out = writer.finish(&meta).unwrap();
+        let reader = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(
+            reader.header(),
+            &Header::for_program(Program::Nhmmer).unwrap()
+        );
+        assert_eq!(reader.meta(), &meta);
+
+        let mut writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        writer.write_meta(&meta).unwrap();
+        assert!(writer.write_record(&dna_record()).is_err());
+        assert!(writer.write_meta(&meta).is_err());
+    }
+
+    #[test]
+    fn test_builder_styles() {
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+
+        let write = |builder: &WriterBuilder| {
+            let mut writer = builder.build(Vec::new());
+            writer.write_header(reader.header()).unwrap();
+            writer.write_records(&records).unwrap();
+            String::from_utf8(writer.finish(reader.meta()).unwrap()).unwrap()
+        };
+
+        let aligned = write(&WriterBuilder::new());
+        let reparsed = Reader::from_reader(aligned.as_bytes())
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(reparsed, records);
+
+        let tab = write(
+            WriterBuilder::new()
+                .delimiter(Delimiter::Tab)
+                .write_header(false)
+                .write_meta(false),
+        );
+        assert_eq!(tab.lines().count(), records.len());
+        assert_eq!(
+            tab.lines().next().unwrap(),
+            "2\t-\tTR\t-\t2\t317\t338944\t339292\t338943\t339292\t133811386\t+\t1.9e-37\t136.7\t1.4\tunmasked:chromosome primary_assembly:daLinVulg1.1:2:1:133811386:1"
+        );
+
+        let compact = write(
+            WriterBuilder::new()
+                .delimiter(Delimiter::SingleSpace)
+                .float_style(FloatStyle::Raw),
+        );
+        let line = compact.lines().find(|l| !l.starts_with('#')).unwrap();
+        assert_eq!(
+            line,
+            "2 - TR - 2 317 338944 339292 338943 339292 133811386 + 1.9e-37 136.7 1.4 unmasked:chromosome primary_assembly:daLinVulg1.1:2:1:133811386:1"
+        );
+        assert!(compact.ends_with("# [ok]\n"));
+    }
+
+    /// Write the records of a file with the given delimiter and read
+    /// them back, returning the output and the records.
+    fn rewrite(path: &std::path::Path, delimiter: Delimiter) -> (String, Vec<Record>) {
+        let mut reader = Reader::from_path(path).unwrap();
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        let mut writer = WriterBuilder::new().delimiter(delimiter).build(Vec::new());
+        writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let reparsed = Reader::from_reader(out.as_bytes())
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        (out, reparsed)
+    }
+
+    #[test]
+    fn test_single_space_reparses() {
+        let mut files = std::fs::read_dir("./data/corpus")
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect::<Vec<_>>();
+        files.push("./data/test.tbl".into());
+        for path in files {
+            let (_, aligned) = rewrite(&path, Delimiter::Aligned);
+            let (_, compact) = rewrite(&path, Delimiter::SingleSpace);
+            assert_eq!(compact, aligned, "{}", path.display());
+        }
+    }
+
+    #[test]
+    fn test_single_space_columns() {
+        let (out, records) = rewrite(
+            std::path::Path::new("./data/test.tbl"),
+            Delimiter::SingleSpace,
+        );
+        let lines = out.lines().filter(|l| !l.starts_with('#'));
+        for (line, record) in lines.zip(&records) {
+            let record = match record {
+                Record::Dna(r) => r,
+                Record::Protein(_) => unreachable!(),
+            };
+            // cut -d' ' -f1, -f7, -f12 and -f13
+            let cut = |n: usize| line.split(' ').nth(n - 1).unwrap();
+            assert_eq!(cut(1), record.target_name());
+            assert_eq!(cut(7).parse::<i64>().unwrap(), record.ali_from());
+            assert_eq!(cut(12), record.strand().to_string());
+            assert_eq!(cut(13).parse::<f64>().unwrap(), record.e_value());
+            // cut -d' ' -f16-
+            assert_eq!(
+                line.splitn(16, ' ').last(),
+                Some(record.description().as_str())
+            );
+        }
+
+        let (out, records) = rewrite(
+            std::path::Path::new("./data/corpus/hmmscan.tbl"),
+            Delimiter::SingleSpace,
+        );
+        let lines = out.lines().filter(|l| !l.starts_with('#'));
+        for (line, record) in lines.zip(&records) {
+            let fields = line.splitn(19, ' ').collect::<Vec<_>>();
+            assert_eq!(fields[2], record.query_name());
+            assert_eq!(fields[18], record.description());
+        }
+    }
+
+    #[test]
+    fn test_write_aligned() {
+        let long = DNARecord::new(
+            "a_very_long_target_name_from_an_assembly".into(),
+            "-".into(),
+            "TR".into(),
+            "-".into(),
+            1,
+            315,
+            1234567890,
+            1234567575,
+            1234567891,
+            1234567574,
+            1999999999,
+            Strand::Negative,
+            1.5e-10,
+            40.2,
+            0.3,
+        );
+        let records = vec![dna_record(), Record::Dna(long), dna_record()];
+
+        let mut writer = Writer::new(Vec::new());
+        assert_eq!(writer.write_aligned(Program::Nhmmer, &records).unwrap(), 3);
+        let out = writer.finish(&Meta::for_program(Program::Nhmmer)).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let mut reader = Reader::from_reader(out.as_bytes()).unwrap();
+        let widths = reader.header().calculate_dashes();
+        assert_eq!(widths[0], 40);
+        assert_eq!(widths[6..11], [10; 5]);
+
+        // every name starts, and every number ends, in its column
+        for line in out.lines().filter(|l| !l.starts_with('#')) {
+            let line = line.as_bytes();
+            let mut start = 0;
+            for (i, w) in widths[..15].iter().enumerate() {
+                let end = start + w;
+                match i {
+                    0..=3 => assert_ne!(line[start], b' '),
+                    11 => assert_ne!(line[end - 3], b' '),
+                    _ => assert_ne!(line[end - 1], b' '),
+                }
+                assert_eq!(line[end], b' ');
+                start = end + 1;
+            }
+        }
+
+        let reparsed = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        assert_eq!(reparsed, records);
+
+        // the wrong kind of record is rejected before anything is written
+        let mut writer = Writer::new(Vec::new());
+        assert!(writer
+            .write_aligned(Program::Hmmsearch, &[dna_record()])
+            .is_err());
+        assert!(writer.into_inner().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_write_concrete_records() {
+        let records = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        let dna = records
+            .iter()
+            .map(|r| match r {
+                Record::Dna(r) => r.clone(),
+                Record::Protein(_) => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        writer.write_record(&dna[0]).unwrap();
+        writer.write_record(&records[1]).unwrap();
+        assert_eq!(writer.write_records(&dna[2..]).unwrap(), dna.len() - 2);
+        let out = writer.finish(&Meta::for_program(Program::Nhmmer)).unwrap();
+
+        let reparsed = Reader::from_reader(out.as_slice())
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(reparsed, records);
+
+        let protein = ProteinRecord::new(
+            "HBB_HUMAN".into(),
+            "-".into(),
+            "MYG_ESCGI".into(),
+            "-".into(),
+            2.3e-11,
+            30.3,
+            0.1,
+            2.5e-11,
+            30.2,
+            0.1,
+            1.0,
+            1,
+            0,
+            0,
+            1,
+            1,
+            1,
+            1,
+        );
+        assert_eq!(protein.kind(), RecordKind::Protein);
+        let mut writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        assert!(writer.write_record(&protein).is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gz_file() {
+        use flate2::read::MultiGzDecoder;
+        use std::io::Read;
+
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tbl.gz");
+
+        let mut writer = Writer::to_gz_file(&path, 6).unwrap();
+        writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+        writer.into_inner().unwrap();
+
+        // there is no reader for plain gzip files, and `from_reader`
+        // needs a reader it can clone, so decode the file up front
+        let mut out = Vec::new();
+        MultiGzDecoder::new(File::open(&path).unwrap())
+            .read_to_end(&mut out)
+            .unwrap();
+
+        let mut reparsed = Reader::from_reader(out.as_slice()).unwrap();
+        assert_eq!(reparsed.meta(), reader.meta());
+        let again = reparsed
+            .records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(again, records);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_file() {
+        let records = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tbl.zst");
+
+        let mut writer = Writer::to_compressed_file(&path, Format::Zstd, 3).unwrap();
+        writer.write_aligned(Program::Nhmmer, &records).unwrap();
+        writer.finish(&Meta::for_program(Program::Nhmmer)).unwrap();
+
+        let out = zstd::decode_all(File::open(&path).unwrap()).unwrap();
+
+        let reparsed = Reader::from_reader(out.as_slice())
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(reparsed, records);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_file_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tbl.zst");
+
+        let mut writer = Writer::to_compressed_file(&path, Format::Zstd, 3).unwrap();
+        writer.write_record(&dna_record()).unwrap();
+        drop(writer);
+
+        let out = zstd::decode_all(File::open(&path).unwrap()).unwrap();
+        assert!(!out.is_empty());
+    }
+
+    #[test]
+    fn test_flush_on_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tbl");
+
+        let records = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .into_records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+        let mut writer = Writer::to_path(&path).unwrap();
+        writer.write_aligned(Program::Nhmmer, &records).unwrap();
+        writer
+            .write_meta(&Meta::for_program(Program::Nhmmer))
+            .unwrap();
+        // neither flushed nor finished
+        drop(writer);
+
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(reader.meta().program(), Program::Nhmmer);
+        let again = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        assert_eq!(again, records);
+    }
+
+    /// Fails every write, as a full disk would.
+    #[derive(Debug)]
+    struct Failing;
+
+    impl io::Write for Failing {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::other("no space left"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_finish_returns_errors() {
+        let mut writer = Writer::new(Failing);
+        writer.write_record(&dna_record()).unwrap();
+        let err = writer
+            .finish(&Meta::for_program(Program::Nhmmer))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "I/O error - no space left");
+        assert!(matches!(err.kind(), ErrorKind::Io(_)));
+    }
+
+    #[test]
+    fn test_write_record_error_names_target() {
+        // too long for the buffer, so written straight through
+        let mut record = dna_record();
+        if let Record::Dna(r) = &mut record {
+            r.set_description("x".repeat(10_000));
+        }
+        let mut writer = Writer::new(Failing);
+        let err = writer.write_record(&record).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "writing record for target chr1 - no space left"
+        );
+        match err.into_kind() {
+            ErrorKind::WriteRecord { target, error } => {
+                assert_eq!(target, "chr1");
+                assert_eq!(error.kind(), io::ErrorKind::Other);
+            }
+            kind => panic!("unexpected error {:?}", kind),
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "error flushing a dropped writer")]
+    fn test_drop_asserts_flushed() {
+        let mut writer = Writer::new(Failing);
+        writer.write_record(&dna_record()).unwrap();
+        drop(writer);
+    }
+
+    #[test]
+    fn test_drop_after_error() {
+        // the error was returned, so dropping the writer is quiet
+        let mut writer = Writer::new(Failing);
+        writer.write_record(&dna_record()).unwrap();
+        assert!(writer.flush().is_err());
+        drop(writer);
+    }
+
+    #[test]
+    fn test_constructors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tbl");
+
+        let mut writer = Writer::to_path(&path).unwrap();
+        writer
+            .write_aligned(Program::Nhmmer, &[dna_record()])
+            .unwrap();
+        writer.finish(&Meta::for_program(Program::Nhmmer)).unwrap();
+        let mut reader = Reader::from_path(&path).unwrap();
+        assert_eq!(reader.records().next().unwrap().unwrap(), dna_record());
+
+        #[allow(deprecated)]
+        let writer = Writer::to_file(path.to_str().unwrap()).unwrap();
+        assert!(writer.into_inner().unwrap().metadata().unwrap().len() == 0);
+
+        let mut writer = Writer::to_vec();
+        writer.write_record(&dna_record()).unwrap();
+        assert_eq!(
+            writer.into_inner().unwrap(),
+            format!("{}\n", dna_record()).into_bytes()
+        );
+
+        let mut writer = Writer::to_stdout();
+        writer.flush().unwrap();
+    }
+
+    fn round_trip(keep: impl Fn(usize) -> bool) -> String {
+        let mut reader = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .keep_raw_lines(true);
+        let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+
+        let mut writer = WriterBuilder::new().round_trip(true).build(Vec::new());
+        writer.write_header(reader.header()).unwrap();
+        for (i, record) in records.iter().enumerate() {
+            if keep(i) {
+                writer.write_record(record).unwrap();
+            }
+        }
+        String::from_utf8(writer.finish(reader.meta()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let original = std::fs::read_to_string("./data/test.tbl").unwrap();
+        assert_eq!(round_trip(|_| true), original);
+
+        // the records which are kept are unchanged
+        let mut i = 0;
+        let expected: String = original
+            .split_inclusive('\n')
+            .filter(|line| {
+                if line.starts_with('#') {
+                    return true;
+                }
+                i += 1;
+                i % 2 == 1
+            })
+            .collect();
+        assert_eq!(round_trip(|i| i % 2 == 0), expected);
+
+        // lines are only kept when asked for
+        let mut reader = Reader::from_path("./data/test.tbl").unwrap();
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(record.raw_line(), None);
+    }
+
+    #[test]
+    fn test_footer_matches_hmmer() {
+        let mut files = std::fs::read_dir("./data/corpus")
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect::<Vec<_>>();
+        files.push("./data/test.tbl".into());
+        for path in files {
+            let mut original = std::fs::read_to_string(&path).unwrap();
+            if path.ends_with("nhmmscan.tbl") {
+                // HMMER 3.2's nhmmscan calls itself hmmscan, which the
+                // reader sees through
+                original =
+                    original.replace("Program:         hmmscan", "Program:         nhmmscan");
+            }
+            let mut reader = Reader::from_path(&path).unwrap();
+            let records = reader.records().collect::<crate::Result<Vec<_>>>().unwrap();
+            let mut writer = Writer::new(Vec::new());
+            writer.write_header(reader.header()).unwrap();
+            writer.write_records(&records).unwrap();
+            let out = String::from_utf8(writer.finish(reader.meta()).unwrap()).unwrap();
+
+            // everything after the last record
+            let footer = |text: &str| {
+                let lines = text.split_inclusive('\n').collect::<Vec<_>>();
+                let last = lines.iter().rposition(|l| !l.starts_with('#')).unwrap();
+                lines[last + 1..].concat()
+            };
+            assert_eq!(footer(&out), footer(&original), "{}", path.display());
+        }
+
+        let meta = Meta::for_program(Program::Phmmer);
+        let mut writer = Writer::new(Vec::new());
+        writer.write_footer(&meta, FooterStyle::MetaOnly).unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out, meta.to_string());
+    }
+
+    /// Write the records of two differently aligned files under the
+    /// header of the first, keeping raw lines, and return the output.
+    fn merge_two(set_header: bool) -> String {
+        let mut a = Reader::from_path("./data/test.tbl")
+            .unwrap()
+            .keep_raw_lines(true);
+        let mut b = Reader::from_path("./data/corpus/nhmmer.tbl")
+            .unwrap()
+            .keep_raw_lines(true);
+        let mut records = a.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        records.extend(b.records().map(|r| r.unwrap()));
+
+        let mut writer = WriterBuilder::new().round_trip(true).build(Vec::new());
+        writer.write_header(a.header()).unwrap();
+        if set_header {
+            writer.set_header(a.header());
+        }
+        writer.write_records(&records).unwrap();
+        String::from_utf8(writer.finish(a.meta()).unwrap()).unwrap()
+    }
+
+    /// Whether every record lines up under the header: each column
+    /// starts where its dashes do, or, if right aligned, ends there.
+    /// The strand is centred, so only has to be within its dashes.
+    fn aligned(out: &str) -> bool {
+        let spans = |line: &str| {
+            let mut spans = Vec::new();
+            let mut start = None;
+            for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+                match (c == ' ', start) {
+                    (false, None) => start = Some(i),
+                    (true, Some(s)) => {
+                        spans.push((s, i));
+                        start = None;
+                    }
+                    _ => (),
+                }
+            }
+            spans
+        };
+        let dashes = spans(out.lines().nth(1).unwrap());
+        out.lines().filter(|l| !l.starts_with('#')).all(|line| {
+            spans(line)
+                .iter()
+                .zip(&dashes[..15])
+                .enumerate()
+                .all(|(i, (col, dash))| match i {
+                    // the strand sits inside its dashes
+                    11 => dash.0 <= col.0 && col.1 <= dash.1,
+                    _ => col.0 == dash.0 || col.1 == dash.1,
+                })
+        })
+    }
+
+    #[test]
+    fn test_set_header() {
+        // the second file's lines keep their own alignment
+        let out = merge_two(false);
+        assert!(!aligned(&out));
+
+        let out = merge_two(true);
+        assert!(aligned(&out));
+        let mut reader = Reader::from_reader(out.as_bytes()).unwrap();
+        assert_eq!(reader.records().count(), 31 + 16);
+        // the first file's lines were aligned to its header already
+        let original = std::fs::read_to_string("./data/test.tbl").unwrap();
+        let first = original.lines().nth(2).unwrap();
+        assert_eq!(out.lines().nth(2), Some(first));
+    }
+
+    #[test]
+    fn test_round_trip_changed() {
+        let line = "2                    -          TR                   -                2     317  338944  339292  338943  339292 133811386    +    1.9e-37  136.7   1.4  some description";
+        let record = crate::reader::parse_dna_line(line).unwrap();
+        assert_eq!(record.raw_line(), None);
+        let record = record.with_raw_line(line);
+        assert_eq!(record.raw_line(), Some(line));
+
+        let mut writer = WriterBuilder::new().round_trip(true).build(Vec::new());
+        writer.write_record(&record).unwrap();
+        let mut changed = record.clone();
+        changed.set_score(140.0);
+        assert_eq!(changed.raw_line(), None);
+        writer.write_record(&changed).unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], line);
+        assert_eq!(lines[1], changed.to_string());
+
+        // other delimiters always format the record
+        let mut writer = WriterBuilder::new()
+            .round_trip(true)
+            .delimiter(Delimiter::Tab)
+            .build(Vec::new());
+        writer.write_record(&record).unwrap();
+        assert!(String::from_utf8(writer.into_inner().unwrap())
+            .unwrap()
+            .contains('\t'));
+    }
+
+    #[test]
+    fn test_narrow_widths() {
+        // a header with a single dash under each column
+        let header = Header::new(
+            None,
+            "# target name".to_string(),
+            format!("#{}", " -".repeat(15)),
+        );
+        assert_eq!(header.calculate_dashes()[11], 1);
+
+        let mut writer = Writer::new(Vec::new());
+        writer.write_header(&header).unwrap();
+        writer.write_record(&dna_record()).unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        // the columns are no narrower than HMMER's
+        assert_eq!(out.lines().nth(2).unwrap(), dna_record().to_string());
+    }
+
+    #[test]
+    fn test_description_modes() {
+        let line = "2                    -          TR                   -                2     317  338944  339292  338943  339292 133811386    +    1.9e-37  136.7   1.4  Linaria  vulgaris ☃ chromosome";
+        let record = crate::reader::parse_dna_line(line).unwrap();
+        let record = Record::Dna(record.with_raw_line(line));
+        let write = |mode: DescriptionMode, delimiter: Delimiter| {
+            let mut writer = WriterBuilder::new()
+                .round_trip(true)
+                .description(mode)
+                .delimiter(delimiter)
+                .build(Vec::new());
+            writer.write_record(&record).unwrap();
+            String::from_utf8(writer.into_inner().unwrap()).unwrap()
+        };
+        let description = |out: String| {
+            let record = crate::reader::parse_dna_line(out.trim_end_matches('\n')).unwrap();
+            record.description()
+        };
+
+        assert_eq!(
+            write(DescriptionMode::Keep, Delimiter::Aligned),
+            format!("{}\n", line)
+        );
+        assert_eq!(
+            description(write(DescriptionMode::Drop, Delimiter::Aligned)),
+            "-"
+        );
+        assert_eq!(
+            description(write(DescriptionMode::Truncate(7), Delimiter::Aligned)),
+            "Linaria"
+        );
+        // the snowman is three bytes, and is not split
+        assert_eq!(
+            description(write(DescriptionMode::Truncate(19), Delimiter::Aligned)),
+            "Linaria  vulgaris ☃"
+        );
+        assert_eq!(
+            description(write(DescriptionMode::Truncate(18), Delimiter::Aligned)),
+            "Linaria  vulgaris "
+        );
+        assert_eq!(
+            description(write(DescriptionMode::Truncate(100), Delimiter::Aligned)),
+            "Linaria  vulgaris ☃ chromosome"
+        );
+        assert_eq!(
+            description(write(DescriptionMode::Truncate(0), Delimiter::Aligned)),
+            "-"
+        );
+        assert_eq!(
+            description(write(
+                DescriptionMode::Replace("masked".to_string()),
+                Delimiter::Aligned
+            )),
+            "masked"
+        );
+        assert!(write(DescriptionMode::Drop, Delimiter::Tab).ends_with("\t1.4\t-\n"));
+
+        // the record itself is unchanged
+        assert_eq!(record.description(), "Linaria  vulgaris ☃ chromosome");
+    }
+
+    /// Read back each section of a file, ending at HMMER's `# [ok]`.
+    fn read_sections(s: &str) -> Vec<(Meta, usize)> {
+        s.split_inclusive("# [ok]\n")
+            .map(|section| {
+                let mut reader = Reader::from_reader(section.as_bytes()).unwrap();
+                let n = reader
+                    .records()
+                    .collect::<crate::Result<Vec<_>>>()
+                    .unwrap()
+                    .len();
+                (reader.meta().clone(), n)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_write_sections() {
+        let mut dna = Reader::from_path("./data/test.tbl").unwrap();
+        let dna_records = dna.records().collect::<crate::Result<Vec<_>>>().unwrap();
+        let hmmsearch = include_str!("../data/corpus/hmmsearch.tbl");
+        let mut protein = Reader::from_reader(hmmsearch.as_bytes()).unwrap();
+        let protein_records = protein
+            .records()
+            .collect::<crate::Result<Vec<_>>>()
+            .unwrap();
+
+        // two sections from one writer
+        let mut writer = Writer::to_vec();
+        let n = writer
+            .write_section(dna.header(), &dna_records, dna.meta())
+            .unwrap();
+        assert_eq!(n, dna_records.len());
+        assert!(writer.write_record(&dna_records[0]).is_err());
+        writer
+            .write_section(protein.header(), &protein_records, protein.meta())
+            .unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out.matches("# [ok]").count(), 2);
+        let expected = vec![
+            (dna.meta().clone(), dna_records.len()),
+            (protein.meta().clone(), protein_records.len()),
+        ];
+        assert_eq!(read_sections(&out), expected);
+
+        // the second section appended to a file holding the first
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.tbl");
+        let mut writer = Writer::to_path(&path).unwrap();
+        writer
+            .write_section(dna.header(), &dna_records, dna.meta())
+            .unwrap();
+        writer.into_inner().unwrap();
+        let mut writer = Writer::append_to_path(&path).unwrap();
+        writer
+            .write_section(protein.header(), &protein_records, protein.meta())
+            .unwrap();
+        writer.into_inner().unwrap();
+        let appended = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(appended, out);
+        assert_eq!(read_sections(&appended), expected);
+    }
+
+    #[test]
+    fn test_write_section_after_records() {
+        // records without a header still start a section
+        let mut writer = Writer::to_vec();
+        writer.write_record(&dna_record()).unwrap();
+        let header = Header::for_program(Program::Nhmmer).unwrap();
+        let meta = Meta::for_program(Program::Nhmmer);
+        let err = writer
+            .write_section(&header, &[dna_record()], &meta)
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+        assert_eq!(
+            writer.into_inner().unwrap(),
+            format!("{}\n", dna_record()).into_bytes()
+        );
+
+        // a section of the wrong kind is rejected before it is started
+        let mut writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        writer
+            .write_section(&header, &[dna_record()], &meta)
+            .unwrap();
+        let before = writer.into_inner().unwrap();
+        let mut writer = Writer::with_program(before.clone(), Program::Nhmmer);
+        let protein = Header::for_program(Program::Hmmsearch).unwrap();
+        let record = protein_record();
+        let err = writer
+            .write_section(&protein, &[record], &Meta::for_program(Program::Hmmsearch))
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+        assert_eq!(writer.into_inner().unwrap(), before);
+    }
+
+    #[test]
+    fn test_write_section_mid_section() {
+        let mut writer = Writer::with_program(Vec::new(), Program::Nhmmer);
+        writer.write_record(&dna_record()).unwrap();
+        let header = Header::for_program(Program::Nhmmer).unwrap();
+        let meta = Meta::for_program(Program::Nhmmer);
+        let records: [Record; 0] = [];
+        let err = writer.write_section(&header, &records, &meta).unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::InvalidInput(_)));
+
+        // once the section is finished another can follow
+        writer.write_meta(&meta).unwrap();
+        writer.write_section(&header, &records, &meta).unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out.matches("# [ok]").count(), 2);
+        assert_eq!(out.matches("# target name").count(), 2);
+    }
+
+    #[test]
+    fn test_raw_numerics() {
+        let original = std::fs::read_to_string("./data/test.tbl").unwrap();
+        let (head, _) = original.split_at(original.find("\n2 ").unwrap() + 1);
+        let record = original.lines().nth(2).unwrap();
+        let (_, meta) = original.split_at(original.find("#\n# Program").unwrap());
+        let tokens = ["0", "1e-312", "2.3e-05"];
+        let mut file = head.to_string();
+        for token in tokens {
+            file.push_str(&record.replace("1.9e-37", token));
+            file.push('\n');
+        }
+        file.push_str(meta);
+
+        let read = |file: &str| {
+            let reader = Reader::from_reader(file.as_bytes())
+                .unwrap()
+                .keep_raw_numerics(true);
+            reader
+                .into_dna_records()
+                .unwrap()
+                .collect::<crate::Result<Vec<_>>>()
+                .unwrap()
+        };
+        let records = read(&file);
+        let raw = records.iter().map(|r| r.e_value_raw()).collect::<Vec<_>>();
+        assert_eq!(raw, tokens.map(Some));
+        assert_eq!(records[0].e_value(), 0.0);
+        assert!(records[1].e_value() > 0.0);
+
+        // the tokens are written in place of the values, whatever the style
+        let mut writer = WriterBuilder::new()
+            .float_style(FloatStyle::Raw)
+            .build(Vec::new());
+        writer
+            .write_all(
+                &Header::default(),
+                &records,
+                &Meta::for_program(Program::Nhmmer),
+            )
+            .unwrap();
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert!(out.contains(" 2.3e-05 "));
+        let again = read(&out);
+        let raw = again.iter().map(|r| r.e_value_raw()).collect::<Vec<_>>();
+        assert_eq!(raw, tokens.map(Some));
+
+        // until the value changes
+        let mut record = records[2].clone();
+        record.set_description("changed".into());
+        assert_eq!(record.e_value_raw(), Some("2.3e-05"));
+        record.set_e_value(2.3e-5);
+        assert_eq!(record.e_value_raw(), None);
+        assert!(record.to_string().contains(" 2.3e-05 "));
+
+        // and only when asked for
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(record.e_value_raw(), None);
+    }
+
+    #[test]
+    fn test_raw_numerics_protein() {
+        let file = include_str!("../data/corpus/hmmsearch.tbl");
+        let mut reader = Reader::from_reader(file.as_bytes())
+            .unwrap()
+            .keep_raw_numerics(true);
+        let record = reader.records().next().unwrap().unwrap();
+        assert_eq!(record.e_value_full_raw(), Some("1.5e-152"));
+        assert_eq!(record.e_value_best_raw(), Some("1.7e-152"));
+        assert_eq!(record.e_value_raw(), None);
+        let mut record = match record {
+            Record::Protein(record) => record,
+            Record::Dna(_) => panic!("not a protein record"),
+        };
+        record.set_e_value_best(1.0);
+        assert_eq!(record.e_value_full_raw(), Some("1.5e-152"));
+        assert_eq!(record.e_value_best_raw(), None);
+    }
+}
@@ -0,0 +1,74 @@
+use std::{fs::OpenOptions, io::Write, thread, time::Duration};
+
+use hmm_tblout::{ErrorKind, Reader, RecordKind, Strand};
+
+const TEST_FILE: &str = include_str!("../data/test.tbl");
+
+#[test]
+fn follow_streams_records_until_footer() {
+    // create the file before following it
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let path = file.path().to_path_buf();
+
+    let writer_path = path.clone();
+    let writer = thread::spawn(move || {
+        let mut f = OpenOptions::new().append(true).open(writer_path).unwrap();
+        for line in TEST_FILE.lines() {
+            // write each line in two halves so that the reader
+            // sees partial lines at the end of the file.
+            let (first, second) = line.split_at(line.len() / 2);
+            f.write_all(first.as_bytes()).unwrap();
+            f.flush().unwrap();
+            thread::sleep(Duration::from_millis(2));
+            f.write_all(second.as_bytes()).unwrap();
+            f.write_all(b"\n").unwrap();
+            f.flush().unwrap();
+        }
+    });
+
+    let records = Reader::follow(&path, Duration::from_millis(1))
+        .unwrap()
+        .collect::<hmm_tblout::Result<Vec<_>>>()
+        .unwrap();
+    writer.join().unwrap();
+
+    assert_eq!(records.len(), 31);
+    assert_eq!(records[0].target_name(), "2".to_string());
+    assert_eq!(records[0].strand(), Some(Strand::Positive));
+    assert_eq!(records[30].ali_from(), Some(94773395));
+}
+
+#[test]
+fn follow_takes_the_kind_from_the_header() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    let protein = include_str!("../data/corpus/hmmsearch.tbl");
+    std::fs::write(file.path(), protein).unwrap();
+    let records = Reader::follow(file.path(), Duration::from_millis(1))
+        .unwrap()
+        .collect::<hmm_tblout::Result<Vec<_>>>()
+        .unwrap();
+    let expected = Reader::from_path("./data/corpus/hmmsearch.tbl")
+        .unwrap()
+        .into_records()
+        .collect::<hmm_tblout::Result<Vec<_>>>()
+        .unwrap();
+    assert_eq!(records, expected);
+
+    // a mangled strand column is a bad DNA record, not a protein one
+    let dna = TEST_FILE.replacen("    +     1.9e-37", "    ?     1.9e-37", 1);
+    std::fs::write(file.path(), dna).unwrap();
+    let mut follow = Reader::follow(file.path(), Duration::from_millis(1)).unwrap();
+    assert!(follow.next().unwrap().is_err());
+    assert_eq!(follow.header().record_kind(), Some(RecordKind::Dna));
+    assert_eq!(follow.count(), 30);
+
+    // without a header the kind is unknown
+    let records = TEST_FILE
+        .lines()
+        .filter(|l| !l.starts_with("#-") && !l.starts_with("# target"));
+    std::fs::write(file.path(), records.collect::<Vec<_>>().join("\n") + "\n").unwrap();
+    let mut follow = Reader::follow(file.path(), Duration::from_millis(1)).unwrap();
+    let err = follow.next().unwrap().unwrap_err();
+    assert!(matches!(err.kind(), ErrorKind::Meta(_)));
+    assert!(follow.next().is_none());
+}
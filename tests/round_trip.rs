@@ -0,0 +1,172 @@
+//! Generated records, written and read back, give the same results.
+//! The comparison is that of `assert_tblout_semantically_eq`, so this
+//! needs the `test-util` feature.
+
+#![cfg(feature = "test-util")]
+
+use hmm_tblout::{
+    assert_tblout_semantically_eq, DNAParts, DNARecord, FloatStyle, Meta, Program, ProteinParts,
+    ProteinRecord, Reader, Record, Strand, WriterBuilder,
+};
+use proptest::{num, prelude::*};
+
+/// A name or accession: a single column, which can't start with `#`
+/// as it may be the first on the line.
+fn name() -> impl Strategy<Value = String> {
+    "[A-Za-z0-9_.|:-][!-~]{0,24}"
+}
+
+/// A description of printable text, which reads back as `-` if blank.
+fn description() -> impl Strategy<Value = String> {
+    "[ -~]{0,40}".prop_map(|s| {
+        if s.trim().is_empty() {
+            "-".to_string()
+        } else {
+            s
+        }
+    })
+}
+
+/// A non-negative E-value, from anywhere in the range of an `f64`.
+fn e_value() -> impl Strategy<Value = f64> {
+    num::f64::POSITIVE | num::f64::ZERO | num::f64::NORMAL | num::f64::SUBNORMAL
+}
+
+/// A finite score, bias or expected number of domains.
+fn score() -> impl Strategy<Value = f32> {
+    num::f32::POSITIVE
+        | num::f32::NEGATIVE
+        | num::f32::ZERO
+        | num::f32::NORMAL
+        | num::f32::SUBNORMAL
+}
+
+/// A 1-based, inclusive range on the given strand, as HMMER writes it:
+/// from the higher position to the lower on the minus strand.
+fn range(strand: Strand, len: i64) -> impl Strategy<Value = (i64, i64)> {
+    (1..=len, 1..=len).prop_map(move |(a, b)| match strand {
+        Strand::Positive => (a.min(b), a.max(b)),
+        Strand::Negative => (a.max(b), a.min(b)),
+    })
+}
+
+prop_compose! {
+    fn dna_record()(
+        strand in prop_oneof![Just(Strand::Positive), Just(Strand::Negative)],
+        // beyond an i32, as for the largest scaffolds
+        sq_len in 1..1i64 << 40,
+    )(
+        names in [name(), name(), name(), name()],
+        hmm in range(Strand::Positive, 100_000),
+        ali in range(strand, sq_len),
+        env in range(strand, sq_len),
+        sq_len in Just(sq_len),
+        strand in Just(strand),
+        e_value in e_value(),
+        score in score(),
+        bias in score(),
+        description in description(),
+    ) -> Record {
+        let [target_name, target_accession, query_name, query_accession] = names;
+        Record::Dna(DNARecord::from_parts(DNAParts {
+            target_name,
+            target_accession,
+            query_name,
+            query_accession,
+            hmm_from: hmm.0 as i32,
+            hmm_to: hmm.1 as i32,
+            ali_from: ali.0,
+            ali_to: ali.1,
+            env_from: env.0,
+            env_to: env.1,
+            sq_len,
+            strand,
+            e_value,
+            score,
+            bias,
+            description,
+        }))
+    }
+}
+
+prop_compose! {
+    fn protein_record()(
+        names in [name(), name(), name(), name()],
+        e_values in [e_value(), e_value()],
+        scores in [score(), score(), score(), score(), score()],
+        counts in [0..1000i32, 0..1000, 0..1000, 0..1000, 0..1000, 0..1000, 0..1000],
+        description in description(),
+    ) -> Record {
+        let [target_name, target_accession, query_name, query_accession] = names;
+        let [reg, clu, ov, env, dom, rep, inc] = counts;
+        Record::Protein(ProteinRecord::from_parts(ProteinParts {
+            target_name,
+            target_accession,
+            query_name,
+            query_accession,
+            e_value_full: e_values[0],
+            score_full: scores[0],
+            bias_full: scores[1],
+            e_value_best: e_values[1],
+            score_best: scores[2],
+            bias_best: scores[3],
+            exp: scores[4],
+            reg,
+            clu,
+            ov,
+            env,
+            dom,
+            rep,
+            inc,
+            description,
+        }))
+    }
+}
+
+/// A program, with records of the kind it writes.
+fn search() -> impl Strategy<Value = (Program, Vec<Record>)> {
+    prop_oneof![
+        Just(Program::Nhmmer),
+        Just(Program::Nhmmscan),
+        Just(Program::Jackhmmer),
+        Just(Program::Hmmscan),
+        Just(Program::Hmmsearch),
+        Just(Program::Phmmer),
+    ]
+    .prop_flat_map(|program| {
+        let record = if program.is_dna_search() {
+            dna_record().boxed()
+        } else {
+            protein_record().boxed()
+        };
+        (Just(program), prop::collection::vec(record, 1..8))
+    })
+}
+
+/// Write the records under a header fitted to them, with floating
+/// point values in the given style.
+fn write(program: Program, records: &[Record], style: FloatStyle) -> String {
+    let mut meta = Meta::for_program(program);
+    meta.set_version("3.4 (Aug 2023)".to_string());
+    let mut writer = WriterBuilder::new().float_style(style).build(Vec::new());
+    writer.write_aligned(program, records).unwrap();
+    String::from_utf8(writer.finish(&meta).unwrap()).unwrap()
+}
+
+proptest! {
+    #[test]
+    fn records_round_trip((program, records) in search()) {
+        // full precision values read back exactly, so the file stands
+        // for the records themselves
+        let exact = write(program, &records, FloatStyle::Raw);
+        let read = Reader::from_reader(exact.as_bytes())
+            .unwrap()
+            .into_records()
+            .collect::<hmm_tblout::Result<Vec<_>>>()
+            .unwrap();
+        prop_assert_eq!(&read, &records);
+
+        let hmmer = write(program, &records, FloatStyle::Hmmer);
+        assert_tblout_semantically_eq(&exact, &hmmer);
+    }
+}
@@ -0,0 +1,149 @@
+#![cfg(feature = "cli")]
+
+use assert_cmd::Command;
+use hmm_tblout::{Reader, Strand};
+
+const TEST_FILE: &str = "data/test.tbl";
+const PROTEIN_FILE: &str = "data/corpus/hmmsearch.tbl";
+
+fn tblout_tools() -> Command {
+    Command::cargo_bin("tblout-tools").unwrap()
+}
+
+fn stdout_of(cmd: &mut Command) -> String {
+    let output = cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn stats() {
+    let out = stdout_of(tblout_tools().args(["stats", TEST_FILE]));
+    assert!(out.contains("program\tnhmmer\n"));
+    assert!(out.contains("hits\t31\n"));
+    assert!(out.contains("queries\t1\n"));
+    assert!(out.contains("best_evalue\t1.9e-37\n"));
+}
+
+#[test]
+fn filter() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("filtered.tbl");
+    tblout_tools()
+        .args([
+            "filter",
+            "--max-evalue",
+            "1e-5",
+            "--strand",
+            "-",
+            TEST_FILE,
+            "-o",
+        ])
+        .arg(&path)
+        .assert()
+        .success();
+
+    let reader = Reader::from_path(&path).unwrap();
+    let records = reader
+        .into_dna_records()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].strand(), Strand::Negative);
+    assert_eq!(records[0].ali_from(), 61501865);
+}
+
+#[test]
+fn filter_bias_suspect() {
+    // the first hit's bias made larger than its score
+    let file = std::fs::read_to_string(TEST_FILE).unwrap().replacen(
+        "1.9e-37  136.7   1.4",
+        "1.9e-37  136.7 140.0",
+        1,
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("biased.tbl");
+    std::fs::write(&input, file).unwrap();
+
+    let out = stdout_of(tblout_tools().arg("filter").arg(&input));
+    assert_eq!(
+        Reader::from_reader(out.as_bytes())
+            .unwrap()
+            .records()
+            .count(),
+        31
+    );
+
+    let out = stdout_of(
+        tblout_tools()
+            .args(["filter", "--exclude-bias-suspect"])
+            .arg(&input),
+    );
+    let mut reader = Reader::from_reader(out.as_bytes()).unwrap();
+    let records = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(records.len(), 30);
+    assert!(records.iter().all(|r| r.e_value() != Some(1.9e-37)));
+}
+
+#[test]
+fn to_bed() {
+    let out = stdout_of(tblout_tools().args(["to-bed", TEST_FILE]));
+    let lines = out.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 31);
+    assert_eq!(lines[0], "2\t338943\t339292\tTR\t136.7\t+");
+    assert_eq!(lines[1], "6\t61501509\t61501865\tTR\t122.3\t-");
+
+    tblout_tools()
+        .args(["to-bed", PROTEIN_FILE])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn to_tsv() {
+    let out = stdout_of(tblout_tools().args(["to-tsv", PROTEIN_FILE]));
+    let mut lines = out.lines();
+    assert!(lines.next().unwrap().starts_with("target_name\t"));
+    assert_eq!(lines.count(), 1);
+}
+
+#[test]
+fn merge() {
+    let out = stdout_of(tblout_tools().args(["merge", TEST_FILE, TEST_FILE]));
+    let records = Reader::from_reader(out.as_bytes())
+        .unwrap()
+        .into_dna_records()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(records.len(), 62);
+    assert!(records.windows(2).all(|w| w[0].e_value() <= w[1].e_value()));
+}
+
+#[test]
+fn stdin() {
+    let input = std::fs::read(TEST_FILE).unwrap();
+    let out = stdout_of(tblout_tools().args(["stats", "-"]).write_stdin(input));
+    assert!(out.contains("hits\t31\n"));
+}
+
+#[test]
+fn errors_give_line_numbers() {
+    let text = std::fs::read_to_string(TEST_FILE).unwrap();
+    let mut lines = text.lines().collect::<Vec<_>>();
+    // break the second record, on the fourth line
+    lines[3] = "not a record";
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut file, (lines.join("\n") + "\n").as_bytes()).unwrap();
+
+    let output = tblout_tools()
+        .args(["stats"])
+        .arg(file.path())
+        .assert()
+        .failure()
+        .get_output()
+        .stderr
+        .clone();
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(stderr.contains("line 4:"), "{}", stderr);
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "tracing")]
+
+use hmm_tblout::{Reader, Writer, PROGRESS_INTERVAL};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+use tracing_subscriber::fmt::{format::FmtSpan, MakeWriter};
+
+const TEST_FILE: &str = include_str!("../data/test.tbl");
+
+/// Collects the formatted events, so they can be checked.
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for Captured {
+    type Writer = Captured;
+
+    fn make_writer(&'a self) -> Captured {
+        self.clone()
+    }
+}
+
+/// Run `f` with a subscriber collecting debug events and span timings,
+/// and return what it wrote.
+fn capture(f: impl FnOnce()) -> String {
+    let captured = Captured::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_ansi(false)
+        .with_writer(captured.clone())
+        .finish();
+    tracing::subscriber::with_default(subscriber, f);
+    let out = captured.0.lock().unwrap().clone();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn reading_reports_counts() {
+    let out = capture(|| {
+        let mut reader = Reader::from_reader(TEST_FILE.as_bytes()).unwrap();
+        assert_eq!(reader.records().count(), 31);
+    });
+
+    // the first pass is timed by its span
+    assert!(out.contains("read_header_and_meta: hmm_tblout::reader: close time.busy"));
+    assert!(out.contains("read the header and metadata lines=43 program=nhmmer"));
+    assert!(out.contains("finished reading records records=31 skipped=12 lines=43"));
+    assert!(!out.contains("WARN"));
+}
+
+#[test]
+fn reading_events_are_rate_limited() {
+    let line = TEST_FILE.lines().nth(2).unwrap();
+    let mut file = String::from("# target name\n#----\n");
+    for _ in 0..3 * PROGRESS_INTERVAL + 1 {
+        file.push_str(line);
+        file.push('\n');
+    }
+    file.push_str("#\n# Program:         nhmmer\n# [ok]\n");
+
+    let out = capture(|| {
+        let reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert!(reader.into_records().all(|r| r.is_ok()));
+    });
+    assert_eq!(out.matches("reader: reading records").count(), 3);
+}
+
+#[test]
+fn oddities_give_warnings() {
+    let out = capture(|| {
+        let crlf = TEST_FILE.replace('\n', "\r\n");
+        Reader::from_reader(crlf.as_bytes()).unwrap();
+    });
+    assert!(out.contains("WARN"));
+    assert!(out.contains("the file has CRLF line endings line=1"));
+
+    let out = capture(|| {
+        // written by HMMER 3.2's nhmmscan, which calls itself hmmscan
+        let file = include_str!("../data/corpus/nhmmscan.tbl");
+        Reader::from_reader(file.as_bytes()).unwrap();
+    });
+    assert!(out.contains("reading the file as nhmmscan output"));
+
+    let out = capture(|| {
+        Reader::from_reader("no metadata\n".as_bytes()).unwrap();
+    });
+    assert!(out.contains("the file has no metadata"));
+
+    let out = capture(|| {
+        let file = TEST_FILE.replace("# Program:         nhmmer\n", "");
+        Reader::from_reader(file.as_bytes()).unwrap();
+    });
+    assert!(out.contains("the metadata does not name the program"));
+
+    let out = capture(|| {
+        let file = include_str!("../data/tabs.tbl");
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert_eq!(reader.records().count(), 4);
+    });
+    // once, for the first record
+    assert_eq!(out.matches("tabs between its columns").count(), 1);
+    assert!(out.contains("so was not written by HMMER line=3"));
+
+    let out = capture(|| {
+        let file = TEST_FILE.replacen("    +     1.9e-37", "    -     1.9e-37", 1);
+        let mut reader = Reader::from_reader(file.as_bytes()).unwrap();
+        assert!(reader.records().all(|r| r.is_ok()));
+    });
+    assert_eq!(out.matches("WARN").count(), 1);
+    assert!(out.contains(
+        "the strand disagrees with the coordinates line=3 column=- coordinates=+ trusted=\"column\""
+    ));
+}
+
+#[test]
+fn writing_reports_counts() {
+    let mut reader = Reader::from_reader(TEST_FILE.as_bytes()).unwrap();
+    let records = reader
+        .records()
+        .collect::<hmm_tblout::Result<Vec<_>>>()
+        .unwrap();
+
+    let out = capture(|| {
+        let mut writer = Writer::new(Vec::new());
+        writer
+            .write_all(reader.header(), &records, reader.meta())
+            .unwrap();
+    });
+    assert!(out.contains("wrote records records=31"));
+    assert!(out.contains("finished writing program=nhmmer"));
+}